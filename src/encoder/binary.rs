@@ -0,0 +1,245 @@
+use crate::types::{ValueType, Limits, TableType, MemoryType, GlobalType, TagType, BlockType, MemoryArgument};
+
+/// Appends the binary encoding of values to a growable buffer. Every
+/// `write_*` method mirrors the corresponding `BinaryReader::read_*` method.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct BinaryWriter {
+    buffer: Vec<u8>,
+}
+
+impl BinaryWriter {
+    pub fn new() -> BinaryWriter {
+        BinaryWriter { buffer: Vec::new() }
+    }
+
+    pub fn get_position(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+
+    pub fn write_byte(&mut self, byte: u8) {
+        self.buffer.push(byte);
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    pub fn write_double_word(&mut self, word: u32) {
+        self.write_bytes(&word.to_le_bytes());
+    }
+
+    pub fn write_leb128_u32(&mut self, value: u32) {
+        self.write_leb128_u64(value as u64);
+    }
+
+    pub fn write_leb128_u64(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0b0111_1111) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.write_byte(byte);
+                break;
+            }
+            self.write_byte(byte | 0b1000_0000);
+        }
+    }
+
+    pub fn write_leb128_s32(&mut self, value: i32) {
+        self.write_leb128_s64(value as i64);
+    }
+
+    pub fn write_leb128_s33(&mut self, value: i64) {
+        self.write_leb128_s64(value);
+    }
+
+    pub fn write_leb128_s64(&mut self, mut value: i64) {
+        loop {
+            let byte = (value & 0b0111_1111) as u8;
+            value >>= 7;
+            //stop once the remaining bits are all copies of the sign bit
+            //which is the highest bit of the group just written
+            let sign_bit_clear = byte & 0b0100_0000 == 0;
+            if (value == 0 && sign_bit_clear) || (value == -1 && !sign_bit_clear) {
+                self.write_byte(byte);
+                break;
+            }
+            self.write_byte(byte | 0b1000_0000);
+        }
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_f64(&mut self, value: f64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_string(&mut self, value: &str) {
+        self.write_bytes_vec(value.as_bytes());
+    }
+
+    pub fn write_bytes_vec(&mut self, bytes: &[u8]) {
+        self.write_leb128_u32(bytes.len() as u32);
+        self.write_bytes(bytes);
+    }
+
+    pub fn write_value_type(&mut self, value_type: ValueType) {
+        self.write_byte(match value_type {
+            ValueType::I32 => 0x7F,
+            ValueType::I64 => 0x7E,
+            ValueType::F32 => 0x7D,
+            ValueType::F64 => 0x7C,
+            ValueType::V128 => 0x7B,
+            ValueType::FuncRef => 0x70,
+            ValueType::ExternRef => 0x6F,
+            ValueType::ExnRef => 0x69,
+        });
+    }
+
+    pub fn write_table_type(&mut self, table_type: &TableType) {
+        self.write_value_type(table_type.element_type);
+        self.write_limits(&table_type.limits);
+    }
+
+    pub fn write_memory_type(&mut self, memory_type: &MemoryType) {
+        let shared_flag = if memory_type.shared { 0x02 } else { 0x00 };
+        let memory64_flag = if memory_type.memory64 { 0x04 } else { 0x00 };
+        self.write_limits_with_flags(&memory_type.limits, shared_flag | memory64_flag);
+    }
+
+    pub fn write_global_type(&mut self, global_type: &GlobalType) {
+        self.write_value_type(global_type.var_type);
+        self.write_byte(global_type.mutable as u8);
+    }
+
+    /// Writes a tag type with the attribute of exceptions, the only kind of tag.
+    pub fn write_tag_type(&mut self, tag_type: &TagType) {
+        self.write_byte(0x00);
+        self.write_leb128_u32(tag_type.type_index.0);
+    }
+
+    fn write_limits(&mut self, limits: &Limits) {
+        self.write_limits_with_flags(limits, 0x00);
+    }
+
+    /// Writes limits whose flags byte also carries the bits in `flags`, as
+    /// memory types do. The bounds of limits within 32 bits are encoded the
+    /// same whichever their width, so they are written as `u64`.
+    fn write_limits_with_flags(&mut self, limits: &Limits, flags: u8) {
+        match limits.max {
+            None => {
+                self.write_byte(flags);
+                self.write_leb128_u64(limits.min);
+            },
+            Some(max) => {
+                self.write_byte(flags | 0x01);
+                self.write_leb128_u64(limits.min);
+                self.write_leb128_u64(max);
+            },
+        }
+    }
+
+    pub fn write_block_type(&mut self, block_type: BlockType) {
+        match block_type {
+            BlockType::Empty => self.write_byte(0x40),
+            BlockType::ValueType(value_type) => self.write_value_type(value_type),
+            BlockType::TypeIndex(type_index) => self.write_leb128_s33(type_index.0 as i64),
+        }
+    }
+
+    /// Memory 0 keeps the MVP encoding, other memories set bit 6 of the
+    /// alignment flags and follow them with their index.
+    pub fn write_memory_argument(&mut self, memory_argument: &MemoryArgument) {
+        if memory_argument.memory_index.0 == 0 {
+            self.write_leb128_u32(memory_argument.alignment);
+        } else {
+            self.write_leb128_u32(memory_argument.alignment | 0x40);
+            self.write_leb128_u32(memory_argument.memory_index.0);
+        }
+        self.write_leb128_u64(memory_argument.offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoder::BinaryWriter;
+    use crate::readers::binary::BinaryReader;
+
+    #[test]
+    fn u32_roundtrip() {
+        for &value in &[0, 1, 63, 64, 127, 128, 624_485, u32::MAX] {
+            let mut writer = BinaryWriter::new();
+            writer.write_leb128_u32(value);
+            let mut reader = BinaryReader::new(writer.as_slice());
+            assert_eq!(Ok(value), reader.read_leb128_u32());
+            assert!(reader.eof());
+        }
+    }
+
+    #[test]
+    fn u32_is_minimal() {
+        let mut writer = BinaryWriter::new();
+        writer.write_leb128_u32(624_485);
+        assert_eq!(&[0xE5, 0x8E, 0x26], writer.as_slice());
+    }
+
+    #[test]
+    fn u64_roundtrip() {
+        for &value in &[0, 1, 127, 128, u32::MAX as u64, 1 << 48, u64::MAX] {
+            let mut writer = BinaryWriter::new();
+            writer.write_leb128_u64(value);
+            let mut reader = BinaryReader::new(writer.as_slice());
+            assert_eq!(Ok(value), reader.read_leb128_u64());
+            assert!(reader.eof());
+        }
+    }
+
+    #[test]
+    fn s32_roundtrip() {
+        for &value in &[0, 1, -1, 63, -64, 64, -65, 123_456, -123_456, i32::MIN, i32::MAX] {
+            let mut writer = BinaryWriter::new();
+            writer.write_leb128_s32(value);
+            let mut reader = BinaryReader::new(writer.as_slice());
+            assert_eq!(Ok(value), reader.read_leb128_s32());
+            assert!(reader.eof());
+        }
+    }
+
+    #[test]
+    fn s33_roundtrip() {
+        for &value in &[0, 1, -1, -64, 64, u32::MAX as i64, -(1 << 32)] {
+            let mut writer = BinaryWriter::new();
+            writer.write_leb128_s33(value);
+            let mut reader = BinaryReader::new(writer.as_slice());
+            assert_eq!(Ok(value), reader.read_leb128_s33());
+            assert!(reader.eof());
+        }
+    }
+
+    #[test]
+    fn s64_roundtrip() {
+        for &value in &[0, 1, -1, 63, -64, 64, -65, -123_456, i64::MIN, i64::MAX] {
+            let mut writer = BinaryWriter::new();
+            writer.write_leb128_s64(value);
+            let mut reader = BinaryReader::new(writer.as_slice());
+            assert_eq!(Ok(value), reader.read_leb128_s64());
+            assert!(reader.eof());
+        }
+    }
+
+    #[test]
+    fn s32_is_minimal() {
+        let mut writer = BinaryWriter::new();
+        writer.write_leb128_s32(-123_456);
+        assert_eq!(&[0xC0, 0xBB, 0x78], writer.as_slice());
+    }
+}
@@ -0,0 +1,9 @@
+use crate::encoder::BinaryWriter;
+
+pub trait SectionEncoder {
+    /// The id byte written before the section size.
+    fn id(&self) -> u8;
+
+    /// Writes the section contents, without the id and size prefix.
+    fn encode(&self, writer: &mut BinaryWriter);
+}
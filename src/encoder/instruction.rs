@@ -0,0 +1,1140 @@
+use crate::encoder::BinaryWriter;
+use crate::types::{Instruction, CatchClause};
+use crate::types::Instruction::*;
+
+impl BinaryWriter {
+    /// Writes `instruction` with its immediates, the inverse of `InstructionReader::read`.
+    pub fn write_instruction(&mut self, instruction: &Instruction) {
+        match instruction {
+            Unreachable => self.write_byte(0x00),
+            Nop => self.write_byte(0x01),
+            Block { block_type } => {
+                self.write_byte(0x02);
+                self.write_block_type(*block_type);
+            },
+            Loop { block_type } => {
+                self.write_byte(0x03);
+                self.write_block_type(*block_type);
+            },
+            If { block_type } => {
+                self.write_byte(0x04);
+                self.write_block_type(*block_type);
+            },
+            Else => self.write_byte(0x05),
+            Try { block_type } => {
+                self.write_byte(0x06);
+                self.write_block_type(*block_type);
+            },
+            Catch { tag_index } => {
+                self.write_byte(0x07);
+                self.write_leb128_u32(tag_index.0);
+            },
+            Throw { tag_index } => {
+                self.write_byte(0x08);
+                self.write_leb128_u32(tag_index.0);
+            },
+            Rethrow { label_index } => {
+                self.write_byte(0x09);
+                self.write_leb128_u32(label_index.0);
+            },
+            ThrowRef => self.write_byte(0x0A),
+            End => self.write_byte(0x0B),
+            Branch { label_index } => {
+                self.write_byte(0x0C);
+                self.write_leb128_u32(label_index.0);
+            },
+            BranchIf { label_index } => {
+                self.write_byte(0x0D);
+                self.write_leb128_u32(label_index.0);
+            },
+            BranchTable { branch_table_reader } => {
+                self.write_byte(0x0E);
+                self.write_bytes(branch_table_reader.get_data());
+            },
+            Return => self.write_byte(0x0F),
+            Call { func_index } => {
+                self.write_byte(0x10);
+                self.write_leb128_u32(func_index.0);
+            },
+            CallIndirect { type_index, table_index } => {
+                self.write_byte(0x11);
+                self.write_leb128_u32(type_index.0);
+                self.write_leb128_u32(table_index.0);
+            },
+            ReturnCall { func_index } => {
+                self.write_byte(0x12);
+                self.write_leb128_u32(func_index.0);
+            },
+            ReturnCallIndirect { type_index, table_index } => {
+                self.write_byte(0x13);
+                self.write_leb128_u32(type_index.0);
+                self.write_leb128_u32(table_index.0);
+            },
+            Delegate { label_index } => {
+                self.write_byte(0x18);
+                self.write_leb128_u32(label_index.0);
+            },
+            CatchAll => self.write_byte(0x19),
+
+            Drop => self.write_byte(0x1A),
+            Select => self.write_byte(0x1B),
+            TypedSelect { value_type } => {
+                self.write_byte(0x1C);
+                self.write_leb128_u32(1);
+                self.write_value_type(*value_type);
+            },
+            TryTable { block_type, catches } => {
+                self.write_byte(0x1F);
+                self.write_block_type(*block_type);
+                self.write_leb128_u32(catches.len() as u32);
+                for catch in catches.iter() {
+                    self.write_catch_clause(catch);
+                }
+            },
+
+            LocalGet { local_index } => {
+                self.write_byte(0x20);
+                self.write_leb128_u32(local_index.0);
+            },
+            LocalSet { local_index } => {
+                self.write_byte(0x21);
+                self.write_leb128_u32(local_index.0);
+            },
+            LocalTee { local_index } => {
+                self.write_byte(0x22);
+                self.write_leb128_u32(local_index.0);
+            },
+            GlobalGet { global_index } => {
+                self.write_byte(0x23);
+                self.write_leb128_u32(global_index.0);
+            },
+            GlobalSet { global_index } => {
+                self.write_byte(0x24);
+                self.write_leb128_u32(global_index.0);
+            },
+            TableGet { table_index } => {
+                self.write_byte(0x25);
+                self.write_leb128_u32(table_index.0);
+            },
+            TableSet { table_index } => {
+                self.write_byte(0x26);
+                self.write_leb128_u32(table_index.0);
+            },
+
+            I32Load { memory_argument } => {
+                self.write_byte(0x28);
+                self.write_memory_argument(memory_argument);
+            },
+            I64Load { memory_argument } => {
+                self.write_byte(0x29);
+                self.write_memory_argument(memory_argument);
+            },
+            F32Load { memory_argument } => {
+                self.write_byte(0x2A);
+                self.write_memory_argument(memory_argument);
+            },
+            F64Load { memory_argument } => {
+                self.write_byte(0x2B);
+                self.write_memory_argument(memory_argument);
+            },
+            I32Load8s { memory_argument } => {
+                self.write_byte(0x2C);
+                self.write_memory_argument(memory_argument);
+            },
+            I32Load8u { memory_argument } => {
+                self.write_byte(0x2D);
+                self.write_memory_argument(memory_argument);
+            },
+            I32Load16s { memory_argument } => {
+                self.write_byte(0x2E);
+                self.write_memory_argument(memory_argument);
+            },
+            I32Load16u { memory_argument } => {
+                self.write_byte(0x2F);
+                self.write_memory_argument(memory_argument);
+            },
+            I64Load8s { memory_argument } => {
+                self.write_byte(0x30);
+                self.write_memory_argument(memory_argument);
+            },
+            I64Load8u { memory_argument } => {
+                self.write_byte(0x31);
+                self.write_memory_argument(memory_argument);
+            },
+            I64Load16s { memory_argument } => {
+                self.write_byte(0x32);
+                self.write_memory_argument(memory_argument);
+            },
+            I64Load16u { memory_argument } => {
+                self.write_byte(0x33);
+                self.write_memory_argument(memory_argument);
+            },
+            I64Load32s { memory_argument } => {
+                self.write_byte(0x34);
+                self.write_memory_argument(memory_argument);
+            },
+            I64Load32u { memory_argument } => {
+                self.write_byte(0x35);
+                self.write_memory_argument(memory_argument);
+            },
+            I32Store { memory_argument } => {
+                self.write_byte(0x36);
+                self.write_memory_argument(memory_argument);
+            },
+            I64Store { memory_argument } => {
+                self.write_byte(0x37);
+                self.write_memory_argument(memory_argument);
+            },
+            F32Store { memory_argument } => {
+                self.write_byte(0x38);
+                self.write_memory_argument(memory_argument);
+            },
+            F64Store { memory_argument } => {
+                self.write_byte(0x39);
+                self.write_memory_argument(memory_argument);
+            },
+            I32Store8 { memory_argument } => {
+                self.write_byte(0x3A);
+                self.write_memory_argument(memory_argument);
+            },
+            I32Store16 { memory_argument } => {
+                self.write_byte(0x3B);
+                self.write_memory_argument(memory_argument);
+            },
+            I64Store8 { memory_argument } => {
+                self.write_byte(0x3C);
+                self.write_memory_argument(memory_argument);
+            },
+            I64Store16 { memory_argument } => {
+                self.write_byte(0x3D);
+                self.write_memory_argument(memory_argument);
+            },
+            I64Store32 { memory_argument } => {
+                self.write_byte(0x3E);
+                self.write_memory_argument(memory_argument);
+            },
+            MemorySize { memory_index } => {
+                self.write_byte(0x3F);
+                self.write_leb128_u32(memory_index.0);
+            },
+            MemoryGrow { memory_index } => {
+                self.write_byte(0x40);
+                self.write_leb128_u32(memory_index.0);
+            },
+
+            I32Const(value) => {
+                self.write_byte(0x41);
+                self.write_leb128_s32(*value);
+            },
+            I64Const(value) => {
+                self.write_byte(0x42);
+                self.write_leb128_s64(*value);
+            },
+            F32Const(value) => {
+                self.write_byte(0x43);
+                self.write_f32(*value);
+            },
+            F64Const(value) => {
+                self.write_byte(0x44);
+                self.write_f64(*value);
+            },
+
+            I32Eqz => self.write_byte(0x45),
+            I32Eq => self.write_byte(0x46),
+            I32Ne => self.write_byte(0x47),
+            I32Lts => self.write_byte(0x48),
+            I32Ltu => self.write_byte(0x49),
+            I32Gts => self.write_byte(0x4A),
+            I32Gtu => self.write_byte(0x4B),
+            I32Les => self.write_byte(0x4C),
+            I32Leu => self.write_byte(0x4D),
+            I32Ges => self.write_byte(0x4E),
+            I32Geu => self.write_byte(0x4F),
+            I64Eqz => self.write_byte(0x50),
+            I64Eq => self.write_byte(0x51),
+            I64Ne => self.write_byte(0x52),
+            I64Lts => self.write_byte(0x53),
+            I64Ltu => self.write_byte(0x54),
+            I64Gts => self.write_byte(0x55),
+            I64Gtu => self.write_byte(0x56),
+            I64Les => self.write_byte(0x57),
+            I64Leu => self.write_byte(0x58),
+            I64Ges => self.write_byte(0x59),
+            I64Geu => self.write_byte(0x5A),
+            F32Eq => self.write_byte(0x5B),
+            F32Ne => self.write_byte(0x5C),
+            F32Lt => self.write_byte(0x5D),
+            F32Gt => self.write_byte(0x5E),
+            F32Le => self.write_byte(0x5F),
+            F32Ge => self.write_byte(0x60),
+            F64Eq => self.write_byte(0x61),
+            F64Ne => self.write_byte(0x62),
+            F64Lt => self.write_byte(0x63),
+            F64Gt => self.write_byte(0x64),
+            F64Le => self.write_byte(0x65),
+            F64Ge => self.write_byte(0x66),
+            I32Clz => self.write_byte(0x67),
+            I32Ctz => self.write_byte(0x68),
+            I32Popcnt => self.write_byte(0x69),
+            I32Add => self.write_byte(0x6A),
+            I32Sub => self.write_byte(0x6B),
+            I32Mul => self.write_byte(0x6C),
+            I32Divs => self.write_byte(0x6D),
+            I32Divu => self.write_byte(0x6E),
+            I32Rems => self.write_byte(0x6F),
+            I32Remu => self.write_byte(0x70),
+            I32And => self.write_byte(0x71),
+            I32Or => self.write_byte(0x72),
+            I32Xor => self.write_byte(0x73),
+            I32Shl => self.write_byte(0x74),
+            I32Shrs => self.write_byte(0x75),
+            I32Shru => self.write_byte(0x76),
+            I32Rotl => self.write_byte(0x77),
+            I32Rotr => self.write_byte(0x78),
+            I64Clz => self.write_byte(0x79),
+            I64Ctz => self.write_byte(0x7A),
+            I64Popcnt => self.write_byte(0x7B),
+            I64Add => self.write_byte(0x7C),
+            I64Sub => self.write_byte(0x7D),
+            I64Mul => self.write_byte(0x7E),
+            I64Divs => self.write_byte(0x7F),
+            I64Divu => self.write_byte(0x80),
+            I64Rems => self.write_byte(0x81),
+            I64Remu => self.write_byte(0x82),
+            I64And => self.write_byte(0x83),
+            I64Or => self.write_byte(0x84),
+            I64Xor => self.write_byte(0x85),
+            I64Shl => self.write_byte(0x86),
+            I64Shrs => self.write_byte(0x87),
+            I64Shru => self.write_byte(0x88),
+            I64Rotl => self.write_byte(0x89),
+            I64Rotr => self.write_byte(0x8A),
+            F32Abs => self.write_byte(0x8B),
+            F32Neg => self.write_byte(0x8C),
+            F32Ceil => self.write_byte(0x8D),
+            F32Floor => self.write_byte(0x8E),
+            F32Trunc => self.write_byte(0x8F),
+            F32Nearest => self.write_byte(0x90),
+            F32Sqrt => self.write_byte(0x91),
+            F32Add => self.write_byte(0x92),
+            F32Sub => self.write_byte(0x93),
+            F32Mul => self.write_byte(0x94),
+            F32Div => self.write_byte(0x95),
+            F32Min => self.write_byte(0x96),
+            F32Max => self.write_byte(0x97),
+            F32Copysign => self.write_byte(0x98),
+            F64Abs => self.write_byte(0x99),
+            F64Neg => self.write_byte(0x9A),
+            F64Ceil => self.write_byte(0x9B),
+            F64Floor => self.write_byte(0x9C),
+            F64Trunc => self.write_byte(0x9D),
+            F64Nearest => self.write_byte(0x9E),
+            F64Sqrt => self.write_byte(0x9F),
+            F64Add => self.write_byte(0xA0),
+            F64Sub => self.write_byte(0xA1),
+            F64Mul => self.write_byte(0xA2),
+            F64Div => self.write_byte(0xA3),
+            F64Min => self.write_byte(0xA4),
+            F64Max => self.write_byte(0xA5),
+            F64Copysign => self.write_byte(0xA6),
+            I32WrapI64 => self.write_byte(0xA7),
+            I32TruncF32s => self.write_byte(0xA8),
+            I32TruncF32u => self.write_byte(0xA9),
+            I32TruncF64s => self.write_byte(0xAA),
+            I32TruncF64u => self.write_byte(0xAB),
+            I64ExtendI32s => self.write_byte(0xAC),
+            I64ExtendI32u => self.write_byte(0xAD),
+            I64TruncF32s => self.write_byte(0xAE),
+            I64TruncF32u => self.write_byte(0xAF),
+            I64TruncF64s => self.write_byte(0xB0),
+            I64TruncF64u => self.write_byte(0xB1),
+            F32ConvertI32s => self.write_byte(0xB2),
+            F32ConvertI32u => self.write_byte(0xB3),
+            F32ConvertI64s => self.write_byte(0xB4),
+            F32ConvertI64u => self.write_byte(0xB5),
+            F32DemoteF64 => self.write_byte(0xB6),
+            F64ConvertI32s => self.write_byte(0xB7),
+            F64ConvertI32u => self.write_byte(0xB8),
+            F64ConvertI64s => self.write_byte(0xB9),
+            F64ConvertI64u => self.write_byte(0xBA),
+            F64PromoteF32 => self.write_byte(0xBB),
+            I32ReinterpretF32 => self.write_byte(0xBC),
+            I64ReinterpretF64 => self.write_byte(0xBD),
+            F32ReinterpretI32 => self.write_byte(0xBE),
+            F64ReinterpretI64 => self.write_byte(0xBF),
+            I32Extend8s => self.write_byte(0xC0),
+            I32Extend16s => self.write_byte(0xC1),
+            I64Extend8s => self.write_byte(0xC2),
+            I64Extend16s => self.write_byte(0xC3),
+            I64Extend32s => self.write_byte(0xC4),
+
+            RefNull { ref_type } => {
+                self.write_byte(0xD0);
+                self.write_value_type(*ref_type);
+            },
+            RefIsNull => self.write_byte(0xD1),
+            RefFunc { func_index } => {
+                self.write_byte(0xD2);
+                self.write_leb128_u32(func_index.0);
+            },
+
+            I32TruncSatF32s => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(0);
+            },
+            I32TruncSatF32u => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(1);
+            },
+            I32TruncSatF64s => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(2);
+            },
+            I32TruncSatF64u => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(3);
+            },
+            I64TruncSatF32s => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(4);
+            },
+            I64TruncSatF32u => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(5);
+            },
+            I64TruncSatF64s => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(6);
+            },
+            I64TruncSatF64u => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(7);
+            },
+            MemoryInit { data_index, memory_index } => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(8);
+                self.write_leb128_u32(data_index.0);
+                self.write_leb128_u32(memory_index.0);
+            },
+            DataDrop { data_index } => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(9);
+                self.write_leb128_u32(data_index.0);
+            },
+            MemoryCopy { destination_memory_index, source_memory_index } => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(10);
+                self.write_leb128_u32(destination_memory_index.0);
+                self.write_leb128_u32(source_memory_index.0);
+            },
+            MemoryFill { memory_index } => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(11);
+                self.write_leb128_u32(memory_index.0);
+            },
+            TableInit { element_index, table_index } => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(12);
+                self.write_leb128_u32(element_index.0);
+                self.write_leb128_u32(table_index.0);
+            },
+            ElemDrop { element_index } => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(13);
+                self.write_leb128_u32(element_index.0);
+            },
+            TableCopy { destination_table_index, source_table_index } => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(14);
+                self.write_leb128_u32(destination_table_index.0);
+                self.write_leb128_u32(source_table_index.0);
+            },
+            TableGrow { table_index } => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(15);
+                self.write_leb128_u32(table_index.0);
+            },
+            TableSize { table_index } => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(16);
+                self.write_leb128_u32(table_index.0);
+            },
+            TableFill { table_index } => {
+                self.write_byte(0xFC);
+                self.write_leb128_u32(17);
+                self.write_leb128_u32(table_index.0);
+            },
+
+            V128Load { memory_argument } => {
+                self.write_simd_opcode(0);
+                self.write_memory_argument(memory_argument);
+            },
+            V128Load8x8s { memory_argument } => {
+                self.write_simd_opcode(1);
+                self.write_memory_argument(memory_argument);
+            },
+            V128Load8x8u { memory_argument } => {
+                self.write_simd_opcode(2);
+                self.write_memory_argument(memory_argument);
+            },
+            V128Load16x4s { memory_argument } => {
+                self.write_simd_opcode(3);
+                self.write_memory_argument(memory_argument);
+            },
+            V128Load16x4u { memory_argument } => {
+                self.write_simd_opcode(4);
+                self.write_memory_argument(memory_argument);
+            },
+            V128Load32x2s { memory_argument } => {
+                self.write_simd_opcode(5);
+                self.write_memory_argument(memory_argument);
+            },
+            V128Load32x2u { memory_argument } => {
+                self.write_simd_opcode(6);
+                self.write_memory_argument(memory_argument);
+            },
+            V128Load8Splat { memory_argument } => {
+                self.write_simd_opcode(7);
+                self.write_memory_argument(memory_argument);
+            },
+            V128Load16Splat { memory_argument } => {
+                self.write_simd_opcode(8);
+                self.write_memory_argument(memory_argument);
+            },
+            V128Load32Splat { memory_argument } => {
+                self.write_simd_opcode(9);
+                self.write_memory_argument(memory_argument);
+            },
+            V128Load64Splat { memory_argument } => {
+                self.write_simd_opcode(10);
+                self.write_memory_argument(memory_argument);
+            },
+            V128Store { memory_argument } => {
+                self.write_simd_opcode(11);
+                self.write_memory_argument(memory_argument);
+            },
+            V128Const(value) => {
+                self.write_simd_opcode(12);
+                self.write_bytes(&value.to_le_bytes());
+            },
+            I8x16Shuffle { lanes } => {
+                self.write_simd_opcode(13);
+                self.write_bytes(lanes);
+            },
+            I8x16Swizzle => self.write_simd_opcode(14),
+            I8x16Splat => self.write_simd_opcode(15),
+            I16x8Splat => self.write_simd_opcode(16),
+            I32x4Splat => self.write_simd_opcode(17),
+            I64x2Splat => self.write_simd_opcode(18),
+            F32x4Splat => self.write_simd_opcode(19),
+            F64x2Splat => self.write_simd_opcode(20),
+            I8x16ExtractLanes { lane } => {
+                self.write_simd_opcode(21);
+                self.write_byte(*lane);
+            },
+            I8x16ExtractLaneu { lane } => {
+                self.write_simd_opcode(22);
+                self.write_byte(*lane);
+            },
+            I8x16ReplaceLane { lane } => {
+                self.write_simd_opcode(23);
+                self.write_byte(*lane);
+            },
+            I16x8ExtractLanes { lane } => {
+                self.write_simd_opcode(24);
+                self.write_byte(*lane);
+            },
+            I16x8ExtractLaneu { lane } => {
+                self.write_simd_opcode(25);
+                self.write_byte(*lane);
+            },
+            I16x8ReplaceLane { lane } => {
+                self.write_simd_opcode(26);
+                self.write_byte(*lane);
+            },
+            I32x4ExtractLane { lane } => {
+                self.write_simd_opcode(27);
+                self.write_byte(*lane);
+            },
+            I32x4ReplaceLane { lane } => {
+                self.write_simd_opcode(28);
+                self.write_byte(*lane);
+            },
+            I64x2ExtractLane { lane } => {
+                self.write_simd_opcode(29);
+                self.write_byte(*lane);
+            },
+            I64x2ReplaceLane { lane } => {
+                self.write_simd_opcode(30);
+                self.write_byte(*lane);
+            },
+            F32x4ExtractLane { lane } => {
+                self.write_simd_opcode(31);
+                self.write_byte(*lane);
+            },
+            F32x4ReplaceLane { lane } => {
+                self.write_simd_opcode(32);
+                self.write_byte(*lane);
+            },
+            F64x2ExtractLane { lane } => {
+                self.write_simd_opcode(33);
+                self.write_byte(*lane);
+            },
+            F64x2ReplaceLane { lane } => {
+                self.write_simd_opcode(34);
+                self.write_byte(*lane);
+            },
+            I8x16Eq => self.write_simd_opcode(35),
+            I8x16Ne => self.write_simd_opcode(36),
+            I8x16Lts => self.write_simd_opcode(37),
+            I8x16Ltu => self.write_simd_opcode(38),
+            I8x16Gts => self.write_simd_opcode(39),
+            I8x16Gtu => self.write_simd_opcode(40),
+            I8x16Les => self.write_simd_opcode(41),
+            I8x16Leu => self.write_simd_opcode(42),
+            I8x16Ges => self.write_simd_opcode(43),
+            I8x16Geu => self.write_simd_opcode(44),
+            I16x8Eq => self.write_simd_opcode(45),
+            I16x8Ne => self.write_simd_opcode(46),
+            I16x8Lts => self.write_simd_opcode(47),
+            I16x8Ltu => self.write_simd_opcode(48),
+            I16x8Gts => self.write_simd_opcode(49),
+            I16x8Gtu => self.write_simd_opcode(50),
+            I16x8Les => self.write_simd_opcode(51),
+            I16x8Leu => self.write_simd_opcode(52),
+            I16x8Ges => self.write_simd_opcode(53),
+            I16x8Geu => self.write_simd_opcode(54),
+            I32x4Eq => self.write_simd_opcode(55),
+            I32x4Ne => self.write_simd_opcode(56),
+            I32x4Lts => self.write_simd_opcode(57),
+            I32x4Ltu => self.write_simd_opcode(58),
+            I32x4Gts => self.write_simd_opcode(59),
+            I32x4Gtu => self.write_simd_opcode(60),
+            I32x4Les => self.write_simd_opcode(61),
+            I32x4Leu => self.write_simd_opcode(62),
+            I32x4Ges => self.write_simd_opcode(63),
+            I32x4Geu => self.write_simd_opcode(64),
+            F32x4Eq => self.write_simd_opcode(65),
+            F32x4Ne => self.write_simd_opcode(66),
+            F32x4Lt => self.write_simd_opcode(67),
+            F32x4Gt => self.write_simd_opcode(68),
+            F32x4Le => self.write_simd_opcode(69),
+            F32x4Ge => self.write_simd_opcode(70),
+            F64x2Eq => self.write_simd_opcode(71),
+            F64x2Ne => self.write_simd_opcode(72),
+            F64x2Lt => self.write_simd_opcode(73),
+            F64x2Gt => self.write_simd_opcode(74),
+            F64x2Le => self.write_simd_opcode(75),
+            F64x2Ge => self.write_simd_opcode(76),
+            V128Not => self.write_simd_opcode(77),
+            V128And => self.write_simd_opcode(78),
+            V128Andnot => self.write_simd_opcode(79),
+            V128Or => self.write_simd_opcode(80),
+            V128Xor => self.write_simd_opcode(81),
+            V128Bitselect => self.write_simd_opcode(82),
+            V128AnyTrue => self.write_simd_opcode(83),
+            V128Load8Lane { memory_argument, lane } => {
+                self.write_simd_opcode(84);
+                self.write_memory_argument(memory_argument);
+                self.write_byte(*lane);
+            },
+            V128Load16Lane { memory_argument, lane } => {
+                self.write_simd_opcode(85);
+                self.write_memory_argument(memory_argument);
+                self.write_byte(*lane);
+            },
+            V128Load32Lane { memory_argument, lane } => {
+                self.write_simd_opcode(86);
+                self.write_memory_argument(memory_argument);
+                self.write_byte(*lane);
+            },
+            V128Load64Lane { memory_argument, lane } => {
+                self.write_simd_opcode(87);
+                self.write_memory_argument(memory_argument);
+                self.write_byte(*lane);
+            },
+            V128Store8Lane { memory_argument, lane } => {
+                self.write_simd_opcode(88);
+                self.write_memory_argument(memory_argument);
+                self.write_byte(*lane);
+            },
+            V128Store16Lane { memory_argument, lane } => {
+                self.write_simd_opcode(89);
+                self.write_memory_argument(memory_argument);
+                self.write_byte(*lane);
+            },
+            V128Store32Lane { memory_argument, lane } => {
+                self.write_simd_opcode(90);
+                self.write_memory_argument(memory_argument);
+                self.write_byte(*lane);
+            },
+            V128Store64Lane { memory_argument, lane } => {
+                self.write_simd_opcode(91);
+                self.write_memory_argument(memory_argument);
+                self.write_byte(*lane);
+            },
+            V128Load32Zero { memory_argument } => {
+                self.write_simd_opcode(92);
+                self.write_memory_argument(memory_argument);
+            },
+            V128Load64Zero { memory_argument } => {
+                self.write_simd_opcode(93);
+                self.write_memory_argument(memory_argument);
+            },
+            F32x4DemoteF64x2Zero => self.write_simd_opcode(94),
+            F64x2PromoteLowF32x4 => self.write_simd_opcode(95),
+            I8x16Abs => self.write_simd_opcode(96),
+            I8x16Neg => self.write_simd_opcode(97),
+            I8x16Popcnt => self.write_simd_opcode(98),
+            I8x16AllTrue => self.write_simd_opcode(99),
+            I8x16Bitmask => self.write_simd_opcode(100),
+            I8x16NarrowI16x8s => self.write_simd_opcode(101),
+            I8x16NarrowI16x8u => self.write_simd_opcode(102),
+            F32x4Ceil => self.write_simd_opcode(103),
+            F32x4Floor => self.write_simd_opcode(104),
+            F32x4Trunc => self.write_simd_opcode(105),
+            F32x4Nearest => self.write_simd_opcode(106),
+            I8x16Shl => self.write_simd_opcode(107),
+            I8x16Shrs => self.write_simd_opcode(108),
+            I8x16Shru => self.write_simd_opcode(109),
+            I8x16Add => self.write_simd_opcode(110),
+            I8x16AddSats => self.write_simd_opcode(111),
+            I8x16AddSatu => self.write_simd_opcode(112),
+            I8x16Sub => self.write_simd_opcode(113),
+            I8x16SubSats => self.write_simd_opcode(114),
+            I8x16SubSatu => self.write_simd_opcode(115),
+            F64x2Ceil => self.write_simd_opcode(116),
+            F64x2Floor => self.write_simd_opcode(117),
+            I8x16Mins => self.write_simd_opcode(118),
+            I8x16Minu => self.write_simd_opcode(119),
+            I8x16Maxs => self.write_simd_opcode(120),
+            I8x16Maxu => self.write_simd_opcode(121),
+            F64x2Trunc => self.write_simd_opcode(122),
+            I8x16Avgru => self.write_simd_opcode(123),
+            I16x8ExtaddPairwiseI8x16s => self.write_simd_opcode(124),
+            I16x8ExtaddPairwiseI8x16u => self.write_simd_opcode(125),
+            I32x4ExtaddPairwiseI16x8s => self.write_simd_opcode(126),
+            I32x4ExtaddPairwiseI16x8u => self.write_simd_opcode(127),
+            I16x8Abs => self.write_simd_opcode(128),
+            I16x8Neg => self.write_simd_opcode(129),
+            I16x8Q15mulrSats => self.write_simd_opcode(130),
+            I16x8AllTrue => self.write_simd_opcode(131),
+            I16x8Bitmask => self.write_simd_opcode(132),
+            I16x8NarrowI32x4s => self.write_simd_opcode(133),
+            I16x8NarrowI32x4u => self.write_simd_opcode(134),
+            I16x8ExtendLowI8x16s => self.write_simd_opcode(135),
+            I16x8ExtendHighI8x16s => self.write_simd_opcode(136),
+            I16x8ExtendLowI8x16u => self.write_simd_opcode(137),
+            I16x8ExtendHighI8x16u => self.write_simd_opcode(138),
+            I16x8Shl => self.write_simd_opcode(139),
+            I16x8Shrs => self.write_simd_opcode(140),
+            I16x8Shru => self.write_simd_opcode(141),
+            I16x8Add => self.write_simd_opcode(142),
+            I16x8AddSats => self.write_simd_opcode(143),
+            I16x8AddSatu => self.write_simd_opcode(144),
+            I16x8Sub => self.write_simd_opcode(145),
+            I16x8SubSats => self.write_simd_opcode(146),
+            I16x8SubSatu => self.write_simd_opcode(147),
+            F64x2Nearest => self.write_simd_opcode(148),
+            I16x8Mul => self.write_simd_opcode(149),
+            I16x8Mins => self.write_simd_opcode(150),
+            I16x8Minu => self.write_simd_opcode(151),
+            I16x8Maxs => self.write_simd_opcode(152),
+            I16x8Maxu => self.write_simd_opcode(153),
+            I16x8Avgru => self.write_simd_opcode(155),
+            I16x8ExtmulLowI8x16s => self.write_simd_opcode(156),
+            I16x8ExtmulHighI8x16s => self.write_simd_opcode(157),
+            I16x8ExtmulLowI8x16u => self.write_simd_opcode(158),
+            I16x8ExtmulHighI8x16u => self.write_simd_opcode(159),
+            I32x4Abs => self.write_simd_opcode(160),
+            I32x4Neg => self.write_simd_opcode(161),
+            I32x4AllTrue => self.write_simd_opcode(163),
+            I32x4Bitmask => self.write_simd_opcode(164),
+            I32x4ExtendLowI16x8s => self.write_simd_opcode(167),
+            I32x4ExtendHighI16x8s => self.write_simd_opcode(168),
+            I32x4ExtendLowI16x8u => self.write_simd_opcode(169),
+            I32x4ExtendHighI16x8u => self.write_simd_opcode(170),
+            I32x4Shl => self.write_simd_opcode(171),
+            I32x4Shrs => self.write_simd_opcode(172),
+            I32x4Shru => self.write_simd_opcode(173),
+            I32x4Add => self.write_simd_opcode(174),
+            I32x4Sub => self.write_simd_opcode(177),
+            I32x4Mul => self.write_simd_opcode(181),
+            I32x4Mins => self.write_simd_opcode(182),
+            I32x4Minu => self.write_simd_opcode(183),
+            I32x4Maxs => self.write_simd_opcode(184),
+            I32x4Maxu => self.write_simd_opcode(185),
+            I32x4DotI16x8s => self.write_simd_opcode(186),
+            I32x4ExtmulLowI16x8s => self.write_simd_opcode(188),
+            I32x4ExtmulHighI16x8s => self.write_simd_opcode(189),
+            I32x4ExtmulLowI16x8u => self.write_simd_opcode(190),
+            I32x4ExtmulHighI16x8u => self.write_simd_opcode(191),
+            I64x2Abs => self.write_simd_opcode(192),
+            I64x2Neg => self.write_simd_opcode(193),
+            I64x2AllTrue => self.write_simd_opcode(195),
+            I64x2Bitmask => self.write_simd_opcode(196),
+            I64x2ExtendLowI32x4s => self.write_simd_opcode(199),
+            I64x2ExtendHighI32x4s => self.write_simd_opcode(200),
+            I64x2ExtendLowI32x4u => self.write_simd_opcode(201),
+            I64x2ExtendHighI32x4u => self.write_simd_opcode(202),
+            I64x2Shl => self.write_simd_opcode(203),
+            I64x2Shrs => self.write_simd_opcode(204),
+            I64x2Shru => self.write_simd_opcode(205),
+            I64x2Add => self.write_simd_opcode(206),
+            I64x2Sub => self.write_simd_opcode(209),
+            I64x2Mul => self.write_simd_opcode(213),
+            I64x2Eq => self.write_simd_opcode(214),
+            I64x2Ne => self.write_simd_opcode(215),
+            I64x2Lts => self.write_simd_opcode(216),
+            I64x2Gts => self.write_simd_opcode(217),
+            I64x2Les => self.write_simd_opcode(218),
+            I64x2Ges => self.write_simd_opcode(219),
+            I64x2ExtmulLowI32x4s => self.write_simd_opcode(220),
+            I64x2ExtmulHighI32x4s => self.write_simd_opcode(221),
+            I64x2ExtmulLowI32x4u => self.write_simd_opcode(222),
+            I64x2ExtmulHighI32x4u => self.write_simd_opcode(223),
+            F32x4Abs => self.write_simd_opcode(224),
+            F32x4Neg => self.write_simd_opcode(225),
+            F32x4Sqrt => self.write_simd_opcode(227),
+            F32x4Add => self.write_simd_opcode(228),
+            F32x4Sub => self.write_simd_opcode(229),
+            F32x4Mul => self.write_simd_opcode(230),
+            F32x4Div => self.write_simd_opcode(231),
+            F32x4Min => self.write_simd_opcode(232),
+            F32x4Max => self.write_simd_opcode(233),
+            F32x4Pmin => self.write_simd_opcode(234),
+            F32x4Pmax => self.write_simd_opcode(235),
+            F64x2Abs => self.write_simd_opcode(236),
+            F64x2Neg => self.write_simd_opcode(237),
+            F64x2Sqrt => self.write_simd_opcode(239),
+            F64x2Add => self.write_simd_opcode(240),
+            F64x2Sub => self.write_simd_opcode(241),
+            F64x2Mul => self.write_simd_opcode(242),
+            F64x2Div => self.write_simd_opcode(243),
+            F64x2Min => self.write_simd_opcode(244),
+            F64x2Max => self.write_simd_opcode(245),
+            F64x2Pmin => self.write_simd_opcode(246),
+            F64x2Pmax => self.write_simd_opcode(247),
+            I32x4TruncSatF32x4s => self.write_simd_opcode(248),
+            I32x4TruncSatF32x4u => self.write_simd_opcode(249),
+            F32x4ConvertI32x4s => self.write_simd_opcode(250),
+            F32x4ConvertI32x4u => self.write_simd_opcode(251),
+            I32x4TruncSatF64x2sZero => self.write_simd_opcode(252),
+            I32x4TruncSatF64x2uZero => self.write_simd_opcode(253),
+            F64x2ConvertLowI32x4s => self.write_simd_opcode(254),
+            F64x2ConvertLowI32x4u => self.write_simd_opcode(255),
+
+            MemoryAtomicNotify { memory_argument } => {
+                self.write_atomic_opcode(0);
+                self.write_memory_argument(memory_argument);
+            },
+            MemoryAtomicWait32 { memory_argument } => {
+                self.write_atomic_opcode(1);
+                self.write_memory_argument(memory_argument);
+            },
+            MemoryAtomicWait64 { memory_argument } => {
+                self.write_atomic_opcode(2);
+                self.write_memory_argument(memory_argument);
+            },
+            AtomicFence => {
+                self.write_atomic_opcode(3);
+                self.write_byte(0x00);
+            },
+            I32AtomicLoad { memory_argument } => {
+                self.write_atomic_opcode(16);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicLoad { memory_argument } => {
+                self.write_atomic_opcode(17);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicLoad8u { memory_argument } => {
+                self.write_atomic_opcode(18);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicLoad16u { memory_argument } => {
+                self.write_atomic_opcode(19);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicLoad8u { memory_argument } => {
+                self.write_atomic_opcode(20);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicLoad16u { memory_argument } => {
+                self.write_atomic_opcode(21);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicLoad32u { memory_argument } => {
+                self.write_atomic_opcode(22);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicStore { memory_argument } => {
+                self.write_atomic_opcode(23);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicStore { memory_argument } => {
+                self.write_atomic_opcode(24);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicStore8 { memory_argument } => {
+                self.write_atomic_opcode(25);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicStore16 { memory_argument } => {
+                self.write_atomic_opcode(26);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicStore8 { memory_argument } => {
+                self.write_atomic_opcode(27);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicStore16 { memory_argument } => {
+                self.write_atomic_opcode(28);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicStore32 { memory_argument } => {
+                self.write_atomic_opcode(29);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmwAdd { memory_argument } => {
+                self.write_atomic_opcode(30);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmwAdd { memory_argument } => {
+                self.write_atomic_opcode(31);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmw8Addu { memory_argument } => {
+                self.write_atomic_opcode(32);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmw16Addu { memory_argument } => {
+                self.write_atomic_opcode(33);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw8Addu { memory_argument } => {
+                self.write_atomic_opcode(34);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw16Addu { memory_argument } => {
+                self.write_atomic_opcode(35);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw32Addu { memory_argument } => {
+                self.write_atomic_opcode(36);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmwSub { memory_argument } => {
+                self.write_atomic_opcode(37);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmwSub { memory_argument } => {
+                self.write_atomic_opcode(38);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmw8Subu { memory_argument } => {
+                self.write_atomic_opcode(39);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmw16Subu { memory_argument } => {
+                self.write_atomic_opcode(40);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw8Subu { memory_argument } => {
+                self.write_atomic_opcode(41);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw16Subu { memory_argument } => {
+                self.write_atomic_opcode(42);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw32Subu { memory_argument } => {
+                self.write_atomic_opcode(43);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmwAnd { memory_argument } => {
+                self.write_atomic_opcode(44);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmwAnd { memory_argument } => {
+                self.write_atomic_opcode(45);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmw8Andu { memory_argument } => {
+                self.write_atomic_opcode(46);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmw16Andu { memory_argument } => {
+                self.write_atomic_opcode(47);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw8Andu { memory_argument } => {
+                self.write_atomic_opcode(48);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw16Andu { memory_argument } => {
+                self.write_atomic_opcode(49);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw32Andu { memory_argument } => {
+                self.write_atomic_opcode(50);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmwOr { memory_argument } => {
+                self.write_atomic_opcode(51);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmwOr { memory_argument } => {
+                self.write_atomic_opcode(52);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmw8Oru { memory_argument } => {
+                self.write_atomic_opcode(53);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmw16Oru { memory_argument } => {
+                self.write_atomic_opcode(54);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw8Oru { memory_argument } => {
+                self.write_atomic_opcode(55);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw16Oru { memory_argument } => {
+                self.write_atomic_opcode(56);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw32Oru { memory_argument } => {
+                self.write_atomic_opcode(57);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmwXor { memory_argument } => {
+                self.write_atomic_opcode(58);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmwXor { memory_argument } => {
+                self.write_atomic_opcode(59);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmw8Xoru { memory_argument } => {
+                self.write_atomic_opcode(60);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmw16Xoru { memory_argument } => {
+                self.write_atomic_opcode(61);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw8Xoru { memory_argument } => {
+                self.write_atomic_opcode(62);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw16Xoru { memory_argument } => {
+                self.write_atomic_opcode(63);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw32Xoru { memory_argument } => {
+                self.write_atomic_opcode(64);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmwXchg { memory_argument } => {
+                self.write_atomic_opcode(65);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmwXchg { memory_argument } => {
+                self.write_atomic_opcode(66);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmw8Xchgu { memory_argument } => {
+                self.write_atomic_opcode(67);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmw16Xchgu { memory_argument } => {
+                self.write_atomic_opcode(68);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw8Xchgu { memory_argument } => {
+                self.write_atomic_opcode(69);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw16Xchgu { memory_argument } => {
+                self.write_atomic_opcode(70);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw32Xchgu { memory_argument } => {
+                self.write_atomic_opcode(71);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmwCmpxchg { memory_argument } => {
+                self.write_atomic_opcode(72);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmwCmpxchg { memory_argument } => {
+                self.write_atomic_opcode(73);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmw8Cmpxchgu { memory_argument } => {
+                self.write_atomic_opcode(74);
+                self.write_memory_argument(memory_argument);
+            },
+            I32AtomicRmw16Cmpxchgu { memory_argument } => {
+                self.write_atomic_opcode(75);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw8Cmpxchgu { memory_argument } => {
+                self.write_atomic_opcode(76);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw16Cmpxchgu { memory_argument } => {
+                self.write_atomic_opcode(77);
+                self.write_memory_argument(memory_argument);
+            },
+            I64AtomicRmw32Cmpxchgu { memory_argument } => {
+                self.write_atomic_opcode(78);
+                self.write_memory_argument(memory_argument);
+            },
+        }
+    }
+
+    fn write_catch_clause(&mut self, catch: &CatchClause) {
+        match catch {
+            CatchClause::Catch { tag_index, label_index } => {
+                self.write_byte(0x00);
+                self.write_leb128_u32(tag_index.0);
+                self.write_leb128_u32(label_index.0);
+            },
+            CatchClause::CatchRef { tag_index, label_index } => {
+                self.write_byte(0x01);
+                self.write_leb128_u32(tag_index.0);
+                self.write_leb128_u32(label_index.0);
+            },
+            CatchClause::CatchAll { label_index } => {
+                self.write_byte(0x02);
+                self.write_leb128_u32(label_index.0);
+            },
+            CatchClause::CatchAllRef { label_index } => {
+                self.write_byte(0x03);
+                self.write_leb128_u32(label_index.0);
+            },
+        }
+    }
+
+    /// Writes the 0xFD prefix and the sub-opcode of a SIMD instruction.
+    fn write_simd_opcode(&mut self, opcode: u32) {
+        self.write_byte(0xFD);
+        self.write_leb128_u32(opcode);
+    }
+
+    /// Writes the 0xFE prefix and the sub-opcode of an atomic instruction.
+    fn write_atomic_opcode(&mut self, opcode: u32) {
+        self.write_byte(0xFE);
+        self.write_leb128_u32(opcode);
+    }
+
+    /// Writes a constant expression followed by its terminating `end`.
+    pub fn write_expression(&mut self, instructions: &[Instruction]) {
+        for instruction in instructions {
+            self.write_instruction(instruction);
+        }
+        self.write_byte(0x0B);
+    }
+}
@@ -0,0 +1,24 @@
+pub use section::code::{CodeSectionEncoder, FunctionBodyEncoder};
+pub use section::custom::CustomSectionEncoder;
+pub use section::data::DataSectionEncoder;
+pub use section::data_count::DataCountSectionEncoder;
+pub use section::element::ElementSectionEncoder;
+pub use section::export::ExportSectionEncoder;
+pub use section::function::FunctionSectionEncoder;
+pub use section::global::GlobalSectionEncoder;
+pub use section::import::ImportSectionEncoder;
+pub use section::memory::MemorySectionEncoder;
+pub use section::start::StartSectionEncoder;
+pub use section::table::TableSectionEncoder;
+pub use section::tag::TagSectionEncoder;
+pub use section::r#type::TypeSectionEncoder;
+
+pub use crate::encoder::binary::BinaryWriter;
+pub use crate::encoder::common::SectionEncoder;
+pub use crate::encoder::module::ModuleEncoder;
+
+mod binary;
+mod common;
+mod instruction;
+mod module;
+mod section;
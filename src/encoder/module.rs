@@ -0,0 +1,310 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+use crate::SectionReader;
+
+/// Builds a module out of section encoders, which are written in the order
+/// they are added.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ModuleEncoder {
+    writer: BinaryWriter,
+}
+
+impl ModuleEncoder {
+    pub fn new() -> ModuleEncoder {
+        let mut writer = BinaryWriter::new();
+        writer.write_bytes(b"\0asm");
+        writer.write_double_word(1);
+        ModuleEncoder { writer }
+    }
+
+    pub fn section(&mut self, section: &impl SectionEncoder) -> &mut Self {
+        let mut contents = BinaryWriter::new();
+        section.encode(&mut contents);
+        self.writer.write_byte(section.id());
+        self.writer.write_bytes_vec(contents.as_slice());
+        self
+    }
+
+    /// Copies a parsed section byte for byte, including any non-canonical
+    /// LEB128 encoding of its size or contents.
+    pub fn copy_section(&mut self, section: &SectionReader) -> &mut Self {
+        self.writer.write_bytes(section.get_raw_bytes());
+        self
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.writer.into_bytes()
+    }
+}
+
+impl Default for ModuleEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoder::*;
+    use crate::{Parser, Validator, Chunk, SectionReader, Instruction, ValueType, BlockType, MemoryArgument, Locals};
+    use crate::{Limits, TableType, MemoryType, GlobalType, ImportDescriptor, ExportDescriptor, CatchClause};
+    use crate::Instruction::*;
+
+    fn validate_module(module: &[u8]) -> usize {
+        let mut parser = Parser::new();
+        let mut validator = Validator::new();
+        let mut position = 0;
+        let mut sections = 0;
+        loop {
+            let (consumed, chunk) = parser.parse(&module[position..], true).unwrap();
+            validator.validate(&chunk).unwrap();
+            match chunk {
+                Chunk::Section(_) => sections += 1,
+                Chunk::Done => return sections,
+                _ => {},
+            }
+            position += consumed;
+        }
+    }
+
+    #[test]
+    fn empty_module() {
+        let module = ModuleEncoder::new().finish();
+        assert_eq!(b"\0asm\x01\0\0\0", module.as_slice());
+        assert_eq!(0, validate_module(&module));
+    }
+
+    #[test]
+    fn function_module_bytes() {
+        let mut types = TypeSectionEncoder::new();
+        types.function_type(&[ValueType::I32], &[ValueType::I32]);
+        let mut functions = FunctionSectionEncoder::new();
+        functions.type_index(0.into());
+        let mut body = FunctionBodyEncoder::new(&[]);
+        body.instruction(&LocalGet { local_index: 0.into() })
+            .instruction(&End);
+        let mut code = CodeSectionEncoder::new();
+        code.function(&body);
+
+        let mut module = ModuleEncoder::new();
+        module.section(&types).section(&functions).section(&code);
+        let module = module.finish();
+        let expected = b"\0asm\x01\0\0\0\
+            \x01\x06\x01\x60\x01\x7F\x01\x7F\
+            \x03\x02\x01\x00\
+            \x0A\x06\x01\x04\x00\x20\x00\x0B";
+        assert_eq!(&expected[..], module.as_slice());
+        assert_eq!(3, validate_module(&module));
+    }
+
+    fn every_section_module() -> Vec<u8> {
+        let mut types = TypeSectionEncoder::new();
+        types.function_type(&[], &[])
+            .function_type(&[ValueType::I32, ValueType::I64], &[ValueType::F64]);
+
+        let mut imports = ImportSectionEncoder::new();
+        imports.import("env", "log", &ImportDescriptor::Func { type_index: 0.into() })
+            .import("env", "base", &ImportDescriptor::Global(GlobalType::new(ValueType::I32, false)));
+
+        let mut functions = FunctionSectionEncoder::new();
+        functions.type_index(0.into()).type_index(1.into());
+
+        let mut tables = TableSectionEncoder::new();
+        tables.table(&TableType::new(ValueType::FuncRef, Limits::new(2, None)));
+
+        let mut memories = MemorySectionEncoder::new();
+        memories.memory(&MemoryType::new(Limits::new(1, Some(2))));
+
+        let mut globals = GlobalSectionEncoder::new();
+        globals.global(&GlobalType::new(ValueType::I64, true), &[I64Const(-123_456)])
+            .global(&GlobalType::new(ValueType::F32, false), &[F32Const(1.5)]);
+
+        let mut exports = ExportSectionEncoder::new();
+        exports.export("run", &ExportDescriptor::Func { func_index: 1.into() })
+            .export("memory", &ExportDescriptor::Memory { memory_index: 0.into() });
+
+        let start = StartSectionEncoder::new(0.into());
+
+        let mut elements = ElementSectionEncoder::new();
+        elements.segment(0.into(), &[I32Const(0)], &[0.into(), 2.into()]);
+
+        let mut first = FunctionBodyEncoder::new(&[]);
+        first.instruction(&Call { func_index: 0.into() })
+            .instruction(&End);
+        let mut second = FunctionBodyEncoder::new(&[Locals { count: 2, value_type: ValueType::F64 }]);
+        second.instruction(&Block { block_type: BlockType::ValueType(ValueType::F64) })
+            .instruction(&LocalGet { local_index: 2.into() })
+            .instruction(&LocalGet { local_index: 0.into() })
+            .branch_table(&[0.into(), 1.into()], 0.into())
+            .instruction(&End)
+            .instruction(&LocalGet { local_index: 1.into() })
+            .instruction(&I32WrapI64)
+            .instruction(&I32Load { memory_argument: MemoryArgument { alignment: 2, offset: 200, memory_index: 0.into() } })
+            .instruction(&Drop)
+            .instruction(&End);
+        let mut code = CodeSectionEncoder::new();
+        code.function(&first).function(&second);
+
+        let mut data = DataSectionEncoder::new();
+        data.segment(0.into(), &[I32Const(1024)], b"hello");
+
+        let custom = CustomSectionEncoder::new("note", b"\x01\x02");
+
+        let mut module = ModuleEncoder::new();
+        module.section(&custom)
+            .section(&types)
+            .section(&imports)
+            .section(&functions)
+            .section(&tables)
+            .section(&memories)
+            .section(&globals)
+            .section(&exports)
+            .section(&start)
+            .section(&elements)
+            .section(&code)
+            .section(&data);
+        module.finish()
+    }
+
+    #[test]
+    fn every_section_is_accepted() {
+        assert_eq!(12, validate_module(&every_section_module()));
+    }
+
+    #[test]
+    fn instructions_roundtrip_through_reader() {
+        let catches = || vec![
+            CatchClause::Catch { tag_index: 0.into(), label_index: 1.into() },
+            CatchClause::CatchAllRef { label_index: 0.into() },
+        ].into_boxed_slice();
+        let mut body = FunctionBodyEncoder::new(&[]);
+        body.instruction(&Loop { block_type: BlockType::TypeIndex(300.into()) })
+            .instruction(&I32Const(11))
+            .instruction(&F64Const(-0.25))
+            .instruction(&I64TruncSatF64u)
+            .instruction(&MemoryGrow { memory_index: 1.into() })
+            .instruction(&MemoryInit { data_index: 3.into(), memory_index: 2.into() })
+            .instruction(&TableCopy { destination_table_index: 1.into(), source_table_index: 0.into() })
+            .instruction(&CallIndirect { type_index: 2.into(), table_index: 1.into() })
+            .instruction(&ReturnCallIndirect { type_index: 3.into(), table_index: 0.into() })
+            .instruction(&TypedSelect { value_type: ValueType::ExternRef })
+            .instruction(&RefNull { ref_type: ValueType::FuncRef })
+            .instruction(&RefFunc { func_index: 5.into() })
+            .instruction(&TableGrow { table_index: 1.into() })
+            .instruction(&V128Const(0x0B0A_0908_0706_0504_0302_0100))
+            .instruction(&V128Load16Lane { memory_argument: MemoryArgument { alignment: 1, offset: 8, memory_index: 1.into() }, lane: 5 })
+            .instruction(&I16x8ReplaceLane { lane: 7 })
+            .instruction(&F64x2ConvertLowI32x4u)
+            .instruction(&AtomicFence)
+            .instruction(&I64AtomicRmw32Cmpxchgu { memory_argument: MemoryArgument { alignment: 2, offset: 16, memory_index: 0.into() } })
+            .instruction(&Throw { tag_index: 1.into() })
+            .instruction(&ThrowRef)
+            .instruction(&Delegate { label_index: 2.into() })
+            .instruction(&TryTable { block_type: BlockType::Empty, catches: catches() })
+            .instruction(&End);
+        let mut code = CodeSectionEncoder::new();
+        code.function(&body);
+        let mut module = ModuleEncoder::new();
+        module.section(&code);
+        let module = module.finish();
+
+        let mut parser = Parser::new();
+        let (consumed, _) = parser.parse(&module, true).unwrap();
+        let (_, chunk) = parser.parse(&module[consumed..], true).unwrap();
+        let mut reader = match chunk {
+            Chunk::Section(SectionReader::Code(reader)) => reader,
+            _ => panic!("expected a code section"),
+        };
+        let code = reader.read().unwrap();
+        let mut locals_reader = code.get_locals_reader().unwrap();
+        let proof = locals_reader.get_iteration_proof().unwrap();
+        let instructions = code.get_instruction_reader(proof).unwrap()
+            .into_iter()
+            .map(|instruction| format!("{:?}", instruction.unwrap()))
+            .collect::<Vec<_>>();
+        let expected = [
+            format!("{:?}", Instruction::Loop { block_type: BlockType::TypeIndex(300.into()) }),
+            format!("{:?}", I32Const(11)),
+            format!("{:?}", F64Const(-0.25)),
+            format!("{:?}", I64TruncSatF64u),
+            format!("{:?}", MemoryGrow { memory_index: 1.into() }),
+            format!("{:?}", MemoryInit { data_index: 3.into(), memory_index: 2.into() }),
+            format!("{:?}", TableCopy { destination_table_index: 1.into(), source_table_index: 0.into() }),
+            format!("{:?}", CallIndirect { type_index: 2.into(), table_index: 1.into() }),
+            format!("{:?}", ReturnCallIndirect { type_index: 3.into(), table_index: 0.into() }),
+            format!("{:?}", TypedSelect { value_type: ValueType::ExternRef }),
+            format!("{:?}", RefNull { ref_type: ValueType::FuncRef }),
+            format!("{:?}", RefFunc { func_index: 5.into() }),
+            format!("{:?}", TableGrow { table_index: 1.into() }),
+            format!("{:?}", V128Const(0x0B0A_0908_0706_0504_0302_0100)),
+            format!("{:?}", V128Load16Lane { memory_argument: MemoryArgument { alignment: 1, offset: 8, memory_index: 1.into() }, lane: 5 }),
+            format!("{:?}", I16x8ReplaceLane { lane: 7 }),
+            format!("{:?}", F64x2ConvertLowI32x4u),
+            format!("{:?}", AtomicFence),
+            format!("{:?}", I64AtomicRmw32Cmpxchgu { memory_argument: MemoryArgument { alignment: 2, offset: 16, memory_index: 0.into() } }),
+            format!("{:?}", Throw { tag_index: 1.into() }),
+            format!("{:?}", ThrowRef),
+            format!("{:?}", Delegate { label_index: 2.into() }),
+            format!("{:?}", TryTable { block_type: BlockType::Empty, catches: catches() }),
+            format!("{:?}", End),
+        ];
+        assert_eq!(&expected[..], &instructions[..]);
+    }
+
+    const NON_CANONICAL: &[u8] = b"\0asm\x01\0\0\0\
+        \x00\x06\x04note\x2A\
+        \x01\x85\x80\x80\x80\x00\x81\x00\x60\x00\x00\
+        \x03\x02\x01\x00\
+        \x0A\x0D\x01\x89\x80\x00\x00\x41\x85\x80\x80\x80\x00\x1A\x0B\
+        \x2A\x03\x01\x02\x03";
+
+    fn copy_sections(module: &[u8]) -> Vec<u8> {
+        let mut parser = Parser::new();
+        let mut encoder = ModuleEncoder::new();
+        let mut position = 0;
+        loop {
+            let (consumed, chunk) = parser.parse(&module[position..], true).unwrap();
+            match chunk {
+                Chunk::Section(section) => {
+                    encoder.copy_section(&section);
+                },
+                Chunk::Done => return encoder.finish(),
+                _ => {},
+            }
+            position += consumed;
+        }
+    }
+
+    #[test]
+    fn copied_sections_reproduce_the_module() {
+        let corpus: [&[u8]; 4] = [
+            include_bytes!("../../hello.wasm"),
+            &every_section_module(),
+            NON_CANONICAL,
+            b"\0asm\x01\0\0\0",
+        ];
+        for module in corpus.iter() {
+            assert_eq!(*module, copy_sections(module).as_slice());
+        }
+    }
+
+    #[test]
+    fn copied_function_bodies_are_unchanged() {
+        let mut parser = Parser::new();
+        let mut position = 0;
+        let reader = loop {
+            let (consumed, chunk) = parser.parse(&NON_CANONICAL[position..], true).unwrap();
+            if let Chunk::Section(SectionReader::Code(reader)) = chunk {
+                break reader;
+            }
+            position += consumed;
+        };
+        let raw_bytes = reader.get_raw_bytes();
+        let mut code = CodeSectionEncoder::new();
+        for body in reader {
+            code.copy_function(&body.unwrap());
+        }
+        let mut module = ModuleEncoder::new();
+        module.section(&code);
+        assert_eq!(raw_bytes, &module.finish()[8..]);
+    }
+}
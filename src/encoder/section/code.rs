@@ -0,0 +1,78 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+use crate::types::{Locals, Instruction, LabelIndex};
+use crate::readers::section::code::Code;
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct CodeSectionEncoder {
+    writer: BinaryWriter,
+    count: u32,
+}
+
+impl CodeSectionEncoder {
+    pub fn new() -> CodeSectionEncoder {
+        Self::default()
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn function(&mut self, body: &FunctionBodyEncoder) -> &mut Self {
+        self.writer.write_bytes_vec(body.writer.as_slice());
+        self.count += 1;
+        self
+    }
+
+    /// Copies a parsed function body byte for byte, size prefix included.
+    pub fn copy_function(&mut self, code: &Code) -> &mut Self {
+        self.writer.write_bytes(code.get_raw_bytes());
+        self.count += 1;
+        self
+    }
+}
+
+impl SectionEncoder for CodeSectionEncoder {
+    fn id(&self) -> u8 {
+        10
+    }
+
+    fn encode(&self, writer: &mut BinaryWriter) {
+        writer.write_leb128_u32(self.count);
+        writer.write_bytes(self.writer.as_slice());
+    }
+}
+
+/// Encodes the locals and instructions of a single function. The body must
+/// be terminated by an explicit `Instruction::End`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct FunctionBodyEncoder {
+    writer: BinaryWriter,
+}
+
+impl FunctionBodyEncoder {
+    pub fn new(locals: &[Locals]) -> FunctionBodyEncoder {
+        let mut writer = BinaryWriter::new();
+        writer.write_leb128_u32(locals.len() as u32);
+        for locals in locals {
+            writer.write_leb128_u32(locals.count);
+            writer.write_value_type(locals.value_type);
+        }
+        FunctionBodyEncoder { writer }
+    }
+
+    pub fn instruction(&mut self, instruction: &Instruction) -> &mut Self {
+        self.writer.write_instruction(instruction);
+        self
+    }
+
+    /// Writes a `br_table`, which can't be built as an `Instruction` outside of a reader.
+    pub fn branch_table(&mut self, labels: &[LabelIndex], default_label: LabelIndex) -> &mut Self {
+        self.writer.write_byte(0x0E);
+        self.writer.write_leb128_u32(labels.len() as u32);
+        for label in labels {
+            self.writer.write_leb128_u32(label.0);
+        }
+        self.writer.write_leb128_u32(default_label.0);
+        self
+    }
+}
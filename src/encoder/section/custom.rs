@@ -0,0 +1,24 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CustomSectionEncoder<'a> {
+    name: &'a str,
+    data: &'a [u8],
+}
+
+impl<'a> CustomSectionEncoder<'a> {
+    pub fn new(name: &'a str, data: &'a [u8]) -> CustomSectionEncoder<'a> {
+        CustomSectionEncoder { name, data }
+    }
+}
+
+impl SectionEncoder for CustomSectionEncoder<'_> {
+    fn id(&self) -> u8 {
+        0
+    }
+
+    fn encode(&self, writer: &mut BinaryWriter) {
+        writer.write_string(self.name);
+        writer.write_bytes(self.data);
+    }
+}
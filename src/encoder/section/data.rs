@@ -0,0 +1,50 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+use crate::types::{MemoryIndex, Instruction};
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct DataSectionEncoder {
+    writer: BinaryWriter,
+    count: u32,
+}
+
+impl DataSectionEncoder {
+    pub fn new() -> DataSectionEncoder {
+        Self::default()
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    /// Adds an active segment whose `offset` expression is written without its terminating `end`.
+    pub fn segment(&mut self, memory_index: MemoryIndex, offset: &[Instruction], bytes: &[u8]) -> &mut Self {
+        if memory_index.0 == 0 {
+            self.writer.write_leb128_u32(0);
+        } else {
+            self.writer.write_leb128_u32(2);
+            self.writer.write_leb128_u32(memory_index.0);
+        }
+        self.writer.write_expression(offset);
+        self.writer.write_bytes_vec(bytes);
+        self.count += 1;
+        self
+    }
+
+    pub fn passive_segment(&mut self, bytes: &[u8]) -> &mut Self {
+        self.writer.write_leb128_u32(1);
+        self.writer.write_bytes_vec(bytes);
+        self.count += 1;
+        self
+    }
+}
+
+impl SectionEncoder for DataSectionEncoder {
+    fn id(&self) -> u8 {
+        11
+    }
+
+    fn encode(&self, writer: &mut BinaryWriter) {
+        writer.write_leb128_u32(self.count);
+        writer.write_bytes(self.writer.as_slice());
+    }
+}
@@ -0,0 +1,22 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct DataCountSectionEncoder {
+    count: u32,
+}
+
+impl DataCountSectionEncoder {
+    pub fn new(count: u32) -> DataCountSectionEncoder {
+        DataCountSectionEncoder { count }
+    }
+}
+
+impl SectionEncoder for DataCountSectionEncoder {
+    fn id(&self) -> u8 {
+        12
+    }
+
+    fn encode(&self, writer: &mut BinaryWriter) {
+        writer.write_leb128_u32(self.count);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+use crate::types::{TableIndex, Instruction, FuncIndex, ValueType};
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct ElementSectionEncoder {
+    writer: BinaryWriter,
+    count: u32,
+}
+
+impl ElementSectionEncoder {
+    pub fn new() -> ElementSectionEncoder {
+        Self::default()
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    /// Adds an active segment whose `offset` expression is written without its terminating `end`.
+    pub fn segment(&mut self, table_index: TableIndex, offset: &[Instruction], function_indices: &[FuncIndex]) -> &mut Self {
+        if table_index.0 == 0 {
+            self.writer.write_leb128_u32(0);
+            self.writer.write_expression(offset);
+        } else {
+            self.writer.write_leb128_u32(2);
+            self.writer.write_leb128_u32(table_index.0);
+            self.writer.write_expression(offset);
+            self.writer.write_byte(0x00);
+        }
+        self.write_function_indices(function_indices);
+        self
+    }
+
+    pub fn passive_segment(&mut self, function_indices: &[FuncIndex]) -> &mut Self {
+        self.writer.write_leb128_u32(1);
+        self.writer.write_byte(0x00);
+        self.write_function_indices(function_indices);
+        self
+    }
+
+    pub fn declarative_segment(&mut self, function_indices: &[FuncIndex]) -> &mut Self {
+        self.writer.write_leb128_u32(3);
+        self.writer.write_byte(0x00);
+        self.write_function_indices(function_indices);
+        self
+    }
+
+    /// Adds an active segment whose items are given as expressions, every
+    /// expression being written without its terminating `end`.
+    pub fn expression_segment(&mut self, table_index: TableIndex, offset: &[Instruction], element_type: ValueType, expressions: &[Vec<Instruction>]) -> &mut Self {
+        if table_index.0 == 0 && element_type == ValueType::FuncRef {
+            self.writer.write_leb128_u32(4);
+            self.writer.write_expression(offset);
+        } else {
+            self.writer.write_leb128_u32(6);
+            self.writer.write_leb128_u32(table_index.0);
+            self.writer.write_expression(offset);
+            self.writer.write_value_type(element_type);
+        }
+        self.write_expressions(expressions);
+        self
+    }
+
+    pub fn passive_expression_segment(&mut self, element_type: ValueType, expressions: &[Vec<Instruction>]) -> &mut Self {
+        self.writer.write_leb128_u32(5);
+        self.writer.write_value_type(element_type);
+        self.write_expressions(expressions);
+        self
+    }
+
+    pub fn declarative_expression_segment(&mut self, element_type: ValueType, expressions: &[Vec<Instruction>]) -> &mut Self {
+        self.writer.write_leb128_u32(7);
+        self.writer.write_value_type(element_type);
+        self.write_expressions(expressions);
+        self
+    }
+
+    fn write_function_indices(&mut self, function_indices: &[FuncIndex]) {
+        self.writer.write_leb128_u32(function_indices.len() as u32);
+        for func_index in function_indices {
+            self.writer.write_leb128_u32(func_index.0);
+        }
+        self.count += 1;
+    }
+
+    fn write_expressions(&mut self, expressions: &[Vec<Instruction>]) {
+        self.writer.write_leb128_u32(expressions.len() as u32);
+        for expression in expressions {
+            self.writer.write_expression(expression);
+        }
+        self.count += 1;
+    }
+}
+
+impl SectionEncoder for ElementSectionEncoder {
+    fn id(&self) -> u8 {
+        9
+    }
+
+    fn encode(&self, writer: &mut BinaryWriter) {
+        writer.write_leb128_u32(self.count);
+        writer.write_bytes(self.writer.as_slice());
+    }
+}
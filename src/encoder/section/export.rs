@@ -0,0 +1,48 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+use crate::types::ExportDescriptor;
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct ExportSectionEncoder {
+    writer: BinaryWriter,
+    count: u32,
+}
+
+impl ExportSectionEncoder {
+    pub fn new() -> ExportSectionEncoder {
+        Self::default()
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn export(&mut self, name: &str, export_descriptor: &ExportDescriptor) -> &mut Self {
+        self.writer.write_string(name);
+        self.write_export_desc(export_descriptor);
+        self.count += 1;
+        self
+    }
+
+    fn write_export_desc(&mut self, export_descriptor: &ExportDescriptor) {
+        let (kind, index) = match export_descriptor {
+            ExportDescriptor::Func { func_index } => (0x00, func_index.0),
+            ExportDescriptor::Table { table_index } => (0x01, table_index.0),
+            ExportDescriptor::Memory { memory_index } => (0x02, memory_index.0),
+            ExportDescriptor::Global { global_index } => (0x03, global_index.0),
+            ExportDescriptor::Tag { tag_index } => (0x04, tag_index.0),
+        };
+        self.writer.write_byte(kind);
+        self.writer.write_leb128_u32(index);
+    }
+}
+
+impl SectionEncoder for ExportSectionEncoder {
+    fn id(&self) -> u8 {
+        7
+    }
+
+    fn encode(&self, writer: &mut BinaryWriter) {
+        writer.write_leb128_u32(self.count);
+        writer.write_bytes(self.writer.as_slice());
+    }
+}
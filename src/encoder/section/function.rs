@@ -0,0 +1,35 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+use crate::types::TypeIndex;
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct FunctionSectionEncoder {
+    writer: BinaryWriter,
+    count: u32,
+}
+
+impl FunctionSectionEncoder {
+    pub fn new() -> FunctionSectionEncoder {
+        Self::default()
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn type_index(&mut self, type_index: TypeIndex) -> &mut Self {
+        self.writer.write_leb128_u32(type_index.0);
+        self.count += 1;
+        self
+    }
+}
+
+impl SectionEncoder for FunctionSectionEncoder {
+    fn id(&self) -> u8 {
+        3
+    }
+
+    fn encode(&self, writer: &mut BinaryWriter) {
+        writer.write_leb128_u32(self.count);
+        writer.write_bytes(self.writer.as_slice());
+    }
+}
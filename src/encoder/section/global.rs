@@ -0,0 +1,37 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+use crate::types::{GlobalType, Instruction};
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct GlobalSectionEncoder {
+    writer: BinaryWriter,
+    count: u32,
+}
+
+impl GlobalSectionEncoder {
+    pub fn new() -> GlobalSectionEncoder {
+        Self::default()
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    /// Adds a global initialized by `init`, which is written without its terminating `end`.
+    pub fn global(&mut self, global_type: &GlobalType, init: &[Instruction]) -> &mut Self {
+        self.writer.write_global_type(global_type);
+        self.writer.write_expression(init);
+        self.count += 1;
+        self
+    }
+}
+
+impl SectionEncoder for GlobalSectionEncoder {
+    fn id(&self) -> u8 {
+        6
+    }
+
+    fn encode(&self, writer: &mut BinaryWriter) {
+        writer.write_leb128_u32(self.count);
+        writer.write_bytes(self.writer.as_slice());
+    }
+}
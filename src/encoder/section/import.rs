@@ -0,0 +1,62 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+use crate::types::ImportDescriptor;
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct ImportSectionEncoder {
+    writer: BinaryWriter,
+    count: u32,
+}
+
+impl ImportSectionEncoder {
+    pub fn new() -> ImportSectionEncoder {
+        Self::default()
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn import(&mut self, module_name: &str, name: &str, import_descriptor: &ImportDescriptor) -> &mut Self {
+        self.writer.write_string(module_name);
+        self.writer.write_string(name);
+        self.write_import_desc(import_descriptor);
+        self.count += 1;
+        self
+    }
+
+    fn write_import_desc(&mut self, import_descriptor: &ImportDescriptor) {
+        match import_descriptor {
+            ImportDescriptor::Func { type_index } => {
+                self.writer.write_byte(0x00);
+                self.writer.write_leb128_u32(type_index.0);
+            },
+            ImportDescriptor::Table(table_type) => {
+                self.writer.write_byte(0x01);
+                self.writer.write_table_type(table_type);
+            },
+            ImportDescriptor::Memory(memory_type) => {
+                self.writer.write_byte(0x02);
+                self.writer.write_memory_type(memory_type);
+            },
+            ImportDescriptor::Global(global_type) => {
+                self.writer.write_byte(0x03);
+                self.writer.write_global_type(global_type);
+            },
+            ImportDescriptor::Tag(tag_type) => {
+                self.writer.write_byte(0x04);
+                self.writer.write_tag_type(tag_type);
+            },
+        }
+    }
+}
+
+impl SectionEncoder for ImportSectionEncoder {
+    fn id(&self) -> u8 {
+        2
+    }
+
+    fn encode(&self, writer: &mut BinaryWriter) {
+        writer.write_leb128_u32(self.count);
+        writer.write_bytes(self.writer.as_slice());
+    }
+}
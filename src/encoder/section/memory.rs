@@ -0,0 +1,35 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+use crate::types::MemoryType;
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct MemorySectionEncoder {
+    writer: BinaryWriter,
+    count: u32,
+}
+
+impl MemorySectionEncoder {
+    pub fn new() -> MemorySectionEncoder {
+        Self::default()
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn memory(&mut self, memory_type: &MemoryType) -> &mut Self {
+        self.writer.write_memory_type(memory_type);
+        self.count += 1;
+        self
+    }
+}
+
+impl SectionEncoder for MemorySectionEncoder {
+    fn id(&self) -> u8 {
+        5
+    }
+
+    fn encode(&self, writer: &mut BinaryWriter) {
+        writer.write_leb128_u32(self.count);
+        writer.write_bytes(self.writer.as_slice());
+    }
+}
@@ -0,0 +1,14 @@
+pub mod code;
+pub mod custom;
+pub mod import;
+pub mod function;
+pub mod global;
+pub mod export;
+pub mod element;
+pub mod data;
+pub mod data_count;
+pub mod r#type;
+pub mod table;
+pub mod memory;
+pub mod start;
+pub mod tag;
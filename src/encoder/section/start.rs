@@ -0,0 +1,23 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+use crate::types::FuncIndex;
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct StartSectionEncoder {
+    func_index: FuncIndex,
+}
+
+impl StartSectionEncoder {
+    pub fn new(func_index: FuncIndex) -> StartSectionEncoder {
+        StartSectionEncoder { func_index }
+    }
+}
+
+impl SectionEncoder for StartSectionEncoder {
+    fn id(&self) -> u8 {
+        8
+    }
+
+    fn encode(&self, writer: &mut BinaryWriter) {
+        writer.write_leb128_u32(self.func_index.0);
+    }
+}
@@ -0,0 +1,35 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+use crate::types::TableType;
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct TableSectionEncoder {
+    writer: BinaryWriter,
+    count: u32,
+}
+
+impl TableSectionEncoder {
+    pub fn new() -> TableSectionEncoder {
+        Self::default()
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn table(&mut self, table_type: &TableType) -> &mut Self {
+        self.writer.write_table_type(table_type);
+        self.count += 1;
+        self
+    }
+}
+
+impl SectionEncoder for TableSectionEncoder {
+    fn id(&self) -> u8 {
+        4
+    }
+
+    fn encode(&self, writer: &mut BinaryWriter) {
+        writer.write_leb128_u32(self.count);
+        writer.write_bytes(self.writer.as_slice());
+    }
+}
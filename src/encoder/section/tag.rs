@@ -0,0 +1,35 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+use crate::types::TagType;
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct TagSectionEncoder {
+    writer: BinaryWriter,
+    count: u32,
+}
+
+impl TagSectionEncoder {
+    pub fn new() -> TagSectionEncoder {
+        Self::default()
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn tag(&mut self, tag_type: &TagType) -> &mut Self {
+        self.writer.write_tag_type(tag_type);
+        self.count += 1;
+        self
+    }
+}
+
+impl SectionEncoder for TagSectionEncoder {
+    fn id(&self) -> u8 {
+        13
+    }
+
+    fn encode(&self, writer: &mut BinaryWriter) {
+        writer.write_leb128_u32(self.count);
+        writer.write_bytes(self.writer.as_slice());
+    }
+}
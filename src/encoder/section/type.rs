@@ -0,0 +1,44 @@
+use crate::encoder::{BinaryWriter, SectionEncoder};
+use crate::types::ValueType;
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct TypeSectionEncoder {
+    writer: BinaryWriter,
+    count: u32,
+}
+
+impl TypeSectionEncoder {
+    pub fn new() -> TypeSectionEncoder {
+        Self::default()
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn function_type(&mut self, params: &[ValueType], results: &[ValueType]) -> &mut Self {
+        self.writer.write_byte(0x60);
+        self.write_types_vec(params);
+        self.write_types_vec(results);
+        self.count += 1;
+        self
+    }
+
+    fn write_types_vec(&mut self, types: &[ValueType]) {
+        self.writer.write_leb128_u32(types.len() as u32);
+        for &value_type in types {
+            self.writer.write_value_type(value_type);
+        }
+    }
+}
+
+impl SectionEncoder for TypeSectionEncoder {
+    fn id(&self) -> u8 {
+        1
+    }
+
+    fn encode(&self, writer: &mut BinaryWriter) {
+        writer.write_leb128_u32(self.count);
+        writer.write_bytes(self.writer.as_slice());
+    }
+}
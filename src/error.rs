@@ -0,0 +1,263 @@
+use std::{error, fmt};
+use crate::{ParseError, ValidationError, PreambleReaderError, BranchReaderError, InstructionReaderError};
+use crate::{CustomReaderError, TypeReaderError, ImportReaderError, FunctionReaderError, TableReaderError};
+use crate::{MemoryReaderError, TagReaderError, GlobalReaderError, ExportReaderError, StartReaderError, ElementReaderError};
+use crate::{CodeReaderError, DataReaderError, DataCountReaderError, NameReaderError, ProducersReaderError, TargetFeaturesReaderError};
+use crate::readers::binary::BinaryReaderError;
+use crate::text::{TextError, PrintError};
+
+/// The error type every reader, the parser and the validator convert into,
+/// so that callers can use `?` across all of them.
+#[derive(PartialEq, Eq, Debug)]
+pub enum Error {
+    Parse(ParseError),
+    BinaryReader(BinaryReaderError),
+    PreambleReader(PreambleReaderError),
+    CustomReader(CustomReaderError),
+    TypeReader(TypeReaderError),
+    ImportReader(ImportReaderError),
+    FunctionReader(FunctionReaderError),
+    TableReader(TableReaderError),
+    MemoryReader(MemoryReaderError),
+    TagReader(TagReaderError),
+    GlobalReader(GlobalReaderError),
+    ExportReader(ExportReaderError),
+    StartReader(StartReaderError),
+    ElementReader(ElementReaderError),
+    CodeReader(CodeReaderError),
+    DataReader(DataReaderError),
+    DataCountReader(DataCountReaderError),
+    NameReader(NameReaderError),
+    ProducersReader(ProducersReaderError),
+    TargetFeaturesReader(TargetFeaturesReaderError),
+    InstructionReader(InstructionReaderError),
+    BranchReader(BranchReaderError),
+    Validation(ValidationError),
+    Text(TextError),
+    Print(PrintError),
+}
+
+impl Error {
+    /// Returns the absolute offset in the module at which the error was detected.
+    pub fn offset(&self) -> usize {
+        match self {
+            Error::Parse(e) => e.offset(),
+            Error::BinaryReader(e) => e.offset(),
+            Error::PreambleReader(e) => e.offset(),
+            Error::CustomReader(e) => e.offset(),
+            Error::TypeReader(e) => e.offset(),
+            Error::ImportReader(e) => e.offset(),
+            Error::FunctionReader(e) => e.offset(),
+            Error::TableReader(e) => e.offset(),
+            Error::MemoryReader(e) => e.offset(),
+            Error::TagReader(e) => e.offset(),
+            Error::GlobalReader(e) => e.offset(),
+            Error::ExportReader(e) => e.offset(),
+            Error::StartReader(e) => e.offset(),
+            Error::ElementReader(e) => e.offset(),
+            Error::CodeReader(e) => e.offset(),
+            Error::DataReader(e) => e.offset(),
+            Error::DataCountReader(e) => e.offset(),
+            Error::NameReader(e) => e.offset(),
+            Error::ProducersReader(e) => e.offset(),
+            Error::TargetFeaturesReader(e) => e.offset(),
+            Error::InstructionReader(e) => e.offset(),
+            Error::BranchReader(e) => e.offset(),
+            Error::Validation(e) => e.offset(),
+            Error::Text(e) => e.offset(),
+            Error::Print(e) => e.offset(),
+        }
+    }
+
+    fn inner(&self) -> &(dyn error::Error + 'static) {
+        match self {
+            Error::Parse(e) => e,
+            Error::BinaryReader(e) => e,
+            Error::PreambleReader(e) => e,
+            Error::CustomReader(e) => e,
+            Error::TypeReader(e) => e,
+            Error::ImportReader(e) => e,
+            Error::FunctionReader(e) => e,
+            Error::TableReader(e) => e,
+            Error::MemoryReader(e) => e,
+            Error::TagReader(e) => e,
+            Error::GlobalReader(e) => e,
+            Error::ExportReader(e) => e,
+            Error::StartReader(e) => e,
+            Error::ElementReader(e) => e,
+            Error::CodeReader(e) => e,
+            Error::DataReader(e) => e,
+            Error::DataCountReader(e) => e,
+            Error::NameReader(e) => e,
+            Error::ProducersReader(e) => e,
+            Error::TargetFeaturesReader(e) => e,
+            Error::InstructionReader(e) => e,
+            Error::BranchReader(e) => e,
+            Error::Validation(e) => e,
+            Error::Text(e) => e,
+            Error::Print(e) => e,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.inner(), f)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.inner().source()
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+impl From<BinaryReaderError> for Error {
+    fn from(e: BinaryReaderError) -> Self {
+        Error::BinaryReader(e)
+    }
+}
+
+impl From<PreambleReaderError> for Error {
+    fn from(e: PreambleReaderError) -> Self {
+        Error::PreambleReader(e)
+    }
+}
+
+impl From<CustomReaderError> for Error {
+    fn from(e: CustomReaderError) -> Self {
+        Error::CustomReader(e)
+    }
+}
+
+impl From<TypeReaderError> for Error {
+    fn from(e: TypeReaderError) -> Self {
+        Error::TypeReader(e)
+    }
+}
+
+impl From<ImportReaderError> for Error {
+    fn from(e: ImportReaderError) -> Self {
+        Error::ImportReader(e)
+    }
+}
+
+impl From<FunctionReaderError> for Error {
+    fn from(e: FunctionReaderError) -> Self {
+        Error::FunctionReader(e)
+    }
+}
+
+impl From<TableReaderError> for Error {
+    fn from(e: TableReaderError) -> Self {
+        Error::TableReader(e)
+    }
+}
+
+impl From<MemoryReaderError> for Error {
+    fn from(e: MemoryReaderError) -> Self {
+        Error::MemoryReader(e)
+    }
+}
+
+impl From<TagReaderError> for Error {
+    fn from(e: TagReaderError) -> Self {
+        Error::TagReader(e)
+    }
+}
+
+impl From<GlobalReaderError> for Error {
+    fn from(e: GlobalReaderError) -> Self {
+        Error::GlobalReader(e)
+    }
+}
+
+impl From<ExportReaderError> for Error {
+    fn from(e: ExportReaderError) -> Self {
+        Error::ExportReader(e)
+    }
+}
+
+impl From<StartReaderError> for Error {
+    fn from(e: StartReaderError) -> Self {
+        Error::StartReader(e)
+    }
+}
+
+impl From<ElementReaderError> for Error {
+    fn from(e: ElementReaderError) -> Self {
+        Error::ElementReader(e)
+    }
+}
+
+impl From<CodeReaderError> for Error {
+    fn from(e: CodeReaderError) -> Self {
+        Error::CodeReader(e)
+    }
+}
+
+impl From<DataReaderError> for Error {
+    fn from(e: DataReaderError) -> Self {
+        Error::DataReader(e)
+    }
+}
+
+impl From<DataCountReaderError> for Error {
+    fn from(e: DataCountReaderError) -> Self {
+        Error::DataCountReader(e)
+    }
+}
+
+impl From<NameReaderError> for Error {
+    fn from(e: NameReaderError) -> Self {
+        Error::NameReader(e)
+    }
+}
+
+impl From<ProducersReaderError> for Error {
+    fn from(e: ProducersReaderError) -> Self {
+        Error::ProducersReader(e)
+    }
+}
+
+impl From<TargetFeaturesReaderError> for Error {
+    fn from(e: TargetFeaturesReaderError) -> Self {
+        Error::TargetFeaturesReader(e)
+    }
+}
+
+impl From<InstructionReaderError> for Error {
+    fn from(e: InstructionReaderError) -> Self {
+        Error::InstructionReader(e)
+    }
+}
+
+impl From<BranchReaderError> for Error {
+    fn from(e: BranchReaderError) -> Self {
+        Error::BranchReader(e)
+    }
+}
+
+impl From<ValidationError> for Error {
+    fn from(e: ValidationError) -> Self {
+        Error::Validation(e)
+    }
+}
+
+impl From<TextError> for Error {
+    fn from(e: TextError) -> Self {
+        Error::Text(e)
+    }
+}
+
+impl From<PrintError> for Error {
+    fn from(e: PrintError) -> Self {
+        Error::Print(e)
+    }
+}
@@ -1,9 +1,21 @@
+pub use crate::error::Error;
 pub use crate::parser::*;
 pub use crate::readers::*;
-pub use crate::types::Instruction;
+pub use crate::types::{Instruction, ValueType, BlockType, MemoryArgument, Locals, Limits, TableType, MemoryType, GlobalType, TagType, CatchClause};
+pub use crate::types::{FunctionType, FunctionTypeParams, FunctionTypeResults};
+pub use crate::types::{TypeIndex, FuncIndex, TableIndex, MemoryIndex, GlobalIndex, ElementIndex, DataIndex, TagIndex, LocalIndex, LabelIndex};
+pub use crate::types::{ImportDescriptor, ExportDescriptor, DataMode, ElementMode, ElementItems};
+pub use crate::types::{Name, Naming, IndirectNaming, ProducersField, ProducersFieldValue, TargetFeature, FeaturePrefix};
 pub use crate::validators::module::*;
+pub use crate::validators::{PreambleValidationError, ImportValidationError, TypeIndexValidationError, MemoryLimitsValidationError};
+pub use crate::validators::TableLimitsValidationError;
+pub use crate::validators::{GlobalValidationError, ExportValidationError, StartValidationError, ElementValidationError};
+pub use crate::validators::{DataValidationError, TagValidationError, CodeValidationError, Operand, IndexSpace, Features};
 
+pub mod encoder;
+mod error;
 mod parser;
 mod readers;
+pub mod text;
 mod types;
 mod validators;
\ No newline at end of file
@@ -1,131 +1,55 @@
-use std::io;
+use std::env;
+use std::error::Error as StdError;
 use std::fs::File;
-use std::io::{BufReader, Error, Read};
-use water::{ParseError, Parser, Chunk, SectionReader, TypeReaderError, ImportReaderError, FunctionReaderError, ExportReaderError, TableReaderError, MemoryReaderError, GlobalReaderError, StartReaderError, ElementReaderError, DataReaderError, InstructionReaderError, CodeReaderError, Validator, ValidationError};
-
-#[derive(Debug)]
-enum MyError {
-    Io(io::Error),
-    Parse(ParseError),
-    TypeReader(TypeReaderError),
-    ImportReader(ImportReaderError),
-    FunctionReader(FunctionReaderError),
-    ExportReader(ExportReaderError),
-    TableReader(TableReaderError),
-    MemoryReader(MemoryReaderError),
-    GlobalReader(GlobalReaderError),
-    StartReader(StartReaderError),
-    ElementReader(ElementReaderError),
-    CodeReader(CodeReaderError),
-    DataReader(DataReaderError),
-    InstructionReader(InstructionReaderError),
-    Validation(ValidationError),
-}
-
-impl From<io::Error> for MyError {
-    fn from(e: Error) -> Self {
-        MyError::Io(e)
-    }
-}
-
-impl From<ParseError> for MyError {
-    fn from(e: ParseError) -> Self {
-        MyError::Parse(e)
-    }
-}
-
-impl From<TypeReaderError> for MyError {
-    fn from(e: TypeReaderError) -> Self {
-        MyError::TypeReader(e)
-    }
-}
-
-impl From<ImportReaderError> for MyError {
-    fn from(e: ImportReaderError) -> Self {
-        MyError::ImportReader(e)
-    }
-}
-
-impl From<FunctionReaderError> for MyError {
-    fn from(e: FunctionReaderError) -> Self {
-        MyError::FunctionReader(e)
-    }
-}
-
-impl From<ExportReaderError> for MyError {
-    fn from(e: ExportReaderError) -> Self {
-        MyError::ExportReader(e)
-    }
-}
-
-impl From<TableReaderError> for MyError {
-    fn from(e: TableReaderError) -> Self {
-        MyError::TableReader(e)
-    }
-}
-
-impl From<MemoryReaderError> for MyError {
-    fn from(e: MemoryReaderError) -> Self {
-        MyError::MemoryReader(e)
-    }
-}
-
-impl From<GlobalReaderError> for MyError {
-    fn from(e: GlobalReaderError) -> Self {
-        MyError::GlobalReader(e)
-    }
-}
-
-impl From<StartReaderError> for MyError {
-    fn from(e: StartReaderError) -> Self {
-        MyError::StartReader(e)
-    }
-}
-
-impl From<ElementReaderError> for MyError {
-    fn from(e: ElementReaderError) -> Self {
-        MyError::ElementReader(e)
-    }
-}
-
-impl From<CodeReaderError> for MyError {
-    fn from(e: CodeReaderError) -> Self {
-        MyError::CodeReader(e)
-    }
-}
-
-impl From<DataReaderError> for MyError {
-    fn from(e: DataReaderError) -> Self {
-        MyError::DataReader(e)
-    }
-}
-
-impl From<InstructionReaderError> for MyError {
-    fn from(e: InstructionReaderError) -> Self {
-        MyError::InstructionReader(e)
-    }
-}
-
-impl From<ValidationError> for MyError {
-    fn from(e: ValidationError) -> Self {
-        MyError::Validation(e)
-    }
+use std::io::Read;
+use std::process;
+use water::{Parser, Chunk, SectionReader, CustomSectionKind, Validator, DataMode, ElementMode};
+
+const CHUNK_SIZE: usize = 4096;
+
+fn main() {
+    let mut args = env::args().skip(1).peekable();
+    let wat = args.next_if_eq("--wat").is_some();
+    let path = args.next().unwrap_or_else(|| "hello.wasm".to_string());
+    let result = File::open(&path)
+        .map_err(Box::from)
+        .and_then(|file| if wat { print_wat(file) } else { dump(file) });
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        let mut source = e.source();
+        while let Some(cause) = source {
+            eprintln!("  caused by: {}", cause);
+            source = cause.source();
+        }
+        process::exit(1);
+    }
+}
+
+fn print_wat(mut file: File) -> Result<(), Box<dyn StdError>> {
+    let mut module = Vec::new();
+    file.read_to_end(&mut module)?;
+    print!("{}", water::text::print(&module)?);
+    Ok(())
 }
 
-fn main() -> Result<(), MyError> {
-    // let f = File::open("hello.wasm")?;
-    let f = File::open("C:/Users/raminder.singh/Downloads/main_bg.wasm")?;
-    let mut reader = BufReader::new(f);
-    let mut v = Vec::new();
-    reader.read_to_end(&mut v)?;
-
+fn dump(mut file: File) -> Result<(), Box<dyn StdError>> {
     let mut parser = Parser::new();
     let mut validator = Validator::new();
+    let mut v = Vec::new();
+    let mut eof = false;
 
     loop {
-        let parse_result = parser.parse(&v)?;
-        let _ = validator.validate(&parse_result.1)?;
+        let parse_result = parser.parse(&v, eof)?;
+        validator.validate(&parse_result.1)?;
         let consumed = match parse_result {
+            (_, Chunk::NeedMoreData { hint }) => {
+                let len = v.len();
+                v.resize(len + hint.max(CHUNK_SIZE), 0);
+                let read = file.read(&mut v[len..])?;
+                v.truncate(len + read);
+                eof = read == 0;
+                continue;
+            },
             (consumed, Chunk::Preamble(magic_number, version)) => {
                 println!("Found header with magic_number: {:?} and version {}", magic_number, version);
                 consumed
@@ -134,6 +58,24 @@ fn main() -> Result<(), MyError> {
                 match section {
                     SectionReader::Custom(reader) => {
                         println!("Found custom section with name {} and {} bytes data.", reader.get_name(), reader.get_data().len());
+                        match reader.get_kind()? {
+                            CustomSectionKind::Name(name_reader) => {
+                                for name in name_reader {
+                                    println!("Found name subsection {:?}", name?);
+                                }
+                            },
+                            CustomSectionKind::Producers(producers_reader) => {
+                                for field in producers_reader {
+                                    println!("Found producers field {:?}", field?);
+                                }
+                            },
+                            CustomSectionKind::TargetFeatures(target_features_reader) => {
+                                for feature in target_features_reader {
+                                    println!("Found target feature {:?}", feature?);
+                                }
+                            },
+                            CustomSectionKind::Unknown => {},
+                        }
                     },
                     SectionReader::Type(reader) => {
                         println!("Found type section.");
@@ -165,6 +107,12 @@ fn main() -> Result<(), MyError> {
                             println!("Found memory {:?}", memory);
                         }
                     },
+                    SectionReader::Tag(reader) => {
+                        println!("Found tag section.");
+                        for tag in reader {
+                            println!("Found tag {:?}", tag);
+                        }
+                    },
                     SectionReader::Global(reader) => {
                         println!("Found global section.");
                         for global in reader {
@@ -190,9 +138,11 @@ fn main() -> Result<(), MyError> {
                         for element_segment in reader {
                             let element_segment = element_segment?;
                             println!("Found element segment {:?}", element_segment);
-                            for instruction in element_segment.instruction_reader {
-                                let instruction = instruction?;
-                                println!("Instruction: {:?}", instruction);
+                            if let ElementMode::Active { instruction_reader, .. } = element_segment.mode {
+                                for instruction in instruction_reader {
+                                    let instruction = instruction?;
+                                    println!("Instruction: {:?}", instruction);
+                                }
                             }
                         }
                     },
@@ -219,13 +169,18 @@ fn main() -> Result<(), MyError> {
                         for data_segment in reader {
                             let data_segment = data_segment?;
                             println!("Found data segment {:?}", data_segment);
-                            for instruction in data_segment.instruction_reader {
-                                let instruction = instruction?;
-                                println!("Instruction: {:?}", instruction);
+                            if let DataMode::Active { instruction_reader, .. } = data_segment.mode {
+                                for instruction in instruction_reader {
+                                    let instruction = instruction?;
+                                    println!("Instruction: {:?}", instruction);
+                                }
                             }
                         }
                     },
-                    SectionReader::Unknown(id) => println!("Found unknown section with id {}.", id),
+                    SectionReader::DataCount(reader) => {
+                        println!("Found data count section with count {}.", reader.get_count())
+                    },
+                    SectionReader::Unknown { id, offset, .. } => println!("Found unknown section with id {} at offset {}.", id, offset),
                 }
                 consumed
             }
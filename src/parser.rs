@@ -1,4 +1,5 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
+use crate::readers::binary::BinaryReaderError::UnexpectedEof;
 use crate::ParseError::*;
 use crate::{CustomSectionReader, CodeSectionReader, PreambleReaderError};
 use crate::TypeSectionReader;
@@ -6,12 +7,15 @@ use crate::ImportSectionReader;
 use crate::FunctionSectionReader;
 use crate::TableSectionReader;
 use crate::MemorySectionReader;
+use crate::TagSectionReader;
 use crate::GlobalSectionReader;
 use crate::ExportSectionReader;
 use crate::StartSectionReader;
 use crate::ElementSectionReader;
 use crate::DataSectionReader;
+use crate::DataCountSectionReader;
 use crate::PreambleReader;
+use std::{error, fmt};
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum SectionReader<'a> {
@@ -21,29 +25,133 @@ pub enum SectionReader<'a> {
     Function(FunctionSectionReader<'a>),
     Table(TableSectionReader<'a>),
     Memory(MemorySectionReader<'a>),
+    Tag(TagSectionReader<'a>),
     Global(GlobalSectionReader<'a>),
     Export(ExportSectionReader<'a>),
     Start(StartSectionReader<'a>),
     Element(ElementSectionReader<'a>),
     Code(CodeSectionReader<'a>),
     Data(DataSectionReader<'a>),
-    Unknown(u8),
+    DataCount(DataCountSectionReader<'a>),
+    Unknown { id: u8, offset: usize, raw_bytes: &'a [u8] },
+}
+
+impl<'a> SectionReader<'a> {
+    pub fn get_id(&self) -> u8 {
+        match self {
+            SectionReader::Custom(_) => 0,
+            SectionReader::Type(_) => 1,
+            SectionReader::Import(_) => 2,
+            SectionReader::Function(_) => 3,
+            SectionReader::Table(_) => 4,
+            SectionReader::Memory(_) => 5,
+            SectionReader::Tag(_) => 13,
+            SectionReader::Global(_) => 6,
+            SectionReader::Export(_) => 7,
+            SectionReader::Start(_) => 8,
+            SectionReader::Element(_) => 9,
+            SectionReader::Code(_) => 10,
+            SectionReader::Data(_) => 11,
+            SectionReader::DataCount(_) => 12,
+            SectionReader::Unknown { id, .. } => *id,
+        }
+    }
+
+    /// Returns the absolute offset of the section in the module, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        match self {
+            SectionReader::Custom(reader) => reader.get_section_offset(),
+            SectionReader::Type(reader) => reader.get_section_offset(),
+            SectionReader::Import(reader) => reader.get_section_offset(),
+            SectionReader::Function(reader) => reader.get_section_offset(),
+            SectionReader::Table(reader) => reader.get_section_offset(),
+            SectionReader::Memory(reader) => reader.get_section_offset(),
+            SectionReader::Tag(reader) => reader.get_section_offset(),
+            SectionReader::Global(reader) => reader.get_section_offset(),
+            SectionReader::Export(reader) => reader.get_section_offset(),
+            SectionReader::Start(reader) => reader.get_section_offset(),
+            SectionReader::Element(reader) => reader.get_section_offset(),
+            SectionReader::Code(reader) => reader.get_section_offset(),
+            SectionReader::Data(reader) => reader.get_section_offset(),
+            SectionReader::DataCount(reader) => reader.get_section_offset(),
+            SectionReader::Unknown { offset, raw_bytes, .. } => {
+                // `offset` is past the id and the LEB128 size, whose last byte has no continuation bit.
+                let size_length = raw_bytes[1..].iter().take_while(|byte| **byte & 0x80 != 0).count() + 1;
+                offset - 1 - size_length
+            },
+        }
+    }
+
+    /// Returns the bytes of the whole section, id and size included, exactly
+    /// as they appear in the module, so that the section can be copied as is.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        match self {
+            SectionReader::Custom(reader) => reader.get_raw_bytes(),
+            SectionReader::Type(reader) => reader.get_raw_bytes(),
+            SectionReader::Import(reader) => reader.get_raw_bytes(),
+            SectionReader::Function(reader) => reader.get_raw_bytes(),
+            SectionReader::Table(reader) => reader.get_raw_bytes(),
+            SectionReader::Memory(reader) => reader.get_raw_bytes(),
+            SectionReader::Tag(reader) => reader.get_raw_bytes(),
+            SectionReader::Global(reader) => reader.get_raw_bytes(),
+            SectionReader::Export(reader) => reader.get_raw_bytes(),
+            SectionReader::Start(reader) => reader.get_raw_bytes(),
+            SectionReader::Element(reader) => reader.get_raw_bytes(),
+            SectionReader::Code(reader) => reader.get_raw_bytes(),
+            SectionReader::Data(reader) => reader.get_raw_bytes(),
+            SectionReader::DataCount(reader) => reader.get_raw_bytes(),
+            SectionReader::Unknown { raw_bytes, .. } => raw_bytes,
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum Chunk<'a> {
     Preamble(&'a [u8;4], u32),
     Section(SectionReader<'a>),
+    /// The buffer ends in the middle of a chunk. At least `hint` more bytes
+    /// must be appended to it before calling `Parser::parse` again.
+    NeedMoreData { hint: usize },
     Done,
 }
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum ParseError {
-    UnneededBytes,
+    UnneededBytes(usize),
     BinaryReader(BinaryReaderError),
     PreambleReader(PreambleReaderError),
 }
 
+impl ParseError {
+    pub fn offset(&self) -> usize {
+        match self {
+            UnneededBytes(offset) => *offset,
+            BinaryReader(e) => e.offset(),
+            PreambleReader(e) => e.offset(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnneededBytes(offset) => write!(f, "unexpected bytes after the end of the module at offset {}", offset),
+            BinaryReader(e) => e.fmt(f),
+            PreambleReader(e) => e.fmt(f),
+        }
+    }
+}
+
+impl error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            UnneededBytes(_) => None,
+            BinaryReader(e) => e.source(),
+            PreambleReader(e) => e.source(),
+        }
+    }
+}
+
 impl From<BinaryReaderError> for ParseError {
     fn from(e: BinaryReaderError) -> Self {
         BinaryReader(e)
@@ -56,6 +164,8 @@ impl From<PreambleReaderError> for ParseError {
     }
 }
 
+const PREAMBLE_SIZE: usize = 8;
+
 enum ParserLocation {
     ModuleHeader,
     Section,
@@ -64,59 +174,90 @@ enum ParserLocation {
 
 pub struct Parser {
     location: ParserLocation,
+    offset: usize,
 }
 
 impl Parser {
     pub fn new() -> Parser {
         Parser {
             location: ParserLocation::ModuleHeader,
+            offset: 0,
         }
     }
 
-    pub fn parse<'a>(&mut self, buffer: &'a [u8]) -> Result<(usize, Chunk<'a>), ParseError> {
-        match self.location {
+    /// Parses the next chunk from `buffer`, which must start right after the
+    /// bytes consumed by the previous call. Returns the number of bytes consumed.
+    ///
+    /// `eof` tells whether `buffer` holds all the remaining bytes of the module.
+    /// When it doesn't and a chunk is incomplete, `Chunk::NeedMoreData` is
+    /// returned instead of an error, and nothing is consumed.
+    pub fn parse<'a>(&mut self, buffer: &'a [u8], eof: bool) -> Result<(usize, Chunk<'a>), ParseError> {
+        let (consumed, chunk) = match self.location {
             ParserLocation::ModuleHeader => {
+                if !eof && buffer.len() < PREAMBLE_SIZE {
+                    return Ok((0, Chunk::NeedMoreData { hint: PREAMBLE_SIZE - buffer.len() }));
+                }
                 let mut preamble_reader = PreambleReader::new(buffer);
                 let (consumed, magic_number, version) = preamble_reader.read_preamble()?;
                 self.location = ParserLocation::Section;
-                Ok((consumed, Chunk::Preamble(magic_number, version)))
+                (consumed, Chunk::Preamble(magic_number, version))
             },
             ParserLocation::Section => {
                 if buffer.is_empty() {
+                    if !eof {
+                        return Ok((0, Chunk::NeedMoreData { hint: 1 }));
+                    }
                     self.location = ParserLocation::End;
-                    Ok((0, Chunk::Done))
+                    (0, Chunk::Done)
                 } else {
-                    let mut reader = BinaryReader::new(buffer);
+                    let mut reader = BinaryReader::new_with_offset(buffer, self.offset);
                     let id = reader.read_byte()?;
-                    let bytes = reader.read_bytes_vec()?;
-                    Ok((reader.get_position(), Chunk::Section(Self::create_section_reader(bytes, id)?)))
+                    let len = match reader.read_leb128_u32() {
+                        Ok(len) => len as usize,
+                        Err(UnexpectedEof(_)) if !eof => {
+                            return Ok((0, Chunk::NeedMoreData { hint: 1 }));
+                        }
+                        Err(e) => return Err(e.into()),
+                    };
+                    let end = reader.get_position() + len;
+                    if !eof && buffer.len() < end {
+                        return Ok((0, Chunk::NeedMoreData { hint: end - buffer.len() }));
+                    }
+                    let offset = reader.get_original_position();
+                    reader.read_bytes(len)?;
+                    let bytes = &buffer[..end];
+                    (end, Chunk::Section(Self::create_section_reader(bytes, id, self.offset, offset)?))
                 }
             }
             ParserLocation::End => {
                 if !buffer.is_empty() {
-                    Err(UnneededBytes)
+                    return Err(UnneededBytes(self.offset));
                 } else {
-                    Ok((0, Chunk::Done))
+                    (0, Chunk::Done)
                 }
             }
-        }
+        };
+        self.offset += consumed;
+        Ok((consumed, chunk))
     }
 
-    fn create_section_reader(buffer: &[u8], id: u8) -> Result<SectionReader, ParseError> {
+    fn create_section_reader(buffer: &[u8], id: u8, offset: usize, contents_offset: usize) -> Result<SectionReader<'_>, ParseError> {
         Ok(match id {
-            0 => SectionReader::Custom(CustomSectionReader::new(buffer)?),
-            1 => SectionReader::Type(TypeSectionReader::new(buffer)?),
-            2 => SectionReader::Import(ImportSectionReader::new(buffer)?),
-            3 => SectionReader::Function(FunctionSectionReader::new(buffer)?),
-            4 => SectionReader::Table(TableSectionReader::new(buffer)?),
-            5 => SectionReader::Memory(MemorySectionReader::new(buffer)?),
-            6 => SectionReader::Global(GlobalSectionReader::new(buffer)?),
-            7 => SectionReader::Export(ExportSectionReader::new(buffer)?),
-            8 => SectionReader::Start(StartSectionReader::new(buffer)?),
-            9 => SectionReader::Element(ElementSectionReader::new(buffer)?),
-            10 => SectionReader::Code(CodeSectionReader::new(buffer)?),
-            11 => SectionReader::Data(DataSectionReader::new(buffer)?),
-            id => SectionReader::Unknown(id),
+            0 => SectionReader::Custom(CustomSectionReader::new(buffer, offset)?),
+            1 => SectionReader::Type(TypeSectionReader::new(buffer, offset)?),
+            2 => SectionReader::Import(ImportSectionReader::new(buffer, offset)?),
+            3 => SectionReader::Function(FunctionSectionReader::new(buffer, offset)?),
+            4 => SectionReader::Table(TableSectionReader::new(buffer, offset)?),
+            5 => SectionReader::Memory(MemorySectionReader::new(buffer, offset)?),
+            6 => SectionReader::Global(GlobalSectionReader::new(buffer, offset)?),
+            7 => SectionReader::Export(ExportSectionReader::new(buffer, offset)?),
+            8 => SectionReader::Start(StartSectionReader::new(buffer, offset)?),
+            9 => SectionReader::Element(ElementSectionReader::new(buffer, offset)?),
+            10 => SectionReader::Code(CodeSectionReader::new(buffer, offset)?),
+            11 => SectionReader::Data(DataSectionReader::new(buffer, offset)?),
+            12 => SectionReader::DataCount(DataCountSectionReader::new(buffer, offset)?),
+            13 => SectionReader::Tag(TagSectionReader::new(buffer, offset)?),
+            id => SectionReader::Unknown { id, offset: contents_offset, raw_bytes: buffer },
         })
     }
 }
@@ -129,34 +270,50 @@ impl Default for Parser {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Parser, Validator, ValidationError};
+    use crate::{Parser, Validator, ValidationError, Chunk, TypeReaderError};
     use crate::readers::binary::BinaryReaderError::UnexpectedEof;
     use crate::Chunk::Preamble;
-    use crate::ParseError::PreambleReader;
+    use crate::ParseError::{PreambleReader, BinaryReader, UnneededBytes};
     use crate::readers::preamble::PreambleReaderError::BinaryReaderError;
     use crate::validators::preamble::PreambleValidationError;
+    use crate::validators::code::{CodeValidationError, Operand};
+    use crate::types::{FuncIndex, ValueType};
+
+    fn validate_module(module: &[u8]) -> Result<(), ValidationError> {
+        let mut parser = Parser::new();
+        let mut validator = Validator::new();
+        let mut position = 0;
+        loop {
+            let (consumed, chunk) = parser.parse(&module[position..], true).unwrap();
+            validator.validate(&chunk)?;
+            if chunk == Chunk::Done {
+                return Ok(());
+            }
+            position += consumed;
+        }
+    }
 
     #[test]
     fn parse_header_from_empty() {
         let mut parser = Parser::new();
-        let result = parser.parse(&[]);
-        let expected = Err(PreambleReader(BinaryReaderError(UnexpectedEof)));
+        let result = parser.parse(&[], true);
+        let expected = Err(PreambleReader(BinaryReaderError(UnexpectedEof(0))));
         assert_eq!(expected, result);
     }
 
     #[test]
     fn parse_header_bad_magic_no() {
         let mut parser = Parser::new();
-        let result = parser.parse(b"\0as");
-        let expected = Err(PreambleReader(BinaryReaderError(UnexpectedEof)));
+        let result = parser.parse(b"\0as", true);
+        let expected = Err(PreambleReader(BinaryReaderError(UnexpectedEof(0))));
         assert_eq!(expected, result);
     }
 
     #[test]
     fn parse_header_only_magic_no() {
         let mut parser = Parser::new();
-        let result = parser.parse(b"\0asm");
-        let expected = Err(PreambleReader(BinaryReaderError(UnexpectedEof)));
+        let result = parser.parse(b"\0asm", true);
+        let expected = Err(PreambleReader(BinaryReaderError(UnexpectedEof(4))));
         assert_eq!(expected, result);
     }
 
@@ -164,7 +321,7 @@ mod tests {
     fn parse_header_bad_version() {
         let mut parser = Parser::new();
         let mut validator = Validator::new();
-        let result = parser.parse(b"\0asm\x02\0\0\0").unwrap();
+        let result = parser.parse(b"\0asm\x02\0\0\0", true).unwrap();
         let actual = validator.validate(&result.1);
         let expected = Err(ValidationError::PreambleValidation(PreambleValidationError::BadVersion));
         assert_eq!(expected, actual);
@@ -173,15 +330,109 @@ mod tests {
     #[test]
     fn parse_good_header() {
         let mut parser = Parser::new();
-        let result = parser.parse(b"\0asm\x01\0\0\0");
-        assert_eq!(Ok((8, Preamble(&[b'\0', b'a', b's', b'm'], 1))), result);
+        let result = parser.parse(b"\0asm\x01\0\0\0", true);
+        assert_eq!(Ok((8, Preamble(b"\0asm", 1))), result);
+    }
+
+    #[test]
+    fn reader_error_has_absolute_offset() {
+        let module = b"\0asm\x01\0\0\0\x01\x02\x01\x61";
+        let expected = Err(ValidationError::TypeReader(TypeReaderError::InvalidLeadingByte(11)));
+        assert_eq!(expected, validate_module(module));
+    }
+
+    #[test]
+    fn code_validation_error_has_function_index_and_offset() {
+        let module = b"\0asm\x01\0\0\0\
+            \x01\x04\x01\x60\x00\x00\
+            \x03\x02\x01\x00\
+            \x0A\x08\x01\x06\x00\x42\x00\x45\x1A\x0B";
+        let expected = Err(ValidationError::CodeValidation {
+            error: CodeValidationError::TypeMismatch {
+                expected: Operand::Known(ValueType::I32),
+                actual: Operand::Known(ValueType::I64),
+            },
+            func_index: FuncIndex(0),
+            offset: 25,
+        });
+        assert_eq!(expected, validate_module(module));
+    }
+
+    #[test]
+    fn error_display_and_source_chain() {
+        use std::error::Error as _;
+
+        let module = b"\0asm\x01\0\0\0\
+            \x01\x04\x01\x60\x00\x00\
+            \x03\x02\x01\x00\
+            \x0A\x08\x01\x06\x00\x42\x00\x45\x1A\x0B";
+        let error = crate::Error::from(validate_module(module).unwrap_err());
+        assert_eq!(25, error.offset());
+        assert_eq!("invalid body of function 0 at offset 25", error.to_string());
+        let source = error.source().unwrap();
+        assert_eq!("type mismatch: expected i32 but found i64", source.to_string());
+        assert!(source.source().is_none());
+    }
+
+    #[test]
+    fn unneeded_bytes_test() {
+        let mut parser = Parser::new();
+        let _ = parser.parse(b"\0asm\x01\0\0\0", true);
+        assert_eq!(Ok((0, Chunk::Done)), parser.parse(&[], true));
+        let result = parser.parse(b"MoreBytes", true);
+        assert_eq!(Err(UnneededBytes(8)), result);
+    }
+
+    #[test]
+    fn bytes_after_the_preamble_are_a_section() {
+        let mut parser = Parser::new();
+        let _ = parser.parse(b"\0asm\x01\0\0\0", true);
+        // Only `Chunk::Done` ends the module, until then "MoreBytes" is a section of id 'M' and size 'o'.
+        let result = parser.parse(b"MoreBytes", true);
+        assert_eq!(Err(BinaryReader(UnexpectedEof(10))), result);
     }
 
-    //#[test]
-    // fn unneeded_bytes_test() {
-    //     let mut parser = Parser::new();
-    //     let _ = parser.parse(b"\0asm\x01\0\0\0");
-    //     let result = parser.parse(b"MoreBytes");
-    //     assert_eq!(Err(UnneededBytes), result);
-    // }
+    #[test]
+    fn need_more_data_for_incomplete_header() {
+        let mut parser = Parser::new();
+        assert_eq!(Ok((0, Chunk::NeedMoreData { hint: 8 })), parser.parse(&[], false));
+        assert_eq!(Ok((0, Chunk::NeedMoreData { hint: 5 })), parser.parse(b"\0as", false));
+        assert_eq!(Ok((8, Preamble(b"\0asm", 1))), parser.parse(b"\0asm\x01\0\0\0", false));
+        assert_eq!(Ok((0, Chunk::NeedMoreData { hint: 1 })), parser.parse(&[], false));
+        assert_eq!(Ok((0, Chunk::NeedMoreData { hint: 1 })), parser.parse(b"\x01\x84", false));
+        assert_eq!(Ok((0, Chunk::NeedMoreData { hint: 3 })), parser.parse(b"\x01\x04\x01", false));
+    }
+
+    #[test]
+    fn streaming_in_fixed_size_chunks() {
+        let module = b"\0asm\x01\0\0\0\
+            \x01\x04\x01\x60\x00\x00\
+            \x03\x02\x01\x00\
+            \x0A\x06\x01\x04\x00\x01\x01\x0B";
+        for chunk_size in 1..=module.len() {
+            let mut parser = Parser::new();
+            let mut validator = Validator::new();
+            let mut buffer = Vec::new();
+            let mut fed = 0;
+            let mut sections = 0;
+            loop {
+                let eof = fed == module.len();
+                let (consumed, chunk) = parser.parse(&buffer, eof).unwrap();
+                validator.validate(&chunk).unwrap();
+                match chunk {
+                    Chunk::NeedMoreData { hint } => {
+                        assert!(!eof && hint > 0);
+                        let end = module.len().min(fed + chunk_size);
+                        buffer.extend_from_slice(&module[fed..end]);
+                        fed = end;
+                    }
+                    Chunk::Section(_) => sections += 1,
+                    Chunk::Preamble(_, _) => {}
+                    Chunk::Done => break,
+                }
+                buffer.drain(..consumed);
+            }
+            assert_eq!(3, sections);
+        }
+    }
 }
\ No newline at end of file
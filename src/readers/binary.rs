@@ -1,44 +1,120 @@
 use std::convert::{TryInto, TryFrom};
 use crate::readers::binary::BinaryReaderError::*;
-use std::{result, str};
-use crate::types::{TableType, Limits, MemoryType, GlobalType, ValueType};
-use crate::types::ValueType::{I32, I64, F32, F64};
-use crate::{BranchTableReader, InstructionReader};
+use std::{error, fmt, result, str};
+use crate::types::{TableType, Limits, MemoryType, GlobalType, ValueType, TagType, TypeIndex};
+use crate::types::ValueType::{I32, I64, F32, F64, V128, FuncRef, ExternRef, ExnRef};
+use crate::{BranchTableReader, InstructionReader, Instruction};
+use crate::readers::instruction::Result as InstructionReaderResult;
 
 pub type Result<T, E = BinaryReaderError> = result::Result<T, E>;
 
+/// Errors raised while decoding the binary format. Every variant carries
+/// the absolute offset in the module at which the error was detected.
 #[derive(PartialEq, Eq, Debug)]
 pub enum BinaryReaderError {
-    UnexpectedEof,
-    InvalidU32,
-    InvalidS32,
-    InvalidS64,
-    InvalidS33,
-    InvalidUtf8,
-    InvalidElementTypeByte,
-    InvalidLimitsByte,
-    InvalidValueTypeByte,
-    InvalidMutableByte,
+    UnexpectedEof(usize),
+    InvalidU32(usize),
+    InvalidU64(usize),
+    InvalidS32(usize),
+    InvalidS64(usize),
+    InvalidS33(usize),
+    InvalidUtf8(usize),
+    InvalidElementTypeByte(usize),
+    InvalidLimitsByte(usize),
+    InvalidValueTypeByte(usize),
+    InvalidMutableByte(usize),
+    InvalidTagAttribute(usize),
 }
 
+impl BinaryReaderError {
+    pub fn offset(&self) -> usize {
+        match *self {
+            UnexpectedEof(offset) |
+            InvalidU32(offset) |
+            InvalidU64(offset) |
+            InvalidS32(offset) |
+            InvalidS64(offset) |
+            InvalidS33(offset) |
+            InvalidUtf8(offset) |
+            InvalidElementTypeByte(offset) |
+            InvalidLimitsByte(offset) |
+            InvalidValueTypeByte(offset) |
+            InvalidMutableByte(offset) |
+            InvalidTagAttribute(offset) => offset,
+        }
+    }
+}
+
+impl fmt::Display for BinaryReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            UnexpectedEof(_) => "unexpected end of input",
+            InvalidU32(_) => "invalid LEB128 encoded u32",
+            InvalidU64(_) => "invalid LEB128 encoded u64",
+            InvalidS32(_) => "invalid LEB128 encoded s32",
+            InvalidS64(_) => "invalid LEB128 encoded s64",
+            InvalidS33(_) => "invalid LEB128 encoded s33",
+            InvalidUtf8(_) => "invalid UTF-8 encoding",
+            InvalidElementTypeByte(_) => "invalid table element type",
+            InvalidLimitsByte(_) => "invalid limits flags",
+            InvalidValueTypeByte(_) => "invalid value type",
+            InvalidMutableByte(_) => "invalid global mutability",
+            InvalidTagAttribute(_) => "invalid tag attribute",
+        };
+        write!(f, "{} at offset {}", message, self.offset())
+    }
+}
+
+impl error::Error for BinaryReaderError {}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct BinaryReader<'a> {
     buffer: &'a [u8],
     position: usize,
+    original_offset: usize,
 }
 
 impl<'a> BinaryReader<'a> {
-    pub fn new(buffer: &[u8]) -> BinaryReader {
+    pub fn new(buffer: &'a [u8]) -> BinaryReader<'a> {
+        Self::new_with_offset(buffer, 0)
+    }
+
+    /// Creates a reader over `buffer` which starts at `original_offset`
+    /// in the module, so that errors report absolute offsets.
+    pub fn new_with_offset(buffer: &'a [u8], original_offset: usize) -> BinaryReader<'a> {
         BinaryReader {
             buffer,
             position: 0,
+            original_offset,
         }
     }
 
+    /// Creates a reader over a whole section starting at `original_offset`,
+    /// positioned past the section id and size.
+    pub(crate) fn new_for_section(buffer: &'a [u8], original_offset: usize) -> Result<BinaryReader<'a>> {
+        let mut reader = Self::new_with_offset(buffer, original_offset);
+        reader.read_byte()?;
+        reader.read_leb128_u32()?;
+        Ok(reader)
+    }
+
     pub(crate) fn get_position(&self) -> usize {
         self.position
     }
 
+    pub fn get_original_position(&self) -> usize {
+        self.original_offset + self.position
+    }
+
+    /// Returns the absolute offset at which the buffer starts.
+    pub(crate) fn get_original_offset(&self) -> usize {
+        self.original_offset
+    }
+
+    pub(crate) fn get_buffer(&self) -> &'a [u8] {
+        self.buffer
+    }
+
     pub(crate) fn eof(&self) -> bool {
         self.position >= self.buffer.len()
     }
@@ -47,7 +123,7 @@ impl<'a> BinaryReader<'a> {
         if self.position + n <= self.buffer.len() {
             Ok(())
         } else {
-            Err(UnexpectedEof)
+            Err(UnexpectedEof(self.get_original_position()))
         }
     }
 
@@ -74,6 +150,7 @@ impl<'a> BinaryReader<'a> {
     }
 
     pub(crate) fn read_leb128_u32(&mut self) -> Result<u32> {
+        let start = self.get_original_position();
         let mut result: u32 = 0;
         let mut shift = 0;
         loop {
@@ -81,7 +158,26 @@ impl<'a> BinaryReader<'a> {
             result |= ((byte & 0b0111_1111) as u32) << shift;
             // The fifth byte's 4 high bits must be zero
             if shift == 28 && (byte >> 4) != 0 {
-                return Err(InvalidU32);
+                return Err(InvalidU32(start));
+            }
+            shift += 7;
+            if byte & 0b1000_0000 == 0 {
+                break;
+            }
+        }
+        Ok(result)
+    }
+
+    pub(crate) fn read_leb128_u64(&mut self) -> Result<u64> {
+        let start = self.get_original_position();
+        let mut result: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_byte()?;
+            result |= ((byte & 0b0111_1111) as u64) << shift;
+            // The tenth byte's 7 high bits must be zero
+            if shift == 63 && (byte >> 1) != 0 {
+                return Err(InvalidU64(start));
             }
             shift += 7;
             if byte & 0b1000_0000 == 0 {
@@ -92,6 +188,7 @@ impl<'a> BinaryReader<'a> {
     }
 
     pub(crate) fn read_leb128_s33(&mut self) -> Result<i64> {
+        let start = self.get_original_position();
         let mut result: i64 = 0;
         let mut shift = 0;
         loop {
@@ -101,7 +198,7 @@ impl<'a> BinaryReader<'a> {
                 let more = (byte & 0b1000_0000) != 0;
                 let sign_and_unused_bits = (byte << 1) as i8 >> 5;
                 return if more || (sign_and_unused_bits != 0 && sign_and_unused_bits != -1) {
-                    Err(InvalidS33)
+                    Err(InvalidS33(start))
                 } else {
                     //extend the sign bit to all the unused bits
                     let unused_bits = 64 - 33;
@@ -125,6 +222,7 @@ impl<'a> BinaryReader<'a> {
     }
 
     pub(crate) fn read_leb128_s32(&mut self) -> Result<i32> {
+        let start = self.get_original_position();
         let mut result: i32 = 0;
         let mut shift = 0;
         loop {
@@ -134,7 +232,7 @@ impl<'a> BinaryReader<'a> {
                 let more = (byte & 0b1000_0000) != 0;
                 let sign_and_unused_bits = (byte << 1) as i8 >> 4;
                 return if more || (sign_and_unused_bits != 0 && sign_and_unused_bits != -1) {
-                    Err(InvalidS32)
+                    Err(InvalidS32(start))
                 } else {
                     Ok(result)
                 }
@@ -155,6 +253,7 @@ impl<'a> BinaryReader<'a> {
     }
 
     pub(crate) fn read_leb128_s64(&mut self) -> Result<i64> {
+        let start = self.get_original_position();
         let mut result: i64 = 0;
         let mut shift = 0;
         loop {
@@ -164,7 +263,7 @@ impl<'a> BinaryReader<'a> {
                 let more = (byte & 0b1000_0000) != 0;
                 let sign_and_unused_bits = (byte << 1) as i8 >> 1;
                 return if more || (sign_and_unused_bits != 0 && sign_and_unused_bits != -1) {
-                    Err(InvalidS64)
+                    Err(InvalidS64(start))
                 } else {
                     Ok(result)
                 }
@@ -200,28 +299,51 @@ impl<'a> BinaryReader<'a> {
         where 'a: 'b
     {
         let (start, end) = BranchTableReader::skip_br_table(self)?;
-        BranchTableReader::new(&self.buffer[start..end])
+        BranchTableReader::new(&self.buffer[start..end], self.original_offset + start)
     }
 
     pub(crate) fn read_string(&mut self) -> Result<&'a str> {
         let len = self.read_leb128_u32()? as usize;
+        let start = self.get_original_position();
         let bytes = self.read_bytes(len)?;
-        str::from_utf8(bytes).map_err(|_| BinaryReaderError::InvalidUtf8)
+        str::from_utf8(bytes).map_err(|_| InvalidUtf8(start))
     }
 
     pub(crate) fn read_table_type(&mut self) -> Result<TableType> {
+        let element_type = self.read_reference_type()?;
+        let limits = self.read_limits()?;
+        Ok(TableType { element_type, limits })
+    }
+
+    pub(crate) fn read_reference_type(&mut self) -> Result<ValueType> {
+        let offset = self.get_original_position();
         match self.read_byte()? {
-            0x70 => {
-                let limits = self.read_limits()?;
-                Ok(TableType { limits })
-            },
-            _ => Err(InvalidElementTypeByte)
+            0x70 => Ok(FuncRef),
+            0x6F => Ok(ExternRef),
+            0x69 => Ok(ExnRef),
+            _ => Err(InvalidElementTypeByte(offset))
         }
     }
 
+    /// Reads a memory type, whose limits flags have a second bit telling
+    /// whether the memory is shared and a third one telling whether it is
+    /// a 64-bit memory, with 64-bit limits.
     pub(crate) fn read_memory_type(&mut self) -> Result<MemoryType> {
-        let limits = self.read_limits()?;
-        Ok(MemoryType { limits })
+        let offset = self.get_original_position();
+        let flags = self.read_byte()?;
+        if flags > 0x07 {
+            return Err(InvalidLimitsByte(offset));
+        }
+        let has_max = flags & 0x01 != 0;
+        let memory64 = flags & 0x04 != 0;
+        let limits = if memory64 {
+            let min = self.read_leb128_u64()?;
+            let max = if has_max { Some(self.read_leb128_u64()?) } else { None };
+            Limits { min, max }
+        } else {
+            self.read_limits_bounds(has_max)?
+        };
+        Ok(MemoryType { limits, shared: flags & 0x02 != 0, memory64 })
     }
 
     pub(crate) fn read_global_type(&mut self) -> Result<GlobalType> {
@@ -230,11 +352,23 @@ impl<'a> BinaryReader<'a> {
         Ok(GlobalType { var_type: tp, mutable })
     }
 
+    /// Reads a tag type, whose attribute byte is 0 for the exceptions that
+    /// are the only kind of tag.
+    pub(crate) fn read_tag_type(&mut self) -> Result<TagType> {
+        let offset = self.get_original_position();
+        if self.read_byte()? != 0x00 {
+            return Err(InvalidTagAttribute(offset));
+        }
+        let type_index = TypeIndex(self.read_leb128_u32()?);
+        Ok(TagType { type_index })
+    }
+
     fn read_mutable_byte(&mut self) -> Result<bool> {
+        let offset = self.get_original_position();
         match self.read_byte()? {
             0x00 => Ok(false),
             0x01 => Ok(true),
-            _ => Err(InvalidMutableByte),
+            _ => Err(InvalidMutableByte(offset)),
         }
     }
 
@@ -245,32 +379,35 @@ impl<'a> BinaryReader<'a> {
             0x7E => Ok(I64),
             0x7D => Ok(F32),
             0x7C => Ok(F64),
+            0x7B => Ok(V128),
+            0x70 => Ok(FuncRef),
+            0x6F => Ok(ExternRef),
+            0x69 => Ok(ExnRef),
             _ => {
                 self.position = position;
-                Err(InvalidValueTypeByte)
+                Err(InvalidValueTypeByte(self.get_original_position()))
             }
         }
     }
 
     fn read_limits(&mut self) -> Result<Limits> {
+        let offset = self.get_original_position();
         match self.read_byte()? {
-            0x00 => {
-                let min = self.read_leb128_u32()?;
-                let max = None;
-                Ok(Limits { min, max })
-            },
-            0x01 => {
-                let min = self.read_leb128_u32()?;
-                let max = Some(self.read_leb128_u32()?);
-                Ok(Limits { min, max })
-            },
-            _ => Err(InvalidLimitsByte)
+            0x00 => self.read_limits_bounds(false),
+            0x01 => self.read_limits_bounds(true),
+            _ => Err(InvalidLimitsByte(offset))
         }
     }
 
+    fn read_limits_bounds(&mut self, has_max: bool) -> Result<Limits> {
+        let min = self.read_leb128_u32()? as u64;
+        let max = if has_max { Some(self.read_leb128_u32()? as u64) } else { None };
+        Ok(Limits { min, max })
+    }
+
     pub(crate) fn create_buffer_slice(&self, start: usize, end: usize) -> Result<&'a [u8]> {
         if end > self.buffer.len() {
-            Err(UnexpectedEof)
+            Err(UnexpectedEof(self.original_offset + self.buffer.len()))
         } else {
             Ok(&self.buffer[start..end])
         }
@@ -284,16 +421,27 @@ impl<'a> BinaryReader<'a> {
         self.create_buffer_slice(start, end)
     }
 
-    pub(crate) fn create_instruction_reader(&mut self) -> Result<InstructionReader<'a>> {
+    /// Creates a reader over the expression starting at the current position,
+    /// which ends with the `end` that closes it rather than with the first
+    /// 0x0B byte, as immediates may contain that byte too. An expression
+    /// running past the end of the buffer is a `BinaryReaderError` wrapped
+    /// in the returned error, which section readers report unwrapped.
+    pub(crate) fn create_instruction_reader(&mut self) -> InstructionReaderResult<InstructionReader<'a>> {
         let before = self.position;
+        let mut scanner = InstructionReader::new(&self.buffer[before..], self.original_offset + before)?;
+        let mut depth = 0;
         loop {
-            match self.read_byte()? {
-                0x0B => break,
-                _ => continue,
+            match scanner.read()? {
+                Instruction::Block { .. } | Instruction::Loop { .. } | Instruction::If { .. } |
+                Instruction::Try { .. } | Instruction::TryTable { .. } => depth += 1,
+                Instruction::End | Instruction::Delegate { .. } if depth == 0 => break,
+                Instruction::End | Instruction::Delegate { .. } => depth -= 1,
+                _ => {},
             }
         }
-        let after = self.position;
-        Ok(InstructionReader::new(self.create_buffer_slice(before, after)?)?)
+        let after = scanner.get_original_position() - self.original_offset;
+        self.position = after;
+        Ok(InstructionReader::new(self.create_buffer_slice(before, after)?, self.original_offset + before)?)
     }
 }
 
@@ -324,13 +472,13 @@ mod tests {
     fn u32_roundtrip() {
         let lot_size = 10000000;
         let mut lot = 1;
-        for i in 0..=u32::max_value() {
+        for i in 0..=u32::MAX {
             let encoded = encode_u32(i);
             let mut reader = BinaryReader::new(&encoded);
             let actual_result: Result<u32, BinaryReaderError> = reader.read_leb128_u32();
             assert_eq!(Ok(i), actual_result);
             if i % lot_size == 0 {
-                println!("Done {} lots of {}", lot, u32::max_value() / lot_size);
+                println!("Done {} lots of {}", lot, u32::MAX / lot_size);
                 lot += 1;
             }
         }
@@ -342,8 +490,8 @@ mod tests {
     fn invalid_more_bit_u32() {
         let lot_size = 10000000;
         let mut lot = 1;
-        let total = u32::max_value() - 268_435_456;
-        for i in 268_435_456..=u32::max_value() {
+        let total = u32::MAX - 268_435_456;
+        for i in 268_435_456..=u32::MAX {
             let mut encoded = encode_u32(i);
             assert_eq!(5, encoded.len());
             let mut last_byte = encoded[4];
@@ -351,7 +499,7 @@ mod tests {
             encoded[4] = last_byte;
             let mut reader = BinaryReader::new(&encoded);
             let actual_result: Result<u32, BinaryReaderError> = reader.read_leb128_u32();
-            assert_eq!(Err(InvalidU32), actual_result);
+            assert_eq!(Err(InvalidU32(0)), actual_result);
             if i % lot_size == 0 {
                 println!("Done {} lots of {}", lot, total / lot_size);
                 lot += 1;
@@ -384,13 +532,13 @@ mod tests {
     fn s32_roundtrip() {
         let lot_size = 10000000;
         let mut lot = 1;
-        for i in i32::min_value()..=i32::max_value() {
+        for i in i32::MIN..=i32::MAX {
             let encoded = encode_s32(i);
             let mut reader = BinaryReader::new(&encoded);
             let actual_result: Result<i32, BinaryReaderError> = reader.read_leb128_s32();
             assert_eq!(Ok(i), actual_result);
             if i % lot_size == 0 {
-                println!("Done {} lots of {}", lot, u32::max_value() / lot_size as u32);
+                println!("Done {} lots of {}", lot, u32::MAX / lot_size as u32);
                 lot += 1;
             }
         }
@@ -1,6 +1,6 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use crate::readers::binary::Result as BinaryReaderResult;
-use std::result;
+use std::{error, fmt, result};
 use crate::types::LabelIndex;
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -14,6 +14,30 @@ pub enum BranchReaderError {
     BinaryReaderError(BinaryReaderError),
 }
 
+impl BranchReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            BranchReaderError::BinaryReaderError(e) => e.offset(),
+        }
+    }
+}
+
+impl fmt::Display for BranchReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BranchReaderError::BinaryReaderError(_) => write!(f, "malformed br_table"),
+        }
+    }
+}
+
+impl error::Error for BranchReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            BranchReaderError::BinaryReaderError(e) => Some(e),
+        }
+    }
+}
+
 impl From<BinaryReaderError> for BranchReaderError {
     fn from(e: BinaryReaderError) -> Self {
         BranchReaderError::BinaryReaderError(e)
@@ -23,8 +47,8 @@ impl From<BinaryReaderError> for BranchReaderError {
 pub type Result<T, E = BranchReaderError> = result::Result<T, E>;
 
 impl<'a> BranchTableReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<BranchTableReader<'a>> {
-        let mut reader = BinaryReader::new(buffer);
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<BranchTableReader<'a>> {
+        let mut reader = BinaryReader::new_with_offset(buffer, offset);
         let num_labels = reader.read_leb128_u32()?;
         Ok(BranchTableReader { reader, num_labels })
     }
@@ -40,6 +64,11 @@ impl<'a> BranchTableReader<'a> {
         Ok((start, end))
     }
 
+    /// Returns the encoded label count and labels, default label included.
+    pub(crate) fn get_data(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
+
     pub fn get_num_labels(&self) -> u32 {
         self.num_labels + 1
     }
@@ -3,6 +3,7 @@ pub trait SectionReader {
     type Error;
     fn read(&mut self) -> Result<Self::Item, Self::Error>;
     fn get_count(&self) -> u32;
+    fn get_original_position(&self) -> usize;
 }
 
 pub struct SectionItemIterator<R>
@@ -45,4 +46,46 @@ impl<R> Iterator for SectionItemIterator<R>
         let count = self.reader.get_count() as usize;
         (count, Some(count))
     }
-}
\ No newline at end of file
+}
+
+/// Iterates over the items of a section along with the absolute offset
+/// in the module at which each item starts.
+pub struct SectionItemWithOffsetIterator<R>
+    where R: SectionReader
+{
+    reader: R,
+    error: bool,
+    remaining_items: u32,
+}
+
+impl<R> SectionItemWithOffsetIterator<R>
+    where R: SectionReader
+{
+    pub fn new(reader: R) -> SectionItemWithOffsetIterator<R> {
+        let remaining_items = reader.get_count();
+        SectionItemWithOffsetIterator { reader, error: false, remaining_items }
+    }
+}
+
+impl<R> Iterator for SectionItemWithOffsetIterator<R>
+    where R: SectionReader
+{
+    type Item = Result<(usize, R::Item), R::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_items == 0 || self.error {
+            None
+        } else {
+            let offset = self.reader.get_original_position();
+            let result = self.reader.read().map(|item| (offset, item));
+            self.error = result.is_err();
+            self.remaining_items -= 1;
+            Some(result)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.reader.get_count() as usize;
+        (count, Some(count))
+    }
+}
@@ -1,8 +1,11 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use crate::readers::binary::Result as BinaryReaderResult;
-use std::result;
+use std::{error, fmt, result};
 use crate::types::{Instruction, BlockType, TypeIndex, LabelIndex, FuncIndex, LocalIndex, GlobalIndex, MemoryArgument};
-use crate::readers::instruction::InstructionReaderError::{InvalidInstruction, InvalidBlockTypeIndex, InvalidMemorySizeByte, InvalidSatOpCode};
+use crate::types::{TableIndex, MemoryIndex, ElementIndex, DataIndex, TagIndex, CatchClause};
+use crate::readers::instruction::InstructionReaderError::{InvalidInstruction, InvalidBlockTypeIndex, InvalidSatOpCode};
+use crate::readers::instruction::InstructionReaderError::{InvalidSelectTypeCount, InvalidSimdOpCode, InvalidAtomicOpCode, InvalidFenceFlags};
+use crate::readers::instruction::InstructionReaderError::InvalidCatchKind;
 use crate::types::Instruction::*;
 
 #[derive(Eq, PartialEq, Debug)]
@@ -13,10 +16,55 @@ pub struct InstructionReader<'a> {
 #[derive(PartialEq, Eq, Debug)]
 pub enum InstructionReaderError {
     BinaryReaderError(BinaryReaderError),
-    InvalidInstruction,
-    InvalidBlockTypeIndex,
-    InvalidMemorySizeByte,
-    InvalidSatOpCode,
+    InvalidInstruction(usize),
+    InvalidBlockTypeIndex(usize),
+    InvalidSatOpCode(usize),
+    InvalidSimdOpCode(usize),
+    InvalidAtomicOpCode(usize),
+    InvalidFenceFlags(usize),
+    InvalidSelectTypeCount(usize),
+    InvalidCatchKind(usize),
+}
+
+impl InstructionReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            InstructionReaderError::BinaryReaderError(e) => e.offset(),
+            InvalidInstruction(offset) |
+            InvalidBlockTypeIndex(offset) |
+            InvalidSatOpCode(offset) |
+            InvalidSimdOpCode(offset) |
+            InvalidAtomicOpCode(offset) |
+            InvalidFenceFlags(offset) |
+            InvalidSelectTypeCount(offset) |
+            InvalidCatchKind(offset) => *offset,
+        }
+    }
+}
+
+impl fmt::Display for InstructionReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstructionReaderError::BinaryReaderError(_) => write!(f, "malformed instruction"),
+            InvalidInstruction(offset) => write!(f, "invalid opcode at offset {}", offset),
+            InvalidBlockTypeIndex(offset) => write!(f, "invalid block type at offset {}", offset),
+            InvalidSatOpCode(offset) => write!(f, "invalid 0xFC prefixed opcode at offset {}", offset),
+            InvalidSimdOpCode(offset) => write!(f, "invalid 0xFD prefixed opcode at offset {}", offset),
+            InvalidAtomicOpCode(offset) => write!(f, "invalid 0xFE prefixed opcode at offset {}", offset),
+            InvalidFenceFlags(offset) => write!(f, "invalid atomic.fence flags at offset {}", offset),
+            InvalidSelectTypeCount(offset) => write!(f, "typed select must have exactly one type, at offset {}", offset),
+            InvalidCatchKind(offset) => write!(f, "invalid try_table catch kind at offset {}", offset),
+        }
+    }
+}
+
+impl error::Error for InstructionReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            InstructionReaderError::BinaryReaderError(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<BinaryReaderError> for InstructionReaderError {
@@ -28,8 +76,8 @@ impl From<BinaryReaderError> for InstructionReaderError {
 pub type Result<T, E = InstructionReaderError> = result::Result<T, E>;
 
 impl<'a> InstructionReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<InstructionReader<'a>> {
-        let reader = BinaryReader::new(buffer);
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<InstructionReader<'a>> {
+        let reader = BinaryReader::new_with_offset(buffer, offset);
         Ok(InstructionReader { reader })
     }
 
@@ -37,9 +85,14 @@ impl<'a> InstructionReader<'a> {
         self.reader.eof()
     }
 
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
     pub fn read<'b>(&mut self) -> Result<Instruction<'b>>
         where 'a: 'b
     {
+        let offset = self.reader.get_original_position();
         match self.reader.read_byte()? {
             0x00 => Ok(Unreachable),
             0x01 => Ok(Nop),
@@ -56,6 +109,23 @@ impl<'a> InstructionReader<'a> {
                 Ok(Instruction::If { block_type })
             },
             0x05 => Ok(Instruction::Else),
+            0x06 => {
+                let block_type = self.read_block_type()?;
+                Ok(Instruction::Try { block_type })
+            },
+            0x07 => {
+                let tag_index = TagIndex(self.reader.read_leb128_u32()?);
+                Ok(Instruction::Catch { tag_index })
+            },
+            0x08 => {
+                let tag_index = TagIndex(self.reader.read_leb128_u32()?);
+                Ok(Instruction::Throw { tag_index })
+            },
+            0x09 => {
+                let label_index = LabelIndex(self.reader.read_leb128_u32()?);
+                Ok(Instruction::Rethrow { label_index })
+            },
+            0x0A => Ok(Instruction::ThrowRef),
             0x0B => Ok(Instruction::End),
             0x0C => {
                 let label_index = LabelIndex(self.reader.read_leb128_u32()?);
@@ -76,11 +146,42 @@ impl<'a> InstructionReader<'a> {
             },
             0x11 => {
                 let type_index = TypeIndex(self.reader.read_leb128_u32()?);
-                Ok(Instruction::CallIndirect { type_index })
+                let table_index = TableIndex(self.reader.read_leb128_u32()?);
+                Ok(Instruction::CallIndirect { type_index, table_index })
+            },
+            0x12 => {
+                let func_index = FuncIndex(self.reader.read_leb128_u32()?);
+                Ok(Instruction::ReturnCall { func_index })
+            },
+            0x13 => {
+                let type_index = TypeIndex(self.reader.read_leb128_u32()?);
+                let table_index = TableIndex(self.reader.read_leb128_u32()?);
+                Ok(Instruction::ReturnCallIndirect { type_index, table_index })
+            },
+            0x18 => {
+                let label_index = LabelIndex(self.reader.read_leb128_u32()?);
+                Ok(Instruction::Delegate { label_index })
             },
+            0x19 => Ok(Instruction::CatchAll),
 
             0x1A => Ok(Instruction::Drop),
             0x1B => Ok(Instruction::Select),
+            0x1C => {
+                if self.reader.read_leb128_u32()? != 1 {
+                    return Err(InvalidSelectTypeCount(offset));
+                }
+                let value_type = self.reader.read_value_type()?;
+                Ok(Instruction::TypedSelect { value_type })
+            },
+            0x1F => {
+                let block_type = self.read_block_type()?;
+                let count = self.reader.read_leb128_u32()?;
+                let mut catches = Vec::new();
+                for _ in 0..count {
+                    catches.push(self.read_catch_clause()?);
+                }
+                Ok(Instruction::TryTable { block_type, catches: catches.into_boxed_slice() })
+            },
 
             0x20 => {
                 let local_index = LocalIndex(self.reader.read_leb128_u32()?);
@@ -102,6 +203,14 @@ impl<'a> InstructionReader<'a> {
                 let global_index = GlobalIndex(self.reader.read_leb128_u32()?);
                 Ok(Instruction::GlobalSet { global_index })
             },
+            0x25 => {
+                let table_index = TableIndex(self.reader.read_leb128_u32()?);
+                Ok(Instruction::TableGet { table_index })
+            },
+            0x26 => {
+                let table_index = TableIndex(self.reader.read_leb128_u32()?);
+                Ok(Instruction::TableSet { table_index })
+            },
 
             0x28 => {
                 let memory_argument = self.read_memory_argument()?;
@@ -196,18 +305,12 @@ impl<'a> InstructionReader<'a> {
                 Ok(Instruction::I64Store32 { memory_argument })
             },
             0x3F => {
-                if let Ok(0x00) = self.reader.read_byte() {
-                    Ok(Instruction::MemorySize)
-                } else {
-                    Err(InvalidMemorySizeByte)
-                }
+                let memory_index = MemoryIndex(self.reader.read_leb128_u32()?);
+                Ok(Instruction::MemorySize { memory_index })
             },
             0x40 => {
-                if let Ok(0x00) = self.reader.read_byte() {
-                    Ok(Instruction::MemoryGrow)
-                } else {
-                    Err(InvalidMemorySizeByte)
-                }
+                let memory_index = MemoryIndex(self.reader.read_leb128_u32()?);
+                Ok(Instruction::MemoryGrow { memory_index })
             },
             0x41 => {
                 let val = self.reader.read_leb128_s32()?;
@@ -364,6 +467,16 @@ impl<'a> InstructionReader<'a> {
             0xC3 => Ok(Instruction::I64Extend16s),
             0xC4 => Ok(Instruction::I64Extend32s),
 
+            0xD0 => {
+                let ref_type = self.reader.read_reference_type()?;
+                Ok(Instruction::RefNull { ref_type })
+            },
+            0xD1 => Ok(Instruction::RefIsNull),
+            0xD2 => {
+                let func_index = FuncIndex(self.reader.read_leb128_u32()?);
+                Ok(Instruction::RefFunc { func_index })
+            },
+
             0xFC => {
                 match self.reader.read_leb128_u32()? {
                     0 => Ok(Instruction::I32TruncSatF32s),
@@ -374,34 +487,751 @@ impl<'a> InstructionReader<'a> {
                     5 => Ok(Instruction::I64TruncSatF32u),
                     6 => Ok(Instruction::I64TruncSatF64s),
                     7 => Ok(Instruction::I64TruncSatF64u),
-                    _ => Err(InvalidSatOpCode)
+                    8 => {
+                        let data_index = DataIndex(self.reader.read_leb128_u32()?);
+                        let memory_index = MemoryIndex(self.reader.read_leb128_u32()?);
+                        Ok(Instruction::MemoryInit { data_index, memory_index })
+                    },
+                    9 => {
+                        let data_index = DataIndex(self.reader.read_leb128_u32()?);
+                        Ok(Instruction::DataDrop { data_index })
+                    },
+                    10 => {
+                        let destination_memory_index = MemoryIndex(self.reader.read_leb128_u32()?);
+                        let source_memory_index = MemoryIndex(self.reader.read_leb128_u32()?);
+                        Ok(Instruction::MemoryCopy { destination_memory_index, source_memory_index })
+                    },
+                    11 => {
+                        let memory_index = MemoryIndex(self.reader.read_leb128_u32()?);
+                        Ok(Instruction::MemoryFill { memory_index })
+                    },
+                    12 => {
+                        let element_index = ElementIndex(self.reader.read_leb128_u32()?);
+                        let table_index = TableIndex(self.reader.read_leb128_u32()?);
+                        Ok(Instruction::TableInit { element_index, table_index })
+                    },
+                    13 => {
+                        let element_index = ElementIndex(self.reader.read_leb128_u32()?);
+                        Ok(Instruction::ElemDrop { element_index })
+                    },
+                    14 => {
+                        let destination_table_index = TableIndex(self.reader.read_leb128_u32()?);
+                        let source_table_index = TableIndex(self.reader.read_leb128_u32()?);
+                        Ok(Instruction::TableCopy { destination_table_index, source_table_index })
+                    },
+                    15 => {
+                        let table_index = TableIndex(self.reader.read_leb128_u32()?);
+                        Ok(Instruction::TableGrow { table_index })
+                    },
+                    16 => {
+                        let table_index = TableIndex(self.reader.read_leb128_u32()?);
+                        Ok(Instruction::TableSize { table_index })
+                    },
+                    17 => {
+                        let table_index = TableIndex(self.reader.read_leb128_u32()?);
+                        Ok(Instruction::TableFill { table_index })
+                    },
+                    _ => Err(InvalidSatOpCode(offset))
+                }
+            }
+
+            0xFD => {
+                match self.reader.read_leb128_u32()? {
+                    0 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::V128Load { memory_argument })
+                    },
+                    1 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::V128Load8x8s { memory_argument })
+                    },
+                    2 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::V128Load8x8u { memory_argument })
+                    },
+                    3 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::V128Load16x4s { memory_argument })
+                    },
+                    4 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::V128Load16x4u { memory_argument })
+                    },
+                    5 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::V128Load32x2s { memory_argument })
+                    },
+                    6 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::V128Load32x2u { memory_argument })
+                    },
+                    7 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::V128Load8Splat { memory_argument })
+                    },
+                    8 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::V128Load16Splat { memory_argument })
+                    },
+                    9 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::V128Load32Splat { memory_argument })
+                    },
+                    10 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::V128Load64Splat { memory_argument })
+                    },
+                    11 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::V128Store { memory_argument })
+                    },
+                    12 => {
+                        let bytes = self.read_v128_bytes()?;
+                        Ok(Instruction::V128Const(i128::from_le_bytes(bytes)))
+                    },
+                    13 => {
+                        let lanes = self.read_v128_bytes()?;
+                        Ok(Instruction::I8x16Shuffle { lanes })
+                    },
+                    14 => Ok(Instruction::I8x16Swizzle),
+                    15 => Ok(Instruction::I8x16Splat),
+                    16 => Ok(Instruction::I16x8Splat),
+                    17 => Ok(Instruction::I32x4Splat),
+                    18 => Ok(Instruction::I64x2Splat),
+                    19 => Ok(Instruction::F32x4Splat),
+                    20 => Ok(Instruction::F64x2Splat),
+                    21 => {
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::I8x16ExtractLanes { lane })
+                    },
+                    22 => {
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::I8x16ExtractLaneu { lane })
+                    },
+                    23 => {
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::I8x16ReplaceLane { lane })
+                    },
+                    24 => {
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::I16x8ExtractLanes { lane })
+                    },
+                    25 => {
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::I16x8ExtractLaneu { lane })
+                    },
+                    26 => {
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::I16x8ReplaceLane { lane })
+                    },
+                    27 => {
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::I32x4ExtractLane { lane })
+                    },
+                    28 => {
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::I32x4ReplaceLane { lane })
+                    },
+                    29 => {
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::I64x2ExtractLane { lane })
+                    },
+                    30 => {
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::I64x2ReplaceLane { lane })
+                    },
+                    31 => {
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::F32x4ExtractLane { lane })
+                    },
+                    32 => {
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::F32x4ReplaceLane { lane })
+                    },
+                    33 => {
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::F64x2ExtractLane { lane })
+                    },
+                    34 => {
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::F64x2ReplaceLane { lane })
+                    },
+                    35 => Ok(Instruction::I8x16Eq),
+                    36 => Ok(Instruction::I8x16Ne),
+                    37 => Ok(Instruction::I8x16Lts),
+                    38 => Ok(Instruction::I8x16Ltu),
+                    39 => Ok(Instruction::I8x16Gts),
+                    40 => Ok(Instruction::I8x16Gtu),
+                    41 => Ok(Instruction::I8x16Les),
+                    42 => Ok(Instruction::I8x16Leu),
+                    43 => Ok(Instruction::I8x16Ges),
+                    44 => Ok(Instruction::I8x16Geu),
+                    45 => Ok(Instruction::I16x8Eq),
+                    46 => Ok(Instruction::I16x8Ne),
+                    47 => Ok(Instruction::I16x8Lts),
+                    48 => Ok(Instruction::I16x8Ltu),
+                    49 => Ok(Instruction::I16x8Gts),
+                    50 => Ok(Instruction::I16x8Gtu),
+                    51 => Ok(Instruction::I16x8Les),
+                    52 => Ok(Instruction::I16x8Leu),
+                    53 => Ok(Instruction::I16x8Ges),
+                    54 => Ok(Instruction::I16x8Geu),
+                    55 => Ok(Instruction::I32x4Eq),
+                    56 => Ok(Instruction::I32x4Ne),
+                    57 => Ok(Instruction::I32x4Lts),
+                    58 => Ok(Instruction::I32x4Ltu),
+                    59 => Ok(Instruction::I32x4Gts),
+                    60 => Ok(Instruction::I32x4Gtu),
+                    61 => Ok(Instruction::I32x4Les),
+                    62 => Ok(Instruction::I32x4Leu),
+                    63 => Ok(Instruction::I32x4Ges),
+                    64 => Ok(Instruction::I32x4Geu),
+                    65 => Ok(Instruction::F32x4Eq),
+                    66 => Ok(Instruction::F32x4Ne),
+                    67 => Ok(Instruction::F32x4Lt),
+                    68 => Ok(Instruction::F32x4Gt),
+                    69 => Ok(Instruction::F32x4Le),
+                    70 => Ok(Instruction::F32x4Ge),
+                    71 => Ok(Instruction::F64x2Eq),
+                    72 => Ok(Instruction::F64x2Ne),
+                    73 => Ok(Instruction::F64x2Lt),
+                    74 => Ok(Instruction::F64x2Gt),
+                    75 => Ok(Instruction::F64x2Le),
+                    76 => Ok(Instruction::F64x2Ge),
+                    77 => Ok(Instruction::V128Not),
+                    78 => Ok(Instruction::V128And),
+                    79 => Ok(Instruction::V128Andnot),
+                    80 => Ok(Instruction::V128Or),
+                    81 => Ok(Instruction::V128Xor),
+                    82 => Ok(Instruction::V128Bitselect),
+                    83 => Ok(Instruction::V128AnyTrue),
+                    84 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::V128Load8Lane { memory_argument, lane })
+                    },
+                    85 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::V128Load16Lane { memory_argument, lane })
+                    },
+                    86 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::V128Load32Lane { memory_argument, lane })
+                    },
+                    87 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::V128Load64Lane { memory_argument, lane })
+                    },
+                    88 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::V128Store8Lane { memory_argument, lane })
+                    },
+                    89 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::V128Store16Lane { memory_argument, lane })
+                    },
+                    90 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::V128Store32Lane { memory_argument, lane })
+                    },
+                    91 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        let lane = self.reader.read_byte()?;
+                        Ok(Instruction::V128Store64Lane { memory_argument, lane })
+                    },
+                    92 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::V128Load32Zero { memory_argument })
+                    },
+                    93 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::V128Load64Zero { memory_argument })
+                    },
+                    94 => Ok(Instruction::F32x4DemoteF64x2Zero),
+                    95 => Ok(Instruction::F64x2PromoteLowF32x4),
+                    96 => Ok(Instruction::I8x16Abs),
+                    97 => Ok(Instruction::I8x16Neg),
+                    98 => Ok(Instruction::I8x16Popcnt),
+                    99 => Ok(Instruction::I8x16AllTrue),
+                    100 => Ok(Instruction::I8x16Bitmask),
+                    101 => Ok(Instruction::I8x16NarrowI16x8s),
+                    102 => Ok(Instruction::I8x16NarrowI16x8u),
+                    103 => Ok(Instruction::F32x4Ceil),
+                    104 => Ok(Instruction::F32x4Floor),
+                    105 => Ok(Instruction::F32x4Trunc),
+                    106 => Ok(Instruction::F32x4Nearest),
+                    107 => Ok(Instruction::I8x16Shl),
+                    108 => Ok(Instruction::I8x16Shrs),
+                    109 => Ok(Instruction::I8x16Shru),
+                    110 => Ok(Instruction::I8x16Add),
+                    111 => Ok(Instruction::I8x16AddSats),
+                    112 => Ok(Instruction::I8x16AddSatu),
+                    113 => Ok(Instruction::I8x16Sub),
+                    114 => Ok(Instruction::I8x16SubSats),
+                    115 => Ok(Instruction::I8x16SubSatu),
+                    116 => Ok(Instruction::F64x2Ceil),
+                    117 => Ok(Instruction::F64x2Floor),
+                    118 => Ok(Instruction::I8x16Mins),
+                    119 => Ok(Instruction::I8x16Minu),
+                    120 => Ok(Instruction::I8x16Maxs),
+                    121 => Ok(Instruction::I8x16Maxu),
+                    122 => Ok(Instruction::F64x2Trunc),
+                    123 => Ok(Instruction::I8x16Avgru),
+                    124 => Ok(Instruction::I16x8ExtaddPairwiseI8x16s),
+                    125 => Ok(Instruction::I16x8ExtaddPairwiseI8x16u),
+                    126 => Ok(Instruction::I32x4ExtaddPairwiseI16x8s),
+                    127 => Ok(Instruction::I32x4ExtaddPairwiseI16x8u),
+                    128 => Ok(Instruction::I16x8Abs),
+                    129 => Ok(Instruction::I16x8Neg),
+                    130 => Ok(Instruction::I16x8Q15mulrSats),
+                    131 => Ok(Instruction::I16x8AllTrue),
+                    132 => Ok(Instruction::I16x8Bitmask),
+                    133 => Ok(Instruction::I16x8NarrowI32x4s),
+                    134 => Ok(Instruction::I16x8NarrowI32x4u),
+                    135 => Ok(Instruction::I16x8ExtendLowI8x16s),
+                    136 => Ok(Instruction::I16x8ExtendHighI8x16s),
+                    137 => Ok(Instruction::I16x8ExtendLowI8x16u),
+                    138 => Ok(Instruction::I16x8ExtendHighI8x16u),
+                    139 => Ok(Instruction::I16x8Shl),
+                    140 => Ok(Instruction::I16x8Shrs),
+                    141 => Ok(Instruction::I16x8Shru),
+                    142 => Ok(Instruction::I16x8Add),
+                    143 => Ok(Instruction::I16x8AddSats),
+                    144 => Ok(Instruction::I16x8AddSatu),
+                    145 => Ok(Instruction::I16x8Sub),
+                    146 => Ok(Instruction::I16x8SubSats),
+                    147 => Ok(Instruction::I16x8SubSatu),
+                    148 => Ok(Instruction::F64x2Nearest),
+                    149 => Ok(Instruction::I16x8Mul),
+                    150 => Ok(Instruction::I16x8Mins),
+                    151 => Ok(Instruction::I16x8Minu),
+                    152 => Ok(Instruction::I16x8Maxs),
+                    153 => Ok(Instruction::I16x8Maxu),
+                    155 => Ok(Instruction::I16x8Avgru),
+                    156 => Ok(Instruction::I16x8ExtmulLowI8x16s),
+                    157 => Ok(Instruction::I16x8ExtmulHighI8x16s),
+                    158 => Ok(Instruction::I16x8ExtmulLowI8x16u),
+                    159 => Ok(Instruction::I16x8ExtmulHighI8x16u),
+                    160 => Ok(Instruction::I32x4Abs),
+                    161 => Ok(Instruction::I32x4Neg),
+                    163 => Ok(Instruction::I32x4AllTrue),
+                    164 => Ok(Instruction::I32x4Bitmask),
+                    167 => Ok(Instruction::I32x4ExtendLowI16x8s),
+                    168 => Ok(Instruction::I32x4ExtendHighI16x8s),
+                    169 => Ok(Instruction::I32x4ExtendLowI16x8u),
+                    170 => Ok(Instruction::I32x4ExtendHighI16x8u),
+                    171 => Ok(Instruction::I32x4Shl),
+                    172 => Ok(Instruction::I32x4Shrs),
+                    173 => Ok(Instruction::I32x4Shru),
+                    174 => Ok(Instruction::I32x4Add),
+                    177 => Ok(Instruction::I32x4Sub),
+                    181 => Ok(Instruction::I32x4Mul),
+                    182 => Ok(Instruction::I32x4Mins),
+                    183 => Ok(Instruction::I32x4Minu),
+                    184 => Ok(Instruction::I32x4Maxs),
+                    185 => Ok(Instruction::I32x4Maxu),
+                    186 => Ok(Instruction::I32x4DotI16x8s),
+                    188 => Ok(Instruction::I32x4ExtmulLowI16x8s),
+                    189 => Ok(Instruction::I32x4ExtmulHighI16x8s),
+                    190 => Ok(Instruction::I32x4ExtmulLowI16x8u),
+                    191 => Ok(Instruction::I32x4ExtmulHighI16x8u),
+                    192 => Ok(Instruction::I64x2Abs),
+                    193 => Ok(Instruction::I64x2Neg),
+                    195 => Ok(Instruction::I64x2AllTrue),
+                    196 => Ok(Instruction::I64x2Bitmask),
+                    199 => Ok(Instruction::I64x2ExtendLowI32x4s),
+                    200 => Ok(Instruction::I64x2ExtendHighI32x4s),
+                    201 => Ok(Instruction::I64x2ExtendLowI32x4u),
+                    202 => Ok(Instruction::I64x2ExtendHighI32x4u),
+                    203 => Ok(Instruction::I64x2Shl),
+                    204 => Ok(Instruction::I64x2Shrs),
+                    205 => Ok(Instruction::I64x2Shru),
+                    206 => Ok(Instruction::I64x2Add),
+                    209 => Ok(Instruction::I64x2Sub),
+                    213 => Ok(Instruction::I64x2Mul),
+                    214 => Ok(Instruction::I64x2Eq),
+                    215 => Ok(Instruction::I64x2Ne),
+                    216 => Ok(Instruction::I64x2Lts),
+                    217 => Ok(Instruction::I64x2Gts),
+                    218 => Ok(Instruction::I64x2Les),
+                    219 => Ok(Instruction::I64x2Ges),
+                    220 => Ok(Instruction::I64x2ExtmulLowI32x4s),
+                    221 => Ok(Instruction::I64x2ExtmulHighI32x4s),
+                    222 => Ok(Instruction::I64x2ExtmulLowI32x4u),
+                    223 => Ok(Instruction::I64x2ExtmulHighI32x4u),
+                    224 => Ok(Instruction::F32x4Abs),
+                    225 => Ok(Instruction::F32x4Neg),
+                    227 => Ok(Instruction::F32x4Sqrt),
+                    228 => Ok(Instruction::F32x4Add),
+                    229 => Ok(Instruction::F32x4Sub),
+                    230 => Ok(Instruction::F32x4Mul),
+                    231 => Ok(Instruction::F32x4Div),
+                    232 => Ok(Instruction::F32x4Min),
+                    233 => Ok(Instruction::F32x4Max),
+                    234 => Ok(Instruction::F32x4Pmin),
+                    235 => Ok(Instruction::F32x4Pmax),
+                    236 => Ok(Instruction::F64x2Abs),
+                    237 => Ok(Instruction::F64x2Neg),
+                    239 => Ok(Instruction::F64x2Sqrt),
+                    240 => Ok(Instruction::F64x2Add),
+                    241 => Ok(Instruction::F64x2Sub),
+                    242 => Ok(Instruction::F64x2Mul),
+                    243 => Ok(Instruction::F64x2Div),
+                    244 => Ok(Instruction::F64x2Min),
+                    245 => Ok(Instruction::F64x2Max),
+                    246 => Ok(Instruction::F64x2Pmin),
+                    247 => Ok(Instruction::F64x2Pmax),
+                    248 => Ok(Instruction::I32x4TruncSatF32x4s),
+                    249 => Ok(Instruction::I32x4TruncSatF32x4u),
+                    250 => Ok(Instruction::F32x4ConvertI32x4s),
+                    251 => Ok(Instruction::F32x4ConvertI32x4u),
+                    252 => Ok(Instruction::I32x4TruncSatF64x2sZero),
+                    253 => Ok(Instruction::I32x4TruncSatF64x2uZero),
+                    254 => Ok(Instruction::F64x2ConvertLowI32x4s),
+                    255 => Ok(Instruction::F64x2ConvertLowI32x4u),
+                    _ => Err(InvalidSimdOpCode(offset))
                 }
             }
 
-            _ => Err(InvalidInstruction),
+            0xFE => {
+                match self.reader.read_leb128_u32()? {
+                    0 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::MemoryAtomicNotify { memory_argument })
+                    },
+                    1 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::MemoryAtomicWait32 { memory_argument })
+                    },
+                    2 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::MemoryAtomicWait64 { memory_argument })
+                    },
+                    3 => {
+                        self.read_fence_flags(offset)?;
+                        Ok(Instruction::AtomicFence)
+                    },
+                    16 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicLoad { memory_argument })
+                    },
+                    17 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicLoad { memory_argument })
+                    },
+                    18 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicLoad8u { memory_argument })
+                    },
+                    19 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicLoad16u { memory_argument })
+                    },
+                    20 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicLoad8u { memory_argument })
+                    },
+                    21 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicLoad16u { memory_argument })
+                    },
+                    22 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicLoad32u { memory_argument })
+                    },
+                    23 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicStore { memory_argument })
+                    },
+                    24 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicStore { memory_argument })
+                    },
+                    25 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicStore8 { memory_argument })
+                    },
+                    26 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicStore16 { memory_argument })
+                    },
+                    27 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicStore8 { memory_argument })
+                    },
+                    28 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicStore16 { memory_argument })
+                    },
+                    29 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicStore32 { memory_argument })
+                    },
+                    30 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmwAdd { memory_argument })
+                    },
+                    31 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmwAdd { memory_argument })
+                    },
+                    32 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmw8Addu { memory_argument })
+                    },
+                    33 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmw16Addu { memory_argument })
+                    },
+                    34 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw8Addu { memory_argument })
+                    },
+                    35 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw16Addu { memory_argument })
+                    },
+                    36 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw32Addu { memory_argument })
+                    },
+                    37 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmwSub { memory_argument })
+                    },
+                    38 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmwSub { memory_argument })
+                    },
+                    39 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmw8Subu { memory_argument })
+                    },
+                    40 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmw16Subu { memory_argument })
+                    },
+                    41 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw8Subu { memory_argument })
+                    },
+                    42 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw16Subu { memory_argument })
+                    },
+                    43 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw32Subu { memory_argument })
+                    },
+                    44 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmwAnd { memory_argument })
+                    },
+                    45 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmwAnd { memory_argument })
+                    },
+                    46 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmw8Andu { memory_argument })
+                    },
+                    47 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmw16Andu { memory_argument })
+                    },
+                    48 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw8Andu { memory_argument })
+                    },
+                    49 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw16Andu { memory_argument })
+                    },
+                    50 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw32Andu { memory_argument })
+                    },
+                    51 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmwOr { memory_argument })
+                    },
+                    52 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmwOr { memory_argument })
+                    },
+                    53 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmw8Oru { memory_argument })
+                    },
+                    54 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmw16Oru { memory_argument })
+                    },
+                    55 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw8Oru { memory_argument })
+                    },
+                    56 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw16Oru { memory_argument })
+                    },
+                    57 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw32Oru { memory_argument })
+                    },
+                    58 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmwXor { memory_argument })
+                    },
+                    59 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmwXor { memory_argument })
+                    },
+                    60 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmw8Xoru { memory_argument })
+                    },
+                    61 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmw16Xoru { memory_argument })
+                    },
+                    62 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw8Xoru { memory_argument })
+                    },
+                    63 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw16Xoru { memory_argument })
+                    },
+                    64 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw32Xoru { memory_argument })
+                    },
+                    65 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmwXchg { memory_argument })
+                    },
+                    66 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmwXchg { memory_argument })
+                    },
+                    67 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmw8Xchgu { memory_argument })
+                    },
+                    68 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmw16Xchgu { memory_argument })
+                    },
+                    69 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw8Xchgu { memory_argument })
+                    },
+                    70 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw16Xchgu { memory_argument })
+                    },
+                    71 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw32Xchgu { memory_argument })
+                    },
+                    72 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmwCmpxchg { memory_argument })
+                    },
+                    73 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmwCmpxchg { memory_argument })
+                    },
+                    74 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmw8Cmpxchgu { memory_argument })
+                    },
+                    75 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I32AtomicRmw16Cmpxchgu { memory_argument })
+                    },
+                    76 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw8Cmpxchgu { memory_argument })
+                    },
+                    77 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw16Cmpxchgu { memory_argument })
+                    },
+                    78 => {
+                        let memory_argument = self.read_memory_argument()?;
+                        Ok(Instruction::I64AtomicRmw32Cmpxchgu { memory_argument })
+                    },
+                    _ => Err(InvalidAtomicOpCode(offset))
+                }
+            }
+
+            _ => Err(InvalidInstruction(offset)),
+        }
+    }
+
+    /// Reads the reserved flags byte of `atomic.fence`.
+    fn read_fence_flags(&mut self, offset: usize) -> Result<()> {
+        match self.reader.read_byte()? {
+            0x00 => Ok(()),
+            _ => Err(InvalidFenceFlags(offset)),
         }
     }
 
+    /// Reads the 16 immediate bytes of `v128.const` and `i8x16.shuffle`.
+    fn read_v128_bytes(&mut self) -> Result<[u8; 16]> {
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(self.reader.read_bytes(16)?);
+        Ok(bytes)
+    }
+
+    fn read_catch_clause(&mut self) -> Result<CatchClause> {
+        let offset = self.reader.get_original_position();
+        Ok(match self.reader.read_byte()? {
+            0x00 => {
+                let tag_index = TagIndex(self.reader.read_leb128_u32()?);
+                CatchClause::Catch { tag_index, label_index: LabelIndex(self.reader.read_leb128_u32()?) }
+            },
+            0x01 => {
+                let tag_index = TagIndex(self.reader.read_leb128_u32()?);
+                CatchClause::CatchRef { tag_index, label_index: LabelIndex(self.reader.read_leb128_u32()?) }
+            },
+            0x02 => CatchClause::CatchAll { label_index: LabelIndex(self.reader.read_leb128_u32()?) },
+            0x03 => CatchClause::CatchAllRef { label_index: LabelIndex(self.reader.read_leb128_u32()?) },
+            _ => return Err(InvalidCatchKind(offset)),
+        })
+    }
+
+    /// Bit 6 of the alignment flags announces an explicit memory index,
+    /// otherwise the access is to memory 0.
     fn read_memory_argument(&mut self) -> Result<MemoryArgument> {
-        let alignment = self.reader.read_leb128_u32()?;
-        let offset = self.reader.read_leb128_u32()?;
-        Ok(MemoryArgument { alignment, offset })
+        let flags = self.reader.read_leb128_u32()?;
+        let memory_index = if flags & 0x40 != 0 {
+            MemoryIndex(self.reader.read_leb128_u32()?)
+        } else {
+            MemoryIndex(0)
+        };
+        let offset = self.reader.read_leb128_u64()?;
+        Ok(MemoryArgument { alignment: flags & !0x40, offset, memory_index })
     }
 
     fn read_block_type(&mut self) -> Result<BlockType> {
+        let offset = self.reader.get_original_position();
         if let Ok(val_type) = self.reader.read_value_type() {
             Ok(BlockType::ValueType(val_type))
         } else {
-            match self.reader.read_byte()? {
-                0x40 => Ok(BlockType::Empty),
-                _ => {
-                    let index = self.reader.read_leb128_s33()?;
-                    if index < 0 || index > u32::max_value() as i64 {
-                        Err(InvalidBlockTypeIndex)
-                    } else {
-                        Ok(BlockType::TypeIndex(TypeIndex(index as u32)))
-                    }
-                }
+            //0x40 is the single byte s33 encoding of -64
+            match self.reader.read_leb128_s33()? {
+                -64 => Ok(BlockType::Empty),
+                index if index >= 0 && index <= u32::MAX as i64 => Ok(BlockType::TypeIndex(TypeIndex(index as u32))),
+                _ => Err(InvalidBlockTypeIndex(offset)),
             }
         }
     }
@@ -444,3 +1274,30 @@ impl<'a> IntoIterator for InstructionReader<'a> {
         InstructionIterator::new(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::readers::instruction::{InstructionReader, Result};
+    use crate::readers::instruction::InstructionReaderError::InvalidBlockTypeIndex;
+    use crate::types::{BlockType, Instruction, TypeIndex, ValueType};
+
+    fn read_block_type(bytes: &[u8]) -> Result<BlockType> {
+        let mut reader = InstructionReader::new(bytes, 0).unwrap();
+        match reader.read()? {
+            Instruction::Block { block_type } => {
+                assert!(reader.eof());
+                Ok(block_type)
+            }
+            instruction => panic!("expected a block, found {:?}", instruction),
+        }
+    }
+
+    #[test]
+    fn block_types_are_read_as_s33() {
+        assert!(matches!(read_block_type(&[0x02, 0x40]), Ok(BlockType::Empty)));
+        assert!(matches!(read_block_type(&[0x02, 0x7F]), Ok(BlockType::ValueType(ValueType::I32))));
+        assert!(matches!(read_block_type(&[0x02, 0x00]), Ok(BlockType::TypeIndex(TypeIndex(0)))));
+        assert!(matches!(read_block_type(&[0x02, 0x81, 0x01]), Ok(BlockType::TypeIndex(TypeIndex(129)))));
+        assert_eq!(Err(InvalidBlockTypeIndex(1)), read_block_type(&[0x02, 0x41]).map(|_| ()));
+    }
+}
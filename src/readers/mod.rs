@@ -1,14 +1,19 @@
 pub use section::code::{CodeReaderError, CodeSectionReader};
-pub use section::custom::{CustomReaderError, CustomSectionReader};
+pub use section::custom::{CustomReaderError, CustomSectionReader, CustomSectionKind};
 pub use section::data::{DataReaderError, DataSectionReader};
+pub use section::data_count::{DataCountReaderError, DataCountSectionReader};
 pub use section::element::{ElementReaderError, ElementSectionReader};
 pub use section::export::{ExportReaderError, ExportSectionReader};
 pub use section::function::{FunctionReaderError, FunctionSectionReader};
 pub use section::global::{GlobalReaderError, GlobalSectionReader};
 pub use section::import::{ImportReaderError, ImportSectionReader};
 pub use section::memory::{MemoryReaderError, MemorySectionReader};
+pub use section::name::{NameReaderError, NameSectionReader};
+pub use section::producers::{ProducersReaderError, ProducersSectionReader};
 pub use section::start::{StartReaderError, StartSectionReader};
 pub use section::table::{TableReaderError, TableSectionReader};
+pub use section::tag::{TagReaderError, TagSectionReader};
+pub use section::target_features::{TargetFeaturesReaderError, TargetFeaturesSectionReader};
 pub use section::r#type::{TypeReaderError, TypeSectionReader};
 
 pub use crate::readers::branch_table::{BranchReaderError, BranchTableReader};
@@ -1,4 +1,4 @@
-use std::result;
+use std::{error, fmt, result};
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use std::convert::TryFrom;
 
@@ -7,6 +7,30 @@ pub enum PreambleReaderError {
     BinaryReaderError(BinaryReaderError),
 }
 
+impl PreambleReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            PreambleReaderError::BinaryReaderError(e) => e.offset(),
+        }
+    }
+}
+
+impl fmt::Display for PreambleReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreambleReaderError::BinaryReaderError(_) => write!(f, "malformed module preamble"),
+        }
+    }
+}
+
+impl error::Error for PreambleReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PreambleReaderError::BinaryReaderError(e) => Some(e),
+        }
+    }
+}
+
 impl From<BinaryReaderError> for PreambleReaderError {
     fn from(e: BinaryReaderError) -> Self {
         PreambleReaderError::BinaryReaderError(e)
@@ -21,7 +45,7 @@ pub struct PreambleReader<'a> {
 }
 
 impl<'a> PreambleReader<'a> {
-    pub fn new(buffer: &[u8]) -> PreambleReader {
+    pub fn new(buffer: &'a [u8]) -> PreambleReader<'a> {
         let reader = BinaryReader::new(buffer);
         PreambleReader { reader }
     }
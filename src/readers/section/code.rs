@@ -1,25 +1,38 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use crate::readers::binary::Result as BinaryReaderResult;
-use std::result;
+use std::{error, fmt, result};
 use crate::types::Locals;
 use crate::InstructionReader;
-use crate::readers::common::{SectionReader, SectionItemIterator};
+use crate::readers::common::{SectionReader, SectionItemIterator, SectionItemWithOffsetIterator};
 
 pub type Result<T, E = CodeReaderError> = result::Result<T, E>;
 
 #[derive(Debug)]
 pub struct Code<'a> {
-    data: &'a [u8]
+    data: &'a [u8],
+    offset: usize,
+    raw_bytes: &'a [u8],
 }
 
 impl<'a> Code<'a> {
-    pub fn get_locals_reader(&self) -> Result<LocalsReader> {
-        Ok(LocalsReader::new(self.data)?)
+    pub fn get_locals_reader(&self) -> Result<LocalsReader<'a>> {
+        Ok(LocalsReader::new(self.data, self.offset)?)
     }
 
-    pub fn get_instruction_reader(&self, locals_iteration_proof: LocalsIterationProof) -> Result<InstructionReader> {
+    pub fn get_instruction_reader(&self, locals_iteration_proof: LocalsIterationProof) -> Result<InstructionReader<'a>> {
         let buffer = &self.data[locals_iteration_proof.position..];
-        Ok(InstructionReader::new(buffer)?)
+        Ok(InstructionReader::new(buffer, self.offset + locals_iteration_proof.position)?)
+    }
+
+    /// Returns the absolute offset of the function body, past its size prefix.
+    pub fn get_original_position(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the bytes of the function body, size prefix included, exactly
+    /// as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.raw_bytes
     }
 }
 
@@ -34,6 +47,30 @@ pub enum CodeReaderError {
     BinaryReaderError(BinaryReaderError),
 }
 
+impl CodeReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            CodeReaderError::BinaryReaderError(e) => e.offset(),
+        }
+    }
+}
+
+impl fmt::Display for CodeReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodeReaderError::BinaryReaderError(_) => write!(f, "malformed code section"),
+        }
+    }
+}
+
+impl error::Error for CodeReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            CodeReaderError::BinaryReaderError(e) => Some(e),
+        }
+    }
+}
+
 impl From<BinaryReaderError> for CodeReaderError {
     fn from(e: BinaryReaderError) -> Self {
         CodeReaderError::BinaryReaderError(e)
@@ -41,8 +78,9 @@ impl From<BinaryReaderError> for CodeReaderError {
 }
 
 impl<'a> CodeSectionReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<CodeSectionReader<'a>> {
-        let mut reader = BinaryReader::new(buffer);
+    /// Creates a reader over a whole section, id and size included, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<CodeSectionReader<'a>> {
+        let mut reader = BinaryReader::new_for_section(buffer, offset)?;
         let count = reader.read_leb128_u32()?;
         Ok(CodeSectionReader { reader, count })
     }
@@ -51,17 +89,39 @@ impl<'a> CodeSectionReader<'a> {
         self.count
     }
 
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    /// Returns the absolute offset of the section, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        self.reader.get_original_offset()
+    }
+
+    /// Returns the bytes of the whole section exactly as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
+
+    pub fn into_iter_with_offsets(self) -> SectionItemWithOffsetIterator<CodeSectionReader<'a>> {
+        SectionItemWithOffsetIterator::new(self)
+    }
+
     pub fn read<'b>(&mut self) -> Result<Code<'b>>
         where 'a: 'b
     {
-        Ok(self.read_code()?)
+        self.read_code()
     }
 
     fn read_code<'b>(&mut self) -> Result<Code<'b>>
         where 'a: 'b
     {
-        let data = self.reader.read_bytes_vec()?;
-        Ok(Code { data })
+        let start = self.reader.get_position();
+        let size = self.reader.read_leb128_u32()? as usize;
+        let offset = self.reader.get_original_position();
+        let data = self.reader.read_bytes(size)?;
+        let raw_bytes = self.reader.create_buffer_slice(start, self.reader.get_position())?;
+        Ok(Code { data, offset, raw_bytes })
     }
 }
 
@@ -76,6 +136,10 @@ impl<'a> SectionReader for CodeSectionReader<'a> {
     fn get_count(&self) -> u32 {
         self.get_count()
     }
+
+    fn get_original_position(&self) -> usize {
+        self.get_original_position()
+    }
 }
 
 impl<'a> IntoIterator for CodeSectionReader<'a> {
@@ -98,8 +162,8 @@ pub struct LocalsIterationProof {
 }
 
 impl<'a> LocalsReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<LocalsReader<'a>> {
-        let mut reader = BinaryReader::new(buffer);
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<LocalsReader<'a>> {
+        let mut reader = BinaryReader::new_with_offset(buffer, offset);
         let count = reader.read_leb128_u32()?;
         Ok(LocalsReader { reader, count, remaining_items: count })
     }
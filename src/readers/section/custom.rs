@@ -1,5 +1,7 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use crate::readers::binary::Result as BinaryReaderResult;
+use std::{error, fmt};
+use crate::{NameSectionReader, ProducersSectionReader, TargetFeaturesSectionReader};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct CustomSectionReader<'a> {
@@ -8,11 +10,44 @@ pub struct CustomSectionReader<'a> {
     data: &'a [u8],
 }
 
-#[derive(Debug)]
+/// The custom sections this crate knows how to decode, dispatched by name.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum CustomSectionKind<'a> {
+    Name(NameSectionReader<'a>),
+    Producers(ProducersSectionReader<'a>),
+    TargetFeatures(TargetFeaturesSectionReader<'a>),
+    Unknown,
+}
+
+#[derive(PartialEq, Eq, Debug)]
 pub enum CustomReaderError {
     BinaryReaderError(BinaryReaderError),
 }
 
+impl CustomReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            CustomReaderError::BinaryReaderError(e) => e.offset(),
+        }
+    }
+}
+
+impl fmt::Display for CustomReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CustomReaderError::BinaryReaderError(_) => write!(f, "malformed custom section"),
+        }
+    }
+}
+
+impl error::Error for CustomReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            CustomReaderError::BinaryReaderError(e) => Some(e),
+        }
+    }
+}
+
 impl From<BinaryReaderError> for CustomReaderError {
     fn from(e: BinaryReaderError) -> Self {
         CustomReaderError::BinaryReaderError(e)
@@ -20,8 +55,9 @@ impl From<BinaryReaderError> for CustomReaderError {
 }
 
 impl<'a> CustomSectionReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<CustomSectionReader<'a>> {
-        let mut reader = BinaryReader::new(buffer);
+    /// Creates a reader over a whole section, id and size included, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<CustomSectionReader<'a>> {
+        let mut reader = BinaryReader::new_for_section(buffer, offset)?;
         let name = reader.read_string()?;
         let data = &buffer[reader.get_position()..];
         Ok(CustomSectionReader { reader, name, data })
@@ -34,4 +70,160 @@ impl<'a> CustomSectionReader<'a> {
     pub fn get_data(&self) -> &[u8] {
         self.data
     }
+
+    /// Returns the absolute offset at which the data following the name starts.
+    pub fn get_data_offset(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    /// Returns a reader over the subsections of a `name` section, or `None`
+    /// when this custom section has another name.
+    pub fn get_name_section_reader(&self) -> Option<NameSectionReader<'a>> {
+        if self.name == "name" {
+            Some(NameSectionReader::new(self.data, self.get_data_offset()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns a typed reader for the data of the section, chosen by its name.
+    pub fn get_kind(&self) -> Result<CustomSectionKind<'a>, CustomReaderError> {
+        let offset = self.get_data_offset();
+        Ok(match self.name {
+            "name" => CustomSectionKind::Name(NameSectionReader::new(self.data, offset)),
+            "producers" => CustomSectionKind::Producers(ProducersSectionReader::new(self.data, offset)?),
+            "target_features" => CustomSectionKind::TargetFeatures(TargetFeaturesSectionReader::new(self.data, offset)?),
+            _ => CustomSectionKind::Unknown,
+        })
+    }
+
+    /// Returns the absolute offset of the section, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        self.reader.get_original_offset()
+    }
+
+    /// Returns the bytes of the whole section exactly as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoder::{BinaryWriter, CustomSectionEncoder, ModuleEncoder};
+    use crate::{Parser, Chunk, SectionReader, CustomSectionKind, ProducersReaderError, TargetFeaturesReaderError};
+    use crate::readers::binary::BinaryReaderError;
+    use crate::{ProducersField, ProducersFieldValue, TargetFeature, FeaturePrefix};
+
+    /// Wraps `data` in a custom section and runs `test` on the kind of the section.
+    fn custom_section_kind(name: &str, data: &[u8], test: impl FnOnce(CustomSectionKind)) {
+        let mut module = ModuleEncoder::new();
+        module.section(&CustomSectionEncoder::new(name, data));
+        let module = module.finish();
+        let mut parser = Parser::new();
+        let (consumed, _) = parser.parse(&module, true).unwrap();
+        match parser.parse(&module[consumed..], true).unwrap() {
+            (_, Chunk::Section(SectionReader::Custom(reader))) => test(reader.get_kind().unwrap()),
+            _ => panic!("expected a custom section"),
+        }
+    }
+
+    fn producers_field(writer: &mut BinaryWriter, name: &str, values: &[(&str, &str)]) {
+        writer.write_string(name);
+        writer.write_leb128_u32(values.len() as u32);
+        for (name, version) in values {
+            writer.write_string(name);
+            writer.write_string(version);
+        }
+    }
+
+    #[test]
+    fn producers() {
+        let mut data = BinaryWriter::new();
+        data.write_leb128_u32(2);
+        producers_field(&mut data, "language", &[("Rust", "1.70.0")]);
+        producers_field(&mut data, "processed-by", &[("rustc", "1.70.0"), ("wasm-bindgen", "0.2.87")]);
+        custom_section_kind("producers", data.as_slice(), |kind| match kind {
+            CustomSectionKind::Producers(reader) => {
+                let fields = reader.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+                assert_eq!(fields, vec![
+                    ProducersField {
+                        name: "language",
+                        values: vec![ProducersFieldValue { name: "Rust", version: "1.70.0" }].into_boxed_slice(),
+                    },
+                    ProducersField {
+                        name: "processed-by",
+                        values: vec![
+                            ProducersFieldValue { name: "rustc", version: "1.70.0" },
+                            ProducersFieldValue { name: "wasm-bindgen", version: "0.2.87" },
+                        ].into_boxed_slice(),
+                    },
+                ]);
+            },
+            _ => panic!("expected a producers section"),
+        });
+
+        // The section data starts at offset 20: preamble, id, size and "producers".
+        let mut data = BinaryWriter::new();
+        data.write_leb128_u32(2);
+        producers_field(&mut data, "sdk", &[]);
+        producers_field(&mut data, "sdk", &[]);
+        custom_section_kind("producers", data.as_slice(), |kind| match kind {
+            CustomSectionKind::Producers(mut reader) => {
+                reader.read().unwrap();
+                assert_eq!(reader.read(), Err(ProducersReaderError::DuplicateField(26)));
+            },
+            _ => panic!("expected a producers section"),
+        });
+
+        let mut data = BinaryWriter::new();
+        data.write_leb128_u32(1);
+        producers_field(&mut data, "compiler", &[]);
+        custom_section_kind("producers", data.as_slice(), |kind| match kind {
+            CustomSectionKind::Producers(mut reader) => {
+                assert_eq!(reader.read(), Err(ProducersReaderError::UnknownField(21)));
+            },
+            _ => panic!("expected a producers section"),
+        });
+
+        // A huge declared value count runs out of bytes instead of allocating for it.
+        let mut data = BinaryWriter::new();
+        data.write_leb128_u32(1);
+        data.write_string("sdk");
+        data.write_leb128_u32(u32::MAX);
+        custom_section_kind("producers", data.as_slice(), |kind| match kind {
+            CustomSectionKind::Producers(mut reader) => {
+                assert_eq!(reader.read(), Err(ProducersReaderError::BinaryReaderError(BinaryReaderError::UnexpectedEof(30))));
+            },
+            _ => panic!("expected a producers section"),
+        });
+    }
+
+    #[test]
+    fn target_features() {
+        let mut data = BinaryWriter::new();
+        data.write_leb128_u32(4);
+        for (prefix, name) in &[(b'+', "simd128"), (b'-', "threads"), (b'=', "mutable-globals"), (b'*', "atomics")] {
+            data.write_byte(*prefix);
+            data.write_string(name);
+        }
+        custom_section_kind("target_features", data.as_slice(), |kind| match kind {
+            CustomSectionKind::TargetFeatures(reader) => {
+                let features = reader.into_iter().collect::<Vec<_>>();
+                assert_eq!(features, vec![
+                    Ok(TargetFeature { prefix: FeaturePrefix::Used, name: "simd128" }),
+                    Ok(TargetFeature { prefix: FeaturePrefix::Disallowed, name: "threads" }),
+                    Ok(TargetFeature { prefix: FeaturePrefix::Required, name: "mutable-globals" }),
+                    Err(TargetFeaturesReaderError::InvalidPrefix(62)),
+                ]);
+            },
+            _ => panic!("expected a target_features section"),
+        });
+    }
+
+    #[test]
+    fn other_custom_sections() {
+        custom_section_kind("name", &[], |kind| assert!(matches!(kind, CustomSectionKind::Name(_))));
+        custom_section_kind("sourceMappingURL", b"\x07foo.map", |kind| assert_eq!(kind, CustomSectionKind::Unknown));
+    }
 }
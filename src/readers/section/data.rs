@@ -1,8 +1,9 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use crate::readers::binary::Result as BinaryReaderResult;
-use std::result;
-use crate::types::{DataSegment, MemoryIndex};
-use crate::readers::common::{SectionReader, SectionItemIterator};
+use crate::readers::instruction::InstructionReaderError;
+use std::{error, fmt, result};
+use crate::types::{DataSegment, DataMode, MemoryIndex};
+use crate::readers::common::{SectionReader, SectionItemIterator, SectionItemWithOffsetIterator};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct DataSectionReader<'a> {
@@ -13,6 +14,38 @@ pub struct DataSectionReader<'a> {
 #[derive(Debug, Eq, PartialEq)]
 pub enum DataReaderError {
     BinaryReaderError(BinaryReaderError),
+    InstructionReaderError(InstructionReaderError),
+    InvalidSegmentFlags(usize),
+}
+
+impl DataReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            DataReaderError::BinaryReaderError(e) => e.offset(),
+            DataReaderError::InstructionReaderError(e) => e.offset(),
+            DataReaderError::InvalidSegmentFlags(offset) => *offset,
+        }
+    }
+}
+
+impl fmt::Display for DataReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataReaderError::BinaryReaderError(_) => write!(f, "malformed data section"),
+            DataReaderError::InstructionReaderError(_) => write!(f, "malformed data expression"),
+            DataReaderError::InvalidSegmentFlags(offset) => write!(f, "invalid data segment flags at offset {}", offset),
+        }
+    }
+}
+
+impl error::Error for DataReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DataReaderError::BinaryReaderError(e) => Some(e),
+            DataReaderError::InstructionReaderError(e) => Some(e),
+            DataReaderError::InvalidSegmentFlags(_) => None,
+        }
+    }
 }
 
 impl From<BinaryReaderError> for DataReaderError {
@@ -21,11 +54,21 @@ impl From<BinaryReaderError> for DataReaderError {
     }
 }
 
+impl From<InstructionReaderError> for DataReaderError {
+    fn from(e: InstructionReaderError) -> Self {
+        match e {
+            InstructionReaderError::BinaryReaderError(e) => DataReaderError::BinaryReaderError(e),
+            e => DataReaderError::InstructionReaderError(e),
+        }
+    }
+}
+
 pub type Result<T, E = DataReaderError> = result::Result<T, E>;
 
 impl<'a> DataSectionReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<DataSectionReader<'a>> {
-        let mut reader = BinaryReader::new(buffer);
+    /// Creates a reader over a whole section, id and size included, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<DataSectionReader<'a>> {
+        let mut reader = BinaryReader::new_for_section(buffer, offset)?;
         let count = reader.read_leb128_u32()?;
         Ok(DataSectionReader { reader, count })
     }
@@ -34,6 +77,24 @@ impl<'a> DataSectionReader<'a> {
         self.count
     }
 
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    /// Returns the absolute offset of the section, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        self.reader.get_original_offset()
+    }
+
+    /// Returns the bytes of the whole section exactly as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
+
+    pub fn into_iter_with_offsets(self) -> SectionItemWithOffsetIterator<DataSectionReader<'a>> {
+        SectionItemWithOffsetIterator::new(self)
+    }
+
     pub fn read<'b>(&mut self) -> Result<DataSegment<'b>>
         where 'a: 'b
     {
@@ -43,10 +104,22 @@ impl<'a> DataSectionReader<'a> {
     fn read_data_segment<'b>(&mut self) -> Result<DataSegment<'b>>
         where 'a: 'b
     {
-        let memory_index = MemoryIndex(self.reader.read_leb128_u32()?);
-        let instruction_reader = self.reader.create_instruction_reader()?;
+        let offset = self.reader.get_original_position();
+        let mode = match self.reader.read_leb128_u32()? {
+            0 => {
+                let instruction_reader = self.reader.create_instruction_reader()?;
+                DataMode::Active { memory_index: MemoryIndex(0), instruction_reader }
+            },
+            1 => DataMode::Passive,
+            2 => {
+                let memory_index = MemoryIndex(self.reader.read_leb128_u32()?);
+                let instruction_reader = self.reader.create_instruction_reader()?;
+                DataMode::Active { memory_index, instruction_reader }
+            },
+            _ => return Err(DataReaderError::InvalidSegmentFlags(offset)),
+        };
         let bytes = self.reader.read_bytes_vec()?;
-        Ok(DataSegment { memory_index, instruction_reader, bytes })
+        Ok(DataSegment { mode, bytes })
     }
 }
 
@@ -61,6 +134,10 @@ impl<'a> SectionReader for DataSectionReader<'a> {
     fn get_count(&self) -> u32 {
         self.get_count()
     }
+
+    fn get_original_position(&self) -> usize {
+        self.get_original_position()
+    }
 }
 
 impl<'a> IntoIterator for DataSectionReader<'a> {
@@ -0,0 +1,74 @@
+use crate::readers::binary::{BinaryReader, BinaryReaderError};
+use crate::readers::binary::Result as BinaryReaderResult;
+use std::{error, fmt};
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct DataCountSectionReader<'a> {
+    reader: BinaryReader<'a>,
+    count: u32,
+    offset: usize,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum DataCountReaderError {
+    BinaryReaderError(BinaryReaderError),
+}
+
+impl DataCountReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            DataCountReaderError::BinaryReaderError(e) => e.offset(),
+        }
+    }
+}
+
+impl fmt::Display for DataCountReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataCountReaderError::BinaryReaderError(_) => write!(f, "malformed data count section"),
+        }
+    }
+}
+
+impl error::Error for DataCountReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DataCountReaderError::BinaryReaderError(e) => Some(e),
+        }
+    }
+}
+
+impl From<BinaryReaderError> for DataCountReaderError {
+    fn from(e: BinaryReaderError) -> Self {
+        DataCountReaderError::BinaryReaderError(e)
+    }
+}
+
+impl<'a> DataCountSectionReader<'a> {
+    /// Creates a reader over a whole section, id and size included, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<DataCountSectionReader<'a>> {
+        let mut reader = BinaryReader::new_for_section(buffer, offset)?;
+        let offset = reader.get_original_position();
+        let count = reader.read_leb128_u32()?;
+        Ok(DataCountSectionReader { reader, count, offset })
+    }
+
+    pub fn get_original_position(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the number of segments the data section must declare.
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    /// Returns the absolute offset of the section, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        self.reader.get_original_offset()
+    }
+
+    /// Returns the bytes of the whole section exactly as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
+}
@@ -1,8 +1,9 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use crate::readers::binary::Result as BinaryReaderResult;
-use std::result;
-use crate::types::{ElementSegment, TableIndex, FuncIndex};
-use crate::readers::common::{SectionReader, SectionItemIterator};
+use crate::readers::instruction::InstructionReaderError;
+use std::{error, fmt, result};
+use crate::types::{ElementSegment, ElementMode, ElementItems, TableIndex, FuncIndex, ValueType};
+use crate::readers::common::{SectionReader, SectionItemIterator, SectionItemWithOffsetIterator};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct ElementSectionReader<'a> {
@@ -13,6 +14,42 @@ pub struct ElementSectionReader<'a> {
 #[derive(Debug, Eq, PartialEq)]
 pub enum ElementReaderError {
     BinaryReaderError(BinaryReaderError),
+    InstructionReaderError(InstructionReaderError),
+    InvalidSegmentFlags(usize),
+    InvalidElementKind(usize),
+}
+
+impl ElementReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            ElementReaderError::BinaryReaderError(e) => e.offset(),
+            ElementReaderError::InstructionReaderError(e) => e.offset(),
+            ElementReaderError::InvalidSegmentFlags(offset) |
+            ElementReaderError::InvalidElementKind(offset) => *offset,
+        }
+    }
+}
+
+impl fmt::Display for ElementReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElementReaderError::BinaryReaderError(_) => write!(f, "malformed element section"),
+            ElementReaderError::InstructionReaderError(_) => write!(f, "malformed element expression"),
+            ElementReaderError::InvalidSegmentFlags(offset) => write!(f, "invalid element segment flags at offset {}", offset),
+            ElementReaderError::InvalidElementKind(offset) => write!(f, "invalid element kind at offset {}", offset),
+        }
+    }
+}
+
+impl error::Error for ElementReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ElementReaderError::BinaryReaderError(e) => Some(e),
+            ElementReaderError::InstructionReaderError(e) => Some(e),
+            ElementReaderError::InvalidSegmentFlags(_) |
+            ElementReaderError::InvalidElementKind(_) => None,
+        }
+    }
 }
 
 impl From<BinaryReaderError> for ElementReaderError {
@@ -21,11 +58,21 @@ impl From<BinaryReaderError> for ElementReaderError {
     }
 }
 
+impl From<InstructionReaderError> for ElementReaderError {
+    fn from(e: InstructionReaderError) -> Self {
+        match e {
+            InstructionReaderError::BinaryReaderError(e) => ElementReaderError::BinaryReaderError(e),
+            e => ElementReaderError::InstructionReaderError(e),
+        }
+    }
+}
+
 pub type Result<T, E = ElementReaderError> = result::Result<T, E>;
 
 impl<'a> ElementSectionReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<ElementSectionReader<'a>> {
-        let mut reader = BinaryReader::new(buffer);
+    /// Creates a reader over a whole section, id and size included, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<ElementSectionReader<'a>> {
+        let mut reader = BinaryReader::new_for_section(buffer, offset)?;
         let count = reader.read_leb128_u32()?;
         Ok(ElementSectionReader { reader, count })
     }
@@ -34,25 +81,88 @@ impl<'a> ElementSectionReader<'a> {
         self.count
     }
 
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    /// Returns the absolute offset of the section, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        self.reader.get_original_offset()
+    }
+
+    /// Returns the bytes of the whole section exactly as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
+
+    pub fn into_iter_with_offsets(self) -> SectionItemWithOffsetIterator<ElementSectionReader<'a>> {
+        SectionItemWithOffsetIterator::new(self)
+    }
+
     pub fn read<'b>(&mut self) -> Result<ElementSegment<'b>>
         where 'a: 'b
     {
-        Ok(self.read_element_segment()?)
+        self.read_element_segment()
     }
 
+    /// Bit 0 of the flags marks a passive or declarative segment, bit 1 an
+    /// explicit table index for an active segment or a declarative one
+    /// otherwise, and bit 2 items given as expressions instead of function
+    /// indices.
     fn read_element_segment<'b>(&mut self) -> Result<ElementSegment<'b>>
         where 'a: 'b
     {
-        let table_index = TableIndex(self.reader.read_leb128_u32()?);
-        let instruction_reader = self.reader.create_instruction_reader()?;
-        let len = self.reader.read_leb128_u32()?;
-        let mut func_indices = Vec::with_capacity(len as usize);
-        for _ in 0..len {
-            let func_index = FuncIndex(self.reader.read_leb128_u32()?);
-            func_indices.push(func_index);
+        let offset = self.reader.get_original_position();
+        let flags = self.reader.read_leb128_u32()?;
+        if flags > 7 {
+            return Err(ElementReaderError::InvalidSegmentFlags(offset));
         }
+        let mode = match flags & 0b011 {
+            0b000 => {
+                let instruction_reader = self.reader.create_instruction_reader()?;
+                ElementMode::Active { table_index: TableIndex(0), instruction_reader }
+            },
+            0b001 => ElementMode::Passive,
+            0b010 => {
+                let table_index = TableIndex(self.reader.read_leb128_u32()?);
+                let instruction_reader = self.reader.create_instruction_reader()?;
+                ElementMode::Active { table_index, instruction_reader }
+            },
+            _ => ElementMode::Declarative,
+        };
+        let uses_expressions = flags & 0b100 != 0;
+        let element_type = match flags {
+            0 | 4 => ValueType::FuncRef,
+            _ if uses_expressions => self.reader.read_reference_type()?,
+            _ => self.read_element_kind()?,
+        };
+        let len = self.reader.read_leb128_u32()?;
+        let items = if uses_expressions {
+            let mut expressions = Vec::new();
+            for _ in 0..len {
+                expressions.push(self.reader.create_instruction_reader()?);
+            }
+            ElementItems::Expressions(expressions.into_boxed_slice())
+        } else {
+            let mut func_indices = Vec::new();
+            for _ in 0..len {
+                let func_index = FuncIndex(self.reader.read_leb128_u32()?);
+                func_indices.push(func_index);
+            }
+            ElementItems::Functions(func_indices.into_boxed_slice())
+        };
 
-        Ok(ElementSegment { table_index, instruction_reader, function_indices: func_indices.into_boxed_slice() })
+        Ok(ElementSegment { mode, element_type, items })
+    }
+
+    /// Reads the element kind of segments listing function indices, of which
+    /// only `0x00`, standing for `funcref`, exists.
+    fn read_element_kind(&mut self) -> Result<ValueType> {
+        let offset = self.reader.get_original_position();
+        match self.reader.read_byte()? {
+            0x00 => Ok(ValueType::FuncRef),
+            _ => Err(ElementReaderError::InvalidElementKind(offset)),
+        }
     }
 }
 
@@ -67,6 +177,10 @@ impl<'a> SectionReader for ElementSectionReader<'a> {
     fn get_count(&self) -> u32 {
         self.get_count()
     }
+
+    fn get_original_position(&self) -> usize {
+        self.get_original_position()
+    }
 }
 
 impl<'a> IntoIterator for ElementSectionReader<'a> {
@@ -1,8 +1,8 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use crate::readers::binary::Result as BinaryReaderResult;
-use std::result;
-use crate::types::{Export, ExportDescriptor, FuncIndex, TableIndex, MemoryIndex, GlobalIndex};
-use crate::readers::common::{SectionReader, SectionItemIterator};
+use std::{error, fmt, result};
+use crate::types::{Export, ExportDescriptor, FuncIndex, TableIndex, MemoryIndex, GlobalIndex, TagIndex};
+use crate::readers::common::{SectionReader, SectionItemIterator, SectionItemWithOffsetIterator};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct ExportSectionReader<'a> {
@@ -13,7 +13,34 @@ pub struct ExportSectionReader<'a> {
 #[derive(Eq, PartialEq, Debug)]
 pub enum ExportReaderError {
     BinaryReaderError(BinaryReaderError),
-    InvalidExportDescByte,
+    InvalidExportDescByte(usize),
+}
+
+impl ExportReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            ExportReaderError::BinaryReaderError(e) => e.offset(),
+            ExportReaderError::InvalidExportDescByte(offset) => *offset,
+        }
+    }
+}
+
+impl fmt::Display for ExportReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportReaderError::BinaryReaderError(_) => write!(f, "malformed export section"),
+            ExportReaderError::InvalidExportDescByte(offset) => write!(f, "invalid export kind at offset {}", offset),
+        }
+    }
+}
+
+impl error::Error for ExportReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ExportReaderError::BinaryReaderError(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<BinaryReaderError> for ExportReaderError {
@@ -25,8 +52,9 @@ impl From<BinaryReaderError> for ExportReaderError {
 pub type Result<T, E = ExportReaderError> = result::Result<T, E>;
 
 impl<'a> ExportSectionReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<ExportSectionReader<'a>> {
-        let mut reader = BinaryReader::new(buffer);
+    /// Creates a reader over a whole section, id and size included, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<ExportSectionReader<'a>> {
+        let mut reader = BinaryReader::new_for_section(buffer, offset)?;
         let count = reader.read_leb128_u32()?;
         Ok(ExportSectionReader { reader, count })
     }
@@ -35,6 +63,24 @@ impl<'a> ExportSectionReader<'a> {
         self.count
     }
 
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    /// Returns the absolute offset of the section, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        self.reader.get_original_offset()
+    }
+
+    /// Returns the bytes of the whole section exactly as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
+
+    pub fn into_iter_with_offsets(self) -> SectionItemWithOffsetIterator<ExportSectionReader<'a>> {
+        SectionItemWithOffsetIterator::new(self)
+    }
+
     pub fn read<'b>(&mut self) -> Result<Export<'b>>
         where 'a: 'b
     {
@@ -44,6 +90,7 @@ impl<'a> ExportSectionReader<'a> {
     }
 
     fn read_export_desc(&mut self) -> Result<ExportDescriptor> {
+        let offset = self.reader.get_original_position();
         match self.reader.read_byte()? {
             0x00 => {
                 let func_index = FuncIndex(self.reader.read_leb128_u32()?);
@@ -61,7 +108,11 @@ impl<'a> ExportSectionReader<'a> {
                 let global_index = GlobalIndex(self.reader.read_leb128_u32()?);
                 Ok(ExportDescriptor::Global { global_index })
             },
-            _ => Err(ExportReaderError::InvalidExportDescByte)
+            0x04 => {
+                let tag_index = TagIndex(self.reader.read_leb128_u32()?);
+                Ok(ExportDescriptor::Tag { tag_index })
+            },
+            _ => Err(ExportReaderError::InvalidExportDescByte(offset))
         }
     }
 }
@@ -77,6 +128,10 @@ impl<'a> SectionReader for ExportSectionReader<'a> {
     fn get_count(&self) -> u32 {
         self.get_count()
     }
+
+    fn get_original_position(&self) -> usize {
+        self.get_original_position()
+    }
 }
 
 impl<'a> IntoIterator for ExportSectionReader<'a> {
@@ -1,7 +1,7 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use crate::readers::binary::Result as BinaryReaderResult;
-use std::result;
-use crate::readers::common::{SectionReader, SectionItemIterator};
+use std::{error, fmt, result};
+use crate::readers::common::{SectionReader, SectionItemIterator, SectionItemWithOffsetIterator};
 use crate::types::TypeIndex;
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -15,6 +15,30 @@ pub enum FunctionReaderError {
     BinaryReaderError(BinaryReaderError),
 }
 
+impl FunctionReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            FunctionReaderError::BinaryReaderError(e) => e.offset(),
+        }
+    }
+}
+
+impl fmt::Display for FunctionReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FunctionReaderError::BinaryReaderError(_) => write!(f, "malformed function section"),
+        }
+    }
+}
+
+impl error::Error for FunctionReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            FunctionReaderError::BinaryReaderError(e) => Some(e),
+        }
+    }
+}
+
 impl From<BinaryReaderError> for FunctionReaderError {
     fn from(e: BinaryReaderError) -> Self {
         FunctionReaderError::BinaryReaderError(e)
@@ -24,8 +48,9 @@ impl From<BinaryReaderError> for FunctionReaderError {
 pub type Result<T, E = FunctionReaderError> = result::Result<T, E>;
 
 impl<'a> FunctionSectionReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<FunctionSectionReader<'a>> {
-        let mut reader = BinaryReader::new(buffer);
+    /// Creates a reader over a whole section, id and size included, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<FunctionSectionReader<'a>> {
+        let mut reader = BinaryReader::new_for_section(buffer, offset)?;
         let count = reader.read_leb128_u32()?;
         Ok(FunctionSectionReader { reader, count })
     }
@@ -34,6 +59,24 @@ impl<'a> FunctionSectionReader<'a> {
         self.count
     }
 
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    /// Returns the absolute offset of the section, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        self.reader.get_original_offset()
+    }
+
+    /// Returns the bytes of the whole section exactly as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
+
+    pub fn into_iter_with_offsets(self) -> SectionItemWithOffsetIterator<FunctionSectionReader<'a>> {
+        SectionItemWithOffsetIterator::new(self)
+    }
+
     pub fn read(&mut self) -> Result<TypeIndex> {
         Ok(TypeIndex(self.reader.read_leb128_u32()?))
     }
@@ -50,6 +93,10 @@ impl<'a> SectionReader for FunctionSectionReader<'a> {
     fn get_count(&self) -> u32 {
         self.get_count()
     }
+
+    fn get_original_position(&self) -> usize {
+        self.get_original_position()
+    }
 }
 
 impl<'a> IntoIterator for FunctionSectionReader<'a> {
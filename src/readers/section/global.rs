@@ -1,8 +1,9 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use crate::readers::binary::Result as BinaryReaderResult;
-use std::result;
+use crate::readers::instruction::InstructionReaderError;
+use std::{error, fmt, result};
 use crate::types::GlobalSegment;
-use crate::readers::common::{SectionReader, SectionItemIterator};
+use crate::readers::common::{SectionReader, SectionItemIterator, SectionItemWithOffsetIterator};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct GlobalSectionReader<'a> {
@@ -13,6 +14,34 @@ pub struct GlobalSectionReader<'a> {
 #[derive(PartialEq, Eq, Debug)]
 pub enum GlobalReaderError {
     BinaryReaderError(BinaryReaderError),
+    InstructionReaderError(InstructionReaderError),
+}
+
+impl GlobalReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            GlobalReaderError::BinaryReaderError(e) => e.offset(),
+            GlobalReaderError::InstructionReaderError(e) => e.offset(),
+        }
+    }
+}
+
+impl fmt::Display for GlobalReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GlobalReaderError::BinaryReaderError(_) => write!(f, "malformed global section"),
+            GlobalReaderError::InstructionReaderError(_) => write!(f, "malformed global expression"),
+        }
+    }
+}
+
+impl error::Error for GlobalReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            GlobalReaderError::BinaryReaderError(e) => Some(e),
+            GlobalReaderError::InstructionReaderError(e) => Some(e),
+        }
+    }
 }
 
 impl From<BinaryReaderError> for GlobalReaderError {
@@ -21,11 +50,21 @@ impl From<BinaryReaderError> for GlobalReaderError {
     }
 }
 
+impl From<InstructionReaderError> for GlobalReaderError {
+    fn from(e: InstructionReaderError) -> Self {
+        match e {
+            InstructionReaderError::BinaryReaderError(e) => GlobalReaderError::BinaryReaderError(e),
+            e => GlobalReaderError::InstructionReaderError(e),
+        }
+    }
+}
+
 pub type Result<T, E = GlobalReaderError> = result::Result<T, E>;
 
 impl<'a> GlobalSectionReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<GlobalSectionReader<'a>> {
-        let mut reader = BinaryReader::new(buffer);
+    /// Creates a reader over a whole section, id and size included, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<GlobalSectionReader<'a>> {
+        let mut reader = BinaryReader::new_for_section(buffer, offset)?;
         let count = reader.read_leb128_u32()?;
         Ok(GlobalSectionReader { reader, count })
     }
@@ -34,6 +73,24 @@ impl<'a> GlobalSectionReader<'a> {
         self.count
     }
 
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    /// Returns the absolute offset of the section, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        self.reader.get_original_offset()
+    }
+
+    /// Returns the bytes of the whole section exactly as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
+
+    pub fn into_iter_with_offsets(self) -> SectionItemWithOffsetIterator<GlobalSectionReader<'a>> {
+        SectionItemWithOffsetIterator::new(self)
+    }
+
     pub fn read<'b>(&mut self) -> Result<GlobalSegment<'b>>
         where 'a: 'b
     {
@@ -60,6 +117,10 @@ impl<'a> SectionReader for GlobalSectionReader<'a> {
     fn get_count(&self) -> u32 {
         self.get_count()
     }
+
+    fn get_original_position(&self) -> usize {
+        self.get_original_position()
+    }
 }
 
 impl<'a> IntoIterator for GlobalSectionReader<'a> {
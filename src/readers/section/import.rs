@@ -1,8 +1,8 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use crate::readers::binary::Result as BinaryReaderResult;
-use std::result;
+use std::{error, fmt, result};
 use crate::types::{Import, ImportDescriptor, TypeIndex};
-use crate::readers::common::{SectionReader, SectionItemIterator};
+use crate::readers::common::{SectionReader, SectionItemIterator, SectionItemWithOffsetIterator};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct ImportSectionReader<'a> {
@@ -13,7 +13,34 @@ pub struct ImportSectionReader<'a> {
 #[derive(PartialEq, Eq, Debug)]
 pub enum ImportReaderError {
     BinaryReaderError(BinaryReaderError),
-    InvalidImportDescByte,
+    InvalidImportDescByte(usize),
+}
+
+impl ImportReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            ImportReaderError::BinaryReaderError(e) => e.offset(),
+            ImportReaderError::InvalidImportDescByte(offset) => *offset,
+        }
+    }
+}
+
+impl fmt::Display for ImportReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportReaderError::BinaryReaderError(_) => write!(f, "malformed import section"),
+            ImportReaderError::InvalidImportDescByte(offset) => write!(f, "invalid import kind at offset {}", offset),
+        }
+    }
+}
+
+impl error::Error for ImportReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ImportReaderError::BinaryReaderError(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<BinaryReaderError> for ImportReaderError {
@@ -25,8 +52,9 @@ impl From<BinaryReaderError> for ImportReaderError {
 pub type Result<T, E = ImportReaderError> = result::Result<T, E>;
 
 impl<'a> ImportSectionReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<ImportSectionReader<'a>> {
-        let mut reader = BinaryReader::new(buffer);
+    /// Creates a reader over a whole section, id and size included, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<ImportSectionReader<'a>> {
+        let mut reader = BinaryReader::new_for_section(buffer, offset)?;
         let count = reader.read_leb128_u32()?;
         Ok(ImportSectionReader { reader, count })
     }
@@ -35,6 +63,24 @@ impl<'a> ImportSectionReader<'a> {
         self.count
     }
 
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    /// Returns the absolute offset of the section, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        self.reader.get_original_offset()
+    }
+
+    /// Returns the bytes of the whole section exactly as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
+
+    pub fn into_iter_with_offsets(self) -> SectionItemWithOffsetIterator<ImportSectionReader<'a>> {
+        SectionItemWithOffsetIterator::new(self)
+    }
+
     pub fn read<'b>(&mut self) -> Result<Import<'b>>
         where 'a: 'b
     {
@@ -45,6 +91,7 @@ impl<'a> ImportSectionReader<'a> {
     }
 
     fn read_import_desc(&mut self) -> Result<ImportDescriptor> {
+        let offset = self.reader.get_original_position();
         match self.reader.read_byte()? {
             0x00 => {
                 let type_index = TypeIndex(self.reader.read_leb128_u32()?);
@@ -62,7 +109,11 @@ impl<'a> ImportSectionReader<'a> {
                 let global_type = self.reader.read_global_type()?;
                 Ok(ImportDescriptor::Global(global_type))
             },
-            _ => Err(ImportReaderError::InvalidImportDescByte)
+            0x04 => {
+                let tag_type = self.reader.read_tag_type()?;
+                Ok(ImportDescriptor::Tag(tag_type))
+            },
+            _ => Err(ImportReaderError::InvalidImportDescByte(offset))
         }
     }
 }
@@ -78,6 +129,10 @@ impl<'a> SectionReader for ImportSectionReader<'a> {
     fn get_count(&self) -> u32 {
         self.get_count()
     }
+
+    fn get_original_position(&self) -> usize {
+        self.get_original_position()
+    }
 }
 
 impl<'a> IntoIterator for ImportSectionReader<'a> {
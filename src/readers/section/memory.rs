@@ -1,8 +1,8 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use crate::readers::binary::Result as BinaryReaderResult;
-use std::result;
+use std::{error, fmt, result};
 use crate::types::MemoryType;
-use crate::readers::common::{SectionReader, SectionItemIterator};
+use crate::readers::common::{SectionReader, SectionItemIterator, SectionItemWithOffsetIterator};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct MemorySectionReader<'a> {
@@ -15,6 +15,30 @@ pub enum MemoryReaderError {
     BinaryReaderError(BinaryReaderError),
 }
 
+impl MemoryReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            MemoryReaderError::BinaryReaderError(e) => e.offset(),
+        }
+    }
+}
+
+impl fmt::Display for MemoryReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryReaderError::BinaryReaderError(_) => write!(f, "malformed memory section"),
+        }
+    }
+}
+
+impl error::Error for MemoryReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            MemoryReaderError::BinaryReaderError(e) => Some(e),
+        }
+    }
+}
+
 impl From<BinaryReaderError> for MemoryReaderError {
     fn from(e: BinaryReaderError) -> Self {
         MemoryReaderError::BinaryReaderError(e)
@@ -24,8 +48,9 @@ impl From<BinaryReaderError> for MemoryReaderError {
 pub type Result<T, E = MemoryReaderError> = result::Result<T, E>;
 
 impl<'a> MemorySectionReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<MemorySectionReader<'a>> {
-        let mut reader = BinaryReader::new(buffer);
+    /// Creates a reader over a whole section, id and size included, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<MemorySectionReader<'a>> {
+        let mut reader = BinaryReader::new_for_section(buffer, offset)?;
         let count = reader.read_leb128_u32()?;
         Ok(MemorySectionReader { reader, count })
     }
@@ -34,6 +59,24 @@ impl<'a> MemorySectionReader<'a> {
         self.count
     }
 
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    /// Returns the absolute offset of the section, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        self.reader.get_original_offset()
+    }
+
+    /// Returns the bytes of the whole section exactly as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
+
+    pub fn into_iter_with_offsets(self) -> SectionItemWithOffsetIterator<MemorySectionReader<'a>> {
+        SectionItemWithOffsetIterator::new(self)
+    }
+
     pub fn read(&mut self) -> Result<MemoryType> {
         Ok(self.reader.read_memory_type()?)
     }
@@ -50,6 +93,10 @@ impl<'a> SectionReader for MemorySectionReader<'a> {
     fn get_count(&self) -> u32 {
         self.get_count()
     }
+
+    fn get_original_position(&self) -> usize {
+        self.get_original_position()
+    }
 }
 
 impl<'a> IntoIterator for MemorySectionReader<'a> {
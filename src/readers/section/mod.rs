@@ -6,7 +6,12 @@ pub mod global;
 pub mod export;
 pub mod element;
 pub mod data;
+pub mod data_count;
 pub mod r#type;
 pub mod table;
 pub mod memory;
+pub mod name;
 pub mod start;
+pub mod tag;
+pub mod producers;
+pub mod target_features;
@@ -0,0 +1,259 @@
+use crate::readers::binary::{BinaryReader, BinaryReaderError};
+use std::{error, fmt, result};
+use crate::types::{Name, Naming, IndirectNaming};
+
+/// Reads the subsections of a `name` custom section, in which names are
+/// debugging information that doesn't affect the semantics of the module.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct NameSectionReader<'a> {
+    reader: BinaryReader<'a>,
+    previous_id: Option<u8>,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum NameReaderError {
+    BinaryReaderError(BinaryReaderError),
+    OutOfOrderSubsection(usize),
+    SubsectionSizeMismatch(usize),
+}
+
+impl NameReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            NameReaderError::BinaryReaderError(e) => e.offset(),
+            NameReaderError::OutOfOrderSubsection(offset) => *offset,
+            NameReaderError::SubsectionSizeMismatch(offset) => *offset,
+        }
+    }
+}
+
+impl fmt::Display for NameReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameReaderError::BinaryReaderError(_) => write!(f, "malformed name section"),
+            NameReaderError::OutOfOrderSubsection(offset) => write!(f, "out of order or duplicate name subsection at offset {}", offset),
+            NameReaderError::SubsectionSizeMismatch(offset) => write!(f, "name subsection at offset {} doesn't match its size", offset),
+        }
+    }
+}
+
+impl error::Error for NameReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            NameReaderError::BinaryReaderError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<BinaryReaderError> for NameReaderError {
+    fn from(e: BinaryReaderError) -> Self {
+        NameReaderError::BinaryReaderError(e)
+    }
+}
+
+pub type Result<T, E = NameReaderError> = result::Result<T, E>;
+
+impl<'a> NameSectionReader<'a> {
+    /// Creates a reader over the data of a `name` section, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> NameSectionReader<'a> {
+        let reader = BinaryReader::new_with_offset(buffer, offset);
+        NameSectionReader { reader, previous_id: None }
+    }
+
+    pub fn eof(&self) -> bool {
+        self.reader.eof()
+    }
+
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    /// Reads the next subsection. Subsections must appear at most once and
+    /// in increasing id order; unknown ones are returned undecoded.
+    pub fn read<'b>(&mut self) -> Result<Name<'b>>
+        where 'a: 'b
+    {
+        let offset = self.reader.get_original_position();
+        let id = self.reader.read_byte()?;
+        if self.previous_id.is_some_and(|previous_id| id <= previous_id) {
+            return Err(NameReaderError::OutOfOrderSubsection(offset));
+        }
+        self.previous_id = Some(id);
+        let size = self.reader.read_leb128_u32()? as usize;
+        let contents_offset = self.reader.get_original_position();
+        let contents = self.reader.read_bytes(size)?;
+        let mut reader = BinaryReader::new_with_offset(contents, contents_offset);
+        let name = match id {
+            0 => Name::Module(reader.read_string()?),
+            1 => Name::Function(read_name_map(&mut reader)?),
+            2 => Name::Local(read_indirect_name_map(&mut reader)?),
+            3 => Name::Label(read_indirect_name_map(&mut reader)?),
+            4 => Name::Type(read_name_map(&mut reader)?),
+            5 => Name::Table(read_name_map(&mut reader)?),
+            6 => Name::Memory(read_name_map(&mut reader)?),
+            7 => Name::Global(read_name_map(&mut reader)?),
+            8 => Name::Element(read_name_map(&mut reader)?),
+            9 => Name::Data(read_name_map(&mut reader)?),
+            _ => return Ok(Name::Unknown { id, data: contents }),
+        };
+        if !reader.eof() {
+            return Err(NameReaderError::SubsectionSizeMismatch(offset));
+        }
+        Ok(name)
+    }
+}
+
+fn read_name_map<'a>(reader: &mut BinaryReader<'a>) -> Result<Box<[Naming<'a>]>> {
+    let count = reader.read_leb128_u32()?;
+    let mut names = Vec::new();
+    for _ in 0..count {
+        let index = reader.read_leb128_u32()?;
+        let name = reader.read_string()?;
+        names.push(Naming { index, name });
+    }
+    Ok(names.into_boxed_slice())
+}
+
+fn read_indirect_name_map<'a>(reader: &mut BinaryReader<'a>) -> Result<Box<[IndirectNaming<'a>]>> {
+    let count = reader.read_leb128_u32()?;
+    let mut names = Vec::new();
+    for _ in 0..count {
+        let index = reader.read_leb128_u32()?;
+        names.push(IndirectNaming { index, names: read_name_map(reader)? });
+    }
+    Ok(names.into_boxed_slice())
+}
+
+pub struct NameSectionIterator<'a> {
+    reader: NameSectionReader<'a>,
+    error: bool,
+}
+
+impl<'a> Iterator for NameSectionIterator<'a> {
+    type Item = Result<Name<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reader.eof() || self.error {
+            None
+        } else {
+            let result = self.reader.read();
+            self.error = result.is_err();
+            Some(result)
+        }
+    }
+}
+
+impl<'a> IntoIterator for NameSectionReader<'a> {
+    type Item = Result<Name<'a>>;
+    type IntoIter = NameSectionIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        NameSectionIterator { reader: self, error: false }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoder::{BinaryWriter, CustomSectionEncoder, ModuleEncoder};
+    use crate::{Parser, Chunk, SectionReader, NameSectionReader, NameReaderError, Name, Naming, IndirectNaming};
+    use crate::readers::binary::BinaryReaderError;
+
+    fn subsection(writer: &mut BinaryWriter, id: u8, contents: &BinaryWriter) {
+        writer.write_byte(id);
+        writer.write_bytes_vec(contents.as_slice());
+    }
+
+    fn name_map(names: &[(u32, &str)]) -> BinaryWriter {
+        let mut writer = BinaryWriter::new();
+        writer.write_leb128_u32(names.len() as u32);
+        for (index, name) in names {
+            writer.write_leb128_u32(*index);
+            writer.write_string(name);
+        }
+        writer
+    }
+
+    /// Wraps `data` in a `name` section and runs `test` on a reader over it.
+    fn read_name_section(data: &[u8], test: impl FnOnce(NameSectionReader)) {
+        let mut module = ModuleEncoder::new();
+        module.section(&CustomSectionEncoder::new("name", data));
+        let module = module.finish();
+        let mut parser = Parser::new();
+        let (consumed, _) = parser.parse(&module, true).unwrap();
+        match parser.parse(&module[consumed..], true).unwrap() {
+            (_, Chunk::Section(SectionReader::Custom(reader))) => test(reader.get_name_section_reader().unwrap()),
+            _ => panic!("expected a custom section"),
+        }
+    }
+
+    #[test]
+    fn subsections() {
+        let mut data = BinaryWriter::new();
+        let mut module_name = BinaryWriter::new();
+        module_name.write_string("demo");
+        subsection(&mut data, 0, &module_name);
+        subsection(&mut data, 1, &name_map(&[(0, "main"), (3, "helper")]));
+        let mut locals = name_map(&[(0, "x"), (1, "y")]);
+        let mut local_names = BinaryWriter::new();
+        local_names.write_leb128_u32(1);
+        local_names.write_leb128_u32(3);
+        local_names.write_bytes(locals.as_slice());
+        subsection(&mut data, 2, &local_names);
+        subsection(&mut data, 7, &name_map(&[(0, "counter")]));
+        locals = BinaryWriter::new();
+        locals.write_bytes(&[1, 2, 3]);
+        subsection(&mut data, 12, &locals);
+
+        read_name_section(data.as_slice(), |reader| {
+            let names = reader.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(names, vec![
+                Name::Module("demo"),
+                Name::Function(vec![Naming { index: 0, name: "main" }, Naming { index: 3, name: "helper" }].into_boxed_slice()),
+                Name::Local(vec![IndirectNaming {
+                    index: 3,
+                    names: vec![Naming { index: 0, name: "x" }, Naming { index: 1, name: "y" }].into_boxed_slice(),
+                }].into_boxed_slice()),
+                Name::Global(vec![Naming { index: 0, name: "counter" }].into_boxed_slice()),
+                Name::Unknown { id: 12, data: &[1, 2, 3] },
+            ]);
+        });
+    }
+
+    #[test]
+    fn malformed_subsections() {
+        // The name section data starts at offset 15: preamble, id, size and "name".
+        let mut data = BinaryWriter::new();
+        subsection(&mut data, 1, &name_map(&[(0, "a")]));
+        subsection(&mut data, 1, &name_map(&[(1, "b")]));
+        read_name_section(data.as_slice(), |mut reader| {
+            reader.read().unwrap();
+            assert_eq!(reader.read(), Err(NameReaderError::OutOfOrderSubsection(21)));
+        });
+
+        let mut data = BinaryWriter::new();
+        let mut contents = name_map(&[(0, "a")]);
+        contents.write_byte(0);
+        subsection(&mut data, 1, &contents);
+        read_name_section(data.as_slice(), |mut reader| {
+            assert_eq!(reader.read(), Err(NameReaderError::SubsectionSizeMismatch(15)));
+        });
+
+        let mut data = BinaryWriter::new();
+        data.write_byte(1);
+        data.write_leb128_u32(10);
+        data.write_bytes(&[0]);
+        read_name_section(data.as_slice(), |mut reader| {
+            assert_eq!(reader.read(), Err(NameReaderError::BinaryReaderError(BinaryReaderError::UnexpectedEof(17))));
+        });
+
+        // A huge declared count runs out of bytes instead of allocating for it.
+        let mut data = BinaryWriter::new();
+        let mut contents = BinaryWriter::new();
+        contents.write_leb128_u32(u32::MAX);
+        subsection(&mut data, 1, &contents);
+        read_name_section(data.as_slice(), |mut reader| {
+            assert_eq!(reader.read(), Err(NameReaderError::BinaryReaderError(BinaryReaderError::UnexpectedEof(22))));
+        });
+    }
+}
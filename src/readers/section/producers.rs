@@ -0,0 +1,131 @@
+use crate::readers::binary::{BinaryReader, BinaryReaderError};
+use crate::readers::binary::Result as BinaryReaderResult;
+use std::{error, fmt, result};
+use crate::types::{ProducersField, ProducersFieldValue};
+use crate::readers::common::{SectionReader, SectionItemIterator, SectionItemWithOffsetIterator};
+
+const FIELD_NAMES: [&str; 3] = ["language", "processed-by", "sdk"];
+
+/// Reads the fields of a `producers` custom section, which records the
+/// languages and tools that produced the module.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ProducersSectionReader<'a> {
+    reader: BinaryReader<'a>,
+    count: u32,
+    seen_fields: [bool; 3],
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum ProducersReaderError {
+    BinaryReaderError(BinaryReaderError),
+    UnknownField(usize),
+    DuplicateField(usize),
+}
+
+impl ProducersReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            ProducersReaderError::BinaryReaderError(e) => e.offset(),
+            ProducersReaderError::UnknownField(offset) => *offset,
+            ProducersReaderError::DuplicateField(offset) => *offset,
+        }
+    }
+}
+
+impl fmt::Display for ProducersReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProducersReaderError::BinaryReaderError(_) => write!(f, "malformed producers section"),
+            ProducersReaderError::UnknownField(offset) => write!(f, "unknown producers field at offset {}", offset),
+            ProducersReaderError::DuplicateField(offset) => write!(f, "duplicate producers field at offset {}", offset),
+        }
+    }
+}
+
+impl error::Error for ProducersReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ProducersReaderError::BinaryReaderError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<BinaryReaderError> for ProducersReaderError {
+    fn from(e: BinaryReaderError) -> Self {
+        ProducersReaderError::BinaryReaderError(e)
+    }
+}
+
+pub type Result<T, E = ProducersReaderError> = result::Result<T, E>;
+
+impl<'a> ProducersSectionReader<'a> {
+    /// Creates a reader over the data of a `producers` section, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<ProducersSectionReader<'a>> {
+        let mut reader = BinaryReader::new_with_offset(buffer, offset);
+        let count = reader.read_leb128_u32()?;
+        Ok(ProducersSectionReader { reader, count, seen_fields: [false; 3] })
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    pub fn into_iter_with_offsets(self) -> SectionItemWithOffsetIterator<ProducersSectionReader<'a>> {
+        SectionItemWithOffsetIterator::new(self)
+    }
+
+    /// Reads the next field. Each of `language`, `processed-by` and `sdk` may
+    /// appear at most once.
+    pub fn read<'b>(&mut self) -> Result<ProducersField<'b>>
+        where 'a: 'b
+    {
+        let offset = self.reader.get_original_position();
+        let name = self.reader.read_string()?;
+        let field = FIELD_NAMES.iter()
+            .position(|field_name| *field_name == name)
+            .ok_or(ProducersReaderError::UnknownField(offset))?;
+        if self.seen_fields[field] {
+            return Err(ProducersReaderError::DuplicateField(offset));
+        }
+        self.seen_fields[field] = true;
+        let len = self.reader.read_leb128_u32()?;
+        let mut values = Vec::new();
+        for _ in 0..len {
+            let name = self.reader.read_string()?;
+            let version = self.reader.read_string()?;
+            values.push(ProducersFieldValue { name, version });
+        }
+        Ok(ProducersField { name, values: values.into_boxed_slice() })
+    }
+}
+
+impl<'a> SectionReader for ProducersSectionReader<'a> {
+    type Item = ProducersField<'a>;
+    type Error = ProducersReaderError;
+
+    fn read(&mut self) -> Result<Self::Item, Self::Error> {
+        self.read()
+    }
+
+    fn get_count(&self) -> u32 {
+        self.get_count()
+    }
+
+    fn get_original_position(&self) -> usize {
+        self.get_original_position()
+    }
+}
+
+impl<'a> IntoIterator for ProducersSectionReader<'a> {
+    type Item = Result<ProducersField<'a>>;
+    type IntoIter = SectionItemIterator<ProducersSectionReader<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SectionItemIterator::new(self)
+    }
+}
@@ -1,18 +1,44 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use crate::readers::binary::Result as BinaryReaderResult;
+use std::{error, fmt};
 use crate::types::FuncIndex;
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct StartSectionReader<'a> {
     reader: BinaryReader<'a>,
     func_index: FuncIndex,
+    offset: usize,
 }
 
-#[derive(Debug)]
+#[derive(PartialEq, Eq, Debug)]
 pub enum StartReaderError {
     BinaryReaderError(BinaryReaderError),
 }
 
+impl StartReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            StartReaderError::BinaryReaderError(e) => e.offset(),
+        }
+    }
+}
+
+impl fmt::Display for StartReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartReaderError::BinaryReaderError(_) => write!(f, "malformed start section"),
+        }
+    }
+}
+
+impl error::Error for StartReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            StartReaderError::BinaryReaderError(e) => Some(e),
+        }
+    }
+}
+
 impl From<BinaryReaderError> for StartReaderError {
     fn from(e: BinaryReaderError) -> Self {
         StartReaderError::BinaryReaderError(e)
@@ -20,13 +46,29 @@ impl From<BinaryReaderError> for StartReaderError {
 }
 
 impl<'a> StartSectionReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<StartSectionReader<'a>> {
-        let mut reader = BinaryReader::new(buffer);
+    /// Creates a reader over a whole section, id and size included, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<StartSectionReader<'a>> {
+        let mut reader = BinaryReader::new_for_section(buffer, offset)?;
+        let offset = reader.get_original_position();
         let index = reader.read_leb128_u32()?;
-        Ok(StartSectionReader { reader, func_index: FuncIndex(index)})
+        Ok(StartSectionReader { reader, func_index: FuncIndex(index), offset })
+    }
+
+    pub fn get_original_position(&self) -> usize {
+        self.offset
     }
 
     pub fn get_func_index(&self) -> FuncIndex {
         self.func_index
     }
+
+    /// Returns the absolute offset of the section, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        self.reader.get_original_offset()
+    }
+
+    /// Returns the bytes of the whole section exactly as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
 }
@@ -1,8 +1,8 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use crate::readers::binary::Result as BinaryReaderResult;
-use std::result;
+use std::{error, fmt, result};
 use crate::types::TableType;
-use crate::readers::common::{SectionReader, SectionItemIterator};
+use crate::readers::common::{SectionReader, SectionItemIterator, SectionItemWithOffsetIterator};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct TableSectionReader<'a> {
@@ -15,6 +15,30 @@ pub enum TableReaderError {
     BinaryReaderError(BinaryReaderError),
 }
 
+impl TableReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            TableReaderError::BinaryReaderError(e) => e.offset(),
+        }
+    }
+}
+
+impl fmt::Display for TableReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableReaderError::BinaryReaderError(_) => write!(f, "malformed table section"),
+        }
+    }
+}
+
+impl error::Error for TableReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            TableReaderError::BinaryReaderError(e) => Some(e),
+        }
+    }
+}
+
 impl From<BinaryReaderError> for TableReaderError {
     fn from(e: BinaryReaderError) -> Self {
         TableReaderError::BinaryReaderError(e)
@@ -24,8 +48,9 @@ impl From<BinaryReaderError> for TableReaderError {
 pub type Result<T, E = TableReaderError> = result::Result<T, E>;
 
 impl<'a> TableSectionReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<TableSectionReader<'a>> {
-        let mut reader = BinaryReader::new(buffer);
+    /// Creates a reader over a whole section, id and size included, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<TableSectionReader<'a>> {
+        let mut reader = BinaryReader::new_for_section(buffer, offset)?;
         let count = reader.read_leb128_u32()?;
         Ok(TableSectionReader { reader, count })
     }
@@ -34,6 +59,24 @@ impl<'a> TableSectionReader<'a> {
         self.count
     }
 
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    /// Returns the absolute offset of the section, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        self.reader.get_original_offset()
+    }
+
+    /// Returns the bytes of the whole section exactly as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
+
+    pub fn into_iter_with_offsets(self) -> SectionItemWithOffsetIterator<TableSectionReader<'a>> {
+        SectionItemWithOffsetIterator::new(self)
+    }
+
     pub fn read(&mut self) -> Result<TableType> {
         Ok(self.reader.read_table_type()?)
     }
@@ -50,6 +93,10 @@ impl<'a> SectionReader for TableSectionReader<'a> {
     fn get_count(&self) -> u32 {
         self.get_count()
     }
+
+    fn get_original_position(&self) -> usize {
+        self.get_original_position()
+    }
 }
 
 impl<'a> IntoIterator for TableSectionReader<'a> {
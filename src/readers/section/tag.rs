@@ -0,0 +1,109 @@
+use crate::readers::binary::{BinaryReader, BinaryReaderError};
+use crate::readers::binary::Result as BinaryReaderResult;
+use std::{error, fmt, result};
+use crate::types::TagType;
+use crate::readers::common::{SectionReader, SectionItemIterator, SectionItemWithOffsetIterator};
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct TagSectionReader<'a> {
+    reader: BinaryReader<'a>,
+    count: u32,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TagReaderError {
+    BinaryReaderError(BinaryReaderError),
+}
+
+impl TagReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            TagReaderError::BinaryReaderError(e) => e.offset(),
+        }
+    }
+}
+
+impl fmt::Display for TagReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TagReaderError::BinaryReaderError(_) => write!(f, "malformed tag section"),
+        }
+    }
+}
+
+impl error::Error for TagReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            TagReaderError::BinaryReaderError(e) => Some(e),
+        }
+    }
+}
+
+impl From<BinaryReaderError> for TagReaderError {
+    fn from(e: BinaryReaderError) -> Self {
+        TagReaderError::BinaryReaderError(e)
+    }
+}
+
+pub type Result<T, E = TagReaderError> = result::Result<T, E>;
+
+impl<'a> TagSectionReader<'a> {
+    /// Creates a reader over a whole section, id and size included, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<TagSectionReader<'a>> {
+        let mut reader = BinaryReader::new_for_section(buffer, offset)?;
+        let count = reader.read_leb128_u32()?;
+        Ok(TagSectionReader { reader, count })
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    /// Returns the absolute offset of the section, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        self.reader.get_original_offset()
+    }
+
+    /// Returns the bytes of the whole section exactly as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
+
+    pub fn into_iter_with_offsets(self) -> SectionItemWithOffsetIterator<TagSectionReader<'a>> {
+        SectionItemWithOffsetIterator::new(self)
+    }
+
+    pub fn read(&mut self) -> Result<TagType> {
+        Ok(self.reader.read_tag_type()?)
+    }
+}
+
+impl<'a> SectionReader for TagSectionReader<'a> {
+    type Item = TagType;
+    type Error = TagReaderError;
+
+    fn read(&mut self) -> Result<Self::Item, Self::Error> {
+        self.read()
+    }
+
+    fn get_count(&self) -> u32 {
+        self.get_count()
+    }
+
+    fn get_original_position(&self) -> usize {
+        self.get_original_position()
+    }
+}
+
+impl<'a> IntoIterator for TagSectionReader<'a> {
+    type Item = Result<TagType>;
+    type IntoIter = SectionItemIterator<TagSectionReader<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SectionItemIterator::new(self)
+    }
+}
@@ -0,0 +1,115 @@
+use crate::readers::binary::{BinaryReader, BinaryReaderError};
+use crate::readers::binary::Result as BinaryReaderResult;
+use std::{error, fmt, result};
+use crate::types::{TargetFeature, FeaturePrefix};
+use crate::readers::common::{SectionReader, SectionItemIterator, SectionItemWithOffsetIterator};
+
+/// Reads the entries of a `target_features` custom section, which lists the
+/// features the module was compiled with or against.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct TargetFeaturesSectionReader<'a> {
+    reader: BinaryReader<'a>,
+    count: u32,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum TargetFeaturesReaderError {
+    BinaryReaderError(BinaryReaderError),
+    InvalidPrefix(usize),
+}
+
+impl TargetFeaturesReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            TargetFeaturesReaderError::BinaryReaderError(e) => e.offset(),
+            TargetFeaturesReaderError::InvalidPrefix(offset) => *offset,
+        }
+    }
+}
+
+impl fmt::Display for TargetFeaturesReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetFeaturesReaderError::BinaryReaderError(_) => write!(f, "malformed target_features section"),
+            TargetFeaturesReaderError::InvalidPrefix(offset) => write!(f, "invalid target feature prefix at offset {}", offset),
+        }
+    }
+}
+
+impl error::Error for TargetFeaturesReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            TargetFeaturesReaderError::BinaryReaderError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<BinaryReaderError> for TargetFeaturesReaderError {
+    fn from(e: BinaryReaderError) -> Self {
+        TargetFeaturesReaderError::BinaryReaderError(e)
+    }
+}
+
+pub type Result<T, E = TargetFeaturesReaderError> = result::Result<T, E>;
+
+impl<'a> TargetFeaturesSectionReader<'a> {
+    /// Creates a reader over the data of a `target_features` section, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<TargetFeaturesSectionReader<'a>> {
+        let mut reader = BinaryReader::new_with_offset(buffer, offset);
+        let count = reader.read_leb128_u32()?;
+        Ok(TargetFeaturesSectionReader { reader, count })
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    pub fn into_iter_with_offsets(self) -> SectionItemWithOffsetIterator<TargetFeaturesSectionReader<'a>> {
+        SectionItemWithOffsetIterator::new(self)
+    }
+
+    pub fn read<'b>(&mut self) -> Result<TargetFeature<'b>>
+        where 'a: 'b
+    {
+        let offset = self.reader.get_original_position();
+        let prefix = match self.reader.read_byte()? {
+            b'+' => FeaturePrefix::Used,
+            b'-' => FeaturePrefix::Disallowed,
+            b'=' => FeaturePrefix::Required,
+            _ => return Err(TargetFeaturesReaderError::InvalidPrefix(offset)),
+        };
+        let name = self.reader.read_string()?;
+        Ok(TargetFeature { prefix, name })
+    }
+}
+
+impl<'a> SectionReader for TargetFeaturesSectionReader<'a> {
+    type Item = TargetFeature<'a>;
+    type Error = TargetFeaturesReaderError;
+
+    fn read(&mut self) -> Result<Self::Item, Self::Error> {
+        self.read()
+    }
+
+    fn get_count(&self) -> u32 {
+        self.get_count()
+    }
+
+    fn get_original_position(&self) -> usize {
+        self.get_original_position()
+    }
+}
+
+impl<'a> IntoIterator for TargetFeaturesSectionReader<'a> {
+    type Item = Result<TargetFeature<'a>>;
+    type IntoIter = SectionItemIterator<TargetFeaturesSectionReader<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SectionItemIterator::new(self)
+    }
+}
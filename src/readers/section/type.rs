@@ -1,8 +1,8 @@
 use crate::readers::binary::{BinaryReader, BinaryReaderError};
 use crate::types::{FunctionType, ValueType};
 use crate::readers::binary::Result as BinaryReaderResult;
-use std::result;
-use crate::readers::common::{SectionReader, SectionItemIterator};
+use std::{error, fmt, result};
+use crate::readers::common::{SectionReader, SectionItemIterator, SectionItemWithOffsetIterator};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct TypeSectionReader<'a> {
@@ -13,7 +13,34 @@ pub struct TypeSectionReader<'a> {
 #[derive(PartialEq, Eq, Debug)]
 pub enum TypeReaderError {
     BinaryReaderError(BinaryReaderError),
-    InvalidLeadingByte,
+    InvalidLeadingByte(usize),
+}
+
+impl TypeReaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            TypeReaderError::BinaryReaderError(e) => e.offset(),
+            TypeReaderError::InvalidLeadingByte(offset) => *offset,
+        }
+    }
+}
+
+impl fmt::Display for TypeReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeReaderError::BinaryReaderError(_) => write!(f, "malformed type section"),
+            TypeReaderError::InvalidLeadingByte(offset) => write!(f, "invalid function type form at offset {}", offset),
+        }
+    }
+}
+
+impl error::Error for TypeReaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            TypeReaderError::BinaryReaderError(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<BinaryReaderError> for TypeReaderError {
@@ -25,8 +52,9 @@ impl From<BinaryReaderError> for TypeReaderError {
 pub type Result<T, E = TypeReaderError> = result::Result<T, E>;
 
 impl<'a> TypeSectionReader<'a> {
-    pub(crate) fn new(buffer: &'a [u8]) -> BinaryReaderResult<TypeSectionReader<'a>> {
-        let mut reader = BinaryReader::new(buffer);
+    /// Creates a reader over a whole section, id and size included, starting at `offset` in the module.
+    pub(crate) fn new(buffer: &'a [u8], offset: usize) -> BinaryReaderResult<TypeSectionReader<'a>> {
+        let mut reader = BinaryReader::new_for_section(buffer, offset)?;
         let count = reader.read_leb128_u32()?;
         Ok(TypeSectionReader { reader, count })
     }
@@ -35,11 +63,30 @@ impl<'a> TypeSectionReader<'a> {
         self.count
     }
 
+    pub fn get_original_position(&self) -> usize {
+        self.reader.get_original_position()
+    }
+
+    /// Returns the absolute offset of the section, at its id.
+    pub fn get_section_offset(&self) -> usize {
+        self.reader.get_original_offset()
+    }
+
+    /// Returns the bytes of the whole section exactly as they appear in the module.
+    pub fn get_raw_bytes(&self) -> &'a [u8] {
+        self.reader.get_buffer()
+    }
+
+    pub fn into_iter_with_offsets(self) -> SectionItemWithOffsetIterator<TypeSectionReader<'a>> {
+        SectionItemWithOffsetIterator::new(self)
+    }
+
     pub fn read(&mut self) -> Result<FunctionType> {
+        let offset = self.reader.get_original_position();
         let byte = self.reader.read_byte()?;
         match byte {
             0x60 => self.read_func_type(),
-            _ => Err(TypeReaderError::InvalidLeadingByte),
+            _ => Err(TypeReaderError::InvalidLeadingByte(offset)),
         }
     }
 
@@ -70,6 +117,10 @@ impl<'a> SectionReader for TypeSectionReader<'a> {
     fn get_count(&self) -> u32 {
         self.get_count()
     }
+
+    fn get_original_position(&self) -> usize {
+        self.get_original_position()
+    }
 }
 
 impl<'a> IntoIterator for TypeSectionReader<'a> {
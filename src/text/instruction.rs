@@ -0,0 +1,1249 @@
+use std::convert::TryFrom;
+use crate::encoder::FunctionBodyEncoder;
+use crate::types::{Instruction, MemoryArgument, BlockType, LabelIndex, TableIndex, MemoryIndex, ValueType, CatchClause};
+use crate::types::Instruction::*;
+use crate::text::parser::{TextParser, Result, Scope, Space};
+use crate::text::lexer::TokenKind;
+use crate::text::number::{parse_u32, parse_u64, parse_i8, parse_i16, parse_i32, parse_i64, parse_f32, parse_f64};
+use crate::text::TextError;
+
+/// Instructions without immediates, by their text format name.
+pub(crate) const PLAIN_INSTRUCTIONS: &[(&str, Instruction<'static>)] = &[
+    ("unreachable", Unreachable),
+    ("nop", Nop),
+    ("return", Return),
+    ("throw_ref", ThrowRef),
+    ("drop", Drop),
+    ("i32.eqz", I32Eqz),
+    ("i32.eq", I32Eq),
+    ("i32.ne", I32Ne),
+    ("i32.lt_s", I32Lts),
+    ("i32.lt_u", I32Ltu),
+    ("i32.gt_s", I32Gts),
+    ("i32.gt_u", I32Gtu),
+    ("i32.le_s", I32Les),
+    ("i32.le_u", I32Leu),
+    ("i32.ge_s", I32Ges),
+    ("i32.ge_u", I32Geu),
+    ("i64.eqz", I64Eqz),
+    ("i64.eq", I64Eq),
+    ("i64.ne", I64Ne),
+    ("i64.lt_s", I64Lts),
+    ("i64.lt_u", I64Ltu),
+    ("i64.gt_s", I64Gts),
+    ("i64.gt_u", I64Gtu),
+    ("i64.le_s", I64Les),
+    ("i64.le_u", I64Leu),
+    ("i64.ge_s", I64Ges),
+    ("i64.ge_u", I64Geu),
+    ("f32.eq", F32Eq),
+    ("f32.ne", F32Ne),
+    ("f32.lt", F32Lt),
+    ("f32.gt", F32Gt),
+    ("f32.le", F32Le),
+    ("f32.ge", F32Ge),
+    ("f64.eq", F64Eq),
+    ("f64.ne", F64Ne),
+    ("f64.lt", F64Lt),
+    ("f64.gt", F64Gt),
+    ("f64.le", F64Le),
+    ("f64.ge", F64Ge),
+    ("i32.clz", I32Clz),
+    ("i32.ctz", I32Ctz),
+    ("i32.popcnt", I32Popcnt),
+    ("i32.add", I32Add),
+    ("i32.sub", I32Sub),
+    ("i32.mul", I32Mul),
+    ("i32.div_s", I32Divs),
+    ("i32.div_u", I32Divu),
+    ("i32.rem_s", I32Rems),
+    ("i32.rem_u", I32Remu),
+    ("i32.and", I32And),
+    ("i32.or", I32Or),
+    ("i32.xor", I32Xor),
+    ("i32.shl", I32Shl),
+    ("i32.shr_s", I32Shrs),
+    ("i32.shr_u", I32Shru),
+    ("i32.rotl", I32Rotl),
+    ("i32.rotr", I32Rotr),
+    ("i64.clz", I64Clz),
+    ("i64.ctz", I64Ctz),
+    ("i64.popcnt", I64Popcnt),
+    ("i64.add", I64Add),
+    ("i64.sub", I64Sub),
+    ("i64.mul", I64Mul),
+    ("i64.div_s", I64Divs),
+    ("i64.div_u", I64Divu),
+    ("i64.rem_s", I64Rems),
+    ("i64.rem_u", I64Remu),
+    ("i64.and", I64And),
+    ("i64.or", I64Or),
+    ("i64.xor", I64Xor),
+    ("i64.shl", I64Shl),
+    ("i64.shr_s", I64Shrs),
+    ("i64.shr_u", I64Shru),
+    ("i64.rotl", I64Rotl),
+    ("i64.rotr", I64Rotr),
+    ("f32.abs", F32Abs),
+    ("f32.neg", F32Neg),
+    ("f32.ceil", F32Ceil),
+    ("f32.floor", F32Floor),
+    ("f32.trunc", F32Trunc),
+    ("f32.nearest", F32Nearest),
+    ("f32.sqrt", F32Sqrt),
+    ("f32.add", F32Add),
+    ("f32.sub", F32Sub),
+    ("f32.mul", F32Mul),
+    ("f32.div", F32Div),
+    ("f32.min", F32Min),
+    ("f32.max", F32Max),
+    ("f32.copysign", F32Copysign),
+    ("f64.abs", F64Abs),
+    ("f64.neg", F64Neg),
+    ("f64.ceil", F64Ceil),
+    ("f64.floor", F64Floor),
+    ("f64.trunc", F64Trunc),
+    ("f64.nearest", F64Nearest),
+    ("f64.sqrt", F64Sqrt),
+    ("f64.add", F64Add),
+    ("f64.sub", F64Sub),
+    ("f64.mul", F64Mul),
+    ("f64.div", F64Div),
+    ("f64.min", F64Min),
+    ("f64.max", F64Max),
+    ("f64.copysign", F64Copysign),
+    ("i32.wrap_i64", I32WrapI64),
+    ("i32.trunc_f32_s", I32TruncF32s),
+    ("i32.trunc_f32_u", I32TruncF32u),
+    ("i32.trunc_f64_s", I32TruncF64s),
+    ("i32.trunc_f64_u", I32TruncF64u),
+    ("i64.extend_i32_s", I64ExtendI32s),
+    ("i64.extend_i32_u", I64ExtendI32u),
+    ("i64.trunc_f32_s", I64TruncF32s),
+    ("i64.trunc_f32_u", I64TruncF32u),
+    ("i64.trunc_f64_s", I64TruncF64s),
+    ("i64.trunc_f64_u", I64TruncF64u),
+    ("f32.convert_i32_s", F32ConvertI32s),
+    ("f32.convert_i32_u", F32ConvertI32u),
+    ("f32.convert_i64_s", F32ConvertI64s),
+    ("f32.convert_i64_u", F32ConvertI64u),
+    ("f32.demote_f64", F32DemoteF64),
+    ("f64.convert_i32_s", F64ConvertI32s),
+    ("f64.convert_i32_u", F64ConvertI32u),
+    ("f64.convert_i64_s", F64ConvertI64s),
+    ("f64.convert_i64_u", F64ConvertI64u),
+    ("f64.promote_f32", F64PromoteF32),
+    ("i32.reinterpret_f32", I32ReinterpretF32),
+    ("i64.reinterpret_f64", I64ReinterpretF64),
+    ("f32.reinterpret_i32", F32ReinterpretI32),
+    ("f64.reinterpret_i64", F64ReinterpretI64),
+    ("i32.extend8_s", I32Extend8s),
+    ("i32.extend16_s", I32Extend16s),
+    ("i64.extend8_s", I64Extend8s),
+    ("i64.extend16_s", I64Extend16s),
+    ("i64.extend32_s", I64Extend32s),
+    ("ref.is_null", RefIsNull),
+    ("i32.trunc_sat_f32_s", I32TruncSatF32s),
+    ("i32.trunc_sat_f32_u", I32TruncSatF32u),
+    ("i32.trunc_sat_f64_s", I32TruncSatF64s),
+    ("i32.trunc_sat_f64_u", I32TruncSatF64u),
+    ("i64.trunc_sat_f32_s", I64TruncSatF32s),
+    ("i64.trunc_sat_f32_u", I64TruncSatF32u),
+    ("i64.trunc_sat_f64_s", I64TruncSatF64s),
+    ("i64.trunc_sat_f64_u", I64TruncSatF64u),
+    ("i8x16.swizzle", I8x16Swizzle),
+    ("i8x16.splat", I8x16Splat),
+    ("i16x8.splat", I16x8Splat),
+    ("i32x4.splat", I32x4Splat),
+    ("i64x2.splat", I64x2Splat),
+    ("f32x4.splat", F32x4Splat),
+    ("f64x2.splat", F64x2Splat),
+    ("i8x16.eq", I8x16Eq),
+    ("i8x16.ne", I8x16Ne),
+    ("i8x16.lt_s", I8x16Lts),
+    ("i8x16.lt_u", I8x16Ltu),
+    ("i8x16.gt_s", I8x16Gts),
+    ("i8x16.gt_u", I8x16Gtu),
+    ("i8x16.le_s", I8x16Les),
+    ("i8x16.le_u", I8x16Leu),
+    ("i8x16.ge_s", I8x16Ges),
+    ("i8x16.ge_u", I8x16Geu),
+    ("i16x8.eq", I16x8Eq),
+    ("i16x8.ne", I16x8Ne),
+    ("i16x8.lt_s", I16x8Lts),
+    ("i16x8.lt_u", I16x8Ltu),
+    ("i16x8.gt_s", I16x8Gts),
+    ("i16x8.gt_u", I16x8Gtu),
+    ("i16x8.le_s", I16x8Les),
+    ("i16x8.le_u", I16x8Leu),
+    ("i16x8.ge_s", I16x8Ges),
+    ("i16x8.ge_u", I16x8Geu),
+    ("i32x4.eq", I32x4Eq),
+    ("i32x4.ne", I32x4Ne),
+    ("i32x4.lt_s", I32x4Lts),
+    ("i32x4.lt_u", I32x4Ltu),
+    ("i32x4.gt_s", I32x4Gts),
+    ("i32x4.gt_u", I32x4Gtu),
+    ("i32x4.le_s", I32x4Les),
+    ("i32x4.le_u", I32x4Leu),
+    ("i32x4.ge_s", I32x4Ges),
+    ("i32x4.ge_u", I32x4Geu),
+    ("f32x4.eq", F32x4Eq),
+    ("f32x4.ne", F32x4Ne),
+    ("f32x4.lt", F32x4Lt),
+    ("f32x4.gt", F32x4Gt),
+    ("f32x4.le", F32x4Le),
+    ("f32x4.ge", F32x4Ge),
+    ("f64x2.eq", F64x2Eq),
+    ("f64x2.ne", F64x2Ne),
+    ("f64x2.lt", F64x2Lt),
+    ("f64x2.gt", F64x2Gt),
+    ("f64x2.le", F64x2Le),
+    ("f64x2.ge", F64x2Ge),
+    ("v128.not", V128Not),
+    ("v128.and", V128And),
+    ("v128.andnot", V128Andnot),
+    ("v128.or", V128Or),
+    ("v128.xor", V128Xor),
+    ("v128.bitselect", V128Bitselect),
+    ("v128.any_true", V128AnyTrue),
+    ("f32x4.demote_f64x2_zero", F32x4DemoteF64x2Zero),
+    ("f64x2.promote_low_f32x4", F64x2PromoteLowF32x4),
+    ("i8x16.abs", I8x16Abs),
+    ("i8x16.neg", I8x16Neg),
+    ("i8x16.popcnt", I8x16Popcnt),
+    ("i8x16.all_true", I8x16AllTrue),
+    ("i8x16.bitmask", I8x16Bitmask),
+    ("i8x16.narrow_i16x8_s", I8x16NarrowI16x8s),
+    ("i8x16.narrow_i16x8_u", I8x16NarrowI16x8u),
+    ("f32x4.ceil", F32x4Ceil),
+    ("f32x4.floor", F32x4Floor),
+    ("f32x4.trunc", F32x4Trunc),
+    ("f32x4.nearest", F32x4Nearest),
+    ("i8x16.shl", I8x16Shl),
+    ("i8x16.shr_s", I8x16Shrs),
+    ("i8x16.shr_u", I8x16Shru),
+    ("i8x16.add", I8x16Add),
+    ("i8x16.add_sat_s", I8x16AddSats),
+    ("i8x16.add_sat_u", I8x16AddSatu),
+    ("i8x16.sub", I8x16Sub),
+    ("i8x16.sub_sat_s", I8x16SubSats),
+    ("i8x16.sub_sat_u", I8x16SubSatu),
+    ("f64x2.ceil", F64x2Ceil),
+    ("f64x2.floor", F64x2Floor),
+    ("i8x16.min_s", I8x16Mins),
+    ("i8x16.min_u", I8x16Minu),
+    ("i8x16.max_s", I8x16Maxs),
+    ("i8x16.max_u", I8x16Maxu),
+    ("f64x2.trunc", F64x2Trunc),
+    ("i8x16.avgr_u", I8x16Avgru),
+    ("i16x8.extadd_pairwise_i8x16_s", I16x8ExtaddPairwiseI8x16s),
+    ("i16x8.extadd_pairwise_i8x16_u", I16x8ExtaddPairwiseI8x16u),
+    ("i32x4.extadd_pairwise_i16x8_s", I32x4ExtaddPairwiseI16x8s),
+    ("i32x4.extadd_pairwise_i16x8_u", I32x4ExtaddPairwiseI16x8u),
+    ("i16x8.abs", I16x8Abs),
+    ("i16x8.neg", I16x8Neg),
+    ("i16x8.q15mulr_sat_s", I16x8Q15mulrSats),
+    ("i16x8.all_true", I16x8AllTrue),
+    ("i16x8.bitmask", I16x8Bitmask),
+    ("i16x8.narrow_i32x4_s", I16x8NarrowI32x4s),
+    ("i16x8.narrow_i32x4_u", I16x8NarrowI32x4u),
+    ("i16x8.extend_low_i8x16_s", I16x8ExtendLowI8x16s),
+    ("i16x8.extend_high_i8x16_s", I16x8ExtendHighI8x16s),
+    ("i16x8.extend_low_i8x16_u", I16x8ExtendLowI8x16u),
+    ("i16x8.extend_high_i8x16_u", I16x8ExtendHighI8x16u),
+    ("i16x8.shl", I16x8Shl),
+    ("i16x8.shr_s", I16x8Shrs),
+    ("i16x8.shr_u", I16x8Shru),
+    ("i16x8.add", I16x8Add),
+    ("i16x8.add_sat_s", I16x8AddSats),
+    ("i16x8.add_sat_u", I16x8AddSatu),
+    ("i16x8.sub", I16x8Sub),
+    ("i16x8.sub_sat_s", I16x8SubSats),
+    ("i16x8.sub_sat_u", I16x8SubSatu),
+    ("f64x2.nearest", F64x2Nearest),
+    ("i16x8.mul", I16x8Mul),
+    ("i16x8.min_s", I16x8Mins),
+    ("i16x8.min_u", I16x8Minu),
+    ("i16x8.max_s", I16x8Maxs),
+    ("i16x8.max_u", I16x8Maxu),
+    ("i16x8.avgr_u", I16x8Avgru),
+    ("i16x8.extmul_low_i8x16_s", I16x8ExtmulLowI8x16s),
+    ("i16x8.extmul_high_i8x16_s", I16x8ExtmulHighI8x16s),
+    ("i16x8.extmul_low_i8x16_u", I16x8ExtmulLowI8x16u),
+    ("i16x8.extmul_high_i8x16_u", I16x8ExtmulHighI8x16u),
+    ("i32x4.abs", I32x4Abs),
+    ("i32x4.neg", I32x4Neg),
+    ("i32x4.all_true", I32x4AllTrue),
+    ("i32x4.bitmask", I32x4Bitmask),
+    ("i32x4.extend_low_i16x8_s", I32x4ExtendLowI16x8s),
+    ("i32x4.extend_high_i16x8_s", I32x4ExtendHighI16x8s),
+    ("i32x4.extend_low_i16x8_u", I32x4ExtendLowI16x8u),
+    ("i32x4.extend_high_i16x8_u", I32x4ExtendHighI16x8u),
+    ("i32x4.shl", I32x4Shl),
+    ("i32x4.shr_s", I32x4Shrs),
+    ("i32x4.shr_u", I32x4Shru),
+    ("i32x4.add", I32x4Add),
+    ("i32x4.sub", I32x4Sub),
+    ("i32x4.mul", I32x4Mul),
+    ("i32x4.min_s", I32x4Mins),
+    ("i32x4.min_u", I32x4Minu),
+    ("i32x4.max_s", I32x4Maxs),
+    ("i32x4.max_u", I32x4Maxu),
+    ("i32x4.dot_i16x8_s", I32x4DotI16x8s),
+    ("i32x4.extmul_low_i16x8_s", I32x4ExtmulLowI16x8s),
+    ("i32x4.extmul_high_i16x8_s", I32x4ExtmulHighI16x8s),
+    ("i32x4.extmul_low_i16x8_u", I32x4ExtmulLowI16x8u),
+    ("i32x4.extmul_high_i16x8_u", I32x4ExtmulHighI16x8u),
+    ("i64x2.abs", I64x2Abs),
+    ("i64x2.neg", I64x2Neg),
+    ("i64x2.all_true", I64x2AllTrue),
+    ("i64x2.bitmask", I64x2Bitmask),
+    ("i64x2.extend_low_i32x4_s", I64x2ExtendLowI32x4s),
+    ("i64x2.extend_high_i32x4_s", I64x2ExtendHighI32x4s),
+    ("i64x2.extend_low_i32x4_u", I64x2ExtendLowI32x4u),
+    ("i64x2.extend_high_i32x4_u", I64x2ExtendHighI32x4u),
+    ("i64x2.shl", I64x2Shl),
+    ("i64x2.shr_s", I64x2Shrs),
+    ("i64x2.shr_u", I64x2Shru),
+    ("i64x2.add", I64x2Add),
+    ("i64x2.sub", I64x2Sub),
+    ("i64x2.mul", I64x2Mul),
+    ("i64x2.eq", I64x2Eq),
+    ("i64x2.ne", I64x2Ne),
+    ("i64x2.lt_s", I64x2Lts),
+    ("i64x2.gt_s", I64x2Gts),
+    ("i64x2.le_s", I64x2Les),
+    ("i64x2.ge_s", I64x2Ges),
+    ("i64x2.extmul_low_i32x4_s", I64x2ExtmulLowI32x4s),
+    ("i64x2.extmul_high_i32x4_s", I64x2ExtmulHighI32x4s),
+    ("i64x2.extmul_low_i32x4_u", I64x2ExtmulLowI32x4u),
+    ("i64x2.extmul_high_i32x4_u", I64x2ExtmulHighI32x4u),
+    ("f32x4.abs", F32x4Abs),
+    ("f32x4.neg", F32x4Neg),
+    ("f32x4.sqrt", F32x4Sqrt),
+    ("f32x4.add", F32x4Add),
+    ("f32x4.sub", F32x4Sub),
+    ("f32x4.mul", F32x4Mul),
+    ("f32x4.div", F32x4Div),
+    ("f32x4.min", F32x4Min),
+    ("f32x4.max", F32x4Max),
+    ("f32x4.pmin", F32x4Pmin),
+    ("f32x4.pmax", F32x4Pmax),
+    ("f64x2.abs", F64x2Abs),
+    ("f64x2.neg", F64x2Neg),
+    ("f64x2.sqrt", F64x2Sqrt),
+    ("f64x2.add", F64x2Add),
+    ("f64x2.sub", F64x2Sub),
+    ("f64x2.mul", F64x2Mul),
+    ("f64x2.div", F64x2Div),
+    ("f64x2.min", F64x2Min),
+    ("f64x2.max", F64x2Max),
+    ("f64x2.pmin", F64x2Pmin),
+    ("f64x2.pmax", F64x2Pmax),
+    ("i32x4.trunc_sat_f32x4_s", I32x4TruncSatF32x4s),
+    ("i32x4.trunc_sat_f32x4_u", I32x4TruncSatF32x4u),
+    ("f32x4.convert_i32x4_s", F32x4ConvertI32x4s),
+    ("f32x4.convert_i32x4_u", F32x4ConvertI32x4u),
+    ("i32x4.trunc_sat_f64x2_s_zero", I32x4TruncSatF64x2sZero),
+    ("i32x4.trunc_sat_f64x2_u_zero", I32x4TruncSatF64x2uZero),
+    ("f64x2.convert_low_i32x4_s", F64x2ConvertLowI32x4s),
+    ("f64x2.convert_low_i32x4_u", F64x2ConvertLowI32x4u),
+    ("atomic.fence", AtomicFence),
+];
+
+/// Loads and stores by their text format name, with the log2 of their natural alignment.
+pub(crate) const MEMORY_INSTRUCTIONS: &[(&str, u32)] = &[
+    ("i32.load", 2),
+    ("i64.load", 3),
+    ("f32.load", 2),
+    ("f64.load", 3),
+    ("i32.load8_s", 0),
+    ("i32.load8_u", 0),
+    ("i32.load16_s", 1),
+    ("i32.load16_u", 1),
+    ("i64.load8_s", 0),
+    ("i64.load8_u", 0),
+    ("i64.load16_s", 1),
+    ("i64.load16_u", 1),
+    ("i64.load32_s", 2),
+    ("i64.load32_u", 2),
+    ("i32.store", 2),
+    ("i64.store", 3),
+    ("f32.store", 2),
+    ("f64.store", 3),
+    ("i32.store8", 0),
+    ("i32.store16", 1),
+    ("i64.store8", 0),
+    ("i64.store16", 1),
+    ("i64.store32", 2),
+    ("v128.load", 4),
+    ("v128.load8x8_s", 3),
+    ("v128.load8x8_u", 3),
+    ("v128.load16x4_s", 3),
+    ("v128.load16x4_u", 3),
+    ("v128.load32x2_s", 3),
+    ("v128.load32x2_u", 3),
+    ("v128.load8_splat", 0),
+    ("v128.load16_splat", 1),
+    ("v128.load32_splat", 2),
+    ("v128.load64_splat", 3),
+    ("v128.store", 4),
+    ("v128.load32_zero", 2),
+    ("v128.load64_zero", 3),
+    ("memory.atomic.notify", 2),
+    ("memory.atomic.wait32", 2),
+    ("memory.atomic.wait64", 3),
+    ("i32.atomic.load", 2),
+    ("i64.atomic.load", 3),
+    ("i32.atomic.load8_u", 0),
+    ("i32.atomic.load16_u", 1),
+    ("i64.atomic.load8_u", 0),
+    ("i64.atomic.load16_u", 1),
+    ("i64.atomic.load32_u", 2),
+    ("i32.atomic.store", 2),
+    ("i64.atomic.store", 3),
+    ("i32.atomic.store8", 0),
+    ("i32.atomic.store16", 1),
+    ("i64.atomic.store8", 0),
+    ("i64.atomic.store16", 1),
+    ("i64.atomic.store32", 2),
+    ("i32.atomic.rmw.add", 2),
+    ("i64.atomic.rmw.add", 3),
+    ("i32.atomic.rmw8.add_u", 0),
+    ("i32.atomic.rmw16.add_u", 1),
+    ("i64.atomic.rmw8.add_u", 0),
+    ("i64.atomic.rmw16.add_u", 1),
+    ("i64.atomic.rmw32.add_u", 2),
+    ("i32.atomic.rmw.sub", 2),
+    ("i64.atomic.rmw.sub", 3),
+    ("i32.atomic.rmw8.sub_u", 0),
+    ("i32.atomic.rmw16.sub_u", 1),
+    ("i64.atomic.rmw8.sub_u", 0),
+    ("i64.atomic.rmw16.sub_u", 1),
+    ("i64.atomic.rmw32.sub_u", 2),
+    ("i32.atomic.rmw.and", 2),
+    ("i64.atomic.rmw.and", 3),
+    ("i32.atomic.rmw8.and_u", 0),
+    ("i32.atomic.rmw16.and_u", 1),
+    ("i64.atomic.rmw8.and_u", 0),
+    ("i64.atomic.rmw16.and_u", 1),
+    ("i64.atomic.rmw32.and_u", 2),
+    ("i32.atomic.rmw.or", 2),
+    ("i64.atomic.rmw.or", 3),
+    ("i32.atomic.rmw8.or_u", 0),
+    ("i32.atomic.rmw16.or_u", 1),
+    ("i64.atomic.rmw8.or_u", 0),
+    ("i64.atomic.rmw16.or_u", 1),
+    ("i64.atomic.rmw32.or_u", 2),
+    ("i32.atomic.rmw.xor", 2),
+    ("i64.atomic.rmw.xor", 3),
+    ("i32.atomic.rmw8.xor_u", 0),
+    ("i32.atomic.rmw16.xor_u", 1),
+    ("i64.atomic.rmw8.xor_u", 0),
+    ("i64.atomic.rmw16.xor_u", 1),
+    ("i64.atomic.rmw32.xor_u", 2),
+    ("i32.atomic.rmw.xchg", 2),
+    ("i64.atomic.rmw.xchg", 3),
+    ("i32.atomic.rmw8.xchg_u", 0),
+    ("i32.atomic.rmw16.xchg_u", 1),
+    ("i64.atomic.rmw8.xchg_u", 0),
+    ("i64.atomic.rmw16.xchg_u", 1),
+    ("i64.atomic.rmw32.xchg_u", 2),
+    ("i32.atomic.rmw.cmpxchg", 2),
+    ("i64.atomic.rmw.cmpxchg", 3),
+    ("i32.atomic.rmw8.cmpxchg_u", 0),
+    ("i32.atomic.rmw16.cmpxchg_u", 1),
+    ("i64.atomic.rmw8.cmpxchg_u", 0),
+    ("i64.atomic.rmw16.cmpxchg_u", 1),
+    ("i64.atomic.rmw32.cmpxchg_u", 2),
+];
+
+pub(crate) fn memory_instruction(name: &str, memory_argument: MemoryArgument) -> Instruction<'static> {
+    match name {
+        "i32.load" => I32Load { memory_argument },
+        "i64.load" => I64Load { memory_argument },
+        "f32.load" => F32Load { memory_argument },
+        "f64.load" => F64Load { memory_argument },
+        "i32.load8_s" => I32Load8s { memory_argument },
+        "i32.load8_u" => I32Load8u { memory_argument },
+        "i32.load16_s" => I32Load16s { memory_argument },
+        "i32.load16_u" => I32Load16u { memory_argument },
+        "i64.load8_s" => I64Load8s { memory_argument },
+        "i64.load8_u" => I64Load8u { memory_argument },
+        "i64.load16_s" => I64Load16s { memory_argument },
+        "i64.load16_u" => I64Load16u { memory_argument },
+        "i64.load32_s" => I64Load32s { memory_argument },
+        "i64.load32_u" => I64Load32u { memory_argument },
+        "i32.store" => I32Store { memory_argument },
+        "i64.store" => I64Store { memory_argument },
+        "f32.store" => F32Store { memory_argument },
+        "f64.store" => F64Store { memory_argument },
+        "i32.store8" => I32Store8 { memory_argument },
+        "i32.store16" => I32Store16 { memory_argument },
+        "i64.store8" => I64Store8 { memory_argument },
+        "i64.store16" => I64Store16 { memory_argument },
+        "i64.store32" => I64Store32 { memory_argument },
+        "v128.load" => V128Load { memory_argument },
+        "v128.load8x8_s" => V128Load8x8s { memory_argument },
+        "v128.load8x8_u" => V128Load8x8u { memory_argument },
+        "v128.load16x4_s" => V128Load16x4s { memory_argument },
+        "v128.load16x4_u" => V128Load16x4u { memory_argument },
+        "v128.load32x2_s" => V128Load32x2s { memory_argument },
+        "v128.load32x2_u" => V128Load32x2u { memory_argument },
+        "v128.load8_splat" => V128Load8Splat { memory_argument },
+        "v128.load16_splat" => V128Load16Splat { memory_argument },
+        "v128.load32_splat" => V128Load32Splat { memory_argument },
+        "v128.load64_splat" => V128Load64Splat { memory_argument },
+        "v128.load32_zero" => V128Load32Zero { memory_argument },
+        "v128.load64_zero" => V128Load64Zero { memory_argument },
+        "memory.atomic.notify" => MemoryAtomicNotify { memory_argument },
+        "memory.atomic.wait32" => MemoryAtomicWait32 { memory_argument },
+        "memory.atomic.wait64" => MemoryAtomicWait64 { memory_argument },
+        "i32.atomic.load" => I32AtomicLoad { memory_argument },
+        "i64.atomic.load" => I64AtomicLoad { memory_argument },
+        "i32.atomic.load8_u" => I32AtomicLoad8u { memory_argument },
+        "i32.atomic.load16_u" => I32AtomicLoad16u { memory_argument },
+        "i64.atomic.load8_u" => I64AtomicLoad8u { memory_argument },
+        "i64.atomic.load16_u" => I64AtomicLoad16u { memory_argument },
+        "i64.atomic.load32_u" => I64AtomicLoad32u { memory_argument },
+        "i32.atomic.store" => I32AtomicStore { memory_argument },
+        "i64.atomic.store" => I64AtomicStore { memory_argument },
+        "i32.atomic.store8" => I32AtomicStore8 { memory_argument },
+        "i32.atomic.store16" => I32AtomicStore16 { memory_argument },
+        "i64.atomic.store8" => I64AtomicStore8 { memory_argument },
+        "i64.atomic.store16" => I64AtomicStore16 { memory_argument },
+        "i64.atomic.store32" => I64AtomicStore32 { memory_argument },
+        "i32.atomic.rmw.add" => I32AtomicRmwAdd { memory_argument },
+        "i64.atomic.rmw.add" => I64AtomicRmwAdd { memory_argument },
+        "i32.atomic.rmw8.add_u" => I32AtomicRmw8Addu { memory_argument },
+        "i32.atomic.rmw16.add_u" => I32AtomicRmw16Addu { memory_argument },
+        "i64.atomic.rmw8.add_u" => I64AtomicRmw8Addu { memory_argument },
+        "i64.atomic.rmw16.add_u" => I64AtomicRmw16Addu { memory_argument },
+        "i64.atomic.rmw32.add_u" => I64AtomicRmw32Addu { memory_argument },
+        "i32.atomic.rmw.sub" => I32AtomicRmwSub { memory_argument },
+        "i64.atomic.rmw.sub" => I64AtomicRmwSub { memory_argument },
+        "i32.atomic.rmw8.sub_u" => I32AtomicRmw8Subu { memory_argument },
+        "i32.atomic.rmw16.sub_u" => I32AtomicRmw16Subu { memory_argument },
+        "i64.atomic.rmw8.sub_u" => I64AtomicRmw8Subu { memory_argument },
+        "i64.atomic.rmw16.sub_u" => I64AtomicRmw16Subu { memory_argument },
+        "i64.atomic.rmw32.sub_u" => I64AtomicRmw32Subu { memory_argument },
+        "i32.atomic.rmw.and" => I32AtomicRmwAnd { memory_argument },
+        "i64.atomic.rmw.and" => I64AtomicRmwAnd { memory_argument },
+        "i32.atomic.rmw8.and_u" => I32AtomicRmw8Andu { memory_argument },
+        "i32.atomic.rmw16.and_u" => I32AtomicRmw16Andu { memory_argument },
+        "i64.atomic.rmw8.and_u" => I64AtomicRmw8Andu { memory_argument },
+        "i64.atomic.rmw16.and_u" => I64AtomicRmw16Andu { memory_argument },
+        "i64.atomic.rmw32.and_u" => I64AtomicRmw32Andu { memory_argument },
+        "i32.atomic.rmw.or" => I32AtomicRmwOr { memory_argument },
+        "i64.atomic.rmw.or" => I64AtomicRmwOr { memory_argument },
+        "i32.atomic.rmw8.or_u" => I32AtomicRmw8Oru { memory_argument },
+        "i32.atomic.rmw16.or_u" => I32AtomicRmw16Oru { memory_argument },
+        "i64.atomic.rmw8.or_u" => I64AtomicRmw8Oru { memory_argument },
+        "i64.atomic.rmw16.or_u" => I64AtomicRmw16Oru { memory_argument },
+        "i64.atomic.rmw32.or_u" => I64AtomicRmw32Oru { memory_argument },
+        "i32.atomic.rmw.xor" => I32AtomicRmwXor { memory_argument },
+        "i64.atomic.rmw.xor" => I64AtomicRmwXor { memory_argument },
+        "i32.atomic.rmw8.xor_u" => I32AtomicRmw8Xoru { memory_argument },
+        "i32.atomic.rmw16.xor_u" => I32AtomicRmw16Xoru { memory_argument },
+        "i64.atomic.rmw8.xor_u" => I64AtomicRmw8Xoru { memory_argument },
+        "i64.atomic.rmw16.xor_u" => I64AtomicRmw16Xoru { memory_argument },
+        "i64.atomic.rmw32.xor_u" => I64AtomicRmw32Xoru { memory_argument },
+        "i32.atomic.rmw.xchg" => I32AtomicRmwXchg { memory_argument },
+        "i64.atomic.rmw.xchg" => I64AtomicRmwXchg { memory_argument },
+        "i32.atomic.rmw8.xchg_u" => I32AtomicRmw8Xchgu { memory_argument },
+        "i32.atomic.rmw16.xchg_u" => I32AtomicRmw16Xchgu { memory_argument },
+        "i64.atomic.rmw8.xchg_u" => I64AtomicRmw8Xchgu { memory_argument },
+        "i64.atomic.rmw16.xchg_u" => I64AtomicRmw16Xchgu { memory_argument },
+        "i64.atomic.rmw32.xchg_u" => I64AtomicRmw32Xchgu { memory_argument },
+        "i32.atomic.rmw.cmpxchg" => I32AtomicRmwCmpxchg { memory_argument },
+        "i64.atomic.rmw.cmpxchg" => I64AtomicRmwCmpxchg { memory_argument },
+        "i32.atomic.rmw8.cmpxchg_u" => I32AtomicRmw8Cmpxchgu { memory_argument },
+        "i32.atomic.rmw16.cmpxchg_u" => I32AtomicRmw16Cmpxchgu { memory_argument },
+        "i64.atomic.rmw8.cmpxchg_u" => I64AtomicRmw8Cmpxchgu { memory_argument },
+        "i64.atomic.rmw16.cmpxchg_u" => I64AtomicRmw16Cmpxchgu { memory_argument },
+        "i64.atomic.rmw32.cmpxchg_u" => I64AtomicRmw32Cmpxchgu { memory_argument },
+        _ => V128Store { memory_argument },
+    }
+}
+
+/// Returns the memory argument of a load or a store.
+pub(crate) fn memory_argument(instruction: &Instruction) -> Option<MemoryArgument> {
+    match instruction {
+        I32Load { memory_argument } |
+        I64Load { memory_argument } |
+        F32Load { memory_argument } |
+        F64Load { memory_argument } |
+        I32Load8s { memory_argument } |
+        I32Load8u { memory_argument } |
+        I32Load16s { memory_argument } |
+        I32Load16u { memory_argument } |
+        I64Load8s { memory_argument } |
+        I64Load8u { memory_argument } |
+        I64Load16s { memory_argument } |
+        I64Load16u { memory_argument } |
+        I64Load32s { memory_argument } |
+        I64Load32u { memory_argument } |
+        I32Store { memory_argument } |
+        I64Store { memory_argument } |
+        F32Store { memory_argument } |
+        F64Store { memory_argument } |
+        I32Store8 { memory_argument } |
+        I32Store16 { memory_argument } |
+        I64Store8 { memory_argument } |
+        I64Store16 { memory_argument } |
+        I64Store32 { memory_argument } |
+        V128Load { memory_argument } |
+        V128Load8x8s { memory_argument } |
+        V128Load8x8u { memory_argument } |
+        V128Load16x4s { memory_argument } |
+        V128Load16x4u { memory_argument } |
+        V128Load32x2s { memory_argument } |
+        V128Load32x2u { memory_argument } |
+        V128Load8Splat { memory_argument } |
+        V128Load16Splat { memory_argument } |
+        V128Load32Splat { memory_argument } |
+        V128Load64Splat { memory_argument } |
+                V128Load32Zero { memory_argument } |
+        V128Load64Zero { memory_argument } |
+        MemoryAtomicNotify { memory_argument } |
+        MemoryAtomicWait32 { memory_argument } |
+        MemoryAtomicWait64 { memory_argument } |
+        I32AtomicLoad { memory_argument } |
+        I64AtomicLoad { memory_argument } |
+        I32AtomicLoad8u { memory_argument } |
+        I32AtomicLoad16u { memory_argument } |
+        I64AtomicLoad8u { memory_argument } |
+        I64AtomicLoad16u { memory_argument } |
+        I64AtomicLoad32u { memory_argument } |
+        I32AtomicStore { memory_argument } |
+        I64AtomicStore { memory_argument } |
+        I32AtomicStore8 { memory_argument } |
+        I32AtomicStore16 { memory_argument } |
+        I64AtomicStore8 { memory_argument } |
+        I64AtomicStore16 { memory_argument } |
+        I64AtomicStore32 { memory_argument } |
+        I32AtomicRmwAdd { memory_argument } |
+        I64AtomicRmwAdd { memory_argument } |
+        I32AtomicRmw8Addu { memory_argument } |
+        I32AtomicRmw16Addu { memory_argument } |
+        I64AtomicRmw8Addu { memory_argument } |
+        I64AtomicRmw16Addu { memory_argument } |
+        I64AtomicRmw32Addu { memory_argument } |
+        I32AtomicRmwSub { memory_argument } |
+        I64AtomicRmwSub { memory_argument } |
+        I32AtomicRmw8Subu { memory_argument } |
+        I32AtomicRmw16Subu { memory_argument } |
+        I64AtomicRmw8Subu { memory_argument } |
+        I64AtomicRmw16Subu { memory_argument } |
+        I64AtomicRmw32Subu { memory_argument } |
+        I32AtomicRmwAnd { memory_argument } |
+        I64AtomicRmwAnd { memory_argument } |
+        I32AtomicRmw8Andu { memory_argument } |
+        I32AtomicRmw16Andu { memory_argument } |
+        I64AtomicRmw8Andu { memory_argument } |
+        I64AtomicRmw16Andu { memory_argument } |
+        I64AtomicRmw32Andu { memory_argument } |
+        I32AtomicRmwOr { memory_argument } |
+        I64AtomicRmwOr { memory_argument } |
+        I32AtomicRmw8Oru { memory_argument } |
+        I32AtomicRmw16Oru { memory_argument } |
+        I64AtomicRmw8Oru { memory_argument } |
+        I64AtomicRmw16Oru { memory_argument } |
+        I64AtomicRmw32Oru { memory_argument } |
+        I32AtomicRmwXor { memory_argument } |
+        I64AtomicRmwXor { memory_argument } |
+        I32AtomicRmw8Xoru { memory_argument } |
+        I32AtomicRmw16Xoru { memory_argument } |
+        I64AtomicRmw8Xoru { memory_argument } |
+        I64AtomicRmw16Xoru { memory_argument } |
+        I64AtomicRmw32Xoru { memory_argument } |
+        I32AtomicRmwXchg { memory_argument } |
+        I64AtomicRmwXchg { memory_argument } |
+        I32AtomicRmw8Xchgu { memory_argument } |
+        I32AtomicRmw16Xchgu { memory_argument } |
+        I64AtomicRmw8Xchgu { memory_argument } |
+        I64AtomicRmw16Xchgu { memory_argument } |
+        I64AtomicRmw32Xchgu { memory_argument } |
+        I32AtomicRmwCmpxchg { memory_argument } |
+        I64AtomicRmwCmpxchg { memory_argument } |
+        I32AtomicRmw8Cmpxchgu { memory_argument } |
+        I32AtomicRmw16Cmpxchgu { memory_argument } |
+        I64AtomicRmw8Cmpxchgu { memory_argument } |
+        I64AtomicRmw16Cmpxchgu { memory_argument } |
+        I64AtomicRmw32Cmpxchgu { memory_argument } |
+        V128Store { memory_argument } => Some(*memory_argument),
+        _ => None,
+    }
+}
+
+/// Where parsed instructions go: a function body, or a constant expression
+/// which can't contain a `br_table`.
+pub(crate) enum Sink<'s> {
+    Body(&'s mut FunctionBodyEncoder),
+    Expression(&'s mut Vec<Instruction<'static>>),
+}
+
+/// An instruction whose immediates have been parsed, waiting for its folded operands.
+enum Pending {
+    Instruction(Instruction<'static>),
+    BranchTable(Vec<LabelIndex>, LabelIndex, usize),
+}
+
+impl Sink<'_> {
+    fn emit(&mut self, instruction: Instruction<'static>) {
+        match self {
+            Sink::Body(body) => {
+                body.instruction(&instruction);
+            },
+            Sink::Expression(instructions) => instructions.push(instruction),
+        }
+    }
+
+    fn emit_pending(&mut self, pending: Pending) -> Result<()> {
+        match (pending, self) {
+            (Pending::Instruction(instruction), sink) => sink.emit(instruction),
+            (Pending::BranchTable(labels, default_label, _), Sink::Body(body)) => {
+                body.branch_table(&labels, default_label);
+            },
+            (Pending::BranchTable(_, _, offset), Sink::Expression(_)) => {
+                return Err(TextError::Expected("constant instruction", offset));
+            },
+        }
+        Ok(())
+    }
+}
+
+fn is_index(kind: Option<&TokenKind>) -> bool {
+    match kind {
+        Some(TokenKind::Id(_)) => true,
+        Some(TokenKind::Atom(atom)) => parse_u32(atom).is_some(),
+        _ => false,
+    }
+}
+
+impl<'a> TextParser<'a> {
+    /// Parses flat and folded instructions up to a closing parenthesis or a
+    /// keyword ending a block or one of its parts, such as `end` or `else`.
+    pub(crate) fn parse_instructions(&mut self, scope: &mut Scope<'a>, sink: &mut Sink) -> Result<()> {
+        loop {
+            match self.peek_kind() {
+                None | Some(TokenKind::RightParen) => return Ok(()),
+                Some(TokenKind::Atom("end")) | Some(TokenKind::Atom("else")) => return Ok(()),
+                Some(TokenKind::Atom("catch")) | Some(TokenKind::Atom("catch_all")) | Some(TokenKind::Atom("delegate")) => return Ok(()),
+                Some(TokenKind::LeftParen) => self.parse_folded_instruction(scope, sink)?,
+                _ => self.parse_flat_instruction(scope, sink)?,
+            }
+        }
+    }
+
+    fn parse_flat_instruction(&mut self, scope: &mut Scope<'a>, sink: &mut Sink) -> Result<()> {
+        let (name, offset) = self.expect_atom()?;
+        match name {
+            "block" | "loop" | "if" => {
+                let label = self.take_id();
+                let block_type = self.parse_block_type()?;
+                sink.emit(match name {
+                    "block" => Block { block_type },
+                    "loop" => Loop { block_type },
+                    _ => If { block_type },
+                });
+                scope.labels.push(label);
+                self.parse_instructions(scope, sink)?;
+                if name == "if" && self.take_keyword("else") {
+                    self.take_matching_label(label)?;
+                    sink.emit(Else);
+                    self.parse_instructions(scope, sink)?;
+                }
+                scope.labels.pop();
+                self.expect_keyword("end")?;
+                self.take_matching_label(label)?;
+                sink.emit(End);
+                Ok(())
+            },
+            "try" => {
+                let label = self.take_id();
+                let block_type = self.parse_block_type()?;
+                sink.emit(Try { block_type });
+                scope.labels.push(label);
+                self.parse_instructions(scope, sink)?;
+                loop {
+                    if self.take_keyword("catch") {
+                        sink.emit(Catch { tag_index: self.parse_index(Space::Tag)?.into() });
+                    } else if self.take_keyword("catch_all") {
+                        sink.emit(CatchAll);
+                    } else {
+                        break;
+                    }
+                    self.parse_instructions(scope, sink)?;
+                }
+                scope.labels.pop();
+                if self.take_keyword("delegate") {
+                    // The label is looked up outside of the try block.
+                    sink.emit(Delegate { label_index: self.parse_label(scope)? });
+                    return Ok(());
+                }
+                self.expect_keyword("end")?;
+                self.take_matching_label(label)?;
+                sink.emit(End);
+                Ok(())
+            },
+            "try_table" => {
+                let label = self.take_id();
+                let block_type = self.parse_block_type()?;
+                let catches = self.parse_catch_clauses(scope)?;
+                sink.emit(TryTable { block_type, catches });
+                scope.labels.push(label);
+                self.parse_instructions(scope, sink)?;
+                scope.labels.pop();
+                self.expect_keyword("end")?;
+                self.take_matching_label(label)?;
+                sink.emit(End);
+                Ok(())
+            },
+            _ => {
+                let pending = self.parse_plain_instruction(name, offset, scope)?;
+                sink.emit_pending(pending)
+            },
+        }
+    }
+
+    pub(crate) fn parse_folded_instruction(&mut self, scope: &mut Scope<'a>, sink: &mut Sink) -> Result<()> {
+        self.expect_left_paren()?;
+        let (name, offset) = self.expect_atom()?;
+        match name {
+            "block" | "loop" => {
+                let label = self.take_id();
+                let block_type = self.parse_block_type()?;
+                sink.emit(if name == "block" { Block { block_type } } else { Loop { block_type } });
+                scope.labels.push(label);
+                self.parse_instructions(scope, sink)?;
+                scope.labels.pop();
+                sink.emit(End);
+            },
+            "if" => {
+                let label = self.take_id();
+                let block_type = self.parse_block_type()?;
+                while self.peek_kind() == Some(&TokenKind::LeftParen) && !self.peek_list("then") {
+                    self.parse_folded_instruction(scope, sink)?;
+                }
+                sink.emit(If { block_type });
+                scope.labels.push(label);
+                self.expect_list("then")?;
+                self.parse_instructions(scope, sink)?;
+                self.expect_right_paren()?;
+                if self.peek_list("else") {
+                    self.expect_list("else")?;
+                    sink.emit(Else);
+                    self.parse_instructions(scope, sink)?;
+                    self.expect_right_paren()?;
+                }
+                scope.labels.pop();
+                sink.emit(End);
+            },
+            "try" => {
+                let label = self.take_id();
+                let block_type = self.parse_block_type()?;
+                sink.emit(Try { block_type });
+                scope.labels.push(label);
+                self.expect_list("do")?;
+                self.parse_instructions(scope, sink)?;
+                self.expect_right_paren()?;
+                while self.peek_list("catch") {
+                    self.expect_list("catch")?;
+                    sink.emit(Catch { tag_index: self.parse_index(Space::Tag)?.into() });
+                    self.parse_instructions(scope, sink)?;
+                    self.expect_right_paren()?;
+                }
+                if self.peek_list("catch_all") {
+                    self.expect_list("catch_all")?;
+                    sink.emit(CatchAll);
+                    self.parse_instructions(scope, sink)?;
+                    self.expect_right_paren()?;
+                }
+                scope.labels.pop();
+                if self.peek_list("delegate") {
+                    self.expect_list("delegate")?;
+                    sink.emit(Delegate { label_index: self.parse_label(scope)? });
+                    self.expect_right_paren()?;
+                } else {
+                    sink.emit(End);
+                }
+            },
+            "try_table" => {
+                let label = self.take_id();
+                let block_type = self.parse_block_type()?;
+                let catches = self.parse_catch_clauses(scope)?;
+                sink.emit(TryTable { block_type, catches });
+                scope.labels.push(label);
+                self.parse_instructions(scope, sink)?;
+                scope.labels.pop();
+                sink.emit(End);
+            },
+            _ => {
+                let pending = self.parse_plain_instruction(name, offset, scope)?;
+                while self.peek_kind() == Some(&TokenKind::LeftParen) {
+                    self.parse_folded_instruction(scope, sink)?;
+                }
+                sink.emit_pending(pending)?;
+            },
+        }
+        self.expect_right_paren()
+    }
+
+    fn take_matching_label(&mut self, label: Option<&'a str>) -> Result<()> {
+        let offset = self.peek_offset();
+        match self.take_id() {
+            Some(id) if Some(id) != label => Err(TextError::UnknownIdentifier(offset)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Parses the catch clauses of `try_table`, whose labels are looked up
+    /// outside of the `try_table` block.
+    fn parse_catch_clauses(&mut self, scope: &Scope<'a>) -> Result<Box<[CatchClause]>> {
+        let mut catches = Vec::new();
+        while self.peek_kind() == Some(&TokenKind::LeftParen) {
+            let catch = match self.peek_kind_at(1) {
+                Some(TokenKind::Atom("catch")) => {
+                    self.expect_list("catch")?;
+                    let tag_index = self.parse_index(Space::Tag)?.into();
+                    CatchClause::Catch { tag_index, label_index: self.parse_label(scope)? }
+                },
+                Some(TokenKind::Atom("catch_ref")) => {
+                    self.expect_list("catch_ref")?;
+                    let tag_index = self.parse_index(Space::Tag)?.into();
+                    CatchClause::CatchRef { tag_index, label_index: self.parse_label(scope)? }
+                },
+                Some(TokenKind::Atom("catch_all")) => {
+                    self.expect_list("catch_all")?;
+                    CatchClause::CatchAll { label_index: self.parse_label(scope)? }
+                },
+                Some(TokenKind::Atom("catch_all_ref")) => {
+                    self.expect_list("catch_all_ref")?;
+                    CatchClause::CatchAllRef { label_index: self.parse_label(scope)? }
+                },
+                _ => break,
+            };
+            self.expect_right_paren()?;
+            catches.push(catch);
+        }
+        Ok(catches.into_boxed_slice())
+    }
+
+    fn parse_block_type(&mut self) -> Result<BlockType> {
+        if self.peek_list("type") {
+            return Ok(BlockType::TypeIndex(self.parse_type_use()?.0));
+        }
+        let (params, results) = self.parse_signature(&mut Vec::new(), false)?;
+        match (params.len(), results.len()) {
+            (0, 0) => Ok(BlockType::Empty),
+            (0, 1) => Ok(BlockType::ValueType(results[0])),
+            _ => Ok(BlockType::TypeIndex(self.find_or_add_type(params, results))),
+        }
+    }
+
+    fn parse_plain_instruction(&mut self, name: &str, offset: usize, scope: &Scope<'a>) -> Result<Pending> {
+        if let Some((_, instruction)) = PLAIN_INSTRUCTIONS.iter().find(|(plain, _)| *plain == name) {
+            return Ok(Pending::Instruction(instruction.clone()));
+        }
+        if let Some((_, alignment)) = MEMORY_INSTRUCTIONS.iter().find(|(memory, _)| *memory == name) {
+            let memory_argument = self.parse_memory_argument(*alignment)?;
+            return Ok(Pending::Instruction(memory_instruction(name, memory_argument)));
+        }
+        let instruction = match name {
+            "br" => Branch { label_index: self.parse_label(scope)? },
+            "br_if" => BranchIf { label_index: self.parse_label(scope)? },
+            "br_table" => {
+                let mut labels = vec![self.parse_label(scope)?];
+                while matches!(self.peek_kind(), Some(TokenKind::Id(_)) | Some(TokenKind::Atom(_))) && !self.peek_instruction_keyword() {
+                    labels.push(self.parse_label(scope)?);
+                }
+                let default_label = labels.pop().unwrap();
+                return Ok(Pending::BranchTable(labels, default_label, offset));
+            },
+            "call" => Call { func_index: self.parse_index(Space::Func)?.into() },
+            "call_indirect" => {
+                let table_index = self.parse_optional_table_index()?;
+                CallIndirect { type_index: self.parse_type_use()?.0, table_index }
+            },
+            "throw" => Throw { tag_index: self.parse_index(Space::Tag)?.into() },
+            "rethrow" => Rethrow { label_index: self.parse_label(scope)? },
+            "return_call" => ReturnCall { func_index: self.parse_index(Space::Func)?.into() },
+            "return_call_indirect" => {
+                let table_index = self.parse_optional_table_index()?;
+                ReturnCallIndirect { type_index: self.parse_type_use()?.0, table_index }
+            },
+            "select" => {
+                let (params, results) = self.parse_signature(&mut Vec::new(), false)?;
+                match (params.len(), results.as_slice()) {
+                    (0, []) => Select,
+                    (0, [value_type]) => TypedSelect { value_type: *value_type },
+                    _ => return Err(TextError::Expected("a single select result", offset)),
+                }
+            },
+            "local.get" => LocalGet { local_index: self.parse_local(scope)?.into() },
+            "local.set" => LocalSet { local_index: self.parse_local(scope)?.into() },
+            "local.tee" => LocalTee { local_index: self.parse_local(scope)?.into() },
+            "global.get" => GlobalGet { global_index: self.parse_index(Space::Global)?.into() },
+            "global.set" => GlobalSet { global_index: self.parse_index(Space::Global)?.into() },
+            "table.get" => TableGet { table_index: self.parse_optional_table_index()? },
+            "table.set" => TableSet { table_index: self.parse_optional_table_index()? },
+            "table.size" => TableSize { table_index: self.parse_optional_table_index()? },
+            "table.grow" => TableGrow { table_index: self.parse_optional_table_index()? },
+            "table.fill" => TableFill { table_index: self.parse_optional_table_index()? },
+            "memory.size" => MemorySize { memory_index: self.parse_optional_memory_index()? },
+            "memory.grow" => MemoryGrow { memory_index: self.parse_optional_memory_index()? },
+            "memory.fill" => MemoryFill { memory_index: self.parse_optional_memory_index()? },
+            "memory.copy" => {
+                let (destination, source) = if self.peek_index_pair() {
+                    (self.parse_index(Space::Memory)?, self.parse_index(Space::Memory)?)
+                } else {
+                    (0, 0)
+                };
+                MemoryCopy { destination_memory_index: destination.into(), source_memory_index: source.into() }
+            },
+            "ref.null" => {
+                let (heap_type, heap_type_offset) = self.expect_atom()?;
+                let ref_type = match heap_type {
+                    "func" => ValueType::FuncRef,
+                    "extern" => ValueType::ExternRef,
+                    "exn" => ValueType::ExnRef,
+                    _ => return Err(TextError::Expected("heap type", heap_type_offset)),
+                };
+                RefNull { ref_type }
+            },
+            "ref.func" => RefFunc { func_index: self.parse_index(Space::Func)?.into() },
+            "i32.const" => I32Const(self.parse_number(parse_i32)?),
+            "i64.const" => I64Const(self.parse_number(parse_i64)?),
+            "f32.const" => F32Const(self.parse_number(parse_f32)?),
+            "f64.const" => F64Const(self.parse_number(parse_f64)?),
+            "v128.const" => V128Const(self.parse_v128_const()?),
+            "i8x16.shuffle" => {
+                let mut lanes = [0; 16];
+                for lane in lanes.iter_mut() {
+                    *lane = self.parse_lane_index()?;
+                }
+                I8x16Shuffle { lanes }
+            },
+            "i8x16.extract_lane_s" => I8x16ExtractLanes { lane: self.parse_lane_index()? },
+            "i8x16.extract_lane_u" => I8x16ExtractLaneu { lane: self.parse_lane_index()? },
+            "i8x16.replace_lane" => I8x16ReplaceLane { lane: self.parse_lane_index()? },
+            "i16x8.extract_lane_s" => I16x8ExtractLanes { lane: self.parse_lane_index()? },
+            "i16x8.extract_lane_u" => I16x8ExtractLaneu { lane: self.parse_lane_index()? },
+            "i16x8.replace_lane" => I16x8ReplaceLane { lane: self.parse_lane_index()? },
+            "i32x4.extract_lane" => I32x4ExtractLane { lane: self.parse_lane_index()? },
+            "i32x4.replace_lane" => I32x4ReplaceLane { lane: self.parse_lane_index()? },
+            "i64x2.extract_lane" => I64x2ExtractLane { lane: self.parse_lane_index()? },
+            "i64x2.replace_lane" => I64x2ReplaceLane { lane: self.parse_lane_index()? },
+            "f32x4.extract_lane" => F32x4ExtractLane { lane: self.parse_lane_index()? },
+            "f32x4.replace_lane" => F32x4ReplaceLane { lane: self.parse_lane_index()? },
+            "f64x2.extract_lane" => F64x2ExtractLane { lane: self.parse_lane_index()? },
+            "f64x2.replace_lane" => F64x2ReplaceLane { lane: self.parse_lane_index()? },
+            "v128.load8_lane" => {
+                let memory_argument = self.parse_lane_memory_argument(0)?;
+                V128Load8Lane { memory_argument, lane: self.parse_lane_index()? }
+            },
+            "v128.load16_lane" => {
+                let memory_argument = self.parse_lane_memory_argument(1)?;
+                V128Load16Lane { memory_argument, lane: self.parse_lane_index()? }
+            },
+            "v128.load32_lane" => {
+                let memory_argument = self.parse_lane_memory_argument(2)?;
+                V128Load32Lane { memory_argument, lane: self.parse_lane_index()? }
+            },
+            "v128.load64_lane" => {
+                let memory_argument = self.parse_lane_memory_argument(3)?;
+                V128Load64Lane { memory_argument, lane: self.parse_lane_index()? }
+            },
+            "v128.store8_lane" => {
+                let memory_argument = self.parse_lane_memory_argument(0)?;
+                V128Store8Lane { memory_argument, lane: self.parse_lane_index()? }
+            },
+            "v128.store16_lane" => {
+                let memory_argument = self.parse_lane_memory_argument(1)?;
+                V128Store16Lane { memory_argument, lane: self.parse_lane_index()? }
+            },
+            "v128.store32_lane" => {
+                let memory_argument = self.parse_lane_memory_argument(2)?;
+                V128Store32Lane { memory_argument, lane: self.parse_lane_index()? }
+            },
+            "v128.store64_lane" => {
+                let memory_argument = self.parse_lane_memory_argument(3)?;
+                V128Store64Lane { memory_argument, lane: self.parse_lane_index()? }
+            },
+            "memory.init" => {
+                self.require_data_count();
+                let memory_index = if self.peek_index_pair() { self.parse_index(Space::Memory)? } else { 0 };
+                MemoryInit { data_index: self.parse_index(Space::Data)?.into(), memory_index: memory_index.into() }
+            },
+            "data.drop" => {
+                self.require_data_count();
+                DataDrop { data_index: self.parse_index(Space::Data)?.into() }
+            },
+            "table.init" => {
+                let table_index = if self.peek_index_pair() { self.parse_index(Space::Table)? } else { 0 };
+                TableInit { element_index: self.parse_index(Space::Elem)?.into(), table_index: table_index.into() }
+            },
+            "elem.drop" => ElemDrop { element_index: self.parse_index(Space::Elem)?.into() },
+            "table.copy" => {
+                let (destination, source) = if self.peek_index_pair() {
+                    (self.parse_index(Space::Table)?, self.parse_index(Space::Table)?)
+                } else {
+                    (0, 0)
+                };
+                TableCopy { destination_table_index: destination.into(), source_table_index: source.into() }
+            },
+            _ => return Err(TextError::UnknownInstruction(offset)),
+        };
+        Ok(Pending::Instruction(instruction))
+    }
+
+    /// Tells whether the next two tokens are both indices, as when the optional
+    /// table or memory indices of `table.init`, `table.copy`, `memory.init`
+    /// and `memory.copy` are written out.
+    fn peek_index_pair(&self) -> bool {
+        is_index(self.peek_kind()) && is_index(self.peek_kind_at(1))
+    }
+
+    /// Parses the table index of table instructions, which defaults to 0.
+    fn parse_optional_table_index(&mut self) -> Result<TableIndex> {
+        if is_index(self.peek_kind()) {
+            Ok(self.parse_index(Space::Table)?.into())
+        } else {
+            Ok(0.into())
+        }
+    }
+
+    /// Parses the memory index of memory instructions, which defaults to 0.
+    fn parse_optional_memory_index(&mut self) -> Result<MemoryIndex> {
+        if is_index(self.peek_kind()) {
+            Ok(self.parse_index(Space::Memory)?.into())
+        } else {
+            Ok(0.into())
+        }
+    }
+
+    /// Tells whether the next atom starts a new flat instruction rather than being an index.
+    fn peek_instruction_keyword(&self) -> bool {
+        match self.peek_kind() {
+            Some(TokenKind::Atom(atom)) => parse_u32(atom).is_none(),
+            _ => false,
+        }
+    }
+
+    fn parse_memory_argument(&mut self, natural_alignment: u32) -> Result<MemoryArgument> {
+        let memory_index = self.parse_optional_memory_index()?;
+        self.parse_offset_and_alignment(natural_alignment, memory_index)
+    }
+
+    /// Lane instructions end with a lane index, so a lone index is the lane
+    /// and only one followed by another index or a memory argument keyword
+    /// is a memory index.
+    fn parse_lane_memory_argument(&mut self, natural_alignment: u32) -> Result<MemoryArgument> {
+        let memory_argument_follows = match self.peek_kind_at(1) {
+            Some(TokenKind::Atom(atom)) => atom.starts_with("offset=") || atom.starts_with("align="),
+            _ => false,
+        };
+        let memory_index = if is_index(self.peek_kind()) && (is_index(self.peek_kind_at(1)) || memory_argument_follows) {
+            self.parse_index(Space::Memory)?.into()
+        } else {
+            0.into()
+        };
+        self.parse_offset_and_alignment(natural_alignment, memory_index)
+    }
+
+    fn parse_offset_and_alignment(&mut self, natural_alignment: u32, memory_index: MemoryIndex) -> Result<MemoryArgument> {
+        let mut memory_argument = MemoryArgument { alignment: natural_alignment, offset: 0, memory_index };
+        if let Some(TokenKind::Atom(atom)) = self.peek_kind() {
+            if let Some(offset) = atom.strip_prefix("offset=") {
+                memory_argument.offset = self.parse_keyword_value(offset, parse_u64)?;
+            }
+        }
+        if let Some(TokenKind::Atom(atom)) = self.peek_kind() {
+            if let Some(alignment) = atom.strip_prefix("align=") {
+                let token_offset = self.peek_offset();
+                let alignment = self.parse_keyword_value(alignment, parse_u32)?;
+                if !alignment.is_power_of_two() {
+                    return Err(TextError::InvalidNumber(token_offset));
+                }
+                memory_argument.alignment = alignment.trailing_zeros();
+            }
+        }
+        Ok(memory_argument)
+    }
+
+    /// Parses the lane immediate of lane and shuffle instructions.
+    fn parse_lane_index(&mut self) -> Result<u8> {
+        let (atom, offset) = self.expect_atom()?;
+        parse_u32(atom).and_then(|lane| u8::try_from(lane).ok()).ok_or(TextError::InvalidNumber(offset))
+    }
+
+    /// Parses the shape and the lanes of `v128.const` into the 128 bits they stand for.
+    fn parse_v128_const(&mut self) -> Result<i128> {
+        let (shape, offset) = self.expect_atom()?;
+        let mut bytes = Vec::with_capacity(16);
+        match shape {
+            "i8x16" => for _ in 0..16 { bytes.extend_from_slice(&self.parse_number(parse_i8)?.to_le_bytes()) },
+            "i16x8" => for _ in 0..8 { bytes.extend_from_slice(&self.parse_number(parse_i16)?.to_le_bytes()) },
+            "i32x4" => for _ in 0..4 { bytes.extend_from_slice(&self.parse_number(parse_i32)?.to_le_bytes()) },
+            "i64x2" => for _ in 0..2 { bytes.extend_from_slice(&self.parse_number(parse_i64)?.to_le_bytes()) },
+            "f32x4" => for _ in 0..4 { bytes.extend_from_slice(&self.parse_number(parse_f32)?.to_le_bytes()) },
+            "f64x2" => for _ in 0..2 { bytes.extend_from_slice(&self.parse_number(parse_f64)?.to_le_bytes()) },
+            _ => return Err(TextError::Expected("vector shape", offset)),
+        }
+        let mut value = [0; 16];
+        value.copy_from_slice(&bytes);
+        Ok(i128::from_le_bytes(value))
+    }
+
+    fn parse_keyword_value<T>(&mut self, value: &str, parse: fn(&str) -> Option<T>) -> Result<T> {
+        let (_, offset) = self.expect_atom()?;
+        parse(value).ok_or(TextError::InvalidNumber(offset))
+    }
+
+    fn parse_label(&mut self, scope: &Scope<'a>) -> Result<LabelIndex> {
+        let offset = self.peek_offset();
+        let index = match self.next_kind()? {
+            TokenKind::Id(id) => {
+                let position = scope.labels.iter().rposition(|label| *label == Some(id))
+                    .ok_or(TextError::UnknownIdentifier(offset))?;
+                (scope.labels.len() - 1 - position) as u32
+            },
+            TokenKind::Atom(atom) => parse_u32(atom).ok_or(TextError::InvalidNumber(offset))?,
+            _ => return Err(TextError::Expected("label", offset)),
+        };
+        Ok(index.into())
+    }
+
+    fn parse_local(&mut self, scope: &Scope<'a>) -> Result<u32> {
+        let offset = self.peek_offset();
+        match self.next_kind()? {
+            TokenKind::Id(id) => scope.locals.get(id).copied().ok_or(TextError::UnknownIdentifier(offset)),
+            TokenKind::Atom(atom) => parse_u32(atom).ok_or(TextError::InvalidNumber(offset)),
+            _ => Err(TextError::Expected("local", offset)),
+        }
+    }
+
+    pub(crate) fn parse_number<T>(&mut self, parse: fn(&str) -> Option<T>) -> Result<T> {
+        let (atom, offset) = self.expect_atom()?;
+        parse(atom).ok_or(TextError::InvalidNumber(offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{MemoryArgument, MemoryIndex};
+    use crate::text::instruction::{PLAIN_INSTRUCTIONS, MEMORY_INSTRUCTIONS, memory_instruction};
+
+    #[test]
+    fn tables_agree_with_instruction_names() {
+        for (name, instruction) in PLAIN_INSTRUCTIONS {
+            assert_eq!(instruction.name(), *name);
+        }
+        for (name, natural_alignment) in MEMORY_INSTRUCTIONS {
+            let memory_argument = MemoryArgument { alignment: *natural_alignment, offset: 0, memory_index: MemoryIndex(0) };
+            assert_eq!(memory_instruction(name, memory_argument).name(), *name);
+        }
+    }
+}
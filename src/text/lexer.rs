@@ -0,0 +1,214 @@
+use crate::text::TextError;
+use crate::text::parser::Result;
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum TokenKind<'a> {
+    LeftParen,
+    RightParen,
+    /// A `$name`, without the leading `$`.
+    Id(&'a str),
+    /// Keywords and numbers, which are told apart by the parser.
+    Atom(&'a str),
+    String(Vec<u8>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct Token<'a> {
+    pub(crate) kind: TokenKind<'a>,
+    pub(crate) offset: usize,
+}
+
+pub(crate) struct Lexer<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Lexer<'a> {
+    pub(crate) fn new(source: &'a str) -> Lexer<'a> {
+        Lexer { source, position: 0 }
+    }
+
+    pub(crate) fn tokenize(source: &'a str) -> Result<Vec<Token<'a>>> {
+        let mut lexer = Lexer::new(source);
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token()? {
+            tokens.push(token);
+        }
+        Ok(tokens)
+    }
+
+    fn peek_byte(&self) -> Option<u8> {
+        self.source.as_bytes().get(self.position).copied()
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<()> {
+        let bytes = self.source.as_bytes();
+        loop {
+            match self.peek_byte() {
+                Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') => self.position += 1,
+                Some(b';') if bytes.get(self.position + 1) == Some(&b';') => {
+                    while let Some(byte) = self.peek_byte() {
+                        if byte == b'\n' {
+                            break;
+                        }
+                        self.position += 1;
+                    }
+                },
+                Some(b'(') if bytes.get(self.position + 1) == Some(&b';') => self.skip_block_comment()?,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn skip_block_comment(&mut self) -> Result<()> {
+        let bytes = self.source.as_bytes();
+        let start = self.position;
+        let mut depth = 0;
+        loop {
+            match (bytes.get(self.position), bytes.get(self.position + 1)) {
+                (Some(b'('), Some(b';')) => {
+                    depth += 1;
+                    self.position += 2;
+                },
+                (Some(b';'), Some(b')')) => {
+                    depth -= 1;
+                    self.position += 2;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                },
+                (Some(_), _) => self.position += 1,
+                (None, _) => return Err(TextError::UnterminatedComment(start)),
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'a>>> {
+        self.skip_whitespace_and_comments()?;
+        let offset = self.position;
+        let kind = match self.peek_byte() {
+            None => return Ok(None),
+            Some(b'(') => {
+                self.position += 1;
+                TokenKind::LeftParen
+            },
+            Some(b')') => {
+                self.position += 1;
+                TokenKind::RightParen
+            },
+            Some(b'"') => TokenKind::String(self.read_string()?),
+            Some(byte) if is_idchar(byte) => {
+                while self.peek_byte().is_some_and(is_idchar) {
+                    self.position += 1;
+                }
+                let text = &self.source[offset..self.position];
+                match text.strip_prefix('$') {
+                    Some("") => return Err(TextError::UnexpectedCharacter(offset)),
+                    Some(id) => TokenKind::Id(id),
+                    None => TokenKind::Atom(text),
+                }
+            },
+            Some(_) => return Err(TextError::UnexpectedCharacter(offset)),
+        };
+        Ok(Some(Token { kind, offset }))
+    }
+
+    fn read_string(&mut self) -> Result<Vec<u8>> {
+        let start = self.position;
+        let bytes = self.source.as_bytes();
+        let mut string = Vec::new();
+        self.position += 1;
+        loop {
+            let byte = match bytes.get(self.position) {
+                Some(&byte) => byte,
+                None => return Err(TextError::UnterminatedString(start)),
+            };
+            self.position += 1;
+            match byte {
+                b'"' => return Ok(string),
+                b'\n' => return Err(TextError::UnterminatedString(start)),
+                b'\\' => {
+                    let escape_offset = self.position - 1;
+                    let escaped = *bytes.get(self.position).ok_or(TextError::UnterminatedString(start))?;
+                    self.position += 1;
+                    match escaped {
+                        b'n' => string.push(b'\n'),
+                        b't' => string.push(b'\t'),
+                        b'r' => string.push(b'\r'),
+                        b'"' => string.push(b'"'),
+                        b'\'' => string.push(b'\''),
+                        b'\\' => string.push(b'\\'),
+                        b'u' => {
+                            let code_point = self.read_unicode_escape(escape_offset)?;
+                            let mut buffer = [0; 4];
+                            string.extend_from_slice(code_point.encode_utf8(&mut buffer).as_bytes());
+                        },
+                        high => {
+                            let low = bytes.get(self.position).copied();
+                            match (hex_digit(high), low.and_then(hex_digit)) {
+                                (Some(high), Some(low)) => {
+                                    self.position += 1;
+                                    string.push(high << 4 | low);
+                                },
+                                _ => return Err(TextError::InvalidEscape(escape_offset)),
+                            }
+                        },
+                    }
+                },
+                byte => string.push(byte),
+            }
+        }
+    }
+
+    fn read_unicode_escape(&mut self, escape_offset: usize) -> Result<char> {
+        let rest = &self.source[self.position..];
+        let digits = rest.strip_prefix('{')
+            .and_then(|rest| rest.find('}').map(|end| &rest[..end]))
+            .ok_or(TextError::InvalidEscape(escape_offset))?;
+        self.position += digits.len() + 2;
+        u32::from_str_radix(&digits.replace('_', ""), 16).ok()
+            .and_then(char::from_u32)
+            .ok_or(TextError::InvalidEscape(escape_offset))
+    }
+}
+
+pub(crate) fn is_idchar(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"!#$%&'*+-./:<=>?@\\^_`|~".contains(&byte)
+}
+
+fn hex_digit(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::text::lexer::{Lexer, TokenKind};
+    use crate::text::TextError;
+
+    fn kinds(source: &str) -> Vec<TokenKind<'_>> {
+        Lexer::tokenize(source).unwrap().into_iter().map(|token| token.kind).collect()
+    }
+
+    #[test]
+    fn tokens_and_comments() {
+        let source = "(module ;; line comment\n (; block (; nested ;) ;) $id i32.const -0x1F \"a\\n\\41\\u{e9}\")";
+        let expected = vec![
+            TokenKind::LeftParen,
+            TokenKind::Atom("module"),
+            TokenKind::Id("id"),
+            TokenKind::Atom("i32.const"),
+            TokenKind::Atom("-0x1F"),
+            TokenKind::String(b"a\nA\xC3\xA9".to_vec()),
+            TokenKind::RightParen,
+        ];
+        assert_eq!(expected, kinds(source));
+    }
+
+    #[test]
+    fn errors_have_offsets() {
+        assert_eq!(Err(TextError::UnterminatedString(3)), Lexer::tokenize("(a \"bc"));
+        assert_eq!(Err(TextError::UnterminatedComment(1)), Lexer::tokenize(" (; never closed"));
+        assert_eq!(Err(TextError::InvalidEscape(2)), Lexer::tokenize(" \"\\q\""));
+        assert_eq!(Err(TextError::UnexpectedCharacter(2)), Lexer::tokenize("a {"));
+    }
+}
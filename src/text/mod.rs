@@ -0,0 +1,8 @@
+pub use crate::text::parser::{parse, TextError};
+pub use crate::text::printer::{print, PrintError};
+
+mod instruction;
+mod lexer;
+mod number;
+mod parser;
+mod printer;
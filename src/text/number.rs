@@ -0,0 +1,258 @@
+//! Parsing of the integer and float literals of the text format.
+
+use std::convert::TryFrom;
+
+fn split_sign(text: &str) -> (bool, &str) {
+    if let Some(rest) = text.strip_prefix('-') {
+        (true, rest)
+    } else {
+        (false, text.strip_prefix('+').unwrap_or(text))
+    }
+}
+
+/// Parses an unsigned decimal or `0x` hexadecimal literal, with `_` separators.
+fn parse_unsigned(text: &str) -> Option<u64> {
+    let (digits, radix) = match text.strip_prefix("0x") {
+        Some(digits) => (digits, 16),
+        None => (text, 10),
+    };
+    if digits.is_empty() || digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+        return None;
+    }
+    if !digits.chars().all(|c| c == '_' || c.is_digit(radix)) {
+        return None;
+    }
+    u64::from_str_radix(&digits.replace('_', ""), radix).ok()
+}
+
+pub(crate) fn parse_u32(text: &str) -> Option<u32> {
+    if text.starts_with('+') || text.starts_with('-') {
+        return None;
+    }
+    parse_unsigned(text).and_then(|value| u32::try_from(value).ok())
+}
+
+pub(crate) fn parse_u64(text: &str) -> Option<u64> {
+    if text.starts_with('+') || text.starts_with('-') {
+        return None;
+    }
+    parse_unsigned(text)
+}
+
+/// Parses an `i32` literal, which may be written signed or as its unsigned bit pattern.
+pub(crate) fn parse_i32(text: &str) -> Option<i32> {
+    let (negative, magnitude) = split_sign(text);
+    let magnitude = parse_unsigned(magnitude)?;
+    if negative {
+        if magnitude <= 1 << 31 {
+            Some((magnitude as i64).wrapping_neg() as i32)
+        } else {
+            None
+        }
+    } else {
+        u32::try_from(magnitude).ok().map(|value| value as i32)
+    }
+}
+
+/// Parses an `i64` literal, which may be written signed or as its unsigned bit pattern.
+pub(crate) fn parse_i64(text: &str) -> Option<i64> {
+    let (negative, magnitude) = split_sign(text);
+    let magnitude = parse_unsigned(magnitude)?;
+    if negative {
+        if magnitude <= 1 << 63 {
+            Some(magnitude.wrapping_neg() as i64)
+        } else {
+            None
+        }
+    } else {
+        Some(magnitude as i64)
+    }
+}
+
+/// Parses a literal of at most `bits` bits, written signed or as its unsigned bit pattern.
+fn parse_narrow(text: &str, bits: u32) -> Option<i64> {
+    let (negative, magnitude) = split_sign(text);
+    let magnitude = parse_unsigned(magnitude)?;
+    if negative {
+        if magnitude <= 1 << (bits - 1) {
+            Some(-(magnitude as i64))
+        } else {
+            None
+        }
+    } else if magnitude < 1 << bits {
+        Some(magnitude as i64)
+    } else {
+        None
+    }
+}
+
+/// Parses an `i8` lane of `v128.const`.
+pub(crate) fn parse_i8(text: &str) -> Option<i8> {
+    parse_narrow(text, 8).map(|value| value as i8)
+}
+
+/// Parses an `i16` lane of `v128.const`.
+pub(crate) fn parse_i16(text: &str) -> Option<i16> {
+    parse_narrow(text, 16).map(|value| value as i16)
+}
+
+pub(crate) fn parse_f32(text: &str) -> Option<f32> {
+    let (negative, magnitude) = split_sign(text);
+    let value = if let Some(payload) = magnitude.strip_prefix("nan:0x") {
+        let payload = u32::try_from(parse_unsigned(&format!("0x{}", payload))?).ok()?;
+        if payload == 0 || payload >= 1 << 23 {
+            return None;
+        }
+        f32::from_bits(0x7F80_0000 | payload)
+    } else {
+        match magnitude {
+            "inf" => f32::INFINITY,
+            "nan" => f32::NAN,
+            _ if magnitude.starts_with("0x") => f32::from_bits(parse_hex_float(magnitude, 23, 8)? as u32),
+            // Literals rounding to infinity are out of range rather than `inf`.
+            _ => parse_decimal_float(magnitude)?.parse::<f32>().ok().filter(|value| value.is_finite())?,
+        }
+    };
+    Some(if negative { -value } else { value })
+}
+
+pub(crate) fn parse_f64(text: &str) -> Option<f64> {
+    let (negative, magnitude) = split_sign(text);
+    let value = if let Some(payload) = magnitude.strip_prefix("nan:0x") {
+        let payload = parse_unsigned(&format!("0x{}", payload))?;
+        if payload == 0 || payload >= 1 << 52 {
+            return None;
+        }
+        f64::from_bits(0x7FF0_0000_0000_0000 | payload)
+    } else {
+        match magnitude {
+            "inf" => f64::INFINITY,
+            "nan" => f64::NAN,
+            _ if magnitude.starts_with("0x") => f64::from_bits(parse_hex_float(magnitude, 52, 11)?),
+            _ => parse_decimal_float(magnitude)?.parse::<f64>().ok().filter(|value| value.is_finite())?,
+        }
+    };
+    Some(if negative { -value } else { value })
+}
+
+/// Checks the shape of a decimal float and strips its `_` separators, since
+/// `str::parse` accepts spellings such as `infinity` the text format doesn't.
+fn parse_decimal_float(text: &str) -> Option<String> {
+    let valid = text.starts_with(|c: char| c.is_ascii_digit())
+        && text.chars().all(|c| c.is_ascii_digit() || "._eE+-".contains(c));
+    if valid {
+        Some(text.replace('_', ""))
+    } else {
+        None
+    }
+}
+
+/// Parses a hexadecimal float into the bits of a float with `mantissa_bits`
+/// explicit mantissa bits and `exponent_bits` exponent bits, rounding to
+/// nearest even once. Literals rounding to infinity are out of range.
+fn parse_hex_float(text: &str, mantissa_bits: u32, exponent_bits: u32) -> Option<u64> {
+    let text = text.strip_prefix("0x")?.replace('_', "");
+    let (mantissa, exponent) = match text.find(['p', 'P']) {
+        Some(position) => (&text[..position], text[position + 1..].parse::<i32>().ok()?),
+        None => (&text[..], 0),
+    };
+    let (integer, fraction) = match mantissa.find('.') {
+        Some(position) => (&mantissa[..position], &mantissa[position + 1..]),
+        None => (mantissa, ""),
+    };
+    if integer.is_empty() {
+        return None;
+    }
+
+    // The value is `significand * 2^exponent`, plus less than one unit of
+    // `significand` when `sticky` is set by digits that didn't fit.
+    let mut significand = 0u64;
+    let mut exponent = exponent as i64;
+    let mut sticky = false;
+    for (position, digit) in integer.chars().chain(fraction.chars()).enumerate() {
+        let digit = digit.to_digit(16)? as u64;
+        let in_fraction = position >= integer.len();
+        if significand >> 60 == 0 {
+            significand = significand * 16 + digit;
+            if in_fraction {
+                exponent -= 4;
+            }
+        } else {
+            sticky |= digit != 0;
+            if !in_fraction {
+                exponent += 4;
+            }
+        }
+    }
+    if significand == 0 {
+        return Some(0);
+    }
+
+    // Only keep the bits above the unit in the last place of the result,
+    // which subnormals fix at that of the smallest normal number.
+    let bias = (1i64 << (exponent_bits - 1)) - 1;
+    let top_bit = exponent + 63 - significand.leading_zeros() as i64;
+    let unit = top_bit.max(1 - bias) - mantissa_bits as i64;
+    let shift = unit - exponent;
+    let mantissa = if shift <= 0 {
+        significand << -shift
+    } else if shift > 64 {
+        0
+    } else {
+        let significand = significand as u128;
+        let kept = (significand >> shift) as u64;
+        let rest = significand & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        if rest > half || (rest == half && (sticky || kept & 1 == 1)) {
+            kept + 1
+        } else {
+            kept
+        }
+    };
+
+    // A mantissa carrying into the hidden bit bumps the exponent field, so
+    // subnormals rounding up to the smallest normal number come out right.
+    let biased_exponent = unit + mantissa_bits as i64 + bias - 1;
+    let infinity_exponent = (1i64 << exponent_bits) - 1;
+    if biased_exponent >= infinity_exponent {
+        return None;
+    }
+    let bits = ((biased_exponent as u64) << mantissa_bits) + mantissa;
+    if bits >= (infinity_exponent as u64) << mantissa_bits {
+        None
+    } else {
+        Some(bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::text::number::{parse_f32, parse_f64};
+
+    #[test]
+    fn hex_floats_round_once_to_nearest_even() {
+        assert_eq!(parse_f64("0x1p-1074").map(f64::to_bits), Some(1));
+        assert_eq!(parse_f64("0x1p-1075").map(f64::to_bits), Some(0));
+        assert_eq!(parse_f64("0x1.8p-1075").map(f64::to_bits), Some(1));
+        assert_eq!(parse_f64("0x1.fffffffffffffp-1023"), Some(f64::MIN_POSITIVE));
+        assert_eq!(parse_f32("0x1p-149").map(f32::to_bits), Some(1));
+        assert_eq!(parse_f32("0x1p-126"), Some(f32::MIN_POSITIVE));
+
+        // 20 digits don't fit the 53 bits of an f64, yet the last one decides the rounding.
+        assert_eq!(parse_f64("0x1.0000000000000800000p0"), Some(1.0));
+        assert_eq!(parse_f64("0x1.0000000000000800001p0"), Some(1.0 + f64::EPSILON));
+        assert_eq!(parse_f64("0x1234_5678_9abc_def0_1234p0"), Some(0x1234_5678_9abc_def0_1234u128 as f64));
+
+        // Halfway cases round to even, and anything above them rounds up,
+        // even when rounding through an f64 first would make it a tie.
+        assert_eq!(parse_f32("0x1.000001p0"), Some(1.0));
+        assert_eq!(parse_f32("0x1.000003p0"), Some(1.0 + 2.0 * f32::EPSILON));
+        assert_eq!(parse_f32("0x1.00000100000001p0"), Some(1.0 + f32::EPSILON));
+
+        assert_eq!(parse_f32("0x1.fffffep127"), Some(f32::MAX));
+        assert_eq!(parse_f32("0x1.ffffffp127"), None);
+        assert_eq!(parse_f64("0x1.fffffffffffffp1023"), Some(f64::MAX));
+        assert_eq!(parse_f64("0x1p1024"), None);
+        assert_eq!(parse_f64("-0x0.0p0").map(f64::to_bits), Some(1 << 63));
+    }
+}
@@ -0,0 +1,1176 @@
+use std::collections::HashMap;
+use std::{error, fmt, result};
+use crate::encoder::{ModuleEncoder, TypeSectionEncoder, ImportSectionEncoder, FunctionSectionEncoder, TableSectionEncoder};
+use crate::encoder::{MemorySectionEncoder, TagSectionEncoder, GlobalSectionEncoder, ExportSectionEncoder, StartSectionEncoder};
+use crate::encoder::{ElementSectionEncoder, CodeSectionEncoder, DataSectionEncoder, DataCountSectionEncoder, FunctionBodyEncoder};
+use crate::types::{ValueType, TypeIndex, FuncIndex, Limits, TableType, MemoryType, GlobalType, Locals, Instruction};
+use crate::types::{ImportDescriptor, ExportDescriptor, TagType};
+use crate::text::lexer::{Lexer, Token, TokenKind};
+use crate::text::instruction::Sink;
+use crate::text::number::{parse_u32, parse_u64};
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum TextError {
+    UnexpectedCharacter(usize),
+    UnterminatedString(usize),
+    UnterminatedComment(usize),
+    InvalidEscape(usize),
+    InvalidUtf8(usize),
+    UnexpectedEof(usize),
+    Expected(&'static str, usize),
+    InvalidNumber(usize),
+    UnknownInstruction(usize),
+    UnknownIdentifier(usize),
+    DuplicateIdentifier(usize),
+    TypeUseMismatch(usize),
+}
+
+impl TextError {
+    /// Returns the byte offset in the source text at which the error was detected.
+    pub fn offset(&self) -> usize {
+        match self {
+            TextError::UnexpectedCharacter(offset) |
+            TextError::UnterminatedString(offset) |
+            TextError::UnterminatedComment(offset) |
+            TextError::InvalidEscape(offset) |
+            TextError::InvalidUtf8(offset) |
+            TextError::UnexpectedEof(offset) |
+            TextError::Expected(_, offset) |
+            TextError::InvalidNumber(offset) |
+            TextError::UnknownInstruction(offset) |
+            TextError::UnknownIdentifier(offset) |
+            TextError::DuplicateIdentifier(offset) |
+            TextError::TypeUseMismatch(offset) => *offset,
+        }
+    }
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextError::UnexpectedCharacter(offset) => write!(f, "unexpected character at offset {}", offset),
+            TextError::UnterminatedString(offset) => write!(f, "unterminated string starting at offset {}", offset),
+            TextError::UnterminatedComment(offset) => write!(f, "unterminated block comment starting at offset {}", offset),
+            TextError::InvalidEscape(offset) => write!(f, "invalid escape sequence at offset {}", offset),
+            TextError::InvalidUtf8(offset) => write!(f, "invalid UTF-8 in name at offset {}", offset),
+            TextError::UnexpectedEof(offset) => write!(f, "unexpected end of input at offset {}", offset),
+            TextError::Expected(expected, offset) => write!(f, "expected {} at offset {}", expected, offset),
+            TextError::InvalidNumber(offset) => write!(f, "invalid number at offset {}", offset),
+            TextError::UnknownInstruction(offset) => write!(f, "unknown instruction at offset {}", offset),
+            TextError::UnknownIdentifier(offset) => write!(f, "unknown identifier at offset {}", offset),
+            TextError::DuplicateIdentifier(offset) => write!(f, "duplicate identifier at offset {}", offset),
+            TextError::TypeUseMismatch(offset) => write!(f, "inline signature doesn't match the type at offset {}", offset),
+        }
+    }
+}
+
+impl error::Error for TextError {}
+
+pub type Result<T, E = TextError> = result::Result<T, E>;
+
+/// Parses a module in the text format and lowers it to the binary format.
+pub fn parse(source: &str) -> Result<Vec<u8>> {
+    let tokens = Lexer::tokenize(source)?;
+    let mut parser = TextParser::new(tokens, source.len());
+    parser.parse_module()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Space {
+    Type,
+    Func,
+    Table,
+    Memory,
+    Tag,
+    Global,
+    Elem,
+    Data,
+}
+
+/// Local names and enclosing block labels visible to an instruction.
+#[derive(Default)]
+pub(crate) struct Scope<'a> {
+    pub(crate) locals: HashMap<&'a str, u32>,
+    pub(crate) labels: Vec<Option<&'a str>>,
+}
+
+type Name<'a> = Option<(&'a str, usize)>;
+
+enum ElementList {
+    Functions(Vec<FuncIndex>),
+    Expressions(Vec<Vec<Instruction<'static>>>),
+}
+
+/// The names of one index space, in which imports come before definitions
+/// whatever their order in the text.
+#[derive(Default)]
+struct IndexSpace<'a> {
+    names: HashMap<&'a str, u32>,
+    imported: Vec<Name<'a>>,
+    defined: Vec<Name<'a>>,
+    next_imported: u32,
+    next_defined: u32,
+}
+
+impl<'a> IndexSpace<'a> {
+    fn assign_indices(&mut self) -> Result<()> {
+        for (index, name) in self.imported.iter().chain(self.defined.iter()).enumerate() {
+            if let Some((name, offset)) = name {
+                if self.names.insert(name, index as u32).is_some() {
+                    return Err(TextError::DuplicateIdentifier(*offset));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn next_index(&mut self, imported: bool) -> u32 {
+        if imported {
+            self.next_imported += 1;
+            self.next_imported - 1
+        } else {
+            self.next_defined += 1;
+            self.imported.len() as u32 + self.next_defined - 1
+        }
+    }
+}
+
+#[derive(Default)]
+struct Sections {
+    imports: ImportSectionEncoder,
+    functions: FunctionSectionEncoder,
+    tables: TableSectionEncoder,
+    memories: MemorySectionEncoder,
+    tags: TagSectionEncoder,
+    globals: GlobalSectionEncoder,
+    exports: ExportSectionEncoder,
+    start: Option<StartSectionEncoder>,
+    elements: ElementSectionEncoder,
+    code: CodeSectionEncoder,
+    data: DataSectionEncoder,
+    /// Set when an instruction refers to a data segment, which requires a
+    /// data count section.
+    uses_data_indices: bool,
+}
+
+pub(crate) struct TextParser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    source_len: usize,
+    types: Vec<(Vec<ValueType>, Vec<ValueType>)>,
+    type_names: HashMap<&'a str, u32>,
+    funcs: IndexSpace<'a>,
+    tables: IndexSpace<'a>,
+    memories: IndexSpace<'a>,
+    tags: IndexSpace<'a>,
+    globals: IndexSpace<'a>,
+    elems: IndexSpace<'a>,
+    datas: IndexSpace<'a>,
+    sections: Sections,
+}
+
+impl<'a> TextParser<'a> {
+    fn new(tokens: Vec<Token<'a>>, source_len: usize) -> TextParser<'a> {
+        TextParser {
+            tokens,
+            position: 0,
+            source_len,
+            types: Vec::new(),
+            type_names: HashMap::new(),
+            funcs: IndexSpace::default(),
+            tables: IndexSpace::default(),
+            memories: IndexSpace::default(),
+            tags: IndexSpace::default(),
+            globals: IndexSpace::default(),
+            elems: IndexSpace::default(),
+            datas: IndexSpace::default(),
+            sections: Sections::default(),
+        }
+    }
+
+    pub(crate) fn peek_kind(&self) -> Option<&TokenKind<'a>> {
+        self.tokens.get(self.position).map(|token| &token.kind)
+    }
+
+    /// Returns the kind of the token `distance` tokens after the next one.
+    pub(crate) fn peek_kind_at(&self, distance: usize) -> Option<&TokenKind<'a>> {
+        self.tokens.get(self.position + distance).map(|token| &token.kind)
+    }
+
+    pub(crate) fn peek_offset(&self) -> usize {
+        self.tokens.get(self.position).map_or(self.source_len, |token| token.offset)
+    }
+
+    pub(crate) fn next_kind(&mut self) -> Result<TokenKind<'a>> {
+        let token = self.tokens.get(self.position).ok_or(TextError::UnexpectedEof(self.source_len))?;
+        self.position += 1;
+        Ok(token.kind.clone())
+    }
+
+    pub(crate) fn expect_left_paren(&mut self) -> Result<()> {
+        let offset = self.peek_offset();
+        match self.next_kind()? {
+            TokenKind::LeftParen => Ok(()),
+            _ => Err(TextError::Expected("`(`", offset)),
+        }
+    }
+
+    pub(crate) fn expect_right_paren(&mut self) -> Result<()> {
+        let offset = self.peek_offset();
+        match self.next_kind()? {
+            TokenKind::RightParen => Ok(()),
+            _ => Err(TextError::Expected("`)`", offset)),
+        }
+    }
+
+    pub(crate) fn expect_atom(&mut self) -> Result<(&'a str, usize)> {
+        let offset = self.peek_offset();
+        match self.next_kind()? {
+            TokenKind::Atom(atom) => Ok((atom, offset)),
+            _ => Err(TextError::Expected("keyword", offset)),
+        }
+    }
+
+    pub(crate) fn expect_keyword(&mut self, keyword: &'static str) -> Result<()> {
+        let offset = self.peek_offset();
+        match self.next_kind()? {
+            TokenKind::Atom(atom) if atom == keyword => Ok(()),
+            _ => Err(TextError::Expected(keyword, offset)),
+        }
+    }
+
+    pub(crate) fn take_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek_kind() == Some(&TokenKind::Atom(keyword)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub(crate) fn take_id(&mut self) -> Option<&'a str> {
+        self.take_name().map(|(id, _)| id)
+    }
+
+    fn take_name(&mut self) -> Name<'a> {
+        match self.tokens.get(self.position) {
+            Some(Token { kind: TokenKind::Id(id), offset }) => {
+                self.position += 1;
+                Some((id, *offset))
+            },
+            _ => None,
+        }
+    }
+
+    /// Tells whether the next tokens open a list starting with `keyword`.
+    pub(crate) fn peek_list(&self, keyword: &str) -> bool {
+        self.peek_kind() == Some(&TokenKind::LeftParen)
+            && self.tokens.get(self.position + 1).map(|token| &token.kind) == Some(&TokenKind::Atom(keyword))
+    }
+
+    pub(crate) fn expect_list(&mut self, keyword: &'static str) -> Result<()> {
+        self.expect_left_paren()?;
+        self.expect_keyword(keyword)
+    }
+
+    fn expect_string(&mut self) -> Result<Vec<u8>> {
+        let offset = self.peek_offset();
+        match self.next_kind()? {
+            TokenKind::String(string) => Ok(string),
+            _ => Err(TextError::Expected("string", offset)),
+        }
+    }
+
+    fn expect_name(&mut self) -> Result<String> {
+        let offset = self.peek_offset();
+        String::from_utf8(self.expect_string()?).map_err(|_| TextError::InvalidUtf8(offset))
+    }
+
+    /// Skips the remaining tokens of a list whose opening parenthesis was consumed.
+    fn skip_rest_of_list(&mut self) -> Result<()> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next_kind()? {
+                TokenKind::LeftParen => depth += 1,
+                TokenKind::RightParen => depth -= 1,
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_module(&mut self) -> Result<Vec<u8>> {
+        let wrapped = self.peek_list("module");
+        if wrapped {
+            self.expect_list("module")?;
+            self.take_id();
+        }
+        let fields_start = self.position;
+        self.collect_names()?;
+        self.position = fields_start;
+        while self.peek_kind() == Some(&TokenKind::LeftParen) {
+            self.parse_field()?;
+        }
+        if wrapped {
+            self.expect_right_paren()?;
+        }
+        if self.position < self.tokens.len() {
+            return Err(TextError::Expected("end of input", self.peek_offset()));
+        }
+        Ok(self.encode_module())
+    }
+
+    /// Assigns indices to every named type, function, table, memory, tag,
+    /// global and segment, so that fields can refer to items declared after them.
+    fn collect_names(&mut self) -> Result<()> {
+        while self.peek_kind() == Some(&TokenKind::LeftParen) {
+            self.expect_left_paren()?;
+            let (keyword, _) = self.expect_atom()?;
+            match keyword {
+                "type" => {
+                    let name = self.take_name();
+                    self.expect_list("func")?;
+                    let (params, results) = self.parse_signature(&mut Vec::new(), true)?;
+                    self.expect_right_paren()?;
+                    if let Some((name, offset)) = name {
+                        if self.type_names.insert(name, self.types.len() as u32).is_some() {
+                            return Err(TextError::DuplicateIdentifier(offset));
+                        }
+                    }
+                    self.types.push((params, results));
+                },
+                "import" => {
+                    self.expect_string()?;
+                    self.expect_string()?;
+                    self.expect_left_paren()?;
+                    let (kind, offset) = self.expect_atom()?;
+                    let name = self.take_name();
+                    self.index_space(kind, offset)?.imported.push(name);
+                    self.skip_rest_of_list()?;
+                },
+                "func" | "table" | "memory" | "tag" | "global" => {
+                    let name = self.take_name();
+                    while self.peek_list("export") {
+                        self.expect_left_paren()?;
+                        self.skip_rest_of_list()?;
+                    }
+                    let imported = self.peek_list("import");
+                    // Inline `elem` and `data` lists define unnamed segments.
+                    match (keyword, self.peek_kind()) {
+                        ("table", Some(TokenKind::Atom("funcref"))) | ("table", Some(TokenKind::Atom("anyfunc"))) => {
+                            self.elems.defined.push(None);
+                        },
+                        ("memory", _) if self.peek_list("data") => self.datas.defined.push(None),
+                        _ => {},
+                    }
+                    let space = self.index_space(keyword, 0)?;
+                    if imported {
+                        space.imported.push(name);
+                    } else {
+                        space.defined.push(name);
+                    }
+                },
+                "elem" => {
+                    let name = self.take_name();
+                    self.elems.defined.push(name);
+                },
+                "data" => {
+                    let name = self.take_name();
+                    self.datas.defined.push(name);
+                },
+                _ => {},
+            }
+            self.skip_rest_of_list()?;
+        }
+        self.funcs.assign_indices()?;
+        self.tables.assign_indices()?;
+        self.memories.assign_indices()?;
+        self.tags.assign_indices()?;
+        self.globals.assign_indices()?;
+        self.elems.assign_indices()?;
+        self.datas.assign_indices()
+    }
+
+    fn index_space(&mut self, kind: &str, offset: usize) -> Result<&mut IndexSpace<'a>> {
+        match kind {
+            "func" => Ok(&mut self.funcs),
+            "table" => Ok(&mut self.tables),
+            "memory" => Ok(&mut self.memories),
+            "tag" => Ok(&mut self.tags),
+            "global" => Ok(&mut self.globals),
+            _ => Err(TextError::Expected("import kind", offset)),
+        }
+    }
+
+    fn parse_field(&mut self) -> Result<()> {
+        self.expect_left_paren()?;
+        let (keyword, offset) = self.expect_atom()?;
+        match keyword {
+            "type" => self.skip_rest_of_list(),
+            "import" => self.parse_import(),
+            "func" => self.parse_func(),
+            "table" => self.parse_table(),
+            "memory" => self.parse_memory(),
+            "tag" => self.parse_tag(),
+            "global" => self.parse_global(),
+            "export" => self.parse_export(),
+            "start" => {
+                let func_index = self.parse_index(Space::Func)?;
+                self.sections.start = Some(StartSectionEncoder::new(func_index.into()));
+                self.expect_right_paren()
+            },
+            "elem" => self.parse_elem(),
+            "data" => self.parse_data(),
+            _ => Err(TextError::Expected("module field", offset)),
+        }
+    }
+
+    fn parse_import(&mut self) -> Result<()> {
+        let module_name = self.expect_name()?;
+        let name = self.expect_name()?;
+        self.expect_left_paren()?;
+        let (kind, offset) = self.expect_atom()?;
+        self.take_id();
+        self.index_space(kind, offset)?.next_index(true);
+        let import_descriptor = self.parse_import_descriptor(kind)?;
+        self.expect_right_paren()?;
+        self.expect_right_paren()?;
+        self.sections.imports.import(&module_name, &name, &import_descriptor);
+        Ok(())
+    }
+
+    fn parse_import_descriptor(&mut self, kind: &str) -> Result<ImportDescriptor> {
+        Ok(match kind {
+            "func" => ImportDescriptor::Func { type_index: self.parse_type_use()?.0 },
+            "table" => ImportDescriptor::Table(self.parse_table_type()?),
+            "memory" => ImportDescriptor::Memory(self.parse_memory_type()?),
+            "tag" => ImportDescriptor::Tag(TagType::new(self.parse_type_use()?.0)),
+            _ => ImportDescriptor::Global(self.parse_global_type()?),
+        })
+    }
+
+    fn parse_inline_exports(&mut self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        while self.peek_list("export") {
+            self.expect_list("export")?;
+            names.push(self.expect_name()?);
+            self.expect_right_paren()?;
+        }
+        Ok(names)
+    }
+
+    fn parse_inline_import(&mut self) -> Result<Option<(String, String)>> {
+        if !self.peek_list("import") {
+            return Ok(None);
+        }
+        self.expect_list("import")?;
+        let module_name = self.expect_name()?;
+        let name = self.expect_name()?;
+        self.expect_right_paren()?;
+        Ok(Some((module_name, name)))
+    }
+
+    /// Parses the optional id, inline exports and inline import shared by functions,
+    /// tables, memories, tags and globals, returning the index of the item.
+    fn parse_item_header(&mut self, kind: &str, export: fn(u32) -> ExportDescriptor) -> Result<(u32, Option<(String, String)>)> {
+        self.take_id();
+        let export_names = self.parse_inline_exports()?;
+        let import = self.parse_inline_import()?;
+        let index = self.index_space(kind, 0)?.next_index(import.is_some());
+        for name in export_names {
+            self.sections.exports.export(&name, &export(index));
+        }
+        Ok((index, import))
+    }
+
+    fn parse_func(&mut self) -> Result<()> {
+        let (_, import) = self.parse_item_header("func", |index| ExportDescriptor::Func { func_index: index.into() })?;
+        if let Some((module_name, name)) = import {
+            let type_index = self.parse_type_use()?.0;
+            self.sections.imports.import(&module_name, &name, &ImportDescriptor::Func { type_index });
+            return self.expect_right_paren();
+        }
+        let (type_index, param_names) = self.parse_type_use()?;
+        let mut scope = Scope::default();
+        let mut local_names = param_names;
+        let locals = self.parse_locals(&mut local_names)?;
+        for (index, name) in local_names.iter().enumerate() {
+            if let Some((name, offset)) = name {
+                if scope.locals.insert(name, index as u32).is_some() {
+                    return Err(TextError::DuplicateIdentifier(*offset));
+                }
+            }
+        }
+        let mut body = FunctionBodyEncoder::new(&locals);
+        self.parse_instructions(&mut scope, &mut Sink::Body(&mut body))?;
+        body.instruction(&Instruction::End);
+        self.expect_right_paren()?;
+        self.sections.functions.type_index(type_index);
+        self.sections.code.function(&body);
+        Ok(())
+    }
+
+    fn parse_locals(&mut self, names: &mut Vec<Name<'a>>) -> Result<Vec<Locals>> {
+        let mut locals: Vec<Locals> = Vec::new();
+        while self.peek_list("local") {
+            self.expect_list("local")?;
+            let value_types = self.parse_value_types(names, true)?;
+            for value_type in value_types {
+                match locals.last_mut() {
+                    Some(last) if last.value_type == value_type => last.count += 1,
+                    _ => locals.push(Locals { count: 1, value_type }),
+                }
+            }
+            self.expect_right_paren()?;
+        }
+        Ok(locals)
+    }
+
+    fn parse_table(&mut self) -> Result<()> {
+        let (index, import) = self.parse_item_header("table", |index| ExportDescriptor::Table { table_index: index.into() })?;
+        if let Some((module_name, name)) = import {
+            let table_type = self.parse_table_type()?;
+            self.sections.imports.import(&module_name, &name, &ImportDescriptor::Table(table_type));
+            return self.expect_right_paren();
+        }
+        if let Some(TokenKind::Atom("funcref")) | Some(TokenKind::Atom("anyfunc")) = self.peek_kind() {
+            self.position += 1;
+            self.expect_list("elem")?;
+            let function_indices = self.parse_function_indices()?;
+            self.expect_right_paren()?;
+            let size = function_indices.len() as u64;
+            self.sections.tables.table(&TableType::new(ValueType::FuncRef, Limits::new(size, Some(size))));
+            self.sections.elements.segment(index.into(), &[Instruction::I32Const(0)], &function_indices);
+        } else {
+            let table_type = self.parse_table_type()?;
+            self.sections.tables.table(&table_type);
+        }
+        self.expect_right_paren()
+    }
+
+    fn parse_memory(&mut self) -> Result<()> {
+        let (index, import) = self.parse_item_header("memory", |index| ExportDescriptor::Memory { memory_index: index.into() })?;
+        if let Some((module_name, name)) = import {
+            let memory_type = self.parse_memory_type()?;
+            self.sections.imports.import(&module_name, &name, &ImportDescriptor::Memory(memory_type));
+            return self.expect_right_paren();
+        }
+        let memory64 = self.parse_index_type();
+        if self.peek_list("data") {
+            self.expect_list("data")?;
+            let bytes = self.parse_strings()?;
+            self.expect_right_paren()?;
+            let pages = bytes.len().div_ceil(0x10000) as u64;
+            let limits = Limits::new(pages, Some(pages));
+            if memory64 {
+                self.sections.memories.memory(&MemoryType::new_64(limits));
+                self.sections.data.segment(index.into(), &[Instruction::I64Const(0)], &bytes);
+            } else {
+                self.sections.memories.memory(&MemoryType::new(limits));
+                self.sections.data.segment(index.into(), &[Instruction::I32Const(0)], &bytes);
+            }
+        } else {
+            let memory_type = self.parse_memory_limits(memory64)?;
+            self.sections.memories.memory(&memory_type);
+        }
+        self.expect_right_paren()
+    }
+
+    fn parse_tag(&mut self) -> Result<()> {
+        let (_, import) = self.parse_item_header("tag", |index| ExportDescriptor::Tag { tag_index: index.into() })?;
+        let tag_type = TagType::new(self.parse_type_use()?.0);
+        match import {
+            Some((module_name, name)) => {
+                self.sections.imports.import(&module_name, &name, &ImportDescriptor::Tag(tag_type));
+            },
+            None => {
+                self.sections.tags.tag(&tag_type);
+            },
+        }
+        self.expect_right_paren()
+    }
+
+    fn parse_global(&mut self) -> Result<()> {
+        let (_, import) = self.parse_item_header("global", |index| ExportDescriptor::Global { global_index: index.into() })?;
+        let global_type = self.parse_global_type()?;
+        if let Some((module_name, name)) = import {
+            self.sections.imports.import(&module_name, &name, &ImportDescriptor::Global(global_type));
+            return self.expect_right_paren();
+        }
+        let mut init = Vec::new();
+        self.parse_instructions(&mut Scope::default(), &mut Sink::Expression(&mut init))?;
+        self.expect_right_paren()?;
+        self.sections.globals.global(&global_type, &init);
+        Ok(())
+    }
+
+    fn parse_export(&mut self) -> Result<()> {
+        let name = self.expect_name()?;
+        self.expect_left_paren()?;
+        let (kind, offset) = self.expect_atom()?;
+        let export_descriptor = match kind {
+            "func" => ExportDescriptor::Func { func_index: self.parse_index(Space::Func)?.into() },
+            "table" => ExportDescriptor::Table { table_index: self.parse_index(Space::Table)?.into() },
+            "memory" => ExportDescriptor::Memory { memory_index: self.parse_index(Space::Memory)?.into() },
+            "tag" => ExportDescriptor::Tag { tag_index: self.parse_index(Space::Tag)?.into() },
+            "global" => ExportDescriptor::Global { global_index: self.parse_index(Space::Global)?.into() },
+            _ => return Err(TextError::Expected("export kind", offset)),
+        };
+        self.expect_right_paren()?;
+        self.expect_right_paren()?;
+        self.sections.exports.export(&name, &export_descriptor);
+        Ok(())
+    }
+
+    fn parse_elem(&mut self) -> Result<()> {
+        self.take_id();
+        if self.take_keyword("declare") {
+            match self.parse_element_list()? {
+                (_, ElementList::Functions(function_indices)) => {
+                    self.sections.elements.declarative_segment(&function_indices);
+                },
+                (element_type, ElementList::Expressions(expressions)) => {
+                    self.sections.elements.declarative_expression_segment(element_type, &expressions);
+                },
+            }
+            return self.expect_right_paren();
+        }
+        if let Some(TokenKind::Atom("func")) | Some(TokenKind::Atom("funcref")) | Some(TokenKind::Atom("externref"))
+            | Some(TokenKind::RightParen) = self.peek_kind() {
+            match self.parse_element_list()? {
+                (_, ElementList::Functions(function_indices)) => {
+                    self.sections.elements.passive_segment(&function_indices);
+                },
+                (element_type, ElementList::Expressions(expressions)) => {
+                    self.sections.elements.passive_expression_segment(element_type, &expressions);
+                },
+            }
+            return self.expect_right_paren();
+        }
+        let table_index = self.parse_segment_target("table", Space::Table)?;
+        let offset = self.parse_offset_expression()?;
+        match self.parse_element_list()? {
+            (_, ElementList::Functions(function_indices)) => {
+                self.sections.elements.segment(table_index.into(), &offset, &function_indices);
+            },
+            (element_type, ElementList::Expressions(expressions)) => {
+                self.sections.elements.expression_segment(table_index.into(), &offset, element_type, &expressions);
+            },
+        }
+        self.expect_right_paren()
+    }
+
+    /// Parses the items of an element segment, either `func` followed by
+    /// function indices, which may be written alone, or a reference type
+    /// followed by expressions written as `(item ...)` or as a single folded
+    /// instruction.
+    fn parse_element_list(&mut self) -> Result<(ValueType, ElementList)> {
+        if let Some(TokenKind::Atom("funcref")) | Some(TokenKind::Atom("externref")) = self.peek_kind() {
+            let element_type = self.parse_reference_type()?;
+            let mut expressions = Vec::new();
+            while self.peek_kind() == Some(&TokenKind::LeftParen) {
+                let mut expression = Vec::new();
+                let mut scope = Scope::default();
+                if self.peek_list("item") {
+                    self.expect_list("item")?;
+                    self.parse_instructions(&mut scope, &mut Sink::Expression(&mut expression))?;
+                    self.expect_right_paren()?;
+                } else {
+                    self.parse_folded_instruction(&mut scope, &mut Sink::Expression(&mut expression))?;
+                }
+                expressions.push(expression);
+            }
+            return Ok((element_type, ElementList::Expressions(expressions)));
+        }
+        self.take_keyword("func");
+        Ok((ValueType::FuncRef, ElementList::Functions(self.parse_function_indices()?)))
+    }
+
+    fn parse_data(&mut self) -> Result<()> {
+        self.take_id();
+        if let Some(TokenKind::String(_)) | Some(TokenKind::RightParen) = self.peek_kind() {
+            let bytes = self.parse_strings()?;
+            self.expect_right_paren()?;
+            self.sections.data.passive_segment(&bytes);
+            return Ok(());
+        }
+        let memory_index = self.parse_segment_target("memory", Space::Memory)?;
+        let offset = self.parse_offset_expression()?;
+        let bytes = self.parse_strings()?;
+        self.expect_right_paren()?;
+        self.sections.data.segment(memory_index.into(), &offset, &bytes);
+        Ok(())
+    }
+
+    /// Parses the table or memory of a segment, written either as `(table x)`
+    /// or as a bare index, and defaulting to 0.
+    fn parse_segment_target(&mut self, keyword: &'static str, space: Space) -> Result<u32> {
+        if self.peek_list(keyword) {
+            self.expect_list(keyword)?;
+            let index = self.parse_index(space)?;
+            self.expect_right_paren()?;
+            Ok(index)
+        } else if let Some(TokenKind::Id(_)) | Some(TokenKind::Atom(_)) = self.peek_kind() {
+            self.parse_index(space)
+        } else {
+            Ok(0)
+        }
+    }
+
+    fn parse_offset_expression(&mut self) -> Result<Vec<Instruction<'static>>> {
+        let mut instructions = Vec::new();
+        let mut scope = Scope::default();
+        if self.peek_list("offset") {
+            self.expect_list("offset")?;
+            self.parse_instructions(&mut scope, &mut Sink::Expression(&mut instructions))?;
+            self.expect_right_paren()?;
+        } else {
+            self.parse_folded_instruction(&mut scope, &mut Sink::Expression(&mut instructions))?;
+        }
+        Ok(instructions)
+    }
+
+    fn parse_function_indices(&mut self) -> Result<Vec<FuncIndex>> {
+        let mut function_indices = Vec::new();
+        while let Some(TokenKind::Id(_)) | Some(TokenKind::Atom(_)) = self.peek_kind() {
+            function_indices.push(self.parse_index(Space::Func)?.into());
+        }
+        Ok(function_indices)
+    }
+
+    fn parse_strings(&mut self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        while let Some(TokenKind::String(_)) = self.peek_kind() {
+            bytes.extend(self.expect_string()?);
+        }
+        Ok(bytes)
+    }
+
+    /// Records that the module needs a data count section.
+    pub(crate) fn require_data_count(&mut self) {
+        self.sections.uses_data_indices = true;
+    }
+
+    pub(crate) fn parse_index(&mut self, space: Space) -> Result<u32> {
+        let offset = self.peek_offset();
+        match self.next_kind()? {
+            TokenKind::Atom(atom) => parse_u32(atom).ok_or(TextError::InvalidNumber(offset)),
+            TokenKind::Id(id) => {
+                let names = match space {
+                    Space::Type => &self.type_names,
+                    Space::Func => &self.funcs.names,
+                    Space::Table => &self.tables.names,
+                    Space::Memory => &self.memories.names,
+                    Space::Tag => &self.tags.names,
+                    Space::Global => &self.globals.names,
+                    Space::Elem => &self.elems.names,
+                    Space::Data => &self.datas.names,
+                };
+                names.get(id).copied().ok_or(TextError::UnknownIdentifier(offset))
+            },
+            _ => Err(TextError::Expected("index", offset)),
+        }
+    }
+
+    fn parse_value_type(&mut self) -> Result<ValueType> {
+        let (atom, offset) = self.expect_atom()?;
+        match atom {
+            "i32" => Ok(ValueType::I32),
+            "i64" => Ok(ValueType::I64),
+            "f32" => Ok(ValueType::F32),
+            "f64" => Ok(ValueType::F64),
+            "v128" => Ok(ValueType::V128),
+            "funcref" | "anyfunc" => Ok(ValueType::FuncRef),
+            "externref" => Ok(ValueType::ExternRef),
+            "exnref" => Ok(ValueType::ExnRef),
+            _ => Err(TextError::Expected("value type", offset)),
+        }
+    }
+
+    /// Parses the value types of a `param`, `result` or `local` list, which
+    /// may instead declare a single named value when `allow_names` is set.
+    fn parse_value_types(&mut self, names: &mut Vec<Name<'a>>, allow_names: bool) -> Result<Vec<ValueType>> {
+        let offset = self.peek_offset();
+        if let Some(name) = self.take_name() {
+            if !allow_names {
+                return Err(TextError::Expected("value type", offset));
+            }
+            let value_type = self.parse_value_type()?;
+            names.push(Some(name));
+            return Ok(vec![value_type]);
+        }
+        let mut value_types = Vec::new();
+        while let Some(TokenKind::Atom(_)) = self.peek_kind() {
+            value_types.push(self.parse_value_type()?);
+            names.push(None);
+        }
+        Ok(value_types)
+    }
+
+    /// Parses `(param ...)*` followed by `(result ...)*`, collecting parameter names.
+    pub(crate) fn parse_signature(&mut self, names: &mut Vec<Name<'a>>, allow_names: bool) -> Result<(Vec<ValueType>, Vec<ValueType>)> {
+        let mut params = Vec::new();
+        while self.peek_list("param") {
+            self.expect_list("param")?;
+            params.extend(self.parse_value_types(names, allow_names)?);
+            self.expect_right_paren()?;
+        }
+        let mut results = Vec::new();
+        while self.peek_list("result") {
+            self.expect_list("result")?;
+            results.extend(self.parse_value_types(&mut Vec::new(), false)?);
+            self.expect_right_paren()?;
+        }
+        Ok((params, results))
+    }
+
+    /// Parses a type use: `(type x)` and/or an inline signature. An inline
+    /// signature alone refers to the first identical type, which is appended
+    /// to the type section when there is none.
+    pub(crate) fn parse_type_use(&mut self) -> Result<(TypeIndex, Vec<Name<'a>>)> {
+        let offset = self.peek_offset();
+        let explicit = if self.peek_list("type") {
+            self.expect_list("type")?;
+            let index = self.parse_index(Space::Type)?;
+            self.expect_right_paren()?;
+            Some(index)
+        } else {
+            None
+        };
+        let mut names = Vec::new();
+        let (params, results) = self.parse_signature(&mut names, true)?;
+        match explicit {
+            Some(index) => {
+                let (type_params, type_results) = self.types.get(index as usize)
+                    .ok_or(TextError::UnknownIdentifier(offset))?;
+                if params.is_empty() && results.is_empty() {
+                    names = vec![None; type_params.len()];
+                } else if params != *type_params || results != *type_results {
+                    return Err(TextError::TypeUseMismatch(offset));
+                }
+                Ok((index.into(), names))
+            },
+            None => Ok((self.find_or_add_type(params, results), names)),
+        }
+    }
+
+    pub(crate) fn find_or_add_type(&mut self, params: Vec<ValueType>, results: Vec<ValueType>) -> TypeIndex {
+        let signature = (params, results);
+        let index = match self.types.iter().position(|ty| *ty == signature) {
+            Some(index) => index,
+            None => {
+                self.types.push(signature);
+                self.types.len() - 1
+            },
+        };
+        (index as u32).into()
+    }
+
+    /// Parses limits whose bounds fit in 32 bits, or in 64 bits for the
+    /// limits of 64-bit memories.
+    fn parse_limits(&mut self, memory64: bool) -> Result<Limits> {
+        let parse_bound = |atom: &str| if memory64 { parse_u64(atom) } else { parse_u32(atom).map(u64::from) };
+        let (atom, offset) = self.expect_atom()?;
+        let min = parse_bound(atom).ok_or(TextError::InvalidNumber(offset))?;
+        let max = match self.peek_kind() {
+            Some(TokenKind::Atom(atom)) => parse_bound(atom),
+            _ => None,
+        };
+        if max.is_some() {
+            self.position += 1;
+        }
+        Ok(Limits::new(min, max))
+    }
+
+    /// Parses the optional index type of a memory, telling whether it is a
+    /// 64-bit memory.
+    fn parse_index_type(&mut self) -> bool {
+        if self.take_keyword("i64") {
+            true
+        } else {
+            self.take_keyword("i32");
+            false
+        }
+    }
+
+    /// Parses the index type and limits of a memory, followed by `shared`
+    /// for shared memories.
+    fn parse_memory_type(&mut self) -> Result<MemoryType> {
+        let memory64 = self.parse_index_type();
+        self.parse_memory_limits(memory64)
+    }
+
+    fn parse_memory_limits(&mut self, memory64: bool) -> Result<MemoryType> {
+        let limits = self.parse_limits(memory64)?;
+        let shared = self.take_keyword("shared");
+        Ok(MemoryType { limits, shared, memory64 })
+    }
+
+    fn parse_table_type(&mut self) -> Result<TableType> {
+        let limits = self.parse_limits(false)?;
+        let element_type = self.parse_reference_type()?;
+        Ok(TableType::new(element_type, limits))
+    }
+
+    fn parse_reference_type(&mut self) -> Result<ValueType> {
+        let (atom, offset) = self.expect_atom()?;
+        match atom {
+            "funcref" | "anyfunc" => Ok(ValueType::FuncRef),
+            "externref" => Ok(ValueType::ExternRef),
+            "exnref" => Ok(ValueType::ExnRef),
+            _ => Err(TextError::Expected("reference type", offset)),
+        }
+    }
+
+    fn parse_global_type(&mut self) -> Result<GlobalType> {
+        if self.peek_list("mut") {
+            self.expect_list("mut")?;
+            let value_type = self.parse_value_type()?;
+            self.expect_right_paren()?;
+            Ok(GlobalType::new(value_type, true))
+        } else {
+            Ok(GlobalType::new(self.parse_value_type()?, false))
+        }
+    }
+
+    fn encode_module(&self) -> Vec<u8> {
+        let sections = &self.sections;
+        let mut types = TypeSectionEncoder::new();
+        for (params, results) in &self.types {
+            types.function_type(params, results);
+        }
+        let mut module = ModuleEncoder::new();
+        if types.get_count() > 0 {
+            module.section(&types);
+        }
+        if sections.imports.get_count() > 0 {
+            module.section(&sections.imports);
+        }
+        if sections.functions.get_count() > 0 {
+            module.section(&sections.functions);
+        }
+        if sections.tables.get_count() > 0 {
+            module.section(&sections.tables);
+        }
+        if sections.memories.get_count() > 0 {
+            module.section(&sections.memories);
+        }
+        if sections.tags.get_count() > 0 {
+            module.section(&sections.tags);
+        }
+        if sections.globals.get_count() > 0 {
+            module.section(&sections.globals);
+        }
+        if sections.exports.get_count() > 0 {
+            module.section(&sections.exports);
+        }
+        if let Some(start) = &sections.start {
+            module.section(start);
+        }
+        if sections.elements.get_count() > 0 {
+            module.section(&sections.elements);
+        }
+        if sections.uses_data_indices {
+            module.section(&DataCountSectionEncoder::new(sections.data.get_count()));
+        }
+        if sections.code.get_count() > 0 {
+            module.section(&sections.code);
+        }
+        if sections.data.get_count() > 0 {
+            module.section(&sections.data);
+        }
+        module.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::text::{parse, TextError};
+    use crate::encoder::*;
+    use crate::{Parser, Validator, Chunk, ValueType, BlockType, MemoryArgument, Limits, TableType, MemoryType, GlobalType, Locals};
+    use crate::{ImportDescriptor, ExportDescriptor};
+    use crate::Instruction::*;
+
+    fn validate_module(module: &[u8]) {
+        let mut parser = Parser::new();
+        let mut validator = Validator::new();
+        let mut position = 0;
+        loop {
+            let (consumed, chunk) = parser.parse(&module[position..], true).unwrap();
+            validator.validate(&chunk).unwrap();
+            if let Chunk::Done = chunk {
+                return;
+            }
+            position += consumed;
+        }
+    }
+
+    #[test]
+    fn hello_world() {
+        let module = parse(include_str!("../../hello.wat")).unwrap();
+        validate_module(&module);
+
+        let mut types = TypeSectionEncoder::new();
+        types.function_type(&[], &[]);
+        let mut imports = ImportSectionEncoder::new();
+        imports.import("", "hello", &ImportDescriptor::Func { type_index: 0.into() });
+        let mut functions = FunctionSectionEncoder::new();
+        functions.type_index(0.into());
+        let mut exports = ExportSectionEncoder::new();
+        exports.export("run", &ExportDescriptor::Func { func_index: 1.into() });
+        let mut body = FunctionBodyEncoder::new(&[]);
+        body.instruction(&Call { func_index: 0.into() }).instruction(&End);
+        let mut code = CodeSectionEncoder::new();
+        code.function(&body);
+        let mut expected = ModuleEncoder::new();
+        expected.section(&types).section(&imports).section(&functions).section(&exports).section(&code);
+        assert_eq!(module, expected.finish());
+    }
+
+    #[test]
+    fn folded_and_flat_instructions_agree() {
+        let folded = parse(r#"
+            (memory 1)
+            (func $f (param $x i32) (result i32) (local i64 i64) (local $y f32)
+              (if (result i32) (i32.eqz (local.get $x))
+                (then (i32.const 1))
+                (else (block $out (result i32)
+                  (br_table $out 0 (i32.const 2) (local.get 0)))))
+              (i64.store offset=8 (i32.const 0) (local.get 1))
+              (return))
+        "#).unwrap();
+        let flat = parse(r#"
+            (memory 1)
+            (func $f (param $x i32) (result i32) (local i64 i64) (local $y f32)
+              local.get $x
+              i32.eqz
+              if (result i32)
+                i32.const 1
+              else
+                block $out (result i32)
+                  i32.const 2
+                  local.get 0
+                  br_table $out 0
+                end $out
+              end
+              i32.const 0
+              local.get 1
+              i64.store offset=8 align=8
+              return)
+        "#).unwrap();
+        assert_eq!(folded, flat);
+        validate_module(&flat);
+
+        let mut types = TypeSectionEncoder::new();
+        types.function_type(&[ValueType::I32], &[ValueType::I32]);
+        let mut functions = FunctionSectionEncoder::new();
+        functions.type_index(0.into());
+        let mut body = FunctionBodyEncoder::new(&[
+            Locals { count: 2, value_type: ValueType::I64 },
+            Locals { count: 1, value_type: ValueType::F32 },
+        ]);
+        body.instruction(&LocalGet { local_index: 0.into() })
+            .instruction(&I32Eqz)
+            .instruction(&If { block_type: BlockType::ValueType(ValueType::I32) })
+            .instruction(&I32Const(1))
+            .instruction(&Else)
+            .instruction(&Block { block_type: BlockType::ValueType(ValueType::I32) })
+            .instruction(&I32Const(2))
+            .instruction(&LocalGet { local_index: 0.into() })
+            .branch_table(&[0.into()], 0.into())
+            .instruction(&End)
+            .instruction(&End)
+            .instruction(&I32Const(0))
+            .instruction(&LocalGet { local_index: 1.into() })
+            .instruction(&I64Store { memory_argument: MemoryArgument { alignment: 3, offset: 8, memory_index: 0.into() } })
+            .instruction(&Return)
+            .instruction(&End);
+        let mut memories = MemorySectionEncoder::new();
+        memories.memory(&MemoryType::new(Limits::new(1, None)));
+        let mut code = CodeSectionEncoder::new();
+        code.function(&body);
+        let mut expected = ModuleEncoder::new();
+        expected.section(&types).section(&functions).section(&memories).section(&code);
+        assert_eq!(flat, expected.finish());
+    }
+
+    #[test]
+    fn type_uses_share_identical_signatures() {
+        let module = parse(r#"
+            (module
+              (type $binary (func (param i32 i32) (result i32)))
+              (func $add (type $binary) (i32.add (local.get 0) (local.get 1)))
+              (func $sub (param $a i32) (param $b i32) (result i32)
+                (i32.sub (local.get $a) (local.get $b)))
+              (func $main (call $add (i32.const 1) (i32.const 2)) drop)
+              (memory (export "memory") (data "hi\00"))
+              (global $g (mut i32) (i32.const -1))
+              (table funcref (elem $add $sub))
+              (start $main))
+        "#).unwrap();
+        validate_module(&module);
+
+        let mut types = TypeSectionEncoder::new();
+        types.function_type(&[ValueType::I32, ValueType::I32], &[ValueType::I32])
+            .function_type(&[], &[]);
+        let mut functions = FunctionSectionEncoder::new();
+        functions.type_index(0.into()).type_index(0.into()).type_index(1.into());
+        let mut tables = TableSectionEncoder::new();
+        tables.table(&TableType::new(ValueType::FuncRef, Limits::new(2, Some(2))));
+        let mut memories = MemorySectionEncoder::new();
+        memories.memory(&MemoryType::new(Limits::new(1, Some(1))));
+        let mut globals = GlobalSectionEncoder::new();
+        globals.global(&GlobalType::new(ValueType::I32, true), &[I32Const(-1)]);
+        let mut exports = ExportSectionEncoder::new();
+        exports.export("memory", &ExportDescriptor::Memory { memory_index: 0.into() });
+        let mut elements = ElementSectionEncoder::new();
+        elements.segment(0.into(), &[I32Const(0)], &[0.into(), 1.into()]);
+        let mut add = FunctionBodyEncoder::new(&[]);
+        add.instruction(&LocalGet { local_index: 0.into() })
+            .instruction(&LocalGet { local_index: 1.into() })
+            .instruction(&I32Add)
+            .instruction(&End);
+        let mut sub = FunctionBodyEncoder::new(&[]);
+        sub.instruction(&LocalGet { local_index: 0.into() })
+            .instruction(&LocalGet { local_index: 1.into() })
+            .instruction(&I32Sub)
+            .instruction(&End);
+        let mut main = FunctionBodyEncoder::new(&[]);
+        main.instruction(&I32Const(1))
+            .instruction(&I32Const(2))
+            .instruction(&Call { func_index: 0.into() })
+            .instruction(&Drop)
+            .instruction(&End);
+        let mut code = CodeSectionEncoder::new();
+        code.function(&add).function(&sub).function(&main);
+        let mut data = DataSectionEncoder::new();
+        data.segment(0.into(), &[I32Const(0)], b"hi\0");
+        let mut expected = ModuleEncoder::new();
+        expected.section(&types)
+            .section(&functions)
+            .section(&tables)
+            .section(&memories)
+            .section(&globals)
+            .section(&exports)
+            .section(&StartSectionEncoder::new(2.into()))
+            .section(&elements)
+            .section(&code)
+            .section(&data);
+        assert_eq!(module, expected.finish());
+    }
+
+    #[test]
+    fn errors_have_offsets() {
+        assert_eq!(parse("(func (call $missing))"), Err(TextError::UnknownIdentifier(12)));
+        assert_eq!(parse("(func $f) (func $f)"), Err(TextError::DuplicateIdentifier(16)));
+        assert_eq!(parse("(func (i32.frobnicate))"), Err(TextError::UnknownInstruction(7)));
+        assert_eq!(parse("(func (i32.const 0x1_0000_0000))"), Err(TextError::InvalidNumber(17)));
+        assert_eq!(parse("(func (f64.const 1e400))"), Err(TextError::InvalidNumber(17)));
+        assert_eq!(parse("(func (f32.const 1e39))"), Err(TextError::InvalidNumber(17)));
+        assert_eq!(parse("(func (f32.const -0x1p128))"), Err(TextError::InvalidNumber(17)));
+        assert!(parse("(func (f32.const 3.4028234e38) (f64.const -inf) (f64.const 0x1p1023))").is_ok());
+        assert_eq!(parse("(type (func)) (func (type 0) (param i32))"), Err(TextError::TypeUseMismatch(20)));
+        assert_eq!(parse("(func"), Err(TextError::UnexpectedEof(5)));
+        assert_eq!(parse("(module) (func)"), Err(TextError::Expected("end of input", 9)));
+    }
+}
@@ -0,0 +1,936 @@
+use std::collections::{HashMap, HashSet};
+use std::{error, fmt};
+use crate::{Parser, Chunk, SectionReader, Error, Instruction, BlockType, ValueType};
+use crate::types::{FunctionType, TypeIndex, Limits, GlobalType, ImportDescriptor, ExportDescriptor, Locals};
+use crate::types::{TableType, MemoryType, MemoryIndex, TagType, MemoryArgument, DataMode, ElementMode, ElementItems, CatchClause};
+use crate::{CodeSectionReader, InstructionReader, NameSectionReader, NameReaderError, Name, Naming};
+use crate::text::instruction::{MEMORY_INSTRUCTIONS, memory_argument};
+use crate::text::lexer::is_idchar;
+
+const INDENT: &str = "  ";
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum PrintError {
+    ControlInstructionInExpression(usize),
+    UnmatchedDelegate(usize),
+}
+
+impl PrintError {
+    /// Returns the absolute offset in the module of the instruction which couldn't be printed.
+    pub fn offset(&self) -> usize {
+        match self {
+            PrintError::ControlInstructionInExpression(offset) |
+            PrintError::UnmatchedDelegate(offset) => *offset,
+        }
+    }
+}
+
+impl fmt::Display for PrintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrintError::ControlInstructionInExpression(offset) => {
+                write!(f, "control instruction at offset {} can't be printed in a constant expression", offset)
+            },
+            PrintError::UnmatchedDelegate(offset) => write!(f, "delegate at offset {} doesn't close a try block", offset),
+        }
+    }
+}
+
+impl error::Error for PrintError {}
+
+/// Prints a binary module in the text format, one module field per line and
+/// one instruction per line inside function bodies. Functions and locals are
+/// given the identifiers found in the `name` custom section, if any.
+pub fn print(module: &[u8]) -> Result<String, Error> {
+    let sections = read_sections(module)?;
+    let mut printer = Printer::default();
+    let name_section = sections.iter().find_map(|section| match section {
+        SectionReader::Custom(reader) => reader.get_name_section_reader(),
+        _ => None,
+    });
+    if let Some(reader) = name_section {
+        // Names are debugging information only: a malformed name section
+        // is ignored rather than making the module unprintable.
+        printer.names = read_names(reader).unwrap_or_default();
+    }
+    printer.line(0, "(module");
+    for section in sections {
+        printer.print_section(section)?;
+    }
+    printer.close_list();
+    Ok(printer.output)
+}
+
+fn read_sections(module: &[u8]) -> Result<Vec<SectionReader<'_>>, Error> {
+    let mut parser = Parser::new();
+    let mut sections = Vec::new();
+    let mut position = 0;
+    loop {
+        let (consumed, chunk) = parser.parse(&module[position..], true)?;
+        match chunk {
+            Chunk::Section(section) => sections.push(section),
+            Chunk::Done => return Ok(sections),
+            _ => {},
+        }
+        position += consumed;
+    }
+}
+
+/// Identifiers from the `name` section, `$` included. Names which aren't
+/// valid identifiers, or which are already taken, are left out.
+#[derive(Default)]
+struct Names {
+    functions: HashMap<u32, String>,
+    locals: HashMap<u32, HashMap<u32, String>>,
+}
+
+fn read_names(reader: NameSectionReader) -> Result<Names, NameReaderError> {
+    let mut names = Names::default();
+    for name in reader {
+        match name? {
+            Name::Function(functions) => names.functions = identifiers(&functions),
+            Name::Local(functions) => {
+                for function in functions.iter() {
+                    names.locals.insert(function.index, identifiers(&function.names));
+                }
+            },
+            _ => {},
+        }
+    }
+    Ok(names)
+}
+
+fn identifiers(namings: &[Naming]) -> HashMap<u32, String> {
+    let mut identifiers = HashMap::new();
+    let mut used = HashSet::new();
+    for Naming { index, name } in namings {
+        if !name.is_empty() && name.bytes().all(is_idchar) && used.insert(name) {
+            identifiers.insert(*index, format!("${}", name));
+        }
+    }
+    identifiers
+}
+
+#[derive(Default)]
+struct Printer {
+    output: String,
+    names: Names,
+    types: Vec<FunctionType>,
+    function_types: Vec<TypeIndex>,
+    imported_functions: u32,
+    tables: u32,
+    memories: u32,
+    tags: u32,
+    globals: u32,
+}
+
+impl Printer {
+    fn line(&mut self, depth: usize, text: &str) {
+        for _ in 0..depth {
+            self.output.push_str(INDENT);
+        }
+        self.output.push_str(text);
+        self.output.push('\n');
+    }
+
+    /// Closes the list opened by a previous line on the last printed line.
+    fn close_list(&mut self) {
+        self.output.pop();
+        self.output.push_str(")\n");
+    }
+
+    fn print_section(&mut self, section: SectionReader) -> Result<(), Error> {
+        match section {
+            SectionReader::Type(reader) => {
+                for (index, function_type) in reader.into_iter().enumerate() {
+                    let function_type = function_type?;
+                    let signature = signature(&function_type, &HashMap::new());
+                    self.line(1, &format!("(type (;{};) (func{}))", index, signature));
+                    self.types.push(function_type);
+                }
+            },
+            SectionReader::Import(reader) => {
+                for import in reader {
+                    let import = import?;
+                    let descriptor = match import.import_descriptor {
+                        ImportDescriptor::Func { type_index } => {
+                            let index = self.imported_functions;
+                            self.imported_functions += 1;
+                            self.function_types.push(type_index);
+                            format!("(func {} (type {}))", self.function_id(index), type_index.0)
+                        },
+                        ImportDescriptor::Table(table_type) => format!("(table {})", self.table(&table_type)),
+                        ImportDescriptor::Memory(memory_type) => format!("(memory {})", self.memory(&memory_type)),
+                        ImportDescriptor::Global(global_type) => format!("(global {})", self.global(&global_type)),
+                        ImportDescriptor::Tag(tag_type) => format!("(tag {})", self.tag(&tag_type)),
+                    };
+                    self.line(1, &format!("(import {} {} {})", escape(import.module_name.as_bytes()), escape(import.name.as_bytes()), descriptor));
+                }
+            },
+            SectionReader::Function(reader) => {
+                for type_index in reader {
+                    self.function_types.push(type_index?);
+                }
+            },
+            SectionReader::Table(reader) => {
+                for table_type in reader {
+                    let table = self.table(&table_type?);
+                    self.line(1, &format!("(table {})", table));
+                }
+            },
+            SectionReader::Memory(reader) => {
+                for memory_type in reader {
+                    let memory = self.memory(&memory_type?);
+                    self.line(1, &format!("(memory {})", memory));
+                }
+            },
+            SectionReader::Tag(reader) => {
+                for tag_type in reader {
+                    let tag = self.tag(&tag_type?);
+                    self.line(1, &format!("(tag {})", tag));
+                }
+            },
+            SectionReader::Global(reader) => {
+                for global in reader {
+                    let global = global?;
+                    let global_type = self.global(&global.global_type);
+                    let init = self.expression(global.instruction_reader)?;
+                    self.line(1, &format!("(global {} {})", global_type, init));
+                }
+            },
+            SectionReader::Export(reader) => {
+                for export in reader {
+                    let export = export?;
+                    let descriptor = match export.export_descriptor {
+                        ExportDescriptor::Func { func_index } => format!("func {}", self.function_reference(func_index.0)),
+                        ExportDescriptor::Table { table_index } => format!("table {}", table_index.0),
+                        ExportDescriptor::Memory { memory_index } => format!("memory {}", memory_index.0),
+                        ExportDescriptor::Global { global_index } => format!("global {}", global_index.0),
+                        ExportDescriptor::Tag { tag_index } => format!("tag {}", tag_index.0),
+                    };
+                    self.line(1, &format!("(export {} ({}))", escape(export.name.as_bytes()), descriptor));
+                }
+            },
+            SectionReader::Start(reader) => {
+                let start = format!("(start {})", self.function_reference(reader.get_func_index().0));
+                self.line(1, &start);
+            },
+            SectionReader::Element(reader) => {
+                for (index, segment) in reader.into_iter().enumerate() {
+                    let segment = segment?;
+                    let mut text = format!("(elem (;{};)", index);
+                    match segment.mode {
+                        ElementMode::Active { table_index, instruction_reader } => {
+                            if table_index.0 != 0 {
+                                text.push_str(&format!(" (table {})", table_index.0));
+                            }
+                            text.push_str(&format!(" {}", self.expression(instruction_reader)?));
+                        },
+                        ElementMode::Passive => {},
+                        ElementMode::Declarative => text.push_str(" declare"),
+                    }
+                    match segment.items {
+                        ElementItems::Functions(function_indices) => {
+                            text.push_str(" func");
+                            for func_index in function_indices.iter() {
+                                text.push_str(&format!(" {}", self.function_reference(func_index.0)));
+                            }
+                        },
+                        ElementItems::Expressions(expressions) => {
+                            text.push_str(&format!(" {}", segment.element_type));
+                            for expression in expressions.into_vec() {
+                                text.push_str(&format!(" (item {})", self.expression(expression)?));
+                            }
+                        },
+                    }
+                    text.push(')');
+                    self.line(1, &text);
+                }
+            },
+            SectionReader::Code(reader) => self.print_functions(reader)?,
+            SectionReader::Data(reader) => {
+                for (index, segment) in reader.into_iter().enumerate() {
+                    let segment = segment?;
+                    let mut text = format!("(data (;{};)", index);
+                    if let DataMode::Active { memory_index, instruction_reader } = segment.mode {
+                        if memory_index.0 != 0 {
+                            text.push_str(&format!(" (memory {})", memory_index.0));
+                        }
+                        text.push_str(&format!(" {}", self.expression(instruction_reader)?));
+                    }
+                    text.push_str(&format!(" {})", escape(segment.bytes)));
+                    self.line(1, &text);
+                }
+            },
+            SectionReader::Custom(_) | SectionReader::DataCount(_) | SectionReader::Unknown { .. } => {},
+        }
+        Ok(())
+    }
+
+    fn print_functions(&mut self, reader: CodeSectionReader) -> Result<(), Error> {
+        for (position, code) in reader.into_iter().enumerate() {
+            let code = code?;
+            let func_index = self.imported_functions + position as u32;
+            let type_index = self.function_types.get(func_index as usize).copied().unwrap_or(TypeIndex(0));
+            let local_names = self.names.locals.remove(&func_index).unwrap_or_default();
+            let mut header = format!("(func {} (type {})", self.function_id(func_index), type_index.0);
+            let param_count = match self.types.get(type_index.0 as usize) {
+                Some(function_type) => {
+                    header.push_str(&signature(function_type, &local_names));
+                    function_type.params.len() as u32
+                },
+                None => 0,
+            };
+            self.line(1, &header);
+
+            let mut locals_reader = code.get_locals_reader()?;
+            let mut locals = Vec::new();
+            for entry in &mut locals_reader {
+                locals.push(entry?);
+            }
+            if !locals.is_empty() {
+                let text = value_lists("local", param_count, &expand_locals(&locals), &local_names);
+                self.line(2, &text);
+            }
+            let proof = locals_reader.get_iteration_proof()?;
+            self.print_body(code.get_instruction_reader(proof)?, &local_names)?;
+            self.close_list();
+        }
+        Ok(())
+    }
+
+    fn print_body(&mut self, mut reader: InstructionReader, local_names: &HashMap<u32, String>) -> Result<(), Error> {
+        let mut depth = 2;
+        while !reader.eof() {
+            let offset = reader.get_original_position();
+            let instruction = reader.read()?;
+            match instruction {
+                Instruction::End if depth == 2 => return Ok(()),
+                Instruction::End => {
+                    depth -= 1;
+                    self.line(depth, "end");
+                },
+                Instruction::Else => self.line(depth - 1, "else"),
+                Instruction::Catch { .. } | Instruction::CatchAll => {
+                    let text = self.instruction(instruction, local_names)?;
+                    self.line(depth - 1, &text);
+                },
+                Instruction::Delegate { .. } if depth == 2 => {
+                    return Err(PrintError::UnmatchedDelegate(offset).into());
+                },
+                Instruction::Delegate { .. } => {
+                    depth -= 1;
+                    let text = self.instruction(instruction, local_names)?;
+                    self.line(depth, &text);
+                },
+                Instruction::Block { .. } | Instruction::Loop { .. } | Instruction::If { .. } |
+                Instruction::Try { .. } | Instruction::TryTable { .. } => {
+                    let text = self.instruction(instruction, local_names)?;
+                    self.line(depth, &text);
+                    depth += 1;
+                },
+                _ => {
+                    let text = self.instruction(instruction, local_names)?;
+                    self.line(depth, &text);
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints a constant expression as folded instructions, without its `end`.
+    fn expression(&self, mut reader: InstructionReader) -> Result<String, Error> {
+        let mut parts = Vec::new();
+        while !reader.eof() {
+            let offset = reader.get_original_position();
+            match reader.read()? {
+                Instruction::End => {},
+                // Expressions aren't validated before printing, and folding
+                // would need the block structure these instructions open.
+                Instruction::Block { .. } | Instruction::Loop { .. } | Instruction::If { .. } | Instruction::Else |
+                Instruction::Try { .. } | Instruction::Catch { .. } | Instruction::CatchAll |
+                Instruction::Delegate { .. } | Instruction::TryTable { .. } => {
+                    return Err(PrintError::ControlInstructionInExpression(offset).into());
+                },
+                instruction => parts.push(format!("({})", self.instruction(instruction, &HashMap::new())?)),
+            }
+        }
+        Ok(parts.join(" "))
+    }
+
+    fn instruction(&self, instruction: Instruction, local_names: &HashMap<u32, String>) -> Result<String, Error> {
+        let name = instruction.name();
+        if let Some(memory_argument) = memory_argument(&instruction) {
+            let (_, natural_alignment) = MEMORY_INSTRUCTIONS.iter()
+                .find(|(memory, _)| *memory == name)
+                .unwrap();
+            return Ok(format!("{}{}", name, memory_argument_text(memory_argument, *natural_alignment)));
+        }
+        let local = |index: u32| local_names.get(&index).cloned().unwrap_or_else(|| index.to_string());
+        Ok(match instruction {
+            Instruction::Block { block_type } => format!("{}{}", name, block_type_text(block_type)),
+            Instruction::Loop { block_type } => format!("{}{}", name, block_type_text(block_type)),
+            Instruction::If { block_type } => format!("{}{}", name, block_type_text(block_type)),
+            Instruction::Try { block_type } => format!("{}{}", name, block_type_text(block_type)),
+            Instruction::Catch { tag_index } => format!("{} {}", name, tag_index.0),
+            Instruction::Delegate { label_index } => format!("{} {}", name, label_index.0),
+            Instruction::Throw { tag_index } => format!("{} {}", name, tag_index.0),
+            Instruction::Rethrow { label_index } => format!("{} {}", name, label_index.0),
+            Instruction::TryTable { block_type, catches } => {
+                let mut text = format!("{}{}", name, block_type_text(block_type));
+                for catch in catches.iter() {
+                    text.push_str(&match catch {
+                        CatchClause::Catch { tag_index, label_index } => format!(" (catch {} {})", tag_index.0, label_index.0),
+                        CatchClause::CatchRef { tag_index, label_index } => format!(" (catch_ref {} {})", tag_index.0, label_index.0),
+                        CatchClause::CatchAll { label_index } => format!(" (catch_all {})", label_index.0),
+                        CatchClause::CatchAllRef { label_index } => format!(" (catch_all_ref {})", label_index.0),
+                    });
+                }
+                text
+            },
+            Instruction::Branch { label_index } => format!("{} {}", name, label_index.0),
+            Instruction::BranchIf { label_index } => format!("{} {}", name, label_index.0),
+            Instruction::BranchTable { mut branch_table_reader } => {
+                let mut text = String::from(name);
+                for label_index in &mut branch_table_reader {
+                    text.push_str(&format!(" {}", label_index?.0));
+                }
+                text
+            },
+            Instruction::Call { func_index } => format!("{} {}", name, self.function_reference(func_index.0)),
+            Instruction::CallIndirect { type_index, table_index } if table_index.0 == 0 => {
+                format!("{} (type {})", name, type_index.0)
+            },
+            Instruction::CallIndirect { type_index, table_index } => {
+                format!("{} {} (type {})", name, table_index.0, type_index.0)
+            },
+            Instruction::ReturnCall { func_index } => format!("{} {}", name, self.function_reference(func_index.0)),
+            Instruction::ReturnCallIndirect { type_index, table_index } if table_index.0 == 0 => {
+                format!("{} (type {})", name, type_index.0)
+            },
+            Instruction::ReturnCallIndirect { type_index, table_index } => {
+                format!("{} {} (type {})", name, table_index.0, type_index.0)
+            },
+            Instruction::TypedSelect { value_type } => format!("{} (result {})", name, value_type),
+            Instruction::LocalGet { local_index } => format!("{} {}", name, local(local_index.0)),
+            Instruction::LocalSet { local_index } => format!("{} {}", name, local(local_index.0)),
+            Instruction::LocalTee { local_index } => format!("{} {}", name, local(local_index.0)),
+            Instruction::GlobalGet { global_index } => format!("{} {}", name, global_index.0),
+            Instruction::GlobalSet { global_index } => format!("{} {}", name, global_index.0),
+            Instruction::TableGet { table_index } => format!("{} {}", name, table_index.0),
+            Instruction::TableSet { table_index } => format!("{} {}", name, table_index.0),
+            Instruction::I32Const(value) => format!("{} {}", name, value),
+            Instruction::I64Const(value) => format!("{} {}", name, value),
+            Instruction::F32Const(value) => format!("{} {}", name, format_f32(value)),
+            Instruction::F64Const(value) => format!("{} {}", name, format_f64(value)),
+            Instruction::MemorySize { memory_index } => format!("{}{}", name, memory_index_text(memory_index)),
+            Instruction::MemoryGrow { memory_index } => format!("{}{}", name, memory_index_text(memory_index)),
+            Instruction::MemoryInit { data_index, memory_index } => {
+                format!("{}{} {}", name, memory_index_text(memory_index), data_index.0)
+            },
+            Instruction::DataDrop { data_index } => format!("{} {}", name, data_index.0),
+            Instruction::TableInit { element_index, table_index } if table_index.0 == 0 => {
+                format!("{} {}", name, element_index.0)
+            },
+            Instruction::TableInit { element_index, table_index } => {
+                format!("{} {} {}", name, table_index.0, element_index.0)
+            },
+            Instruction::ElemDrop { element_index } => format!("{} {}", name, element_index.0),
+            Instruction::TableCopy { destination_table_index, source_table_index }
+                if destination_table_index.0 == 0 && source_table_index.0 == 0 => String::from(name),
+            Instruction::TableCopy { destination_table_index, source_table_index } => {
+                format!("{} {} {}", name, destination_table_index.0, source_table_index.0)
+            },
+            Instruction::MemoryCopy { destination_memory_index, source_memory_index }
+                if destination_memory_index.0 == 0 && source_memory_index.0 == 0 => String::from(name),
+            Instruction::MemoryCopy { destination_memory_index, source_memory_index } => {
+                format!("{} {} {}", name, destination_memory_index.0, source_memory_index.0)
+            },
+            Instruction::MemoryFill { memory_index } => format!("{}{}", name, memory_index_text(memory_index)),
+            Instruction::TableGrow { table_index } => format!("{} {}", name, table_index.0),
+            Instruction::TableSize { table_index } => format!("{} {}", name, table_index.0),
+            Instruction::TableFill { table_index } => format!("{} {}", name, table_index.0),
+            Instruction::RefNull { ref_type: ValueType::ExternRef } => format!("{} extern", name),
+            Instruction::RefNull { ref_type: ValueType::ExnRef } => format!("{} exn", name),
+            Instruction::RefNull { .. } => format!("{} func", name),
+            Instruction::RefFunc { func_index } => format!("{} {}", name, self.function_reference(func_index.0)),
+            Instruction::V128Const(value) => v128_const_text(value),
+            Instruction::I8x16Shuffle { lanes } => {
+                let lanes: Vec<String> = lanes.iter().map(|lane| lane.to_string()).collect();
+                format!("{} {}", name, lanes.join(" "))
+            },
+            Instruction::I8x16ExtractLanes { lane } => format!("{} {}", name, lane),
+            Instruction::I8x16ExtractLaneu { lane } => format!("{} {}", name, lane),
+            Instruction::I8x16ReplaceLane { lane } => format!("{} {}", name, lane),
+            Instruction::I16x8ExtractLanes { lane } => format!("{} {}", name, lane),
+            Instruction::I16x8ExtractLaneu { lane } => format!("{} {}", name, lane),
+            Instruction::I16x8ReplaceLane { lane } => format!("{} {}", name, lane),
+            Instruction::I32x4ExtractLane { lane } => format!("{} {}", name, lane),
+            Instruction::I32x4ReplaceLane { lane } => format!("{} {}", name, lane),
+            Instruction::I64x2ExtractLane { lane } => format!("{} {}", name, lane),
+            Instruction::I64x2ReplaceLane { lane } => format!("{} {}", name, lane),
+            Instruction::F32x4ExtractLane { lane } => format!("{} {}", name, lane),
+            Instruction::F32x4ReplaceLane { lane } => format!("{} {}", name, lane),
+            Instruction::F64x2ExtractLane { lane } => format!("{} {}", name, lane),
+            Instruction::F64x2ReplaceLane { lane } => format!("{} {}", name, lane),
+            Instruction::V128Load8Lane { memory_argument, lane } => {
+                format!("{}{} {}", name, memory_argument_text(memory_argument, 0), lane)
+            },
+            Instruction::V128Load16Lane { memory_argument, lane } => {
+                format!("{}{} {}", name, memory_argument_text(memory_argument, 1), lane)
+            },
+            Instruction::V128Load32Lane { memory_argument, lane } => {
+                format!("{}{} {}", name, memory_argument_text(memory_argument, 2), lane)
+            },
+            Instruction::V128Load64Lane { memory_argument, lane } => {
+                format!("{}{} {}", name, memory_argument_text(memory_argument, 3), lane)
+            },
+            Instruction::V128Store8Lane { memory_argument, lane } => {
+                format!("{}{} {}", name, memory_argument_text(memory_argument, 0), lane)
+            },
+            Instruction::V128Store16Lane { memory_argument, lane } => {
+                format!("{}{} {}", name, memory_argument_text(memory_argument, 1), lane)
+            },
+            Instruction::V128Store32Lane { memory_argument, lane } => {
+                format!("{}{} {}", name, memory_argument_text(memory_argument, 2), lane)
+            },
+            Instruction::V128Store64Lane { memory_argument, lane } => {
+                format!("{}{} {}", name, memory_argument_text(memory_argument, 3), lane)
+            },
+            // Memory instructions were printed above, the others have no immediates.
+            _ => String::from(name),
+        })
+    }
+
+    /// The identifier of a function definition: its name or its index in a comment.
+    fn function_id(&self, func_index: u32) -> String {
+        match self.names.functions.get(&func_index) {
+            Some(name) => name.clone(),
+            None => format!("(;{};)", func_index),
+        }
+    }
+
+    fn function_reference(&self, func_index: u32) -> String {
+        match self.names.functions.get(&func_index) {
+            Some(name) => name.clone(),
+            None => func_index.to_string(),
+        }
+    }
+
+    fn table(&mut self, table_type: &TableType) -> String {
+        self.tables += 1;
+        format!("(;{};) {} {}", self.tables - 1, limits(&table_type.limits), table_type.element_type)
+    }
+
+    fn memory(&mut self, memory_type: &MemoryType) -> String {
+        self.memories += 1;
+        let index_type = if memory_type.memory64 { "i64 " } else { "" };
+        let shared = if memory_type.shared { " shared" } else { "" };
+        format!("(;{};) {}{}{}", self.memories - 1, index_type, limits(&memory_type.limits), shared)
+    }
+
+    fn tag(&mut self, tag_type: &TagType) -> String {
+        self.tags += 1;
+        format!("(;{};) (type {})", self.tags - 1, tag_type.type_index.0)
+    }
+
+    fn global(&mut self, global_type: &GlobalType) -> String {
+        self.globals += 1;
+        if global_type.mutable {
+            format!("(;{};) (mut {})", self.globals - 1, global_type.var_type)
+        } else {
+            format!("(;{};) {}", self.globals - 1, global_type.var_type)
+        }
+    }
+}
+
+/// Prints `(param ...)` and `(result ...)` lists, naming parameters when possible.
+fn signature(function_type: &FunctionType, local_names: &HashMap<u32, String>) -> String {
+    let mut text = String::new();
+    if !function_type.params.is_empty() {
+        text.push(' ');
+        text.push_str(&value_lists("param", 0, &function_type.params, local_names));
+    }
+    if !function_type.results.is_empty() {
+        text.push_str(" (result");
+        for value_type in function_type.results.iter() {
+            text.push_str(&format!(" {}", value_type));
+        }
+        text.push(')');
+    }
+    text
+}
+
+/// Prints parameters or locals starting at `first_index`: a named value gets
+/// a list of its own, runs of unnamed values share one.
+fn value_lists(keyword: &str, first_index: u32, value_types: &[ValueType], local_names: &HashMap<u32, String>) -> String {
+    let mut lists: Vec<String> = Vec::new();
+    let mut unnamed = false;
+    for (index, value_type) in (first_index..).zip(value_types) {
+        match local_names.get(&index) {
+            Some(name) => {
+                lists.push(format!("({} {} {})", keyword, name, value_type));
+                unnamed = false;
+            },
+            None if unnamed => {
+                let list = lists.last_mut().unwrap();
+                list.pop();
+                list.push_str(&format!(" {})", value_type));
+            },
+            None => {
+                lists.push(format!("({} {})", keyword, value_type));
+                unnamed = true;
+            },
+        }
+    }
+    lists.join(" ")
+}
+
+fn expand_locals(locals: &[Locals]) -> Vec<ValueType> {
+    locals.iter()
+        .flat_map(|locals| (0..locals.count).map(move |_| locals.value_type))
+        .collect()
+}
+
+fn limits(limits: &Limits) -> String {
+    match limits.max {
+        Some(max) => format!("{} {}", limits.min, max),
+        None => limits.min.to_string(),
+    }
+}
+
+fn block_type_text(block_type: BlockType) -> String {
+    match block_type {
+        BlockType::Empty => String::new(),
+        BlockType::ValueType(value_type) => format!(" (result {})", value_type),
+        BlockType::TypeIndex(type_index) => format!(" (type {})", type_index.0),
+    }
+}
+
+/// The `offset=` and `align=` keywords of a memory argument, left out when
+/// they hold their default.
+/// Memory 0 is the default of memory instructions and is left implicit.
+fn memory_index_text(memory_index: MemoryIndex) -> String {
+    if memory_index.0 == 0 {
+        String::new()
+    } else {
+        format!(" {}", memory_index.0)
+    }
+}
+
+fn memory_argument_text(memory_argument: MemoryArgument, natural_alignment: u32) -> String {
+    let mut text = memory_index_text(memory_argument.memory_index);
+    if memory_argument.offset != 0 {
+        text.push_str(&format!(" offset={}", memory_argument.offset));
+    }
+    if memory_argument.alignment != natural_alignment {
+        text.push_str(&format!(" align={}", 1u64 << memory_argument.alignment));
+    }
+    text
+}
+
+/// Prints a `v128.const` as four `i32` lanes, which can hold any bit pattern.
+fn v128_const_text(value: i128) -> String {
+    let lanes: Vec<String> = value.to_le_bytes()
+        .chunks(4)
+        .map(|lane| i32::from_le_bytes([lane[0], lane[1], lane[2], lane[3]]).to_string())
+        .collect();
+    format!("v128.const i32x4 {}", lanes.join(" "))
+}
+
+fn format_f32(value: f32) -> String {
+    let bits = value.to_bits();
+    let sign = if bits >> 31 == 1 { "-" } else { "" };
+    let payload = bits & 0x7F_FFFF;
+    if value.is_nan() && payload == 0x40_0000 {
+        format!("{}nan", sign)
+    } else if value.is_nan() {
+        format!("{}nan:0x{:x}", sign, payload)
+    } else if value.is_infinite() {
+        format!("{}inf", sign)
+    } else {
+        format!("{:?}", value)
+    }
+}
+
+fn format_f64(value: f64) -> String {
+    let bits = value.to_bits();
+    let sign = if bits >> 63 == 1 { "-" } else { "" };
+    let payload = bits & 0xF_FFFF_FFFF_FFFF;
+    if value.is_nan() && payload == 0x8_0000_0000_0000 {
+        format!("{}nan", sign)
+    } else if value.is_nan() {
+        format!("{}nan:0x{:x}", sign, payload)
+    } else if value.is_infinite() {
+        format!("{}inf", sign)
+    } else {
+        format!("{:?}", value)
+    }
+}
+
+/// Quotes bytes as a string, escaping quotes, backslashes and anything
+/// outside printable ASCII.
+fn escape(bytes: &[u8]) -> String {
+    let mut escaped = String::with_capacity(bytes.len() + 2);
+    escaped.push('"');
+    for &byte in bytes {
+        match byte {
+            b'"' | b'\\' => {
+                escaped.push('\\');
+                escaped.push(byte as char);
+            },
+            0x20..=0x7E => escaped.push(byte as char),
+            _ => escaped.push_str(&format!("\\{:02x}", byte)),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+    use crate::text::{parse, print, PrintError};
+    use crate::encoder::{ModuleEncoder, CustomSectionEncoder, BinaryWriter};
+
+    #[test]
+    fn hello_world() {
+        let module = std::fs::read("hello.wasm").unwrap();
+        assert_eq!(print(&module).unwrap(), concat!(
+            "(module\n",
+            "  (type (;0;) (func))\n",
+            "  (import \"\" \"hello\" (func (;0;) (type 0)))\n",
+            "  (export \"run\" (func 1))\n",
+            "  (func (;1;) (type 0)\n",
+            "    call 0))\n",
+        ));
+    }
+
+    #[test]
+    fn control_instructions_in_expressions_are_errors() {
+        let mut module = ModuleEncoder::new().finish();
+        // An immutable i32 global initialized by `else end`.
+        module.extend_from_slice(&[6, 5, 1, 0x7F, 0, 0x05, 0x0B]);
+        let error = print(&module).unwrap_err();
+        assert_eq!(error, Error::Print(PrintError::ControlInstructionInExpression(13)));
+        assert_eq!(error.to_string(), "control instruction at offset 13 can't be printed in a constant expression");
+    }
+
+    #[test]
+    fn delegate_outside_try_is_an_error() {
+        let mut module = ModuleEncoder::new().finish();
+        // A function of type 0 whose body is `delegate 0 end end`.
+        module.extend_from_slice(&[1, 4, 1, 0x60, 0, 0, 3, 2, 1, 0, 10, 7, 1, 5, 0, 0x18, 0, 0x0B, 0x0B]);
+        assert_eq!(print(&module), Err(Error::Print(PrintError::UnmatchedDelegate(23))));
+    }
+
+    #[test]
+    fn printed_modules_parse_back() {
+        let module = parse(r#"
+            (type $binary (func (param i32 i32) (result i32)))
+            (import "env" "g" (global $imported i32))
+            (func $add (type $binary) (i32.add (local.get 0) (local.get 1)))
+            (func $main (param $x i32) (result f64) (local f32 f32) (local $y i64)
+              (block $outer (result i32)
+                (loop (br_table 0 $outer (i32.const 1) (i32.const 2))))
+              drop
+              (if (result f64) (local.get $x)
+                (then (f64.const -nan:0x1234))
+                (else (f64.const 0.1))))
+            (memory (export "memory") (data "hi\00\"\\\ff"))
+            (global (mut f32) (f32.const -inf))
+            (global (mut i32) (global.get $imported))
+            (table funcref (elem $add $main))
+            (func $store (i64.store offset=3 align=1 (i32.const 0) (i64.const -5)))
+            (start $store)
+        "#).unwrap();
+        let text = print(&module).unwrap();
+        assert!(text.contains("    block (result i32)\n      loop\n"));
+        assert!(text.contains("        br_table 0 1\n      end\n    end\n"));
+        assert!(text.contains("    i64.store offset=3 align=1)\n"));
+        assert!(text.contains("(data (;0;) (i32.const 0) \"hi\\00\\\"\\\\\\ff\")"));
+        assert_eq!(parse(&text).unwrap(), module);
+    }
+
+    #[test]
+    fn bulk_memory_parses_back() {
+        let module = parse(r#"
+            (memory 1)
+            (table 1 funcref)
+            (func $f
+              (memory.init $passive (i32.const 0) (i32.const 0) (i32.const 2))
+              (data.drop $passive)
+              (memory.copy (i32.const 0) (i32.const 8) (i32.const 2))
+              (table.init $elems (i32.const 0) (i32.const 0) (i32.const 1))
+              (elem.drop 0)
+              (table.copy (i32.const 0) (i32.const 0) (i32.const 0)))
+            (elem $elems (i32.const 0) $f)
+            (data $passive "hi")
+        "#).unwrap();
+        let text = print(&module).unwrap();
+        assert!(text.contains("    memory.init 0\n    data.drop 0\n"));
+        assert!(text.contains("    table.init 0\n"));
+        assert!(text.contains("(data (;0;) \"hi\")"));
+        assert_eq!(parse(&text).unwrap(), module);
+    }
+
+    #[test]
+    fn reference_types_parse_back() {
+        let module = parse(r#"
+            (type $t (func))
+            (table $funcs 2 funcref)
+            (table $externs 1 externref)
+            (func $f
+              (table.set $externs (i32.const 0) (ref.null extern))
+              (drop (select (result funcref) (ref.func $f) (ref.null func) (i32.const 1)))
+              (drop (table.grow $funcs (ref.null func) (i32.const 1)))
+              (call_indirect $funcs (type $t) (i32.const 0)))
+            (elem (table $funcs) (i32.const 0) funcref (ref.func $f) (item ref.null func))
+            (elem externref (ref.null extern))
+            (elem declare func $f)
+        "#).unwrap();
+        let text = print(&module).unwrap();
+        assert!(text.contains("(table (;1;) 1 externref)"));
+        assert!(text.contains("    select (result funcref)\n"));
+        assert!(text.contains("    call_indirect (type 0)"));
+        assert!(text.contains("(elem (;0;) (i32.const 0) funcref (item (ref.func 0)) (item (ref.null func)))"));
+        assert!(text.contains("(elem (;2;) declare func 0)"));
+        assert_eq!(parse(&text).unwrap(), module);
+    }
+
+    #[test]
+    fn simd_parse_back() {
+        let module = parse(r#"
+            (memory 1)
+            (func (param v128) (result i32)
+              (v128.store offset=16 (i32.const 0)
+                (i8x16.shuffle 0 1 2 3 4 5 6 7 16 17 18 19 20 21 22 23
+                  (v128.load align=8 (i32.const 0))
+                  (v128.const f32x4 1.5 -0 inf 2)))
+              (drop (v128.load8_lane 15 (i32.const 0) (local.get 0)))
+              (i64x2.extract_lane 1 (i64x2.splat (i64.const -1)))
+              (drop)
+              (i32x4.all_true (v128.const i8x16 -1 255 0 1 2 3 4 5 6 7 8 9 10 11 12 0x80)))
+        "#).unwrap();
+        let text = print(&module).unwrap();
+        assert!(text.contains("(param v128)"));
+        assert!(text.contains("    v128.load align=8\n"));
+        assert!(text.contains("    v128.const i32x4 1069547520 -2147483648 2139095040 1073741824\n"));
+        assert!(text.contains("    v128.load8_lane 15\n"));
+        assert!(text.contains("    i64x2.extract_lane 1\n"));
+        assert!(text.contains("    v128.store offset=16\n"));
+        assert_eq!(parse(&text).unwrap(), module);
+    }
+
+    #[test]
+    fn threads_parse_back() {
+        let module = parse(r#"
+            (memory 1 1 shared)
+            (func (result i64)
+              (atomic.fence)
+              (drop (memory.atomic.notify offset=4 (i32.const 0) (i32.const 1)))
+              (i64.atomic.rmw8.cmpxchg_u (i32.const 0) (i64.const 1) (i64.const 2)))
+        "#).unwrap();
+        let text = print(&module).unwrap();
+        assert!(text.contains("(memory (;0;) 1 1 shared)"));
+        assert!(text.contains("    memory.atomic.notify offset=4\n"));
+        assert!(text.contains("    i64.atomic.rmw8.cmpxchg_u"));
+        assert_eq!(parse(&text).unwrap(), module);
+    }
+
+    #[test]
+    fn exceptions_parse_back() {
+        let module = parse(r#"
+            (tag $e (export "e") (param i32))
+            (func (param i32) (result i32)
+              (try (result i32)
+                (do (local.get 0) (throw $e))
+                (catch $e)
+                (catch_all (rethrow 0))))
+            (func
+              (try (do (nop)) (delegate 0)))
+            (func (result exnref)
+              (block $b (result exnref)
+                (try_table (catch_all_ref $b) (ref.null exn) (throw_ref))
+                (ref.null exn)))
+        "#).unwrap();
+        let text = print(&module).unwrap();
+        assert!(text.contains("(tag (;0;) (type 0))"));
+        assert!(text.contains("(export \"e\" (tag 0))"));
+        assert!(text.contains("  catch 0\n"));
+        assert!(text.contains("  catch_all\n"));
+        assert!(text.contains("    delegate 0)"));
+        assert!(text.contains("      try_table (catch_all_ref 0)\n"));
+        assert_eq!(parse(&text).unwrap(), module);
+    }
+
+    #[test]
+    fn multi_memory_parse_back() {
+        let module = parse(r#"
+            (memory 1)
+            (memory 1)
+            (func (param v128)
+              (drop (i64.load 1 offset=8 align=4 (i32.const 0)))
+              (drop (v128.load8_lane 1 15 (i32.const 0) (local.get 0)))
+              (memory.copy 1 0 (i32.const 0) (i32.const 0) (i32.const 1))
+              (drop (memory.size 1)))
+        "#).unwrap();
+        let text = print(&module).unwrap();
+        assert!(text.contains("    i64.load 1 offset=8 align=4\n"));
+        assert!(text.contains("    v128.load8_lane 1 15\n"));
+        assert!(text.contains("    memory.copy 1 0\n"));
+        assert!(text.contains("    memory.size 1\n"));
+        assert_eq!(parse(&text).unwrap(), module);
+    }
+
+    #[test]
+    fn memory64_parse_back() {
+        let module = parse(r#"
+            (import "env" "memory" (memory i64 1 0x1_0000_0000 shared))
+            (memory $m i64 (data "hi"))
+            (func (result i64)
+              (drop (i64.load offset=0x1_0000_0000 (i64.const 0)))
+              (memory.size $m))
+        "#).unwrap();
+        let text = print(&module).unwrap();
+        assert!(text.contains("(memory (;0;) i64 1 4294967296 shared)"));
+        assert!(text.contains("(memory (;1;) i64 1 1)"));
+        assert!(text.contains("    i64.load offset=4294967296\n"));
+        assert_eq!(parse(&text).unwrap(), module);
+    }
+
+    #[test]
+    fn names_come_from_the_name_section() {
+        let mut module = parse(r#"
+            (func $hello (import "" "hello") (param i32))
+            (func (param i32) (local i64)
+              (call $hello (local.get 0)))
+        "#).unwrap();
+        let mut names = BinaryWriter::new();
+        let mut function_names = BinaryWriter::new();
+        function_names.write_leb128_u32(2);
+        function_names.write_leb128_u32(0);
+        function_names.write_string("hello");
+        function_names.write_leb128_u32(1);
+        function_names.write_string("not an identifier");
+        names.write_byte(1);
+        names.write_bytes_vec(function_names.as_slice());
+        let mut local_names = BinaryWriter::new();
+        local_names.write_leb128_u32(1);
+        local_names.write_leb128_u32(1);
+        local_names.write_leb128_u32(2);
+        local_names.write_leb128_u32(0);
+        local_names.write_string("x");
+        local_names.write_leb128_u32(1);
+        local_names.write_string("y");
+        names.write_byte(2);
+        names.write_bytes_vec(local_names.as_slice());
+        let mut custom = ModuleEncoder::new();
+        custom.section(&CustomSectionEncoder::new("name", names.as_slice()));
+        module.extend_from_slice(&custom.finish()[8..]);
+
+        let text = print(&module).unwrap();
+        assert!(text.contains("(import \"\" \"hello\" (func $hello (type 0)))"));
+        assert!(text.contains("(func (;1;) (type 0) (param $x i32)\n    (local $y i64)\n    local.get $x\n    call $hello)"));
+    }
+}
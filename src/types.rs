@@ -2,11 +2,12 @@ use crate::{BranchTableReader, InstructionReader};
 use std::ops::Range;
 use crate::validators::code::CodeValidationError;
 use std::iter::empty;
+use std::fmt;
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug, PartialOrd, Ord)]
 pub struct TypeIndex(pub(crate) u32);
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug, PartialOrd)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, PartialOrd, Hash)]
 pub struct FuncIndex(pub(crate) u32);
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug, PartialOrd)]
@@ -18,18 +19,114 @@ pub struct MemoryIndex(pub(crate) u32);
 #[derive(PartialEq, Eq, Clone, Copy, Debug, PartialOrd)]
 pub struct GlobalIndex(pub(crate) u32);
 
+#[derive(PartialEq, Eq, Clone, Copy, Debug, PartialOrd)]
+pub struct ElementIndex(pub(crate) u32);
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, PartialOrd)]
+pub struct DataIndex(pub(crate) u32);
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, PartialOrd)]
+pub struct TagIndex(pub(crate) u32);
+
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub struct LocalIndex(pub(crate) u32);
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub struct LabelIndex(pub(crate) u32);
 
+impl From<u32> for TypeIndex {
+    fn from(index: u32) -> Self {
+        TypeIndex(index)
+    }
+}
+
+impl From<u32> for FuncIndex {
+    fn from(index: u32) -> Self {
+        FuncIndex(index)
+    }
+}
+
+impl From<u32> for TableIndex {
+    fn from(index: u32) -> Self {
+        TableIndex(index)
+    }
+}
+
+impl From<u32> for MemoryIndex {
+    fn from(index: u32) -> Self {
+        MemoryIndex(index)
+    }
+}
+
+impl From<u32> for GlobalIndex {
+    fn from(index: u32) -> Self {
+        GlobalIndex(index)
+    }
+}
+
+impl From<u32> for ElementIndex {
+    fn from(index: u32) -> Self {
+        ElementIndex(index)
+    }
+}
+
+impl From<u32> for DataIndex {
+    fn from(index: u32) -> Self {
+        DataIndex(index)
+    }
+}
+
+impl From<u32> for TagIndex {
+    fn from(index: u32) -> Self {
+        TagIndex(index)
+    }
+}
+
+impl From<u32> for LocalIndex {
+    fn from(index: u32) -> Self {
+        LocalIndex(index)
+    }
+}
+
+impl From<u32> for LabelIndex {
+    fn from(index: u32) -> Self {
+        LabelIndex(index)
+    }
+}
+
 #[derive(Eq, PartialEq, Debug, Clone, Copy)]
 pub enum ValueType {
     I32,
     I64,
     F32,
     F64,
+    V128,
+    FuncRef,
+    ExternRef,
+    ExnRef,
+}
+
+impl ValueType {
+    /// Whether values of this type are references, which can be null.
+    pub fn is_reference(&self) -> bool {
+        matches!(self, ValueType::FuncRef | ValueType::ExternRef | ValueType::ExnRef)
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ValueType::I32 => "i32",
+            ValueType::I64 => "i64",
+            ValueType::F32 => "f32",
+            ValueType::F64 => "f64",
+            ValueType::V128 => "v128",
+            ValueType::FuncRef => "funcref",
+            ValueType::ExternRef => "externref",
+            ValueType::ExnRef => "exnref",
+        };
+        f.write_str(name)
+    }
 }
 
 #[derive(Eq, PartialEq, Debug)]
@@ -39,11 +136,11 @@ pub struct FunctionType {
 }
 
 impl FunctionType {
-    fn params(&self) -> FunctionTypeParams {
+    pub fn params(&self) -> FunctionTypeParams<'_> {
         FunctionTypeParams { function_type: self, range: 0..self.params.len() }
     }
 
-    fn results(&self) -> FunctionTypeResults {
+    pub fn results(&self) -> FunctionTypeResults<'_> {
         FunctionTypeResults { function_type: self, range: 0..self.results.len() }
     }
 }
@@ -100,6 +197,7 @@ pub enum ImportDescriptor {
     Table(TableType),
     Memory(MemoryType),
     Global(GlobalType),
+    Tag(TagType),
 }
 
 #[derive(Debug)]
@@ -115,6 +213,7 @@ pub enum ExportDescriptor {
     Table { table_index: TableIndex },
     Memory { memory_index: MemoryIndex },
     Global { global_index: GlobalIndex },
+    Tag { tag_index: TagIndex },
 }
 
 #[derive(Debug)]
@@ -123,20 +222,88 @@ pub struct Export<'a> {
     pub(crate) export_descriptor: ExportDescriptor
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Limits {
-    pub(crate) min: u32,
-    pub(crate) max: Option<u32>,
+    pub(crate) min: u64,
+    pub(crate) max: Option<u64>,
 }
 
-#[derive(Debug)]
+impl Limits {
+    /// Limits are 64-bit wide for the sake of 64-bit memories, everything
+    /// else must keep them within 32 bits.
+    pub fn new(min: u64, max: Option<u64>) -> Limits {
+        Limits { min, max }
+    }
+
+    pub fn min(&self) -> u64 {
+        self.min
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        self.max
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct TableType {
+    pub(crate) element_type: ValueType,
     pub(crate) limits: Limits
 }
 
-#[derive(Debug)]
+impl TableType {
+    pub fn new(element_type: ValueType, limits: Limits) -> TableType {
+        TableType { element_type, limits }
+    }
+
+    pub fn element_type(&self) -> ValueType {
+        self.element_type
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct MemoryType {
-    pub(crate) limits: Limits
+    pub(crate) limits: Limits,
+    pub(crate) shared: bool,
+    pub(crate) memory64: bool,
+}
+
+impl MemoryType {
+    pub fn new(limits: Limits) -> MemoryType {
+        MemoryType { limits, shared: false, memory64: false }
+    }
+
+    /// A memory that can be shared between threads, which atomic instructions
+    /// are meant for.
+    pub fn new_shared(limits: Limits) -> MemoryType {
+        MemoryType { limits, shared: true, memory64: false }
+    }
+
+    /// A memory addressed with `i64` rather than `i32`, whose limits may
+    /// exceed 4GiB.
+    pub fn new_64(limits: Limits) -> MemoryType {
+        MemoryType { limits, shared: false, memory64: true }
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    pub fn is_shared(&self) -> bool {
+        self.shared
+    }
+
+    /// Returns the type of the addresses used to access the memory.
+    pub fn index_type(&self) -> ValueType {
+        if self.memory64 {
+            ValueType::I64
+        } else {
+            ValueType::I32
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -145,26 +312,155 @@ pub struct GlobalType {
     pub(crate) mutable: bool,
 }
 
+impl GlobalType {
+    pub fn new(var_type: ValueType, mutable: bool) -> GlobalType {
+        GlobalType { var_type, mutable }
+    }
+
+    pub fn value_type(&self) -> ValueType {
+        self.var_type
+    }
+
+    pub fn is_mutable(&self) -> bool {
+        self.mutable
+    }
+}
+
+/// The type of an exception tag, whose function type gives the values an
+/// exception carries as params and has no results.
+#[derive(Debug, Clone, Copy)]
+pub struct TagType {
+    pub(crate) type_index: TypeIndex,
+}
+
+impl TagType {
+    pub fn new(type_index: TypeIndex) -> TagType {
+        TagType { type_index }
+    }
+
+    pub fn type_index(&self) -> TypeIndex {
+        self.type_index
+    }
+}
+
 #[derive(Debug)]
 pub struct GlobalSegment<'a> {
     pub global_type: GlobalType,
     pub instruction_reader: InstructionReader<'a>,
 }
 
+/// An active segment is copied into its table when the module is
+/// instantiated, a passive one only by `table.init`. A declarative segment
+/// is never copied, it only declares the functions `ref.func` may refer to.
+#[derive(Debug)]
+pub enum ElementMode<'a> {
+    Active { table_index: TableIndex, instruction_reader: InstructionReader<'a> },
+    Passive,
+    Declarative,
+}
+
+/// The items of an element segment, either given as function indices or as
+/// constant expressions producing references.
+#[derive(Debug)]
+pub enum ElementItems<'a> {
+    Functions(Box<[FuncIndex]>),
+    Expressions(Box<[InstructionReader<'a>]>),
+}
+
+impl ElementItems<'_> {
+    pub fn len(&self) -> usize {
+        match self {
+            ElementItems::Functions(function_indices) => function_indices.len(),
+            ElementItems::Expressions(expressions) => expressions.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 #[derive(Debug)]
 pub struct ElementSegment<'a> {
-    pub table_index: TableIndex,
-    pub instruction_reader: InstructionReader<'a>,
-    pub function_indices: Box<[FuncIndex]>,
+    pub mode: ElementMode<'a>,
+    pub element_type: ValueType,
+    pub items: ElementItems<'a>,
+}
+
+/// An active segment is copied into its memory when the module is
+/// instantiated, a passive one only by `memory.init`.
+#[derive(Debug)]
+pub enum DataMode<'a> {
+    Active { memory_index: MemoryIndex, instruction_reader: InstructionReader<'a> },
+    Passive,
 }
 
 #[derive(Debug)]
 pub struct DataSegment<'a> {
-    pub memory_index: MemoryIndex,
-    pub instruction_reader: InstructionReader<'a>,
+    pub mode: DataMode<'a>,
     pub bytes: &'a [u8],
 }
 
+/// A name given to the item at `index` in some index space.
+#[derive(PartialEq, Eq, Debug)]
+pub struct Naming<'a> {
+    pub index: u32,
+    pub name: &'a str,
+}
+
+/// The names given to the items of a nested index space, such as the locals
+/// of the function at `index`.
+#[derive(PartialEq, Eq, Debug)]
+pub struct IndirectNaming<'a> {
+    pub index: u32,
+    pub names: Box<[Naming<'a>]>,
+}
+
+/// A subsection of the `name` custom section.
+#[derive(PartialEq, Eq, Debug)]
+pub enum Name<'a> {
+    Module(&'a str),
+    Function(Box<[Naming<'a>]>),
+    Local(Box<[IndirectNaming<'a>]>),
+    Label(Box<[IndirectNaming<'a>]>),
+    Type(Box<[Naming<'a>]>),
+    Table(Box<[Naming<'a>]>),
+    Memory(Box<[Naming<'a>]>),
+    Global(Box<[Naming<'a>]>),
+    Element(Box<[Naming<'a>]>),
+    Data(Box<[Naming<'a>]>),
+    Unknown { id: u8, data: &'a [u8] },
+}
+
+/// A field of the `producers` custom section, such as `language`, with the
+/// producers it lists.
+#[derive(PartialEq, Eq, Debug)]
+pub struct ProducersField<'a> {
+    pub name: &'a str,
+    pub values: Box<[ProducersFieldValue<'a>]>,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct ProducersFieldValue<'a> {
+    pub name: &'a str,
+    pub version: &'a str,
+}
+
+/// Whether a feature of the `target_features` custom section was used (`+`),
+/// must not be used by the host (`-`) or is required (`=`).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FeaturePrefix {
+    Used,
+    Disallowed,
+    Required,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct TargetFeature<'a> {
+    pub prefix: FeaturePrefix,
+    pub name: &'a str,
+}
+
 #[derive(Debug)]
 pub struct Locals {
     pub count: u32,
@@ -203,7 +499,7 @@ impl<A, B> DoubleEndedIterator for Choice<A, B>
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum BlockType {
     Empty,
     ValueType(ValueType),
@@ -212,7 +508,7 @@ pub enum BlockType {
 
 impl BlockType {
     pub(crate) fn params<'a>(&self, function_types: &'a [FunctionType]) -> Result<impl DoubleEndedIterator<Item = ValueType> + 'a, CodeValidationError> {
-        return Ok(match self {
+        Ok(match self {
             BlockType::TypeIndex(type_index) => {
                 let ty = if let Some(function_type) = function_types.get(type_index.0 as usize) {
                     function_type
@@ -226,7 +522,7 @@ impl BlockType {
     }
 
     pub(crate) fn results<'a>(&self, function_types: &'a [FunctionType]) -> Result<impl DoubleEndedIterator<Item = ValueType> + 'a, CodeValidationError> {
-        return Ok(match self {
+        Ok(match self {
             BlockType::Empty => { Choice::OrB(None.into_iter()) }
             BlockType::ValueType(ty) => { Choice::OrB(Some(*ty).into_iter()) }
             BlockType::TypeIndex(type_index) => {
@@ -241,13 +537,26 @@ impl BlockType {
     }
 }
 
-#[derive(Debug)]
+/// A clause of `try_table`, telling which exceptions it catches and the label
+/// it branches to with their values. The `Ref` variants also pass the caught
+/// exception as an `exnref`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CatchClause {
+    Catch { tag_index: TagIndex, label_index: LabelIndex },
+    CatchRef { tag_index: TagIndex, label_index: LabelIndex },
+    CatchAll { label_index: LabelIndex },
+    CatchAllRef { label_index: LabelIndex },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct MemoryArgument {
     pub alignment: u32,
-    pub offset: u32,
+    pub offset: u64,
+    /// The memory accessed, which is only encoded when it isn't memory 0.
+    pub memory_index: MemoryIndex,
 }
 
-#[derive(Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum Instruction<'a> {
     Unreachable,
     Nop,
@@ -255,22 +564,35 @@ pub enum Instruction<'a> {
     Loop { block_type: BlockType },
     If { block_type: BlockType },
     Else,
+    Try { block_type: BlockType },
+    Catch { tag_index: TagIndex },
+    Throw { tag_index: TagIndex },
+    Rethrow { label_index: LabelIndex },
+    ThrowRef,
     End,
     Branch { label_index: LabelIndex },
     BranchIf { label_index: LabelIndex },
     BranchTable { branch_table_reader: BranchTableReader<'a> },
     Return,
     Call { func_index: FuncIndex },
-    CallIndirect { type_index: TypeIndex },
+    CallIndirect { type_index: TypeIndex, table_index: TableIndex },
+    ReturnCall { func_index: FuncIndex },
+    ReturnCallIndirect { type_index: TypeIndex, table_index: TableIndex },
+    Delegate { label_index: LabelIndex },
+    CatchAll,
+    TryTable { block_type: BlockType, catches: Box<[CatchClause]> },
 
     Drop,
     Select,
+    TypedSelect { value_type: ValueType },
 
     LocalGet { local_index: LocalIndex },
     LocalSet { local_index: LocalIndex },
     LocalTee { local_index: LocalIndex },
     GlobalGet { global_index: GlobalIndex },
     GlobalSet { global_index: GlobalIndex },
+    TableGet { table_index: TableIndex },
+    TableSet { table_index: TableIndex },
 
     I32Load { memory_argument: MemoryArgument },
     I64Load { memory_argument: MemoryArgument },
@@ -295,8 +617,8 @@ pub enum Instruction<'a> {
     I64Store8 { memory_argument: MemoryArgument },
     I64Store16 { memory_argument: MemoryArgument },
     I64Store32 { memory_argument: MemoryArgument },
-    MemorySize,
-    MemoryGrow,
+    MemorySize { memory_index: MemoryIndex },
+    MemoryGrow { memory_index: MemoryIndex },
 
     I32Const(i32),
     I64Const(i64),
@@ -441,6 +763,10 @@ pub enum Instruction<'a> {
     I64Extend16s,
     I64Extend32s,
 
+    RefNull { ref_type: ValueType },
+    RefIsNull,
+    RefFunc { func_index: FuncIndex },
+
     I32TruncSatF32s,
     I32TruncSatF32u,
     I32TruncSatF64s,
@@ -449,4 +775,861 @@ pub enum Instruction<'a> {
     I64TruncSatF32u,
     I64TruncSatF64s,
     I64TruncSatF64u,
+
+    MemoryInit { data_index: DataIndex, memory_index: MemoryIndex },
+    DataDrop { data_index: DataIndex },
+    MemoryCopy { destination_memory_index: MemoryIndex, source_memory_index: MemoryIndex },
+    MemoryFill { memory_index: MemoryIndex },
+    TableInit { element_index: ElementIndex, table_index: TableIndex },
+    ElemDrop { element_index: ElementIndex },
+    TableCopy { destination_table_index: TableIndex, source_table_index: TableIndex },
+    TableGrow { table_index: TableIndex },
+    TableSize { table_index: TableIndex },
+    TableFill { table_index: TableIndex },
+
+    V128Load { memory_argument: MemoryArgument },
+    V128Load8x8s { memory_argument: MemoryArgument },
+    V128Load8x8u { memory_argument: MemoryArgument },
+    V128Load16x4s { memory_argument: MemoryArgument },
+    V128Load16x4u { memory_argument: MemoryArgument },
+    V128Load32x2s { memory_argument: MemoryArgument },
+    V128Load32x2u { memory_argument: MemoryArgument },
+    V128Load8Splat { memory_argument: MemoryArgument },
+    V128Load16Splat { memory_argument: MemoryArgument },
+    V128Load32Splat { memory_argument: MemoryArgument },
+    V128Load64Splat { memory_argument: MemoryArgument },
+    V128Store { memory_argument: MemoryArgument },
+    V128Const(i128),
+    I8x16Shuffle { lanes: [u8; 16] },
+    I8x16Swizzle,
+    I8x16Splat,
+    I16x8Splat,
+    I32x4Splat,
+    I64x2Splat,
+    F32x4Splat,
+    F64x2Splat,
+    I8x16ExtractLanes { lane: u8 },
+    I8x16ExtractLaneu { lane: u8 },
+    I8x16ReplaceLane { lane: u8 },
+    I16x8ExtractLanes { lane: u8 },
+    I16x8ExtractLaneu { lane: u8 },
+    I16x8ReplaceLane { lane: u8 },
+    I32x4ExtractLane { lane: u8 },
+    I32x4ReplaceLane { lane: u8 },
+    I64x2ExtractLane { lane: u8 },
+    I64x2ReplaceLane { lane: u8 },
+    F32x4ExtractLane { lane: u8 },
+    F32x4ReplaceLane { lane: u8 },
+    F64x2ExtractLane { lane: u8 },
+    F64x2ReplaceLane { lane: u8 },
+    I8x16Eq,
+    I8x16Ne,
+    I8x16Lts,
+    I8x16Ltu,
+    I8x16Gts,
+    I8x16Gtu,
+    I8x16Les,
+    I8x16Leu,
+    I8x16Ges,
+    I8x16Geu,
+    I16x8Eq,
+    I16x8Ne,
+    I16x8Lts,
+    I16x8Ltu,
+    I16x8Gts,
+    I16x8Gtu,
+    I16x8Les,
+    I16x8Leu,
+    I16x8Ges,
+    I16x8Geu,
+    I32x4Eq,
+    I32x4Ne,
+    I32x4Lts,
+    I32x4Ltu,
+    I32x4Gts,
+    I32x4Gtu,
+    I32x4Les,
+    I32x4Leu,
+    I32x4Ges,
+    I32x4Geu,
+    F32x4Eq,
+    F32x4Ne,
+    F32x4Lt,
+    F32x4Gt,
+    F32x4Le,
+    F32x4Ge,
+    F64x2Eq,
+    F64x2Ne,
+    F64x2Lt,
+    F64x2Gt,
+    F64x2Le,
+    F64x2Ge,
+    V128Not,
+    V128And,
+    V128Andnot,
+    V128Or,
+    V128Xor,
+    V128Bitselect,
+    V128AnyTrue,
+    V128Load8Lane { memory_argument: MemoryArgument, lane: u8 },
+    V128Load16Lane { memory_argument: MemoryArgument, lane: u8 },
+    V128Load32Lane { memory_argument: MemoryArgument, lane: u8 },
+    V128Load64Lane { memory_argument: MemoryArgument, lane: u8 },
+    V128Store8Lane { memory_argument: MemoryArgument, lane: u8 },
+    V128Store16Lane { memory_argument: MemoryArgument, lane: u8 },
+    V128Store32Lane { memory_argument: MemoryArgument, lane: u8 },
+    V128Store64Lane { memory_argument: MemoryArgument, lane: u8 },
+    V128Load32Zero { memory_argument: MemoryArgument },
+    V128Load64Zero { memory_argument: MemoryArgument },
+    F32x4DemoteF64x2Zero,
+    F64x2PromoteLowF32x4,
+    I8x16Abs,
+    I8x16Neg,
+    I8x16Popcnt,
+    I8x16AllTrue,
+    I8x16Bitmask,
+    I8x16NarrowI16x8s,
+    I8x16NarrowI16x8u,
+    F32x4Ceil,
+    F32x4Floor,
+    F32x4Trunc,
+    F32x4Nearest,
+    I8x16Shl,
+    I8x16Shrs,
+    I8x16Shru,
+    I8x16Add,
+    I8x16AddSats,
+    I8x16AddSatu,
+    I8x16Sub,
+    I8x16SubSats,
+    I8x16SubSatu,
+    F64x2Ceil,
+    F64x2Floor,
+    I8x16Mins,
+    I8x16Minu,
+    I8x16Maxs,
+    I8x16Maxu,
+    F64x2Trunc,
+    I8x16Avgru,
+    I16x8ExtaddPairwiseI8x16s,
+    I16x8ExtaddPairwiseI8x16u,
+    I32x4ExtaddPairwiseI16x8s,
+    I32x4ExtaddPairwiseI16x8u,
+    I16x8Abs,
+    I16x8Neg,
+    I16x8Q15mulrSats,
+    I16x8AllTrue,
+    I16x8Bitmask,
+    I16x8NarrowI32x4s,
+    I16x8NarrowI32x4u,
+    I16x8ExtendLowI8x16s,
+    I16x8ExtendHighI8x16s,
+    I16x8ExtendLowI8x16u,
+    I16x8ExtendHighI8x16u,
+    I16x8Shl,
+    I16x8Shrs,
+    I16x8Shru,
+    I16x8Add,
+    I16x8AddSats,
+    I16x8AddSatu,
+    I16x8Sub,
+    I16x8SubSats,
+    I16x8SubSatu,
+    F64x2Nearest,
+    I16x8Mul,
+    I16x8Mins,
+    I16x8Minu,
+    I16x8Maxs,
+    I16x8Maxu,
+    I16x8Avgru,
+    I16x8ExtmulLowI8x16s,
+    I16x8ExtmulHighI8x16s,
+    I16x8ExtmulLowI8x16u,
+    I16x8ExtmulHighI8x16u,
+    I32x4Abs,
+    I32x4Neg,
+    I32x4AllTrue,
+    I32x4Bitmask,
+    I32x4ExtendLowI16x8s,
+    I32x4ExtendHighI16x8s,
+    I32x4ExtendLowI16x8u,
+    I32x4ExtendHighI16x8u,
+    I32x4Shl,
+    I32x4Shrs,
+    I32x4Shru,
+    I32x4Add,
+    I32x4Sub,
+    I32x4Mul,
+    I32x4Mins,
+    I32x4Minu,
+    I32x4Maxs,
+    I32x4Maxu,
+    I32x4DotI16x8s,
+    I32x4ExtmulLowI16x8s,
+    I32x4ExtmulHighI16x8s,
+    I32x4ExtmulLowI16x8u,
+    I32x4ExtmulHighI16x8u,
+    I64x2Abs,
+    I64x2Neg,
+    I64x2AllTrue,
+    I64x2Bitmask,
+    I64x2ExtendLowI32x4s,
+    I64x2ExtendHighI32x4s,
+    I64x2ExtendLowI32x4u,
+    I64x2ExtendHighI32x4u,
+    I64x2Shl,
+    I64x2Shrs,
+    I64x2Shru,
+    I64x2Add,
+    I64x2Sub,
+    I64x2Mul,
+    I64x2Eq,
+    I64x2Ne,
+    I64x2Lts,
+    I64x2Gts,
+    I64x2Les,
+    I64x2Ges,
+    I64x2ExtmulLowI32x4s,
+    I64x2ExtmulHighI32x4s,
+    I64x2ExtmulLowI32x4u,
+    I64x2ExtmulHighI32x4u,
+    F32x4Abs,
+    F32x4Neg,
+    F32x4Sqrt,
+    F32x4Add,
+    F32x4Sub,
+    F32x4Mul,
+    F32x4Div,
+    F32x4Min,
+    F32x4Max,
+    F32x4Pmin,
+    F32x4Pmax,
+    F64x2Abs,
+    F64x2Neg,
+    F64x2Sqrt,
+    F64x2Add,
+    F64x2Sub,
+    F64x2Mul,
+    F64x2Div,
+    F64x2Min,
+    F64x2Max,
+    F64x2Pmin,
+    F64x2Pmax,
+    I32x4TruncSatF32x4s,
+    I32x4TruncSatF32x4u,
+    F32x4ConvertI32x4s,
+    F32x4ConvertI32x4u,
+    I32x4TruncSatF64x2sZero,
+    I32x4TruncSatF64x2uZero,
+    F64x2ConvertLowI32x4s,
+    F64x2ConvertLowI32x4u,
+
+    MemoryAtomicNotify { memory_argument: MemoryArgument },
+    MemoryAtomicWait32 { memory_argument: MemoryArgument },
+    MemoryAtomicWait64 { memory_argument: MemoryArgument },
+    AtomicFence,
+    I32AtomicLoad { memory_argument: MemoryArgument },
+    I64AtomicLoad { memory_argument: MemoryArgument },
+    I32AtomicLoad8u { memory_argument: MemoryArgument },
+    I32AtomicLoad16u { memory_argument: MemoryArgument },
+    I64AtomicLoad8u { memory_argument: MemoryArgument },
+    I64AtomicLoad16u { memory_argument: MemoryArgument },
+    I64AtomicLoad32u { memory_argument: MemoryArgument },
+    I32AtomicStore { memory_argument: MemoryArgument },
+    I64AtomicStore { memory_argument: MemoryArgument },
+    I32AtomicStore8 { memory_argument: MemoryArgument },
+    I32AtomicStore16 { memory_argument: MemoryArgument },
+    I64AtomicStore8 { memory_argument: MemoryArgument },
+    I64AtomicStore16 { memory_argument: MemoryArgument },
+    I64AtomicStore32 { memory_argument: MemoryArgument },
+    I32AtomicRmwAdd { memory_argument: MemoryArgument },
+    I64AtomicRmwAdd { memory_argument: MemoryArgument },
+    I32AtomicRmw8Addu { memory_argument: MemoryArgument },
+    I32AtomicRmw16Addu { memory_argument: MemoryArgument },
+    I64AtomicRmw8Addu { memory_argument: MemoryArgument },
+    I64AtomicRmw16Addu { memory_argument: MemoryArgument },
+    I64AtomicRmw32Addu { memory_argument: MemoryArgument },
+    I32AtomicRmwSub { memory_argument: MemoryArgument },
+    I64AtomicRmwSub { memory_argument: MemoryArgument },
+    I32AtomicRmw8Subu { memory_argument: MemoryArgument },
+    I32AtomicRmw16Subu { memory_argument: MemoryArgument },
+    I64AtomicRmw8Subu { memory_argument: MemoryArgument },
+    I64AtomicRmw16Subu { memory_argument: MemoryArgument },
+    I64AtomicRmw32Subu { memory_argument: MemoryArgument },
+    I32AtomicRmwAnd { memory_argument: MemoryArgument },
+    I64AtomicRmwAnd { memory_argument: MemoryArgument },
+    I32AtomicRmw8Andu { memory_argument: MemoryArgument },
+    I32AtomicRmw16Andu { memory_argument: MemoryArgument },
+    I64AtomicRmw8Andu { memory_argument: MemoryArgument },
+    I64AtomicRmw16Andu { memory_argument: MemoryArgument },
+    I64AtomicRmw32Andu { memory_argument: MemoryArgument },
+    I32AtomicRmwOr { memory_argument: MemoryArgument },
+    I64AtomicRmwOr { memory_argument: MemoryArgument },
+    I32AtomicRmw8Oru { memory_argument: MemoryArgument },
+    I32AtomicRmw16Oru { memory_argument: MemoryArgument },
+    I64AtomicRmw8Oru { memory_argument: MemoryArgument },
+    I64AtomicRmw16Oru { memory_argument: MemoryArgument },
+    I64AtomicRmw32Oru { memory_argument: MemoryArgument },
+    I32AtomicRmwXor { memory_argument: MemoryArgument },
+    I64AtomicRmwXor { memory_argument: MemoryArgument },
+    I32AtomicRmw8Xoru { memory_argument: MemoryArgument },
+    I32AtomicRmw16Xoru { memory_argument: MemoryArgument },
+    I64AtomicRmw8Xoru { memory_argument: MemoryArgument },
+    I64AtomicRmw16Xoru { memory_argument: MemoryArgument },
+    I64AtomicRmw32Xoru { memory_argument: MemoryArgument },
+    I32AtomicRmwXchg { memory_argument: MemoryArgument },
+    I64AtomicRmwXchg { memory_argument: MemoryArgument },
+    I32AtomicRmw8Xchgu { memory_argument: MemoryArgument },
+    I32AtomicRmw16Xchgu { memory_argument: MemoryArgument },
+    I64AtomicRmw8Xchgu { memory_argument: MemoryArgument },
+    I64AtomicRmw16Xchgu { memory_argument: MemoryArgument },
+    I64AtomicRmw32Xchgu { memory_argument: MemoryArgument },
+    I32AtomicRmwCmpxchg { memory_argument: MemoryArgument },
+    I64AtomicRmwCmpxchg { memory_argument: MemoryArgument },
+    I32AtomicRmw8Cmpxchgu { memory_argument: MemoryArgument },
+    I32AtomicRmw16Cmpxchgu { memory_argument: MemoryArgument },
+    I64AtomicRmw8Cmpxchgu { memory_argument: MemoryArgument },
+    I64AtomicRmw16Cmpxchgu { memory_argument: MemoryArgument },
+    I64AtomicRmw32Cmpxchgu { memory_argument: MemoryArgument },
+}
+
+impl Instruction<'_> {
+    /// The name of the instruction in the text format, without its immediates.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Instruction::Unreachable => "unreachable",
+            Instruction::Nop => "nop",
+            Instruction::Block { .. } => "block",
+            Instruction::Loop { .. } => "loop",
+            Instruction::If { .. } => "if",
+            Instruction::Else => "else",
+            Instruction::Try { .. } => "try",
+            Instruction::Catch { .. } => "catch",
+            Instruction::Throw { .. } => "throw",
+            Instruction::Rethrow { .. } => "rethrow",
+            Instruction::ThrowRef => "throw_ref",
+            Instruction::End => "end",
+            Instruction::Branch { .. } => "br",
+            Instruction::BranchIf { .. } => "br_if",
+            Instruction::BranchTable { .. } => "br_table",
+            Instruction::Return => "return",
+            Instruction::Call { .. } => "call",
+            Instruction::CallIndirect { .. } => "call_indirect",
+            Instruction::ReturnCall { .. } => "return_call",
+            Instruction::ReturnCallIndirect { .. } => "return_call_indirect",
+            Instruction::Delegate { .. } => "delegate",
+            Instruction::CatchAll => "catch_all",
+            Instruction::TryTable { .. } => "try_table",
+
+            Instruction::Drop => "drop",
+            Instruction::Select => "select",
+            Instruction::TypedSelect { .. } => "select",
+
+            Instruction::LocalGet { .. } => "local.get",
+            Instruction::LocalSet { .. } => "local.set",
+            Instruction::LocalTee { .. } => "local.tee",
+            Instruction::GlobalGet { .. } => "global.get",
+            Instruction::GlobalSet { .. } => "global.set",
+            Instruction::TableGet { .. } => "table.get",
+            Instruction::TableSet { .. } => "table.set",
+
+            Instruction::I32Load { .. } => "i32.load",
+            Instruction::I64Load { .. } => "i64.load",
+            Instruction::F32Load { .. } => "f32.load",
+            Instruction::F64Load { .. } => "f64.load",
+            Instruction::I32Load8s { .. } => "i32.load8_s",
+            Instruction::I32Load8u { .. } => "i32.load8_u",
+            Instruction::I32Load16s { .. } => "i32.load16_s",
+            Instruction::I32Load16u { .. } => "i32.load16_u",
+            Instruction::I64Load8s { .. } => "i64.load8_s",
+            Instruction::I64Load8u { .. } => "i64.load8_u",
+            Instruction::I64Load16s { .. } => "i64.load16_s",
+            Instruction::I64Load16u { .. } => "i64.load16_u",
+            Instruction::I64Load32s { .. } => "i64.load32_s",
+            Instruction::I64Load32u { .. } => "i64.load32_u",
+            Instruction::I32Store { .. } => "i32.store",
+            Instruction::I64Store { .. } => "i64.store",
+            Instruction::F32Store { .. } => "f32.store",
+            Instruction::F64Store { .. } => "f64.store",
+            Instruction::I32Store8 { .. } => "i32.store8",
+            Instruction::I32Store16 { .. } => "i32.store16",
+            Instruction::I64Store8 { .. } => "i64.store8",
+            Instruction::I64Store16 { .. } => "i64.store16",
+            Instruction::I64Store32 { .. } => "i64.store32",
+            Instruction::MemorySize { .. } => "memory.size",
+            Instruction::MemoryGrow { .. } => "memory.grow",
+
+            Instruction::I32Const(_) => "i32.const",
+            Instruction::I64Const(_) => "i64.const",
+            Instruction::F32Const(_) => "f32.const",
+            Instruction::F64Const(_) => "f64.const",
+
+            Instruction::I32Eqz => "i32.eqz",
+            Instruction::I32Eq => "i32.eq",
+            Instruction::I32Ne => "i32.ne",
+            Instruction::I32Lts => "i32.lt_s",
+            Instruction::I32Ltu => "i32.lt_u",
+            Instruction::I32Gts => "i32.gt_s",
+            Instruction::I32Gtu => "i32.gt_u",
+            Instruction::I32Les => "i32.le_s",
+            Instruction::I32Leu => "i32.le_u",
+            Instruction::I32Ges => "i32.ge_s",
+            Instruction::I32Geu => "i32.ge_u",
+
+            Instruction::I64Eqz => "i64.eqz",
+            Instruction::I64Eq => "i64.eq",
+            Instruction::I64Ne => "i64.ne",
+            Instruction::I64Lts => "i64.lt_s",
+            Instruction::I64Ltu => "i64.lt_u",
+            Instruction::I64Gts => "i64.gt_s",
+            Instruction::I64Gtu => "i64.gt_u",
+            Instruction::I64Les => "i64.le_s",
+            Instruction::I64Leu => "i64.le_u",
+            Instruction::I64Ges => "i64.ge_s",
+            Instruction::I64Geu => "i64.ge_u",
+
+            Instruction::F32Eq => "f32.eq",
+            Instruction::F32Ne => "f32.ne",
+            Instruction::F32Lt => "f32.lt",
+            Instruction::F32Gt => "f32.gt",
+            Instruction::F32Le => "f32.le",
+            Instruction::F32Ge => "f32.ge",
+
+            Instruction::F64Eq => "f64.eq",
+            Instruction::F64Ne => "f64.ne",
+            Instruction::F64Lt => "f64.lt",
+            Instruction::F64Gt => "f64.gt",
+            Instruction::F64Le => "f64.le",
+            Instruction::F64Ge => "f64.ge",
+
+            Instruction::I32Clz => "i32.clz",
+            Instruction::I32Ctz => "i32.ctz",
+            Instruction::I32Popcnt => "i32.popcnt",
+            Instruction::I32Add => "i32.add",
+            Instruction::I32Sub => "i32.sub",
+            Instruction::I32Mul => "i32.mul",
+            Instruction::I32Divs => "i32.div_s",
+            Instruction::I32Divu => "i32.div_u",
+            Instruction::I32Rems => "i32.rem_s",
+            Instruction::I32Remu => "i32.rem_u",
+            Instruction::I32And => "i32.and",
+            Instruction::I32Or => "i32.or",
+            Instruction::I32Xor => "i32.xor",
+            Instruction::I32Shl => "i32.shl",
+            Instruction::I32Shrs => "i32.shr_s",
+            Instruction::I32Shru => "i32.shr_u",
+            Instruction::I32Rotl => "i32.rotl",
+            Instruction::I32Rotr => "i32.rotr",
+
+            Instruction::I64Clz => "i64.clz",
+            Instruction::I64Ctz => "i64.ctz",
+            Instruction::I64Popcnt => "i64.popcnt",
+            Instruction::I64Add => "i64.add",
+            Instruction::I64Sub => "i64.sub",
+            Instruction::I64Mul => "i64.mul",
+            Instruction::I64Divs => "i64.div_s",
+            Instruction::I64Divu => "i64.div_u",
+            Instruction::I64Rems => "i64.rem_s",
+            Instruction::I64Remu => "i64.rem_u",
+            Instruction::I64And => "i64.and",
+            Instruction::I64Or => "i64.or",
+            Instruction::I64Xor => "i64.xor",
+            Instruction::I64Shl => "i64.shl",
+            Instruction::I64Shrs => "i64.shr_s",
+            Instruction::I64Shru => "i64.shr_u",
+            Instruction::I64Rotl => "i64.rotl",
+            Instruction::I64Rotr => "i64.rotr",
+
+            Instruction::F32Abs => "f32.abs",
+            Instruction::F32Neg => "f32.neg",
+            Instruction::F32Ceil => "f32.ceil",
+            Instruction::F32Floor => "f32.floor",
+            Instruction::F32Trunc => "f32.trunc",
+            Instruction::F32Nearest => "f32.nearest",
+            Instruction::F32Sqrt => "f32.sqrt",
+            Instruction::F32Add => "f32.add",
+            Instruction::F32Sub => "f32.sub",
+            Instruction::F32Mul => "f32.mul",
+            Instruction::F32Div => "f32.div",
+            Instruction::F32Min => "f32.min",
+            Instruction::F32Max => "f32.max",
+            Instruction::F32Copysign => "f32.copysign",
+
+            Instruction::F64Abs => "f64.abs",
+            Instruction::F64Neg => "f64.neg",
+            Instruction::F64Ceil => "f64.ceil",
+            Instruction::F64Floor => "f64.floor",
+            Instruction::F64Trunc => "f64.trunc",
+            Instruction::F64Nearest => "f64.nearest",
+            Instruction::F64Sqrt => "f64.sqrt",
+            Instruction::F64Add => "f64.add",
+            Instruction::F64Sub => "f64.sub",
+            Instruction::F64Mul => "f64.mul",
+            Instruction::F64Div => "f64.div",
+            Instruction::F64Min => "f64.min",
+            Instruction::F64Max => "f64.max",
+            Instruction::F64Copysign => "f64.copysign",
+
+            Instruction::I32WrapI64 => "i32.wrap_i64",
+            Instruction::I32TruncF32s => "i32.trunc_f32_s",
+            Instruction::I32TruncF32u => "i32.trunc_f32_u",
+            Instruction::I32TruncF64s => "i32.trunc_f64_s",
+            Instruction::I32TruncF64u => "i32.trunc_f64_u",
+            Instruction::I64ExtendI32s => "i64.extend_i32_s",
+            Instruction::I64ExtendI32u => "i64.extend_i32_u",
+            Instruction::I64TruncF32s => "i64.trunc_f32_s",
+            Instruction::I64TruncF32u => "i64.trunc_f32_u",
+            Instruction::I64TruncF64s => "i64.trunc_f64_s",
+            Instruction::I64TruncF64u => "i64.trunc_f64_u",
+            Instruction::F32ConvertI32s => "f32.convert_i32_s",
+            Instruction::F32ConvertI32u => "f32.convert_i32_u",
+            Instruction::F32ConvertI64s => "f32.convert_i64_s",
+            Instruction::F32ConvertI64u => "f32.convert_i64_u",
+            Instruction::F32DemoteF64 => "f32.demote_f64",
+            Instruction::F64ConvertI32s => "f64.convert_i32_s",
+            Instruction::F64ConvertI32u => "f64.convert_i32_u",
+            Instruction::F64ConvertI64s => "f64.convert_i64_s",
+            Instruction::F64ConvertI64u => "f64.convert_i64_u",
+            Instruction::F64PromoteF32 => "f64.promote_f32",
+            Instruction::I32ReinterpretF32 => "i32.reinterpret_f32",
+            Instruction::I64ReinterpretF64 => "i64.reinterpret_f64",
+            Instruction::F32ReinterpretI32 => "f32.reinterpret_i32",
+            Instruction::F64ReinterpretI64 => "f64.reinterpret_i64",
+
+            Instruction::I32Extend8s => "i32.extend8_s",
+            Instruction::I32Extend16s => "i32.extend16_s",
+            Instruction::I64Extend8s => "i64.extend8_s",
+            Instruction::I64Extend16s => "i64.extend16_s",
+            Instruction::I64Extend32s => "i64.extend32_s",
+
+            Instruction::RefNull { .. } => "ref.null",
+            Instruction::RefIsNull => "ref.is_null",
+            Instruction::RefFunc { .. } => "ref.func",
+
+            Instruction::I32TruncSatF32s => "i32.trunc_sat_f32_s",
+            Instruction::I32TruncSatF32u => "i32.trunc_sat_f32_u",
+            Instruction::I32TruncSatF64s => "i32.trunc_sat_f64_s",
+            Instruction::I32TruncSatF64u => "i32.trunc_sat_f64_u",
+            Instruction::I64TruncSatF32s => "i64.trunc_sat_f32_s",
+            Instruction::I64TruncSatF32u => "i64.trunc_sat_f32_u",
+            Instruction::I64TruncSatF64s => "i64.trunc_sat_f64_s",
+            Instruction::I64TruncSatF64u => "i64.trunc_sat_f64_u",
+
+            Instruction::MemoryInit { .. } => "memory.init",
+            Instruction::DataDrop { .. } => "data.drop",
+            Instruction::MemoryCopy { .. } => "memory.copy",
+            Instruction::MemoryFill { .. } => "memory.fill",
+            Instruction::TableInit { .. } => "table.init",
+            Instruction::ElemDrop { .. } => "elem.drop",
+            Instruction::TableCopy { .. } => "table.copy",
+            Instruction::TableGrow { .. } => "table.grow",
+            Instruction::TableSize { .. } => "table.size",
+            Instruction::TableFill { .. } => "table.fill",
+
+            Instruction::V128Load { .. } => "v128.load",
+            Instruction::V128Load8x8s { .. } => "v128.load8x8_s",
+            Instruction::V128Load8x8u { .. } => "v128.load8x8_u",
+            Instruction::V128Load16x4s { .. } => "v128.load16x4_s",
+            Instruction::V128Load16x4u { .. } => "v128.load16x4_u",
+            Instruction::V128Load32x2s { .. } => "v128.load32x2_s",
+            Instruction::V128Load32x2u { .. } => "v128.load32x2_u",
+            Instruction::V128Load8Splat { .. } => "v128.load8_splat",
+            Instruction::V128Load16Splat { .. } => "v128.load16_splat",
+            Instruction::V128Load32Splat { .. } => "v128.load32_splat",
+            Instruction::V128Load64Splat { .. } => "v128.load64_splat",
+            Instruction::V128Store { .. } => "v128.store",
+            Instruction::V128Const(_) => "v128.const",
+            Instruction::I8x16Shuffle { .. } => "i8x16.shuffle",
+            Instruction::I8x16Swizzle => "i8x16.swizzle",
+            Instruction::I8x16Splat => "i8x16.splat",
+            Instruction::I16x8Splat => "i16x8.splat",
+            Instruction::I32x4Splat => "i32x4.splat",
+            Instruction::I64x2Splat => "i64x2.splat",
+            Instruction::F32x4Splat => "f32x4.splat",
+            Instruction::F64x2Splat => "f64x2.splat",
+            Instruction::I8x16ExtractLanes { .. } => "i8x16.extract_lane_s",
+            Instruction::I8x16ExtractLaneu { .. } => "i8x16.extract_lane_u",
+            Instruction::I8x16ReplaceLane { .. } => "i8x16.replace_lane",
+            Instruction::I16x8ExtractLanes { .. } => "i16x8.extract_lane_s",
+            Instruction::I16x8ExtractLaneu { .. } => "i16x8.extract_lane_u",
+            Instruction::I16x8ReplaceLane { .. } => "i16x8.replace_lane",
+            Instruction::I32x4ExtractLane { .. } => "i32x4.extract_lane",
+            Instruction::I32x4ReplaceLane { .. } => "i32x4.replace_lane",
+            Instruction::I64x2ExtractLane { .. } => "i64x2.extract_lane",
+            Instruction::I64x2ReplaceLane { .. } => "i64x2.replace_lane",
+            Instruction::F32x4ExtractLane { .. } => "f32x4.extract_lane",
+            Instruction::F32x4ReplaceLane { .. } => "f32x4.replace_lane",
+            Instruction::F64x2ExtractLane { .. } => "f64x2.extract_lane",
+            Instruction::F64x2ReplaceLane { .. } => "f64x2.replace_lane",
+            Instruction::I8x16Eq => "i8x16.eq",
+            Instruction::I8x16Ne => "i8x16.ne",
+            Instruction::I8x16Lts => "i8x16.lt_s",
+            Instruction::I8x16Ltu => "i8x16.lt_u",
+            Instruction::I8x16Gts => "i8x16.gt_s",
+            Instruction::I8x16Gtu => "i8x16.gt_u",
+            Instruction::I8x16Les => "i8x16.le_s",
+            Instruction::I8x16Leu => "i8x16.le_u",
+            Instruction::I8x16Ges => "i8x16.ge_s",
+            Instruction::I8x16Geu => "i8x16.ge_u",
+            Instruction::I16x8Eq => "i16x8.eq",
+            Instruction::I16x8Ne => "i16x8.ne",
+            Instruction::I16x8Lts => "i16x8.lt_s",
+            Instruction::I16x8Ltu => "i16x8.lt_u",
+            Instruction::I16x8Gts => "i16x8.gt_s",
+            Instruction::I16x8Gtu => "i16x8.gt_u",
+            Instruction::I16x8Les => "i16x8.le_s",
+            Instruction::I16x8Leu => "i16x8.le_u",
+            Instruction::I16x8Ges => "i16x8.ge_s",
+            Instruction::I16x8Geu => "i16x8.ge_u",
+            Instruction::I32x4Eq => "i32x4.eq",
+            Instruction::I32x4Ne => "i32x4.ne",
+            Instruction::I32x4Lts => "i32x4.lt_s",
+            Instruction::I32x4Ltu => "i32x4.lt_u",
+            Instruction::I32x4Gts => "i32x4.gt_s",
+            Instruction::I32x4Gtu => "i32x4.gt_u",
+            Instruction::I32x4Les => "i32x4.le_s",
+            Instruction::I32x4Leu => "i32x4.le_u",
+            Instruction::I32x4Ges => "i32x4.ge_s",
+            Instruction::I32x4Geu => "i32x4.ge_u",
+            Instruction::F32x4Eq => "f32x4.eq",
+            Instruction::F32x4Ne => "f32x4.ne",
+            Instruction::F32x4Lt => "f32x4.lt",
+            Instruction::F32x4Gt => "f32x4.gt",
+            Instruction::F32x4Le => "f32x4.le",
+            Instruction::F32x4Ge => "f32x4.ge",
+            Instruction::F64x2Eq => "f64x2.eq",
+            Instruction::F64x2Ne => "f64x2.ne",
+            Instruction::F64x2Lt => "f64x2.lt",
+            Instruction::F64x2Gt => "f64x2.gt",
+            Instruction::F64x2Le => "f64x2.le",
+            Instruction::F64x2Ge => "f64x2.ge",
+            Instruction::V128Not => "v128.not",
+            Instruction::V128And => "v128.and",
+            Instruction::V128Andnot => "v128.andnot",
+            Instruction::V128Or => "v128.or",
+            Instruction::V128Xor => "v128.xor",
+            Instruction::V128Bitselect => "v128.bitselect",
+            Instruction::V128AnyTrue => "v128.any_true",
+            Instruction::V128Load8Lane { .. } => "v128.load8_lane",
+            Instruction::V128Load16Lane { .. } => "v128.load16_lane",
+            Instruction::V128Load32Lane { .. } => "v128.load32_lane",
+            Instruction::V128Load64Lane { .. } => "v128.load64_lane",
+            Instruction::V128Store8Lane { .. } => "v128.store8_lane",
+            Instruction::V128Store16Lane { .. } => "v128.store16_lane",
+            Instruction::V128Store32Lane { .. } => "v128.store32_lane",
+            Instruction::V128Store64Lane { .. } => "v128.store64_lane",
+            Instruction::V128Load32Zero { .. } => "v128.load32_zero",
+            Instruction::V128Load64Zero { .. } => "v128.load64_zero",
+            Instruction::F32x4DemoteF64x2Zero => "f32x4.demote_f64x2_zero",
+            Instruction::F64x2PromoteLowF32x4 => "f64x2.promote_low_f32x4",
+            Instruction::I8x16Abs => "i8x16.abs",
+            Instruction::I8x16Neg => "i8x16.neg",
+            Instruction::I8x16Popcnt => "i8x16.popcnt",
+            Instruction::I8x16AllTrue => "i8x16.all_true",
+            Instruction::I8x16Bitmask => "i8x16.bitmask",
+            Instruction::I8x16NarrowI16x8s => "i8x16.narrow_i16x8_s",
+            Instruction::I8x16NarrowI16x8u => "i8x16.narrow_i16x8_u",
+            Instruction::F32x4Ceil => "f32x4.ceil",
+            Instruction::F32x4Floor => "f32x4.floor",
+            Instruction::F32x4Trunc => "f32x4.trunc",
+            Instruction::F32x4Nearest => "f32x4.nearest",
+            Instruction::I8x16Shl => "i8x16.shl",
+            Instruction::I8x16Shrs => "i8x16.shr_s",
+            Instruction::I8x16Shru => "i8x16.shr_u",
+            Instruction::I8x16Add => "i8x16.add",
+            Instruction::I8x16AddSats => "i8x16.add_sat_s",
+            Instruction::I8x16AddSatu => "i8x16.add_sat_u",
+            Instruction::I8x16Sub => "i8x16.sub",
+            Instruction::I8x16SubSats => "i8x16.sub_sat_s",
+            Instruction::I8x16SubSatu => "i8x16.sub_sat_u",
+            Instruction::F64x2Ceil => "f64x2.ceil",
+            Instruction::F64x2Floor => "f64x2.floor",
+            Instruction::I8x16Mins => "i8x16.min_s",
+            Instruction::I8x16Minu => "i8x16.min_u",
+            Instruction::I8x16Maxs => "i8x16.max_s",
+            Instruction::I8x16Maxu => "i8x16.max_u",
+            Instruction::F64x2Trunc => "f64x2.trunc",
+            Instruction::I8x16Avgru => "i8x16.avgr_u",
+            Instruction::I16x8ExtaddPairwiseI8x16s => "i16x8.extadd_pairwise_i8x16_s",
+            Instruction::I16x8ExtaddPairwiseI8x16u => "i16x8.extadd_pairwise_i8x16_u",
+            Instruction::I32x4ExtaddPairwiseI16x8s => "i32x4.extadd_pairwise_i16x8_s",
+            Instruction::I32x4ExtaddPairwiseI16x8u => "i32x4.extadd_pairwise_i16x8_u",
+            Instruction::I16x8Abs => "i16x8.abs",
+            Instruction::I16x8Neg => "i16x8.neg",
+            Instruction::I16x8Q15mulrSats => "i16x8.q15mulr_sat_s",
+            Instruction::I16x8AllTrue => "i16x8.all_true",
+            Instruction::I16x8Bitmask => "i16x8.bitmask",
+            Instruction::I16x8NarrowI32x4s => "i16x8.narrow_i32x4_s",
+            Instruction::I16x8NarrowI32x4u => "i16x8.narrow_i32x4_u",
+            Instruction::I16x8ExtendLowI8x16s => "i16x8.extend_low_i8x16_s",
+            Instruction::I16x8ExtendHighI8x16s => "i16x8.extend_high_i8x16_s",
+            Instruction::I16x8ExtendLowI8x16u => "i16x8.extend_low_i8x16_u",
+            Instruction::I16x8ExtendHighI8x16u => "i16x8.extend_high_i8x16_u",
+            Instruction::I16x8Shl => "i16x8.shl",
+            Instruction::I16x8Shrs => "i16x8.shr_s",
+            Instruction::I16x8Shru => "i16x8.shr_u",
+            Instruction::I16x8Add => "i16x8.add",
+            Instruction::I16x8AddSats => "i16x8.add_sat_s",
+            Instruction::I16x8AddSatu => "i16x8.add_sat_u",
+            Instruction::I16x8Sub => "i16x8.sub",
+            Instruction::I16x8SubSats => "i16x8.sub_sat_s",
+            Instruction::I16x8SubSatu => "i16x8.sub_sat_u",
+            Instruction::F64x2Nearest => "f64x2.nearest",
+            Instruction::I16x8Mul => "i16x8.mul",
+            Instruction::I16x8Mins => "i16x8.min_s",
+            Instruction::I16x8Minu => "i16x8.min_u",
+            Instruction::I16x8Maxs => "i16x8.max_s",
+            Instruction::I16x8Maxu => "i16x8.max_u",
+            Instruction::I16x8Avgru => "i16x8.avgr_u",
+            Instruction::I16x8ExtmulLowI8x16s => "i16x8.extmul_low_i8x16_s",
+            Instruction::I16x8ExtmulHighI8x16s => "i16x8.extmul_high_i8x16_s",
+            Instruction::I16x8ExtmulLowI8x16u => "i16x8.extmul_low_i8x16_u",
+            Instruction::I16x8ExtmulHighI8x16u => "i16x8.extmul_high_i8x16_u",
+            Instruction::I32x4Abs => "i32x4.abs",
+            Instruction::I32x4Neg => "i32x4.neg",
+            Instruction::I32x4AllTrue => "i32x4.all_true",
+            Instruction::I32x4Bitmask => "i32x4.bitmask",
+            Instruction::I32x4ExtendLowI16x8s => "i32x4.extend_low_i16x8_s",
+            Instruction::I32x4ExtendHighI16x8s => "i32x4.extend_high_i16x8_s",
+            Instruction::I32x4ExtendLowI16x8u => "i32x4.extend_low_i16x8_u",
+            Instruction::I32x4ExtendHighI16x8u => "i32x4.extend_high_i16x8_u",
+            Instruction::I32x4Shl => "i32x4.shl",
+            Instruction::I32x4Shrs => "i32x4.shr_s",
+            Instruction::I32x4Shru => "i32x4.shr_u",
+            Instruction::I32x4Add => "i32x4.add",
+            Instruction::I32x4Sub => "i32x4.sub",
+            Instruction::I32x4Mul => "i32x4.mul",
+            Instruction::I32x4Mins => "i32x4.min_s",
+            Instruction::I32x4Minu => "i32x4.min_u",
+            Instruction::I32x4Maxs => "i32x4.max_s",
+            Instruction::I32x4Maxu => "i32x4.max_u",
+            Instruction::I32x4DotI16x8s => "i32x4.dot_i16x8_s",
+            Instruction::I32x4ExtmulLowI16x8s => "i32x4.extmul_low_i16x8_s",
+            Instruction::I32x4ExtmulHighI16x8s => "i32x4.extmul_high_i16x8_s",
+            Instruction::I32x4ExtmulLowI16x8u => "i32x4.extmul_low_i16x8_u",
+            Instruction::I32x4ExtmulHighI16x8u => "i32x4.extmul_high_i16x8_u",
+            Instruction::I64x2Abs => "i64x2.abs",
+            Instruction::I64x2Neg => "i64x2.neg",
+            Instruction::I64x2AllTrue => "i64x2.all_true",
+            Instruction::I64x2Bitmask => "i64x2.bitmask",
+            Instruction::I64x2ExtendLowI32x4s => "i64x2.extend_low_i32x4_s",
+            Instruction::I64x2ExtendHighI32x4s => "i64x2.extend_high_i32x4_s",
+            Instruction::I64x2ExtendLowI32x4u => "i64x2.extend_low_i32x4_u",
+            Instruction::I64x2ExtendHighI32x4u => "i64x2.extend_high_i32x4_u",
+            Instruction::I64x2Shl => "i64x2.shl",
+            Instruction::I64x2Shrs => "i64x2.shr_s",
+            Instruction::I64x2Shru => "i64x2.shr_u",
+            Instruction::I64x2Add => "i64x2.add",
+            Instruction::I64x2Sub => "i64x2.sub",
+            Instruction::I64x2Mul => "i64x2.mul",
+            Instruction::I64x2Eq => "i64x2.eq",
+            Instruction::I64x2Ne => "i64x2.ne",
+            Instruction::I64x2Lts => "i64x2.lt_s",
+            Instruction::I64x2Gts => "i64x2.gt_s",
+            Instruction::I64x2Les => "i64x2.le_s",
+            Instruction::I64x2Ges => "i64x2.ge_s",
+            Instruction::I64x2ExtmulLowI32x4s => "i64x2.extmul_low_i32x4_s",
+            Instruction::I64x2ExtmulHighI32x4s => "i64x2.extmul_high_i32x4_s",
+            Instruction::I64x2ExtmulLowI32x4u => "i64x2.extmul_low_i32x4_u",
+            Instruction::I64x2ExtmulHighI32x4u => "i64x2.extmul_high_i32x4_u",
+            Instruction::F32x4Abs => "f32x4.abs",
+            Instruction::F32x4Neg => "f32x4.neg",
+            Instruction::F32x4Sqrt => "f32x4.sqrt",
+            Instruction::F32x4Add => "f32x4.add",
+            Instruction::F32x4Sub => "f32x4.sub",
+            Instruction::F32x4Mul => "f32x4.mul",
+            Instruction::F32x4Div => "f32x4.div",
+            Instruction::F32x4Min => "f32x4.min",
+            Instruction::F32x4Max => "f32x4.max",
+            Instruction::F32x4Pmin => "f32x4.pmin",
+            Instruction::F32x4Pmax => "f32x4.pmax",
+            Instruction::F64x2Abs => "f64x2.abs",
+            Instruction::F64x2Neg => "f64x2.neg",
+            Instruction::F64x2Sqrt => "f64x2.sqrt",
+            Instruction::F64x2Add => "f64x2.add",
+            Instruction::F64x2Sub => "f64x2.sub",
+            Instruction::F64x2Mul => "f64x2.mul",
+            Instruction::F64x2Div => "f64x2.div",
+            Instruction::F64x2Min => "f64x2.min",
+            Instruction::F64x2Max => "f64x2.max",
+            Instruction::F64x2Pmin => "f64x2.pmin",
+            Instruction::F64x2Pmax => "f64x2.pmax",
+            Instruction::I32x4TruncSatF32x4s => "i32x4.trunc_sat_f32x4_s",
+            Instruction::I32x4TruncSatF32x4u => "i32x4.trunc_sat_f32x4_u",
+            Instruction::F32x4ConvertI32x4s => "f32x4.convert_i32x4_s",
+            Instruction::F32x4ConvertI32x4u => "f32x4.convert_i32x4_u",
+            Instruction::I32x4TruncSatF64x2sZero => "i32x4.trunc_sat_f64x2_s_zero",
+            Instruction::I32x4TruncSatF64x2uZero => "i32x4.trunc_sat_f64x2_u_zero",
+            Instruction::F64x2ConvertLowI32x4s => "f64x2.convert_low_i32x4_s",
+            Instruction::F64x2ConvertLowI32x4u => "f64x2.convert_low_i32x4_u",
+
+            Instruction::MemoryAtomicNotify { .. } => "memory.atomic.notify",
+            Instruction::MemoryAtomicWait32 { .. } => "memory.atomic.wait32",
+            Instruction::MemoryAtomicWait64 { .. } => "memory.atomic.wait64",
+            Instruction::AtomicFence => "atomic.fence",
+            Instruction::I32AtomicLoad { .. } => "i32.atomic.load",
+            Instruction::I64AtomicLoad { .. } => "i64.atomic.load",
+            Instruction::I32AtomicLoad8u { .. } => "i32.atomic.load8_u",
+            Instruction::I32AtomicLoad16u { .. } => "i32.atomic.load16_u",
+            Instruction::I64AtomicLoad8u { .. } => "i64.atomic.load8_u",
+            Instruction::I64AtomicLoad16u { .. } => "i64.atomic.load16_u",
+            Instruction::I64AtomicLoad32u { .. } => "i64.atomic.load32_u",
+            Instruction::I32AtomicStore { .. } => "i32.atomic.store",
+            Instruction::I64AtomicStore { .. } => "i64.atomic.store",
+            Instruction::I32AtomicStore8 { .. } => "i32.atomic.store8",
+            Instruction::I32AtomicStore16 { .. } => "i32.atomic.store16",
+            Instruction::I64AtomicStore8 { .. } => "i64.atomic.store8",
+            Instruction::I64AtomicStore16 { .. } => "i64.atomic.store16",
+            Instruction::I64AtomicStore32 { .. } => "i64.atomic.store32",
+            Instruction::I32AtomicRmwAdd { .. } => "i32.atomic.rmw.add",
+            Instruction::I64AtomicRmwAdd { .. } => "i64.atomic.rmw.add",
+            Instruction::I32AtomicRmw8Addu { .. } => "i32.atomic.rmw8.add_u",
+            Instruction::I32AtomicRmw16Addu { .. } => "i32.atomic.rmw16.add_u",
+            Instruction::I64AtomicRmw8Addu { .. } => "i64.atomic.rmw8.add_u",
+            Instruction::I64AtomicRmw16Addu { .. } => "i64.atomic.rmw16.add_u",
+            Instruction::I64AtomicRmw32Addu { .. } => "i64.atomic.rmw32.add_u",
+            Instruction::I32AtomicRmwSub { .. } => "i32.atomic.rmw.sub",
+            Instruction::I64AtomicRmwSub { .. } => "i64.atomic.rmw.sub",
+            Instruction::I32AtomicRmw8Subu { .. } => "i32.atomic.rmw8.sub_u",
+            Instruction::I32AtomicRmw16Subu { .. } => "i32.atomic.rmw16.sub_u",
+            Instruction::I64AtomicRmw8Subu { .. } => "i64.atomic.rmw8.sub_u",
+            Instruction::I64AtomicRmw16Subu { .. } => "i64.atomic.rmw16.sub_u",
+            Instruction::I64AtomicRmw32Subu { .. } => "i64.atomic.rmw32.sub_u",
+            Instruction::I32AtomicRmwAnd { .. } => "i32.atomic.rmw.and",
+            Instruction::I64AtomicRmwAnd { .. } => "i64.atomic.rmw.and",
+            Instruction::I32AtomicRmw8Andu { .. } => "i32.atomic.rmw8.and_u",
+            Instruction::I32AtomicRmw16Andu { .. } => "i32.atomic.rmw16.and_u",
+            Instruction::I64AtomicRmw8Andu { .. } => "i64.atomic.rmw8.and_u",
+            Instruction::I64AtomicRmw16Andu { .. } => "i64.atomic.rmw16.and_u",
+            Instruction::I64AtomicRmw32Andu { .. } => "i64.atomic.rmw32.and_u",
+            Instruction::I32AtomicRmwOr { .. } => "i32.atomic.rmw.or",
+            Instruction::I64AtomicRmwOr { .. } => "i64.atomic.rmw.or",
+            Instruction::I32AtomicRmw8Oru { .. } => "i32.atomic.rmw8.or_u",
+            Instruction::I32AtomicRmw16Oru { .. } => "i32.atomic.rmw16.or_u",
+            Instruction::I64AtomicRmw8Oru { .. } => "i64.atomic.rmw8.or_u",
+            Instruction::I64AtomicRmw16Oru { .. } => "i64.atomic.rmw16.or_u",
+            Instruction::I64AtomicRmw32Oru { .. } => "i64.atomic.rmw32.or_u",
+            Instruction::I32AtomicRmwXor { .. } => "i32.atomic.rmw.xor",
+            Instruction::I64AtomicRmwXor { .. } => "i64.atomic.rmw.xor",
+            Instruction::I32AtomicRmw8Xoru { .. } => "i32.atomic.rmw8.xor_u",
+            Instruction::I32AtomicRmw16Xoru { .. } => "i32.atomic.rmw16.xor_u",
+            Instruction::I64AtomicRmw8Xoru { .. } => "i64.atomic.rmw8.xor_u",
+            Instruction::I64AtomicRmw16Xoru { .. } => "i64.atomic.rmw16.xor_u",
+            Instruction::I64AtomicRmw32Xoru { .. } => "i64.atomic.rmw32.xor_u",
+            Instruction::I32AtomicRmwXchg { .. } => "i32.atomic.rmw.xchg",
+            Instruction::I64AtomicRmwXchg { .. } => "i64.atomic.rmw.xchg",
+            Instruction::I32AtomicRmw8Xchgu { .. } => "i32.atomic.rmw8.xchg_u",
+            Instruction::I32AtomicRmw16Xchgu { .. } => "i32.atomic.rmw16.xchg_u",
+            Instruction::I64AtomicRmw8Xchgu { .. } => "i64.atomic.rmw8.xchg_u",
+            Instruction::I64AtomicRmw16Xchgu { .. } => "i64.atomic.rmw16.xchg_u",
+            Instruction::I64AtomicRmw32Xchgu { .. } => "i64.atomic.rmw32.xchg_u",
+            Instruction::I32AtomicRmwCmpxchg { .. } => "i32.atomic.rmw.cmpxchg",
+            Instruction::I64AtomicRmwCmpxchg { .. } => "i64.atomic.rmw.cmpxchg",
+            Instruction::I32AtomicRmw8Cmpxchgu { .. } => "i32.atomic.rmw8.cmpxchg_u",
+            Instruction::I32AtomicRmw16Cmpxchgu { .. } => "i32.atomic.rmw16.cmpxchg_u",
+            Instruction::I64AtomicRmw8Cmpxchgu { .. } => "i64.atomic.rmw8.cmpxchg_u",
+            Instruction::I64AtomicRmw16Cmpxchgu { .. } => "i64.atomic.rmw16.cmpxchg_u",
+            Instruction::I64AtomicRmw32Cmpxchgu { .. } => "i64.atomic.rmw32.cmpxchg_u",
+        }
+    }
 }
@@ -1,9 +1,16 @@
 use crate::{InstructionReader, Instruction, InstructionReaderError, CodeReaderError, BranchReaderError};
 use crate::types::{ValueType, GlobalType, GlobalIndex, LocalIndex, TypeIndex, FuncIndex, Locals, FunctionType, MemoryIndex, MemoryArgument, TableIndex, BlockType, LabelIndex, Choice};
-use crate::validators::code::CodeValidationError::{InvalidInitExpr, TypeMismatch, InvalidGlobalIndex, InvalidLocalIndex, InvalidTypeIndex, InvalidFunctionIndex, SettingImmutableGlobal, UndefinedMemory, InvalidMemoryAlignment, OperandStackEmpty, UndefinedTable, ValuesAtEndOfBlock, InvalidLabelIndex, TargetLabelsTypeMismatch, ElseWithoutIf};
-use std::result;
+use crate::types::{ElementIndex, DataIndex, TableType, MemoryType, TagIndex, CatchClause};
+use crate::validators::code::CodeValidationError::{InvalidInitExpr, NonConstantInstruction, ConstExprDefinedGlobal, ConstExprMutableGlobal, TypeMismatch, InvalidGlobalIndex, InvalidLocalIndex, InvalidTypeIndex, InvalidFunctionIndex, SettingImmutableGlobal, UndefinedMemory, InvalidMemoryOffset, InvalidMemoryAlignment, OperandStackEmpty, UndefinedTable, ValuesAtEndOfBlock, InvalidLabelIndex, TargetLabelsArityMismatch, ElseWithoutIf, InstructionAfterEnd, MissingEnd, InvalidElementIndex, InvalidDataIndex, MissingDataCount};
+use crate::validators::code::CodeValidationError::{UndeclaredFunctionReference, ReferenceExpected, InvalidUntypedSelect, InvalidLaneIndex, InvalidAtomicAlignment};
+use crate::validators::code::CodeValidationError::{DisabledFeature, TailCallResultsMismatch, InvalidTagIndex, CatchWithoutTry, CatchAfterCatchAll};
+use crate::validators::code::CodeValidationError::{DelegateWithoutTry, InvalidRethrowLabel, CatchClauseMismatch};
+use std::{error, fmt, result};
 use crate::readers::section::code::{Code, LocalsReader, LocalsIterationProof};
 use crate::validators::code::Operand::{Unknown, Known};
+use crate::ValidationError;
+use crate::validators::index_space::IndexSpace;
+use crate::validators::features::Features;
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum CodeValidationError {
@@ -11,22 +18,108 @@ pub enum CodeValidationError {
     InstructionReader(InstructionReaderError),
     BranchReader(BranchReaderError),
     InvalidInitExpr,
+    NonConstantInstruction { instruction: &'static str, offset: usize },
+    ConstExprDefinedGlobal(GlobalIndex),
+    ConstExprMutableGlobal(GlobalIndex),
     InvalidGlobalIndex(GlobalIndex),
     SettingImmutableGlobal(GlobalIndex),
     InvalidLocalIndex(LocalIndex),
     InvalidTypeIndex(TypeIndex),
     InvalidFunctionIndex(FuncIndex),
     InvalidLabelIndex(LabelIndex),
+    InvalidElementIndex(ElementIndex),
+    InvalidDataIndex(DataIndex),
+    InvalidTagIndex(TagIndex),
+    MissingDataCount,
+    UndeclaredFunctionReference(FuncIndex),
     UndefinedMemory,
+    InvalidMemoryOffset,
     UndefinedTable,
     InvalidMemoryAlignment,
+    InvalidAtomicAlignment,
+    InvalidLaneIndex(u8),
     TypeMismatch { expected: Operand, actual: Operand },
-    TargetLabelsTypeMismatch,
+    ReferenceExpected(ValueType),
+    InvalidUntypedSelect(ValueType),
+    TargetLabelsArityMismatch { expected: usize, actual: usize },
+    TailCallResultsMismatch,
+    DisabledFeature(&'static str),
     ElseWithoutIf,
+    CatchWithoutTry,
+    CatchAfterCatchAll,
+    DelegateWithoutTry,
+    InvalidRethrowLabel(LabelIndex),
+    CatchClauseMismatch(LabelIndex),
     ValuesAtEndOfBlock,
+    InstructionAfterEnd,
+    MissingEnd,
     OperandStackEmpty,
 }
 
+impl fmt::Display for CodeValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodeValidationError::CodeReader(e) => e.fmt(f),
+            CodeValidationError::InstructionReader(e) => e.fmt(f),
+            CodeValidationError::BranchReader(e) => e.fmt(f),
+            InvalidInitExpr => write!(f, "constant expression required"),
+            NonConstantInstruction { instruction, offset } => {
+                write!(f, "{} at offset {} is not allowed in a constant expression", instruction, offset)
+            },
+            ConstExprDefinedGlobal(index) => {
+                write!(f, "constant expressions can only read imported globals, global {} is defined in the module", index.0)
+            },
+            ConstExprMutableGlobal(index) => write!(f, "constant expressions can't read mutable global {}", index.0),
+            InvalidGlobalIndex(index) => write!(f, "unknown global {}", index.0),
+            SettingImmutableGlobal(index) => write!(f, "global {} is immutable", index.0),
+            InvalidLocalIndex(index) => write!(f, "unknown local {}", index.0),
+            InvalidTypeIndex(index) => write!(f, "unknown type {}", index.0),
+            InvalidFunctionIndex(index) => write!(f, "unknown function {}", index.0),
+            InvalidLabelIndex(index) => write!(f, "unknown label {}", index.0),
+            InvalidElementIndex(index) => write!(f, "unknown element segment {}", index.0),
+            InvalidDataIndex(index) => write!(f, "unknown data segment {}", index.0),
+            InvalidTagIndex(index) => write!(f, "unknown tag {}", index.0),
+            MissingDataCount => write!(f, "data count section required"),
+            UndeclaredFunctionReference(index) => write!(f, "undeclared function reference {}", index.0),
+            UndefinedMemory => write!(f, "unknown memory"),
+            InvalidMemoryOffset => write!(f, "offset out of range for a 32-bit memory"),
+            UndefinedTable => write!(f, "unknown table"),
+            InvalidMemoryAlignment => write!(f, "alignment must not be larger than natural"),
+            InvalidAtomicAlignment => write!(f, "atomic accesses must use their natural alignment"),
+            InvalidLaneIndex(lane) => write!(f, "invalid lane index {}", lane),
+            TypeMismatch { expected, actual } => write!(f, "type mismatch: expected {} but found {}", expected, actual),
+            ReferenceExpected(actual) => write!(f, "type mismatch: expected a reference but found {}", actual),
+            InvalidUntypedSelect(actual) => write!(f, "type mismatch: select without a type requires numeric operands but found {}", actual),
+            TargetLabelsArityMismatch { expected, actual } => {
+                write!(f, "br_table targets have inconsistent arities: expected {} values but found {}", expected, actual)
+            }
+            TailCallResultsMismatch => write!(f, "type mismatch: tail calls must return the results of the calling function"),
+            DisabledFeature(feature) => write!(f, "{} support is not enabled", feature),
+            ElseWithoutIf => write!(f, "else found outside an if block"),
+            CatchWithoutTry => write!(f, "catch found outside a try block"),
+            CatchAfterCatchAll => write!(f, "catch found after catch_all"),
+            DelegateWithoutTry => write!(f, "delegate found outside a try block"),
+            InvalidRethrowLabel(index) => write!(f, "rethrow target {} is not a catch block", index.0),
+            CatchClauseMismatch(index) => write!(f, "type mismatch: caught values don't match the types of label {}", index.0),
+            ValuesAtEndOfBlock => write!(f, "values remaining on the stack at the end of a block"),
+            InstructionAfterEnd => write!(f, "instructions found after the end of the function"),
+            MissingEnd => write!(f, "function body ends before all of its blocks are closed"),
+            OperandStackEmpty => write!(f, "type mismatch: operand stack is empty"),
+        }
+    }
+}
+
+impl error::Error for CodeValidationError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            CodeValidationError::CodeReader(e) => e.source(),
+            CodeValidationError::InstructionReader(e) => e.source(),
+            CodeValidationError::BranchReader(e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
 impl From<CodeReaderError> for CodeValidationError {
     fn from(e: CodeReaderError) -> Self {
         CodeValidationError::CodeReader(e)
@@ -47,42 +140,86 @@ impl From<BranchReaderError> for CodeValidationError {
 
 pub type Result<T, E = CodeValidationError> = result::Result<T, E>;
 
+/// Checks that the expression read by `instruction_reader` is a constant one
+/// producing a single value of `expected_type`, and returns the function it
+/// refers to if it contains a `ref.func`.
+///
+/// Constant expressions run on an operand stack of their own, on which the
+/// arithmetic of extended constant expressions combines values.
 pub fn is_expr_const_and_of_right_type(
     instruction_reader: &mut InstructionReader,
     expected_type: ValueType,
-    globals: &[GlobalType]
-) -> Result<()> {
-    let instruction = instruction_reader.read()?;
-    let const_type = match instruction {
-        Instruction::I32Const(_) => ValueType::I32,
-        Instruction::I64Const(_) => ValueType::I64,
-        Instruction::F32Const(_) => ValueType::F32,
-        Instruction::F64Const(_) => ValueType::F64,
-        Instruction::GlobalGet { global_index } => {
-            if let Some(global) = globals.get(global_index.0 as usize) {
-                global.var_type
-            } else {
-                return Err(InvalidInitExpr);
+    index_space: &IndexSpace,
+    features: &Features,
+) -> Result<Option<FuncIndex>> {
+    let mut operands = Vec::new();
+    let mut referenced_function = None;
+    loop {
+        let offset = instruction_reader.get_original_position();
+        match instruction_reader.read()? {
+            Instruction::I32Const(_) => operands.push(ValueType::I32),
+            Instruction::I64Const(_) => operands.push(ValueType::I64),
+            Instruction::F32Const(_) => operands.push(ValueType::F32),
+            Instruction::F64Const(_) => operands.push(ValueType::F64),
+            Instruction::V128Const(_) => operands.push(ValueType::V128),
+            Instruction::RefNull { ref_type } => {
+                if !features.accepts_value_type(ref_type) {
+                    return Err(DisabledFeature("exception handling"));
+                }
+                operands.push(ref_type);
+            }
+            Instruction::RefFunc { func_index } => {
+                if func_index.0 >= index_space.get_function_count() {
+                    return Err(InvalidFunctionIndex(func_index));
+                }
+                referenced_function = Some(func_index);
+                operands.push(ValueType::FuncRef);
+            }
+            Instruction::GlobalGet { global_index } => {
+                let global = match index_space.get_global_type(global_index) {
+                    Some(global) => global,
+                    None => return Err(InvalidGlobalIndex(global_index)),
+                };
+                // Only imported globals are initialized before the module is,
+                // and only immutable ones keep the value read here.
+                if !index_space.is_imported_global(global_index) {
+                    return Err(ConstExprDefinedGlobal(global_index));
+                }
+                if global.mutable {
+                    return Err(ConstExprMutableGlobal(global_index));
+                }
+                operands.push(global.var_type);
+            }
+            Instruction::I32Add | Instruction::I32Sub | Instruction::I32Mul if features.extended_const => {
+                pop_const_operand(&mut operands, ValueType::I32)?;
+                pop_const_operand(&mut operands, ValueType::I32)?;
+                operands.push(ValueType::I32);
+            }
+            Instruction::I64Add | Instruction::I64Sub | Instruction::I64Mul if features.extended_const => {
+                pop_const_operand(&mut operands, ValueType::I64)?;
+                pop_const_operand(&mut operands, ValueType::I64)?;
+                operands.push(ValueType::I64);
+            }
+            Instruction::End => break,
+            instruction => {
+                return Err(NonConstantInstruction { instruction: instruction.name(), offset });
             }
         }
-        _ => return Err(InvalidInitExpr),
-    };
+    }
 
-    if const_type != expected_type {
+    if !instruction_reader.eof() || operands != [expected_type] {
         return Err(InvalidInitExpr);
     }
 
-    let instruction = instruction_reader.read()?;
-    match instruction {
-        Instruction::End => {},
-        _ => return Err(InvalidInitExpr),
-    }
+    Ok(referenced_function)
+}
 
-    if instruction_reader.read().is_ok() {
-        return Err(InvalidInitExpr);
+fn pop_const_operand(operands: &mut Vec<ValueType>, expected: ValueType) -> Result<()> {
+    match operands.pop() {
+        None => Err(OperandStackEmpty),
+        Some(actual) if actual == expected => Ok(()),
+        Some(actual) => Err(TypeMismatch { expected: Known(expected), actual: Known(actual) }),
     }
-
-    Ok(())
 }
 
 struct ControlFrame {
@@ -116,6 +253,20 @@ fn get_func_type<'a>(
     })
 }
 
+/// The module level state an instruction may refer to while being validated.
+struct FunctionContext<'a> {
+    globals: &'a [GlobalType],
+    locals: &'a [ValueType],
+    function_types: &'a [FunctionType],
+    function_type_indices: &'a [TypeIndex],
+    tables: &'a [TableType],
+    memories: &'a [MemoryType],
+    element_types: &'a [ValueType],
+    data_count: Option<u32>,
+    index_space: &'a IndexSpace,
+    features: Features,
+}
+
 pub struct CodeValidator<'a> {
     code: Code<'a>,
 }
@@ -126,28 +277,56 @@ impl<'a> CodeValidator<'a> {
     }
 
     pub fn validate(&mut self,
-                    globals: &[GlobalType],
-                    function_types: &[FunctionType],
-                    function_type_indices: &[TypeIndex],
+                    index_space: &IndexSpace,
+                    features: &Features,
                     function_index: FuncIndex,
-                    max_table_index: Option<TableIndex>,
-                    max_memory_index: Option<MemoryIndex>,
-    ) -> Result<()> {
-        let func_type_index = get_func_type_index(function_type_indices, function_index)?;
+    ) -> result::Result<(), ValidationError> {
+        let function_types = &index_space.function_types;
+        let function_type_indices = &index_space.function_type_indices;
+        let located = |error: CodeValidationError, offset: usize| {
+            ValidationError::CodeValidation { error, func_index: function_index, offset }
+        };
+        let body_offset = self.code.get_original_position();
+        let func_type_index = get_func_type_index(function_type_indices, function_index)
+            .map_err(|e| located(e, body_offset))?;
         let mut state = CodeValidatorState::new(func_type_index);
-        let locals_reader = self.code.get_locals_reader()?;
+        let locals_reader = self.code.get_locals_reader()
+            .map_err(|e| located(e.into(), body_offset))?;
         let (locals, locals_iteration_proof) = self.create_locals(
             locals_reader,
             function_types,
             function_type_indices,
-            function_index
-        )?;
-        let instruction_reader = self.code.get_instruction_reader(locals_iteration_proof)?;
+            function_index,
+            features,
+        ).map_err(|e| located(e, body_offset))?;
+        let mut instruction_reader = self.code.get_instruction_reader(locals_iteration_proof)
+            .map_err(|e| located(e.into(), body_offset))?;
 
-        for instruction in instruction_reader {
-            let instruction = instruction?;
-            println!("Validating instruction: {:?}", instruction);
-            state.validate_instruction(&instruction, globals, &locals, function_types, function_type_indices, max_table_index, max_memory_index)?;
+        let context = FunctionContext {
+            globals: &index_space.globals,
+            locals: &locals,
+            function_types,
+            function_type_indices,
+            tables: &index_space.tables,
+            memories: &index_space.memories,
+            element_types: &index_space.element_types,
+            data_count: index_space.data_count,
+            index_space,
+            features: *features,
+        };
+        while !instruction_reader.eof() {
+            let offset = instruction_reader.get_original_position();
+            // The function's own frame is the last one closed, by the final `end`.
+            if state.control_stack.is_empty() {
+                return Err(located(InstructionAfterEnd, offset));
+            }
+            let instruction = instruction_reader.read()
+                .map_err(|e| located(e.into(), offset))?;
+            state.validate_instruction(&instruction, &context)
+                .map_err(|e| located(e, offset))?;
+        }
+        if !state.control_stack.is_empty() {
+            return Err(located(MissingEnd, instruction_reader.get_original_position()));
         }
         Ok(())
     }
@@ -156,7 +335,8 @@ impl<'a> CodeValidator<'a> {
                      mut locals_reader: LocalsReader,
                      function_types: &'a [FunctionType],
                      function_type_indices: &[TypeIndex],
-                     function_index: FuncIndex
+                     function_index: FuncIndex,
+                     features: &Features,
     ) -> Result<(Vec<ValueType>, LocalsIterationProof)> {
         //TODO:For now we are creating a vec of locals,
         //this can be represented more compactly which allows binary search
@@ -164,12 +344,15 @@ impl<'a> CodeValidator<'a> {
         let mut locals = Vec::new();
         let function_type = get_func_type(function_types, function_type_indices, function_index)?;
         let params = &function_type.params;
-        for param in params.into_iter() {
+        for param in params.iter() {
             locals.push(*param);
         }
         let locals_results: Vec<Result<Locals, CodeReaderError>> = locals_reader.into_iter().collect();
         for local in locals_results {
             let local = local?;
+            if !features.accepts_value_type(local.value_type) {
+                return Err(DisabledFeature("exception handling"));
+            }
             for _ in 0..local.count {
                 locals.push(local.value_type);
             }
@@ -185,18 +368,25 @@ pub enum Operand {
     Unknown,
 }
 
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Known(value_type) => value_type.fmt(f),
+            Unknown => write!(f, "any"),
+        }
+    }
+}
+
 impl Operand {
     fn is_known(&self) -> bool {
-        let result = match self {
+        match self {
             Known(_) => { true }
             Unknown => { false }
-        };
-        result
+        }
     }
 
     fn is_unknown(&self) -> bool {
-        let result = !self.is_known();
-        result
+        !self.is_known()
     }
 }
 
@@ -206,6 +396,10 @@ enum ControlFrameKind {
     If,
     Else,
     Loop,
+    Try,
+    Catch,
+    CatchAll,
+    TryTable,
 }
 
 struct CodeValidatorState {
@@ -237,7 +431,7 @@ impl CodeValidatorState {
     fn pop_operand(&mut self) -> Result<Operand> {
         //TODO:ensure that unwrap doesn't panic
         let last = self.control_stack.last().unwrap();
-        if self.operand_stack.len() == last.height as usize {
+        if self.operand_stack.len() == last.height {
             if last.unreachable {
                 Ok(Unknown)
             } else {
@@ -256,10 +450,12 @@ impl CodeValidatorState {
     //     self.pop_expected(Unknown)
     // }
     //
+    /// Pops an operand matching `expected` and returns it as it was on the
+    /// stack, so that an unknown operand stays unknown when pushed back.
     fn pop_expected(&mut self, expected: Operand) -> Result<Operand> {
         let actual = self.pop_operand()?;
         if actual.is_unknown() {
-            return Ok(expected);
+            return Ok(actual);
         }
 
         if expected.is_unknown() {
@@ -280,10 +476,26 @@ impl CodeValidatorState {
     //     Ok(())
     // }
 
-    fn push_control_frame(&mut self, kind: ControlFrameKind, block_type: BlockType) {
+    /// Enters a block, whose params become the first operands of its body.
+    fn push_control_frame(&mut self, kind: ControlFrameKind, block_type: BlockType, function_types: &[FunctionType]) -> Result<()> {
         let height = self.operand_stack.len();
         let frame = ControlFrame { kind, block_type, height, unreachable: false };
         self.control_stack.push(frame);
+        for ty in block_type.params(function_types)? {
+            self.push_known(ty);
+        }
+        Ok(())
+    }
+
+    /// Enters an exception handler of the try block described by `block_type`,
+    /// whose body starts with the values of the caught exception.
+    fn push_handler_frame(&mut self, kind: ControlFrameKind, block_type: BlockType, values: &[ValueType]) {
+        let height = self.operand_stack.len();
+        let frame = ControlFrame { kind, block_type, height, unreachable: false };
+        self.control_stack.push(frame);
+        for ty in values {
+            self.push_known(*ty);
+        }
     }
 
     fn pop_control_frame(&mut self, function_types: &[FunctionType]) -> Result<ControlFrame> {
@@ -294,7 +506,7 @@ impl CodeValidatorState {
             self.pop_known(ty)?;
         }
 
-        if self.operand_stack.len() != height as usize {
+        if self.operand_stack.len() != height {
             return Err(ValuesAtEndOfBlock);
         }
 
@@ -324,80 +536,252 @@ impl CodeValidatorState {
         last.unreachable = true;
     }
 
-    fn validate_table_index(max_table_index: Option<TableIndex>) -> Result<()> {
-        if max_table_index.is_none() {
-            return Err(UndefinedTable);
+    /// The table index of `call_indirect` was a reserved zero byte before
+    /// reference types.
+    fn validate_reserved_table_index(table_index: TableIndex, features: Features) -> Result<()> {
+        if table_index.0 != 0 {
+            Self::validate_feature(features.reference_types, "reference types")?;
         }
 
         Ok(())
     }
 
-    fn validate_memory_index(max_memory_index: Option<MemoryIndex>) -> Result<()> {
-        if max_memory_index.is_none() {
-            return Err(UndefinedMemory);
+    /// Returns the type of the references held by the table at `table_index`.
+    fn get_table(tables: &[TableType], table_index: TableIndex) -> Result<ValueType> {
+        match tables.get(table_index.0 as usize) {
+            Some(table_type) => Ok(table_type.element_type),
+            None => Err(UndefinedTable),
+        }
+    }
+
+    fn get_element_type(element_types: &[ValueType], element_index: ElementIndex) -> Result<ValueType> {
+        match element_types.get(element_index.0 as usize) {
+            Some(element_type) => Ok(*element_type),
+            None => Err(InvalidElementIndex(element_index)),
+        }
+    }
+
+    /// Checks that references of type `actual` can be stored where `expected`
+    /// ones are.
+    fn validate_reference_types(expected: ValueType, actual: ValueType) -> Result<()> {
+        if expected != actual {
+            return Err(TypeMismatch { expected: Known(expected), actual: Known(actual) });
         }
 
         Ok(())
     }
 
+    /// Data indices can only be used in code when the data count section
+    /// announced the number of data segments.
+    fn validate_data_index(data_count: Option<u32>, data_index: DataIndex) -> Result<()> {
+        match data_count {
+            None => Err(MissingDataCount),
+            Some(count) if data_index.0 >= count => Err(InvalidDataIndex(data_index)),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Returns the type of the addresses of the memory at `memory_index`.
+    fn validate_memory_index(memories: &[MemoryType], memory_index: MemoryIndex) -> Result<ValueType> {
+        match memories.get(memory_index.0 as usize) {
+            Some(memory_type) => Ok(memory_type.index_type()),
+            None => Err(UndefinedMemory),
+        }
+    }
+
+    /// The memory index of `memory.size`, `memory.grow` and the bulk memory
+    /// instructions was a reserved zero byte before multi-memory.
+    fn validate_reserved_memory_index(memory_index: MemoryIndex, features: Features) -> Result<()> {
+        if memory_index.0 != 0 {
+            Self::validate_feature(features.multi_memory, "multi-memory")?;
+        }
+
+        Ok(())
+    }
+
+    /// Offsets into 32-bit memories must themselves fit in 32 bits.
+    fn validate_memory_offset(memories: &[MemoryType], memory_argument: &MemoryArgument) -> Result<ValueType> {
+        let index_type = Self::validate_memory_index(memories, memory_argument.memory_index)?;
+        if index_type == ValueType::I32 && memory_argument.offset > u32::MAX as u64 {
+            return Err(InvalidMemoryOffset);
+        }
+
+        Ok(index_type)
+    }
+
     fn validate_memory_index_and_alignment(
-        max_memory_index: Option<MemoryIndex>,
+        memories: &[MemoryType],
         memory_argument: &MemoryArgument,
         max_alignment: u32,
-    ) -> Result<()> {
-        Self::validate_memory_index(max_memory_index)?;
+    ) -> Result<ValueType> {
+        let index_type = Self::validate_memory_offset(memories, memory_argument)?;
         if memory_argument.alignment > max_alignment {
             return Err(InvalidMemoryAlignment);
         }
 
-        Ok(())
+        Ok(index_type)
+    }
+
+    /// Unlike other accesses, atomic ones can't be less aligned than their width.
+    fn validate_atomic_memory_argument(
+        memories: &[MemoryType],
+        memory_argument: &MemoryArgument,
+        natural_alignment: u32,
+    ) -> Result<ValueType> {
+        let index_type = Self::validate_memory_offset(memories, memory_argument)?;
+        if memory_argument.alignment != natural_alignment {
+            return Err(InvalidAtomicAlignment);
+        }
+
+        Ok(index_type)
     }
 
     fn validate_load(&mut self,
-                     max_memory_index: Option<MemoryIndex>,
+                     memories: &[MemoryType],
                      memory_argument: &MemoryArgument,
                      max_alignment: u32,
                      result_type: ValueType,
     ) -> Result<()> {
-        Self::validate_memory_index_and_alignment(max_memory_index, memory_argument, max_alignment)?;
-        self.pop_known(ValueType::I32)?;
+        let index_type = Self::validate_memory_index_and_alignment(memories, memory_argument, max_alignment)?;
+        self.pop_known(index_type)?;
         self.push_known(result_type);
         Ok(())
     }
 
     fn validate_store(&mut self,
-                      max_memory_index: Option<MemoryIndex>,
+                      memories: &[MemoryType],
                       memory_argument: &MemoryArgument,
                       max_alignment: u32,
                       param_type: ValueType,
     ) -> Result<()> {
-        Self::validate_memory_index_and_alignment(max_memory_index, memory_argument, max_alignment)?;
+        let index_type = Self::validate_memory_index_and_alignment(memories, memory_argument, max_alignment)?;
         self.pop_known(param_type)?;
-        self.pop_known(ValueType::I32)?;
+        self.pop_known(index_type)?;
+        Ok(())
+    }
+
+    /// Lane immediates must address one of the `lane_count` lanes of their vector.
+    fn validate_lane_index(lane: u8, lane_count: u8) -> Result<()> {
+        if lane >= lane_count {
+            return Err(InvalidLaneIndex(lane));
+        }
+
+        Ok(())
+    }
+
+    fn validate_load_lane(&mut self,
+                          memories: &[MemoryType],
+                          memory_argument: &MemoryArgument,
+                          max_alignment: u32,
+                          lane: u8,
+                          lane_count: u8,
+    ) -> Result<()> {
+        let index_type = Self::validate_memory_index_and_alignment(memories, memory_argument, max_alignment)?;
+        Self::validate_lane_index(lane, lane_count)?;
+        self.pop_known(ValueType::V128)?;
+        self.pop_known(index_type)?;
+        self.push_known(ValueType::V128);
+        Ok(())
+    }
+
+    fn validate_store_lane(&mut self,
+                           memories: &[MemoryType],
+                           memory_argument: &MemoryArgument,
+                           max_alignment: u32,
+                           lane: u8,
+                           lane_count: u8,
+    ) -> Result<()> {
+        let index_type = Self::validate_memory_index_and_alignment(memories, memory_argument, max_alignment)?;
+        Self::validate_lane_index(lane, lane_count)?;
+        self.pop_known(ValueType::V128)?;
+        self.pop_known(index_type)?;
         Ok(())
     }
 
     fn validate_function_type(&mut self, ty: &FunctionType) -> Result<()> {
-        for param in ty.params.into_iter().rev() {
+        for param in ty.params.iter().rev() {
             self.pop_known(*param)?;
         }
-        for result in ty.results.into_iter() {
+        for result in ty.results.iter() {
             self.push_known(*result);
         }
 
         Ok(())
     }
 
-    fn validate_block_type(&mut self, kind: ControlFrameKind, block_type: BlockType, function_types: &[FunctionType]) -> Result<()> {
-        for ty in block_type.params(function_types)? {
-            self.pop_known(ty)?;
+    /// A tail call replaces the calling function, whose results it returns.
+    fn validate_tail_call(&mut self, ty: &FunctionType, function_types: &[FunctionType]) -> Result<()> {
+        if !self.control_stack[0].block_type.results(function_types)?.eq(ty.results.iter().copied()) {
+            return Err(TailCallResultsMismatch);
+        }
+        for param in ty.params.iter().rev() {
+            self.pop_known(*param)?;
+        }
+        self.unreachable();
+        Ok(())
+    }
+
+    /// Returns the function type of the tag at `tag_index`, whose params are
+    /// the values its exceptions carry.
+    fn get_tag_type<'a>(index_space: &IndexSpace, function_types: &'a [FunctionType], tag_index: TagIndex) -> Result<&'a FunctionType> {
+        let tag_type = match index_space.get_tag_type(tag_index) {
+            Some(tag_type) => tag_type,
+            None => return Err(InvalidTagIndex(tag_index)),
+        };
+        match function_types.get(tag_type.type_index.0 as usize) {
+            Some(function_type) => Ok(function_type),
+            None => Err(InvalidTypeIndex(tag_type.type_index)),
+        }
+    }
+
+    /// Checks that the label of a `try_table` catch clause takes the values
+    /// the clause branches to it with.
+    fn validate_catch_clause(&mut self, catch: &CatchClause, index_space: &IndexSpace, function_types: &[FunctionType]) -> Result<()> {
+        let (values, label_index) = match *catch {
+            CatchClause::Catch { tag_index, label_index } => {
+                (Self::get_tag_type(index_space, function_types, tag_index)?.params.to_vec(), label_index)
+            }
+            CatchClause::CatchRef { tag_index, label_index } => {
+                let mut values = Self::get_tag_type(index_space, function_types, tag_index)?.params.to_vec();
+                values.push(ValueType::ExnRef);
+                (values, label_index)
+            }
+            CatchClause::CatchAll { label_index } => (Vec::new(), label_index),
+            CatchClause::CatchAllRef { label_index } => (vec![ValueType::ExnRef], label_index),
+        };
+        let (kind, block_type) = self.validate_jump(label_index)?;
+        if !self.get_label_types(kind, block_type, function_types)?.eq(values) {
+            return Err(CatchClauseMismatch(label_index));
         }
-        self.push_control_frame(kind, block_type);
+
         Ok(())
     }
 
+    fn validate_value_type(value_type: ValueType, features: Features) -> Result<()> {
+        Self::validate_feature(features.accepts_value_type(value_type), "exception handling")
+    }
+
+    fn validate_feature(enabled: bool, feature: &'static str) -> Result<()> {
+        if !enabled {
+            return Err(DisabledFeature(feature));
+        }
+
+        Ok(())
+    }
+
+    fn validate_block_type(&mut self, kind: ControlFrameKind, block_type: BlockType, context: &FunctionContext) -> Result<()> {
+        let function_types = context.function_types;
+        if let BlockType::ValueType(value_type) = block_type {
+            Self::validate_value_type(value_type, context.features)?;
+        }
+        for ty in block_type.params(function_types)?.rev() {
+            self.pop_known(ty)?;
+        }
+        self.push_control_frame(kind, block_type, function_types)
+    }
+
     fn validate_jump(&mut self, label_index: LabelIndex) -> Result<(ControlFrameKind, BlockType)> {
-        return match (self.control_stack.len() - 1).checked_sub(label_index.0 as usize) {
+        match (self.control_stack.len() - 1).checked_sub(label_index.0 as usize) {
             None => {
                 Err(InvalidLabelIndex(label_index))
             }
@@ -415,47 +799,119 @@ impl CodeValidatorState {
         })
     }
 
-    fn validate_instruction(&mut self,
-                            instruction: &Instruction,
-                            globals: &[GlobalType],
-                            locals: &[ValueType],
-                            function_types: &[FunctionType],
-                            function_type_indices: &[TypeIndex],
-                            max_table_index: Option<TableIndex>,
-                            max_memory_index: Option<MemoryIndex>,
-    ) -> Result<()> {
+    fn validate_instruction(&mut self, instruction: &Instruction, context: &FunctionContext) -> Result<()> {
+        let FunctionContext {
+            globals,
+            locals,
+            function_types,
+            function_type_indices,
+            tables,
+            memories,
+            element_types,
+            data_count,
+            index_space,
+            features,
+        } = *context;
         match instruction {
             Instruction::Unreachable => {
                 self.unreachable();
             }
             Instruction::Nop => {}
             Instruction::Block { block_type } => {
-                self.validate_block_type(ControlFrameKind::Block, *block_type, function_types)?;
+                self.validate_block_type(ControlFrameKind::Block, *block_type, context)?;
             }
             Instruction::Loop { block_type } => {
-                self.validate_block_type(ControlFrameKind::Loop, *block_type, function_types)?;
+                self.validate_block_type(ControlFrameKind::Loop, *block_type, context)?;
             }
             Instruction::If { block_type } => {
                 self.pop_known(ValueType::I32)?;
-                self.validate_block_type(ControlFrameKind::If, *block_type, function_types)?;
+                self.validate_block_type(ControlFrameKind::If, *block_type, context)?;
             }
             Instruction::Else => {
                 let frame = self.pop_control_frame(function_types)?;
                 match frame.kind {
                     ControlFrameKind::If => {
-                        self.push_control_frame(ControlFrameKind::Else, frame.block_type);
+                        self.push_control_frame(ControlFrameKind::Else, frame.block_type, function_types)?;
                     }
                     _ => { return Err(ElseWithoutIf) }
                 }
             }
-            Instruction::End => {
-                let mut frame = self.pop_control_frame(function_types)?;
+            Instruction::Try { block_type } => {
+                Self::validate_feature(features.exceptions, "exception handling")?;
+                self.validate_block_type(ControlFrameKind::Try, *block_type, context)?;
+            }
+            Instruction::Catch { tag_index } => {
+                Self::validate_feature(features.exceptions, "exception handling")?;
+                let ty = Self::get_tag_type(index_space, function_types, *tag_index)?;
+                let frame = self.pop_control_frame(function_types)?;
                 match frame.kind {
-                    ControlFrameKind::If => {
-                        self.push_control_frame(ControlFrameKind::Else, frame.block_type);
-                        frame = self.pop_control_frame(function_types)?;
+                    ControlFrameKind::Try | ControlFrameKind::Catch => {
+                        self.push_handler_frame(ControlFrameKind::Catch, frame.block_type, &ty.params);
+                    }
+                    ControlFrameKind::CatchAll => return Err(CatchAfterCatchAll),
+                    _ => return Err(CatchWithoutTry),
+                }
+            }
+            Instruction::CatchAll => {
+                Self::validate_feature(features.exceptions, "exception handling")?;
+                let frame = self.pop_control_frame(function_types)?;
+                match frame.kind {
+                    ControlFrameKind::Try | ControlFrameKind::Catch => {
+                        self.push_handler_frame(ControlFrameKind::CatchAll, frame.block_type, &[]);
                     }
-                    _ => {}
+                    ControlFrameKind::CatchAll => return Err(CatchAfterCatchAll),
+                    _ => return Err(CatchWithoutTry),
+                }
+            }
+            Instruction::Delegate { label_index } => {
+                Self::validate_feature(features.exceptions, "exception handling")?;
+                let frame = self.pop_control_frame(function_types)?;
+                if frame.kind != ControlFrameKind::Try {
+                    return Err(DelegateWithoutTry);
+                }
+                // The label is counted from the block enclosing the try block,
+                // which has just been popped.
+                self.validate_jump(*label_index)?;
+                for ty in frame.block_type.results(function_types)? {
+                    self.push_known(ty);
+                }
+            }
+            Instruction::Throw { tag_index } => {
+                Self::validate_feature(features.exceptions, "exception handling")?;
+                let ty = Self::get_tag_type(index_space, function_types, *tag_index)?;
+                for param in ty.params.iter().rev() {
+                    self.pop_known(*param)?;
+                }
+                self.unreachable();
+            }
+            Instruction::Rethrow { label_index } => {
+                Self::validate_feature(features.exceptions, "exception handling")?;
+                let (kind, _) = self.validate_jump(*label_index)?;
+                if kind != ControlFrameKind::Catch && kind != ControlFrameKind::CatchAll {
+                    return Err(InvalidRethrowLabel(*label_index));
+                }
+                self.unreachable();
+            }
+            Instruction::ThrowRef => {
+                Self::validate_feature(features.exceptions, "exception handling")?;
+                self.pop_known(ValueType::ExnRef)?;
+                self.unreachable();
+            }
+            Instruction::TryTable { block_type, catches } => {
+                Self::validate_feature(features.exceptions, "exception handling")?;
+                // Catch clause labels are counted from outside the try_table block.
+                for catch in catches.iter() {
+                    self.validate_catch_clause(catch, index_space, function_types)?;
+                }
+                self.validate_block_type(ControlFrameKind::TryTable, *block_type, context)?;
+            }
+            Instruction::End => {
+                let mut frame = self.pop_control_frame(function_types)?;
+                if frame.kind == ControlFrameKind::If {
+                    // Without an else, the params flow through the missing
+                    // branch, so they must match the results.
+                    self.push_control_frame(ControlFrameKind::Else, frame.block_type, function_types)?;
+                    frame = self.pop_control_frame(function_types)?;
                 }
                 for ty in frame.block_type.results(function_types)? {
                     self.push_known(ty);
@@ -469,6 +925,7 @@ impl CodeValidatorState {
                 self.unreachable();
             }
             Instruction::BranchIf { label_index } => {
+                self.pop_known(ValueType::I32)?;
                 let (kind, block_type) = self.validate_jump(*label_index)?;
                 for ty in self.get_label_types(kind, block_type, function_types)?.rev() {
                     self.pop_known(ty)?;
@@ -476,29 +933,29 @@ impl CodeValidatorState {
                 for ty in self.get_label_types(kind, block_type, function_types)? {
                     self.push_known(ty);
                 }
-                self.unreachable();
             }
             Instruction::BranchTable { branch_table_reader } => {
                 self.pop_known(ValueType::I32)?;
-                let mut reader = branch_table_reader.clone();
-                let mut label = None;
-                for label_index in reader.into_iter() {
-                    let label_index = label_index?;
-                    let block = self.validate_jump(label_index)?;
-                    match label {
-                        None => label = Some(block),
-                        Some(prev) => {
-                            let a = self.get_label_types(block.0, block.1, function_types)?;
-                            let b = self.get_label_types(prev.0, prev.1, function_types)?;
-                            if a.ne(b) {
-                                return Err(TargetLabelsTypeMismatch);
-                            }
-                        }
+                let label_indices = branch_table_reader.clone()
+                    .into_iter()
+                    .collect::<result::Result<Vec<_>, _>>()?;
+                // The default label comes last and sets the arity every other
+                // label must have.
+                let (default_kind, default_block_type) = self.validate_jump(*label_indices.last().unwrap())?;
+                let arity = self.get_label_types(default_kind, default_block_type, function_types)?.count();
+                for label_index in label_indices {
+                    let (kind, block_type) = self.validate_jump(label_index)?;
+                    let label_types: Vec<ValueType> = self.get_label_types(kind, block_type, function_types)?.collect();
+                    if label_types.len() != arity {
+                        return Err(TargetLabelsArityMismatch { expected: arity, actual: label_types.len() });
+                    }
+                    let mut operands = Vec::with_capacity(arity);
+                    for ty in label_types.into_iter().rev() {
+                        operands.push(self.pop_known(ty)?);
+                    }
+                    for operand in operands.into_iter().rev() {
+                        self.push_operand(operand);
                     }
-                }
-                let (kind, block_type) = label.unwrap();
-                for ty in self.get_label_types(kind, block_type, function_types)?.rev() {
-                    self.pop_known(ty)?;
                 }
                 self.unreachable();
             }
@@ -512,8 +969,10 @@ impl CodeValidatorState {
                 let ty = get_func_type(function_types, function_type_indices, *func_index)?;
                 self.validate_function_type(ty)?;
             }
-            Instruction::CallIndirect { type_index } => {
-                Self::validate_table_index(max_table_index)?;
+            Instruction::CallIndirect { type_index, table_index } => {
+                Self::validate_reserved_table_index(*table_index, features)?;
+                let element_type = Self::get_table(tables, *table_index)?;
+                Self::validate_reference_types(ValueType::FuncRef, element_type)?;
                 if let Some(ty) = function_types.get(type_index.0 as usize) {
                     self.pop_known(ValueType::I32)?;
                     self.validate_function_type(ty)?;
@@ -521,6 +980,23 @@ impl CodeValidatorState {
                     return Err(InvalidTypeIndex(*type_index));
                 }
             }
+            Instruction::ReturnCall { func_index } => {
+                Self::validate_feature(features.tail_call, "tail call")?;
+                let ty = get_func_type(function_types, function_type_indices, *func_index)?;
+                self.validate_tail_call(ty, function_types)?;
+            }
+            Instruction::ReturnCallIndirect { type_index, table_index } => {
+                Self::validate_feature(features.tail_call, "tail call")?;
+                Self::validate_reserved_table_index(*table_index, features)?;
+                let element_type = Self::get_table(tables, *table_index)?;
+                Self::validate_reference_types(ValueType::FuncRef, element_type)?;
+                if let Some(ty) = function_types.get(type_index.0 as usize) {
+                    self.pop_known(ValueType::I32)?;
+                    self.validate_tail_call(ty, function_types)?;
+                } else {
+                    return Err(InvalidTypeIndex(*type_index));
+                }
+            }
             Instruction::Drop => {
                 self.pop_operand()?;
             }
@@ -528,11 +1004,25 @@ impl CodeValidatorState {
                 self.pop_known(ValueType::I32)?;
                 let first = self.pop_operand()?;
                 let second = self.pop_operand()?;
-                //TODO:should unknown operands be allowed here?
-                if first.is_unknown() || second.is_unknown() || first != second {
+                for operand in [&first, &second] {
+                    if let Known(value_type) = operand {
+                        if value_type.is_reference() {
+                            return Err(InvalidUntypedSelect(*value_type));
+                        }
+                    }
+                }
+                // An unknown operand from unreachable code takes the type of the other one.
+                if first.is_known() && second.is_known() && first != second {
                     return Err(TypeMismatch { expected: first, actual: second });
                 }
-                self.push_operand(second);
+                self.push_operand(if first.is_known() { first } else { second });
+            }
+            Instruction::TypedSelect { value_type } => {
+                Self::validate_value_type(*value_type, features)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(*value_type)?;
+                self.pop_known(*value_type)?;
+                self.push_known(*value_type);
             }
             Instruction::LocalGet { local_index } => {
                 let local_type = Self::get_local(locals, *local_index)?;
@@ -560,83 +1050,95 @@ impl CodeValidatorState {
                     return Err(SettingImmutableGlobal(*global_index));
                 }
             }
+            Instruction::TableGet { table_index } => {
+                let element_type = Self::get_table(tables, *table_index)?;
+                self.pop_known(ValueType::I32)?;
+                self.push_known(element_type);
+            }
+            Instruction::TableSet { table_index } => {
+                let element_type = Self::get_table(tables, *table_index)?;
+                self.pop_known(element_type)?;
+                self.pop_known(ValueType::I32)?;
+            }
             Instruction::I32Load { memory_argument } => {
-                self.validate_load(max_memory_index, memory_argument, 2, ValueType::I32)?;
+                self.validate_load(memories, memory_argument, 2, ValueType::I32)?;
             }
             Instruction::I64Load { memory_argument } => {
-                self.validate_load(max_memory_index, memory_argument, 3, ValueType::I64)?;
+                self.validate_load(memories, memory_argument, 3, ValueType::I64)?;
             }
             Instruction::F32Load { memory_argument } => {
-                self.validate_load(max_memory_index, memory_argument, 2, ValueType::F32)?;
+                self.validate_load(memories, memory_argument, 2, ValueType::F32)?;
             }
             Instruction::F64Load { memory_argument } => {
-                self.validate_load(max_memory_index, memory_argument, 3, ValueType::F64)?;
+                self.validate_load(memories, memory_argument, 3, ValueType::F64)?;
             }
             Instruction::I32Load8s { memory_argument } => {
-                self.validate_load(max_memory_index, memory_argument, 0, ValueType::I32)?;
+                self.validate_load(memories, memory_argument, 0, ValueType::I32)?;
             }
             Instruction::I32Load8u { memory_argument } => {
-                self.validate_load(max_memory_index, memory_argument, 0, ValueType::I32)?;
+                self.validate_load(memories, memory_argument, 0, ValueType::I32)?;
             }
             Instruction::I32Load16s { memory_argument } => {
-                self.validate_load(max_memory_index, memory_argument, 1, ValueType::I32)?;
+                self.validate_load(memories, memory_argument, 1, ValueType::I32)?;
             }
             Instruction::I32Load16u { memory_argument } => {
-                self.validate_load(max_memory_index, memory_argument, 1, ValueType::I32)?;
+                self.validate_load(memories, memory_argument, 1, ValueType::I32)?;
             }
             Instruction::I64Load8s { memory_argument } => {
-                self.validate_load(max_memory_index, memory_argument, 0, ValueType::I64)?;
+                self.validate_load(memories, memory_argument, 0, ValueType::I64)?;
             }
             Instruction::I64Load8u { memory_argument } => {
-                self.validate_load(max_memory_index, memory_argument, 0, ValueType::I64)?;
+                self.validate_load(memories, memory_argument, 0, ValueType::I64)?;
             }
             Instruction::I64Load16s { memory_argument } => {
-                self.validate_load(max_memory_index, memory_argument, 1, ValueType::I64)?;
+                self.validate_load(memories, memory_argument, 1, ValueType::I64)?;
             }
             Instruction::I64Load16u { memory_argument } => {
-                self.validate_load(max_memory_index, memory_argument, 1, ValueType::I64)?;
+                self.validate_load(memories, memory_argument, 1, ValueType::I64)?;
             }
             Instruction::I64Load32s { memory_argument } => {
-                self.validate_load(max_memory_index, memory_argument, 2, ValueType::I64)?;
+                self.validate_load(memories, memory_argument, 2, ValueType::I64)?;
             }
             Instruction::I64Load32u { memory_argument } => {
-                self.validate_load(max_memory_index, memory_argument, 2, ValueType::I64)?;
+                self.validate_load(memories, memory_argument, 2, ValueType::I64)?;
             }
             Instruction::I32Store { memory_argument } => {
-                self.validate_store(max_memory_index, memory_argument, 2, ValueType::I32)?;
+                self.validate_store(memories, memory_argument, 2, ValueType::I32)?;
             }
             Instruction::I64Store { memory_argument } => {
-                self.validate_store(max_memory_index, memory_argument, 3, ValueType::I64)?;
+                self.validate_store(memories, memory_argument, 3, ValueType::I64)?;
             }
             Instruction::F32Store { memory_argument } => {
-                self.validate_store(max_memory_index, memory_argument, 2, ValueType::F32)?;
+                self.validate_store(memories, memory_argument, 2, ValueType::F32)?;
             }
             Instruction::F64Store { memory_argument } => {
-                self.validate_store(max_memory_index, memory_argument, 3, ValueType::F64)?;
+                self.validate_store(memories, memory_argument, 3, ValueType::F64)?;
             }
             Instruction::I32Store8 { memory_argument } => {
-                self.validate_store(max_memory_index, memory_argument, 0, ValueType::I32)?;
+                self.validate_store(memories, memory_argument, 0, ValueType::I32)?;
             }
             Instruction::I32Store16 { memory_argument } => {
-                self.validate_store(max_memory_index, memory_argument, 1, ValueType::I32)?;
+                self.validate_store(memories, memory_argument, 1, ValueType::I32)?;
             }
             Instruction::I64Store8 { memory_argument } => {
-                self.validate_store(max_memory_index, memory_argument, 0, ValueType::I64)?;
+                self.validate_store(memories, memory_argument, 0, ValueType::I64)?;
             }
             Instruction::I64Store16 { memory_argument } => {
-                self.validate_store(max_memory_index, memory_argument, 1, ValueType::I64)?;
+                self.validate_store(memories, memory_argument, 1, ValueType::I64)?;
             }
             Instruction::I64Store32 { memory_argument } => {
-                self.validate_store(max_memory_index, memory_argument, 2, ValueType::I64)?;
+                self.validate_store(memories, memory_argument, 2, ValueType::I64)?;
             }
-            Instruction::MemorySize => {
-                Self::validate_memory_index(max_memory_index)?;
-                self.push_known(ValueType::I32);
+            Instruction::MemorySize { memory_index } => {
+                Self::validate_reserved_memory_index(*memory_index, features)?;
+                let index_type = Self::validate_memory_index(memories, *memory_index)?;
+                self.push_known(index_type);
             }
-            Instruction::MemoryGrow => {
-                Self::validate_memory_index(max_memory_index)?;
-                self.pop_known(ValueType::I32)?;
-                self.push_known(ValueType::I32);
+            Instruction::MemoryGrow { memory_index } => {
+                Self::validate_reserved_memory_index(*memory_index, features)?;
+                let index_type = Self::validate_memory_index(memories, *memory_index)?;
+                self.pop_known(index_type)?;
+                self.push_known(index_type);
             }
             Instruction::I32Const(_) => {
                 self.push_known(ValueType::I32);
@@ -882,6 +1384,25 @@ impl CodeValidatorState {
                 self.pop_known(ValueType::I64)?;
                 self.push_known(ValueType::I64);
             }
+            Instruction::RefNull { ref_type } => {
+                Self::validate_value_type(*ref_type, features)?;
+                self.push_known(*ref_type);
+            }
+            Instruction::RefIsNull => {
+                if let Known(value_type) = self.pop_operand()? {
+                    if !value_type.is_reference() {
+                        return Err(ReferenceExpected(value_type));
+                    }
+                }
+                self.push_known(ValueType::I32);
+            }
+            Instruction::RefFunc { func_index } => {
+                get_func_type_index(function_type_indices, *func_index)?;
+                if !index_space.is_function_referenced(*func_index) {
+                    return Err(UndeclaredFunctionReference(*func_index));
+                }
+                self.push_known(ValueType::FuncRef);
+            }
             Instruction::I32TruncSatF32s |
             Instruction::I32TruncSatF32u => {
                 self.pop_known(ValueType::F32)?;
@@ -902,6 +1423,649 @@ impl CodeValidatorState {
                 self.pop_known(ValueType::F64)?;
                 self.push_known(ValueType::I64);
             }
+            Instruction::MemoryInit { data_index, memory_index } => {
+                Self::validate_reserved_memory_index(*memory_index, features)?;
+                let index_type = Self::validate_memory_index(memories, *memory_index)?;
+                Self::validate_data_index(data_count, *data_index)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(index_type)?;
+            }
+            Instruction::DataDrop { data_index } => {
+                Self::validate_data_index(data_count, *data_index)?;
+            }
+            Instruction::MemoryCopy { destination_memory_index, source_memory_index } => {
+                Self::validate_reserved_memory_index(*destination_memory_index, features)?;
+                Self::validate_reserved_memory_index(*source_memory_index, features)?;
+                let destination_type = Self::validate_memory_index(memories, *destination_memory_index)?;
+                let source_type = Self::validate_memory_index(memories, *source_memory_index)?;
+                // The length can only be 64-bit when both memories are.
+                let length_type = if destination_type == ValueType::I64 && source_type == ValueType::I64 {
+                    ValueType::I64
+                } else {
+                    ValueType::I32
+                };
+                self.pop_known(length_type)?;
+                self.pop_known(source_type)?;
+                self.pop_known(destination_type)?;
+            }
+            Instruction::MemoryFill { memory_index } => {
+                Self::validate_reserved_memory_index(*memory_index, features)?;
+                let index_type = Self::validate_memory_index(memories, *memory_index)?;
+                self.pop_known(index_type)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(index_type)?;
+            }
+            Instruction::TableInit { element_index, table_index } => {
+                let table_type = Self::get_table(tables, *table_index)?;
+                let element_type = Self::get_element_type(element_types, *element_index)?;
+                Self::validate_reference_types(table_type, element_type)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(ValueType::I32)?;
+            }
+            Instruction::ElemDrop { element_index } => {
+                Self::get_element_type(element_types, *element_index)?;
+            }
+            Instruction::TableCopy { destination_table_index, source_table_index } => {
+                let destination_type = Self::get_table(tables, *destination_table_index)?;
+                let source_type = Self::get_table(tables, *source_table_index)?;
+                Self::validate_reference_types(destination_type, source_type)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(ValueType::I32)?;
+            }
+            Instruction::TableGrow { table_index } => {
+                let element_type = Self::get_table(tables, *table_index)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(element_type)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::TableSize { table_index } => {
+                Self::get_table(tables, *table_index)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::TableFill { table_index } => {
+                let element_type = Self::get_table(tables, *table_index)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(element_type)?;
+                self.pop_known(ValueType::I32)?;
+            }
+            Instruction::V128Load { memory_argument } => {
+                self.validate_load(memories, memory_argument, 4, ValueType::V128)?;
+            }
+            Instruction::V128Load8x8s { memory_argument } |
+            Instruction::V128Load8x8u { memory_argument } |
+            Instruction::V128Load16x4s { memory_argument } |
+            Instruction::V128Load16x4u { memory_argument } |
+            Instruction::V128Load32x2s { memory_argument } |
+            Instruction::V128Load32x2u { memory_argument } |
+            Instruction::V128Load64Splat { memory_argument } |
+            Instruction::V128Load64Zero { memory_argument } => {
+                self.validate_load(memories, memory_argument, 3, ValueType::V128)?;
+            }
+            Instruction::V128Load8Splat { memory_argument } => {
+                self.validate_load(memories, memory_argument, 0, ValueType::V128)?;
+            }
+            Instruction::V128Load16Splat { memory_argument } => {
+                self.validate_load(memories, memory_argument, 1, ValueType::V128)?;
+            }
+            Instruction::V128Load32Splat { memory_argument } |
+            Instruction::V128Load32Zero { memory_argument } => {
+                self.validate_load(memories, memory_argument, 2, ValueType::V128)?;
+            }
+            Instruction::V128Store { memory_argument } => {
+                self.validate_store(memories, memory_argument, 4, ValueType::V128)?;
+            }
+            Instruction::V128Load8Lane { memory_argument, lane } => {
+                self.validate_load_lane(memories, memory_argument, 0, *lane, 16)?;
+            }
+            Instruction::V128Load16Lane { memory_argument, lane } => {
+                self.validate_load_lane(memories, memory_argument, 1, *lane, 8)?;
+            }
+            Instruction::V128Load32Lane { memory_argument, lane } => {
+                self.validate_load_lane(memories, memory_argument, 2, *lane, 4)?;
+            }
+            Instruction::V128Load64Lane { memory_argument, lane } => {
+                self.validate_load_lane(memories, memory_argument, 3, *lane, 2)?;
+            }
+            Instruction::V128Store8Lane { memory_argument, lane } => {
+                self.validate_store_lane(memories, memory_argument, 0, *lane, 16)?;
+            }
+            Instruction::V128Store16Lane { memory_argument, lane } => {
+                self.validate_store_lane(memories, memory_argument, 1, *lane, 8)?;
+            }
+            Instruction::V128Store32Lane { memory_argument, lane } => {
+                self.validate_store_lane(memories, memory_argument, 2, *lane, 4)?;
+            }
+            Instruction::V128Store64Lane { memory_argument, lane } => {
+                self.validate_store_lane(memories, memory_argument, 3, *lane, 2)?;
+            }
+            Instruction::V128Const(_) => {
+                self.push_known(ValueType::V128);
+            }
+            Instruction::I8x16Shuffle { lanes } => {
+                for lane in lanes.iter() {
+                    Self::validate_lane_index(*lane, 32)?;
+                }
+                self.pop_known(ValueType::V128)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::I8x16Splat |
+            Instruction::I16x8Splat |
+            Instruction::I32x4Splat => {
+                self.pop_known(ValueType::I32)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::I64x2Splat => {
+                self.pop_known(ValueType::I64)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::F32x4Splat => {
+                self.pop_known(ValueType::F32)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::F64x2Splat => {
+                self.pop_known(ValueType::F64)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::I8x16ExtractLanes { lane } |
+            Instruction::I8x16ExtractLaneu { lane } => {
+                Self::validate_lane_index(*lane, 16)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::I16x8ExtractLanes { lane } |
+            Instruction::I16x8ExtractLaneu { lane } => {
+                Self::validate_lane_index(*lane, 8)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::I32x4ExtractLane { lane } => {
+                Self::validate_lane_index(*lane, 4)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::I64x2ExtractLane { lane } => {
+                Self::validate_lane_index(*lane, 2)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::I64);
+            }
+            Instruction::F32x4ExtractLane { lane } => {
+                Self::validate_lane_index(*lane, 4)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::F32);
+            }
+            Instruction::F64x2ExtractLane { lane } => {
+                Self::validate_lane_index(*lane, 2)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::F64);
+            }
+            Instruction::I8x16ReplaceLane { lane } => {
+                Self::validate_lane_index(*lane, 16)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::I16x8ReplaceLane { lane } => {
+                Self::validate_lane_index(*lane, 8)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::I32x4ReplaceLane { lane } => {
+                Self::validate_lane_index(*lane, 4)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::I64x2ReplaceLane { lane } => {
+                Self::validate_lane_index(*lane, 2)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::F32x4ReplaceLane { lane } => {
+                Self::validate_lane_index(*lane, 4)?;
+                self.pop_known(ValueType::F32)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::F64x2ReplaceLane { lane } => {
+                Self::validate_lane_index(*lane, 2)?;
+                self.pop_known(ValueType::F64)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::V128Not |
+            Instruction::F32x4DemoteF64x2Zero |
+            Instruction::F64x2PromoteLowF32x4 |
+            Instruction::I8x16Abs |
+            Instruction::I8x16Neg |
+            Instruction::I8x16Popcnt |
+            Instruction::F32x4Ceil |
+            Instruction::F32x4Floor |
+            Instruction::F32x4Trunc |
+            Instruction::F32x4Nearest |
+            Instruction::F64x2Ceil |
+            Instruction::F64x2Floor |
+            Instruction::F64x2Trunc |
+            Instruction::I16x8ExtaddPairwiseI8x16s |
+            Instruction::I16x8ExtaddPairwiseI8x16u |
+            Instruction::I32x4ExtaddPairwiseI16x8s |
+            Instruction::I32x4ExtaddPairwiseI16x8u |
+            Instruction::I16x8Abs |
+            Instruction::I16x8Neg |
+            Instruction::I16x8ExtendLowI8x16s |
+            Instruction::I16x8ExtendHighI8x16s |
+            Instruction::I16x8ExtendLowI8x16u |
+            Instruction::I16x8ExtendHighI8x16u |
+            Instruction::F64x2Nearest |
+            Instruction::I32x4Abs |
+            Instruction::I32x4Neg |
+            Instruction::I32x4ExtendLowI16x8s |
+            Instruction::I32x4ExtendHighI16x8s |
+            Instruction::I32x4ExtendLowI16x8u |
+            Instruction::I32x4ExtendHighI16x8u |
+            Instruction::I64x2Abs |
+            Instruction::I64x2Neg |
+            Instruction::I64x2ExtendLowI32x4s |
+            Instruction::I64x2ExtendHighI32x4s |
+            Instruction::I64x2ExtendLowI32x4u |
+            Instruction::I64x2ExtendHighI32x4u |
+            Instruction::F32x4Abs |
+            Instruction::F32x4Neg |
+            Instruction::F32x4Sqrt |
+            Instruction::F64x2Abs |
+            Instruction::F64x2Neg |
+            Instruction::F64x2Sqrt |
+            Instruction::I32x4TruncSatF32x4s |
+            Instruction::I32x4TruncSatF32x4u |
+            Instruction::F32x4ConvertI32x4s |
+            Instruction::F32x4ConvertI32x4u |
+            Instruction::I32x4TruncSatF64x2sZero |
+            Instruction::I32x4TruncSatF64x2uZero |
+            Instruction::F64x2ConvertLowI32x4s |
+            Instruction::F64x2ConvertLowI32x4u => {
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::I8x16Swizzle |
+            Instruction::I8x16Eq |
+            Instruction::I8x16Ne |
+            Instruction::I8x16Lts |
+            Instruction::I8x16Ltu |
+            Instruction::I8x16Gts |
+            Instruction::I8x16Gtu |
+            Instruction::I8x16Les |
+            Instruction::I8x16Leu |
+            Instruction::I8x16Ges |
+            Instruction::I8x16Geu |
+            Instruction::I16x8Eq |
+            Instruction::I16x8Ne |
+            Instruction::I16x8Lts |
+            Instruction::I16x8Ltu |
+            Instruction::I16x8Gts |
+            Instruction::I16x8Gtu |
+            Instruction::I16x8Les |
+            Instruction::I16x8Leu |
+            Instruction::I16x8Ges |
+            Instruction::I16x8Geu |
+            Instruction::I32x4Eq |
+            Instruction::I32x4Ne |
+            Instruction::I32x4Lts |
+            Instruction::I32x4Ltu |
+            Instruction::I32x4Gts |
+            Instruction::I32x4Gtu |
+            Instruction::I32x4Les |
+            Instruction::I32x4Leu |
+            Instruction::I32x4Ges |
+            Instruction::I32x4Geu |
+            Instruction::F32x4Eq |
+            Instruction::F32x4Ne |
+            Instruction::F32x4Lt |
+            Instruction::F32x4Gt |
+            Instruction::F32x4Le |
+            Instruction::F32x4Ge |
+            Instruction::F64x2Eq |
+            Instruction::F64x2Ne |
+            Instruction::F64x2Lt |
+            Instruction::F64x2Gt |
+            Instruction::F64x2Le |
+            Instruction::F64x2Ge |
+            Instruction::V128And |
+            Instruction::V128Andnot |
+            Instruction::V128Or |
+            Instruction::V128Xor |
+            Instruction::I8x16NarrowI16x8s |
+            Instruction::I8x16NarrowI16x8u |
+            Instruction::I8x16Add |
+            Instruction::I8x16AddSats |
+            Instruction::I8x16AddSatu |
+            Instruction::I8x16Sub |
+            Instruction::I8x16SubSats |
+            Instruction::I8x16SubSatu |
+            Instruction::I8x16Mins |
+            Instruction::I8x16Minu |
+            Instruction::I8x16Maxs |
+            Instruction::I8x16Maxu |
+            Instruction::I8x16Avgru |
+            Instruction::I16x8Q15mulrSats |
+            Instruction::I16x8NarrowI32x4s |
+            Instruction::I16x8NarrowI32x4u |
+            Instruction::I16x8Add |
+            Instruction::I16x8AddSats |
+            Instruction::I16x8AddSatu |
+            Instruction::I16x8Sub |
+            Instruction::I16x8SubSats |
+            Instruction::I16x8SubSatu |
+            Instruction::I16x8Mul |
+            Instruction::I16x8Mins |
+            Instruction::I16x8Minu |
+            Instruction::I16x8Maxs |
+            Instruction::I16x8Maxu |
+            Instruction::I16x8Avgru |
+            Instruction::I16x8ExtmulLowI8x16s |
+            Instruction::I16x8ExtmulHighI8x16s |
+            Instruction::I16x8ExtmulLowI8x16u |
+            Instruction::I16x8ExtmulHighI8x16u |
+            Instruction::I32x4Add |
+            Instruction::I32x4Sub |
+            Instruction::I32x4Mul |
+            Instruction::I32x4Mins |
+            Instruction::I32x4Minu |
+            Instruction::I32x4Maxs |
+            Instruction::I32x4Maxu |
+            Instruction::I32x4DotI16x8s |
+            Instruction::I32x4ExtmulLowI16x8s |
+            Instruction::I32x4ExtmulHighI16x8s |
+            Instruction::I32x4ExtmulLowI16x8u |
+            Instruction::I32x4ExtmulHighI16x8u |
+            Instruction::I64x2Add |
+            Instruction::I64x2Sub |
+            Instruction::I64x2Mul |
+            Instruction::I64x2Eq |
+            Instruction::I64x2Ne |
+            Instruction::I64x2Lts |
+            Instruction::I64x2Gts |
+            Instruction::I64x2Les |
+            Instruction::I64x2Ges |
+            Instruction::I64x2ExtmulLowI32x4s |
+            Instruction::I64x2ExtmulHighI32x4s |
+            Instruction::I64x2ExtmulLowI32x4u |
+            Instruction::I64x2ExtmulHighI32x4u |
+            Instruction::F32x4Add |
+            Instruction::F32x4Sub |
+            Instruction::F32x4Mul |
+            Instruction::F32x4Div |
+            Instruction::F32x4Min |
+            Instruction::F32x4Max |
+            Instruction::F32x4Pmin |
+            Instruction::F32x4Pmax |
+            Instruction::F64x2Add |
+            Instruction::F64x2Sub |
+            Instruction::F64x2Mul |
+            Instruction::F64x2Div |
+            Instruction::F64x2Min |
+            Instruction::F64x2Max |
+            Instruction::F64x2Pmin |
+            Instruction::F64x2Pmax => {
+                self.pop_known(ValueType::V128)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::V128Bitselect => {
+                self.pop_known(ValueType::V128)?;
+                self.pop_known(ValueType::V128)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::V128AnyTrue |
+            Instruction::I8x16AllTrue |
+            Instruction::I8x16Bitmask |
+            Instruction::I16x8AllTrue |
+            Instruction::I16x8Bitmask |
+            Instruction::I32x4AllTrue |
+            Instruction::I32x4Bitmask |
+            Instruction::I64x2AllTrue |
+            Instruction::I64x2Bitmask => {
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::I8x16Shl |
+            Instruction::I8x16Shrs |
+            Instruction::I8x16Shru |
+            Instruction::I16x8Shl |
+            Instruction::I16x8Shrs |
+            Instruction::I16x8Shru |
+            Instruction::I32x4Shl |
+            Instruction::I32x4Shrs |
+            Instruction::I32x4Shru |
+            Instruction::I64x2Shl |
+            Instruction::I64x2Shrs |
+            Instruction::I64x2Shru => {
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(ValueType::V128)?;
+                self.push_known(ValueType::V128);
+            }
+            Instruction::MemoryAtomicNotify { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 2)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::MemoryAtomicWait32 { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 2)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::MemoryAtomicWait64 { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 3)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::I32AtomicLoad { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 2)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::I64AtomicLoad { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 3)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I64);
+            }
+            Instruction::I32AtomicLoad8u { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 0)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::I32AtomicLoad16u { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 1)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::I64AtomicLoad8u { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 0)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I64);
+            }
+            Instruction::I64AtomicLoad16u { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 1)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I64);
+            }
+            Instruction::I64AtomicLoad32u { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 2)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I64);
+            }
+            Instruction::I32AtomicStore { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 2)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(index_type)?;
+            }
+            Instruction::I64AtomicStore { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 3)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(index_type)?;
+            }
+            Instruction::I32AtomicStore8 { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 0)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(index_type)?;
+            }
+            Instruction::I32AtomicStore16 { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 1)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(index_type)?;
+            }
+            Instruction::I64AtomicStore8 { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 0)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(index_type)?;
+            }
+            Instruction::I64AtomicStore16 { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 1)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(index_type)?;
+            }
+            Instruction::I64AtomicStore32 { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 2)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(index_type)?;
+            }
+            Instruction::I32AtomicRmwAdd { memory_argument } |
+            Instruction::I32AtomicRmwSub { memory_argument } |
+            Instruction::I32AtomicRmwAnd { memory_argument } |
+            Instruction::I32AtomicRmwOr { memory_argument } |
+            Instruction::I32AtomicRmwXor { memory_argument } |
+            Instruction::I32AtomicRmwXchg { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 2)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::I64AtomicRmwAdd { memory_argument } |
+            Instruction::I64AtomicRmwSub { memory_argument } |
+            Instruction::I64AtomicRmwAnd { memory_argument } |
+            Instruction::I64AtomicRmwOr { memory_argument } |
+            Instruction::I64AtomicRmwXor { memory_argument } |
+            Instruction::I64AtomicRmwXchg { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 3)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I64);
+            }
+            Instruction::I32AtomicRmw8Addu { memory_argument } |
+            Instruction::I32AtomicRmw8Subu { memory_argument } |
+            Instruction::I32AtomicRmw8Andu { memory_argument } |
+            Instruction::I32AtomicRmw8Oru { memory_argument } |
+            Instruction::I32AtomicRmw8Xoru { memory_argument } |
+            Instruction::I32AtomicRmw8Xchgu { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 0)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::I32AtomicRmw16Addu { memory_argument } |
+            Instruction::I32AtomicRmw16Subu { memory_argument } |
+            Instruction::I32AtomicRmw16Andu { memory_argument } |
+            Instruction::I32AtomicRmw16Oru { memory_argument } |
+            Instruction::I32AtomicRmw16Xoru { memory_argument } |
+            Instruction::I32AtomicRmw16Xchgu { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 1)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::I64AtomicRmw8Addu { memory_argument } |
+            Instruction::I64AtomicRmw8Subu { memory_argument } |
+            Instruction::I64AtomicRmw8Andu { memory_argument } |
+            Instruction::I64AtomicRmw8Oru { memory_argument } |
+            Instruction::I64AtomicRmw8Xoru { memory_argument } |
+            Instruction::I64AtomicRmw8Xchgu { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 0)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I64);
+            }
+            Instruction::I64AtomicRmw16Addu { memory_argument } |
+            Instruction::I64AtomicRmw16Subu { memory_argument } |
+            Instruction::I64AtomicRmw16Andu { memory_argument } |
+            Instruction::I64AtomicRmw16Oru { memory_argument } |
+            Instruction::I64AtomicRmw16Xoru { memory_argument } |
+            Instruction::I64AtomicRmw16Xchgu { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 1)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I64);
+            }
+            Instruction::I64AtomicRmw32Addu { memory_argument } |
+            Instruction::I64AtomicRmw32Subu { memory_argument } |
+            Instruction::I64AtomicRmw32Andu { memory_argument } |
+            Instruction::I64AtomicRmw32Oru { memory_argument } |
+            Instruction::I64AtomicRmw32Xoru { memory_argument } |
+            Instruction::I64AtomicRmw32Xchgu { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 2)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I64);
+            }
+            Instruction::I32AtomicRmwCmpxchg { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 2)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::I64AtomicRmwCmpxchg { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 3)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I64);
+            }
+            Instruction::I32AtomicRmw8Cmpxchgu { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 0)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::I32AtomicRmw16Cmpxchgu { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 1)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(ValueType::I32)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I32);
+            }
+            Instruction::I64AtomicRmw8Cmpxchgu { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 0)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I64);
+            }
+            Instruction::I64AtomicRmw16Cmpxchgu { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 1)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I64);
+            }
+            Instruction::I64AtomicRmw32Cmpxchgu { memory_argument } => {
+                let index_type = Self::validate_atomic_memory_argument(memories, memory_argument, 2)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(ValueType::I64)?;
+                self.pop_known(index_type)?;
+                self.push_known(ValueType::I64);
+            }
+            Instruction::AtomicFence => {}
         }
 
         Ok(())
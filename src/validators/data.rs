@@ -1,6 +1,9 @@
-use crate::types::{MemoryIndex, DataSegment, GlobalType, ValueType};
+use crate::types::{MemoryIndex, DataSegment, DataMode};
 use crate::validators::code::{CodeValidationError, is_expr_const_and_of_right_type};
 use crate::validators::data::DataValidationError::InvalidMemoryIndex;
+use crate::validators::index_space::IndexSpace;
+use crate::validators::features::Features;
+use std::{error, fmt};
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum DataValidationError {
@@ -8,6 +11,24 @@ pub enum DataValidationError {
     CodeValidation(CodeValidationError),
 }
 
+impl fmt::Display for DataValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidMemoryIndex(index) => write!(f, "data segment refers to unknown memory {}", index.0),
+            DataValidationError::CodeValidation(_) => write!(f, "invalid data segment offset"),
+        }
+    }
+}
+
+impl error::Error for DataValidationError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DataValidationError::CodeValidation(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl From<CodeValidationError> for DataValidationError {
     fn from(e: CodeValidationError) -> Self {
         DataValidationError::CodeValidation(e)
@@ -16,16 +37,20 @@ impl From<CodeValidationError> for DataValidationError {
 
 pub fn validate_data(
     data_segment: &mut DataSegment,
-    max_memory_index: Option<MemoryIndex>,
-    globals: &[GlobalType]
+    index_space: &IndexSpace,
+    features: &Features,
 ) -> Result<(), DataValidationError> {
-    if max_memory_index.is_none() || data_segment.memory_index > max_memory_index.unwrap() {
-        return Err(InvalidMemoryIndex(data_segment.memory_index));
+    if let DataMode::Active { memory_index, instruction_reader } = &mut data_segment.mode {
+        let memory_type = match index_space.get_memory_type(*memory_index) {
+            Some(memory_type) => memory_type,
+            None => return Err(InvalidMemoryIndex(*memory_index)),
+        };
+        is_expr_const_and_of_right_type(
+            instruction_reader,
+            memory_type.index_type(),
+            index_space,
+            features
+        )?;
     }
-    is_expr_const_and_of_right_type(
-        &mut data_segment.instruction_reader,
-        ValueType::I32,
-        globals
-    )?;
     Ok(())
 }
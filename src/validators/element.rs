@@ -1,38 +1,85 @@
-use crate::types::{ElementSegment, TableIndex, ValueType, GlobalType, FuncIndex};
-use crate::validators::element::ElementValidationError::{InvalidTableIndex, InvalidFuncIndex};
+use crate::types::{ElementSegment, ElementMode, ElementItems, TableIndex, ValueType, FuncIndex};
+use crate::validators::element::ElementValidationError::{InvalidTableIndex, InvalidFuncIndex, ElementTypeMismatch};
 use crate::validators::code::{is_expr_const_and_of_right_type, CodeValidationError};
+use crate::validators::index_space::IndexSpace;
+use crate::validators::features::Features;
+use std::{error, fmt};
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum ElementValidationError {
     InvalidTableIndex(TableIndex),
     InvalidFuncIndex(FuncIndex),
+    ElementTypeMismatch { table_type: ValueType, element_type: ValueType },
     CodeValidation(CodeValidationError),
 }
 
+impl fmt::Display for ElementValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidTableIndex(index) => write!(f, "element segment refers to unknown table {}", index.0),
+            InvalidFuncIndex(index) => write!(f, "element segment refers to unknown function {}", index.0),
+            ElementTypeMismatch { table_type, element_type } => {
+                write!(f, "element segment of {} can't initialize a table of {}", element_type, table_type)
+            }
+            ElementValidationError::CodeValidation(_) => write!(f, "invalid element segment expression"),
+        }
+    }
+}
+
+impl error::Error for ElementValidationError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ElementValidationError::CodeValidation(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl From<CodeValidationError> for ElementValidationError {
     fn from(e: CodeValidationError) -> Self {
         ElementValidationError::CodeValidation(e)
     }
 }
 
+/// Validates an element segment and returns the functions it refers to,
+/// which become valid targets of `ref.func`.
 pub fn validate_element(
     element_segment: &mut ElementSegment,
-    max_table_index: Option<TableIndex>,
-    max_func_index: Option<FuncIndex>,
-    globals: &[GlobalType]
-) -> Result<(), ElementValidationError> {
-    if max_table_index.is_none() || element_segment.table_index > max_table_index.unwrap() {
-        return Err(InvalidTableIndex(element_segment.table_index));
+    index_space: &IndexSpace,
+    features: &Features,
+) -> Result<Vec<FuncIndex>, ElementValidationError> {
+    let element_type = element_segment.element_type;
+    if let ElementMode::Active { table_index, instruction_reader } = &mut element_segment.mode {
+        let table_type = match index_space.get_table_type(*table_index) {
+            Some(table_type) => table_type,
+            None => return Err(InvalidTableIndex(*table_index)),
+        };
+        if table_type.element_type != element_type {
+            return Err(ElementTypeMismatch { table_type: table_type.element_type, element_type });
+        }
+        is_expr_const_and_of_right_type(
+            instruction_reader,
+            ValueType::I32,
+            index_space,
+            features
+        )?;
     }
-    is_expr_const_and_of_right_type(
-        &mut element_segment.instruction_reader,
-        ValueType::I32,
-        globals
-    )?;
-    for func_index in &*element_segment.function_indices {
-        if max_func_index.is_none() || *func_index > max_func_index.unwrap() {
-            return Err(InvalidFuncIndex(*func_index));
+    let mut referenced_functions = Vec::new();
+    match &mut element_segment.items {
+        ElementItems::Functions(function_indices) => {
+            for func_index in function_indices.iter() {
+                if func_index.0 >= index_space.get_function_count() {
+                    return Err(InvalidFuncIndex(*func_index));
+                }
+                referenced_functions.push(*func_index);
+            }
+        }
+        ElementItems::Expressions(expressions) => {
+            for expression in expressions.iter_mut() {
+                let referenced_function = is_expr_const_and_of_right_type(expression, element_type, index_space, features)?;
+                referenced_functions.extend(referenced_function);
+            }
         }
     }
-    Ok(())
-}
\ No newline at end of file
+    Ok(referenced_functions)
+}
@@ -1,6 +1,7 @@
-use crate::types::{Export, ExportDescriptor, FuncIndex, TableIndex, MemoryIndex, GlobalIndex};
+use crate::types::{Export, ExportDescriptor, FuncIndex, TableIndex, MemoryIndex, GlobalIndex, TagIndex};
 use std::collections::HashSet;
-use crate::validators::export::ExportValidationError::{DuplicateName, InvalidFuncIndex, InvalidTableIndex, InvalidMemoryIndex, InvalidGlobalIndex};
+use std::{error, fmt};
+use crate::validators::export::ExportValidationError::{DuplicateName, InvalidFuncIndex, InvalidTableIndex, InvalidMemoryIndex, InvalidGlobalIndex, InvalidTagIndex};
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum ExportValidationError {
@@ -9,8 +10,24 @@ pub enum ExportValidationError {
     InvalidTableIndex(TableIndex),
     InvalidMemoryIndex(MemoryIndex),
     InvalidGlobalIndex(GlobalIndex),
+    InvalidTagIndex(TagIndex),
 }
 
+impl fmt::Display for ExportValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DuplicateName => write!(f, "duplicate export name"),
+            InvalidFuncIndex(index) => write!(f, "exported function {} is unknown", index.0),
+            InvalidTableIndex(index) => write!(f, "exported table {} is unknown", index.0),
+            InvalidMemoryIndex(index) => write!(f, "exported memory {} is unknown", index.0),
+            InvalidGlobalIndex(index) => write!(f, "exported global {} is unknown", index.0),
+            InvalidTagIndex(index) => write!(f, "exported tag {} is unknown", index.0),
+        }
+    }
+}
+
+impl error::Error for ExportValidationError {}
+
 pub struct ExportValidator {
     exported_names: HashSet<String>,
 }
@@ -26,6 +43,7 @@ impl ExportValidator {
                     max_table_index: Option<TableIndex>,
                     max_memory_index: Option<MemoryIndex>,
                     max_global_index: Option<GlobalIndex>,
+                    max_tag_index: Option<TagIndex>,
     ) -> Result<(), ExportValidationError> {
         if !self.exported_names.insert(export.name.to_string()) {
             return Err(DuplicateName);
@@ -51,6 +69,11 @@ impl ExportValidator {
                     return Err(InvalidGlobalIndex(global_index));
                 }
             }
+            ExportDescriptor::Tag { tag_index } => {
+                if max_tag_index.is_none() || tag_index > max_tag_index.unwrap() {
+                    return Err(InvalidTagIndex(tag_index));
+                }
+            }
         }
         Ok(())
     }
@@ -0,0 +1,32 @@
+use crate::ValueType;
+
+/// The proposals beyond the MVP that a `Validator` accepts.
+///
+/// Proposals supported before this struct existed are always accepted, apart
+/// from the multiple tables of reference types, which the MVP forbids. Each
+/// flag opts into one more proposal, so the default only accepts the MVP and
+/// those earlier proposals.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Features {
+    /// `return_call` and `return_call_indirect`.
+    pub tail_call: bool,
+    /// Tags and the `try`, `throw` and `try_table` families of instructions.
+    pub exceptions: bool,
+    /// More than one memory, and instructions accessing memories other than 0.
+    pub multi_memory: bool,
+    /// Memories addressed with `i64`.
+    pub memory64: bool,
+    /// `i32` and `i64` `add`, `sub` and `mul` in constant expressions.
+    pub extended_const: bool,
+    /// More than one table, and `call_indirect` on tables other than 0. The
+    /// rest of the reference types proposal is always accepted.
+    pub reference_types: bool,
+}
+
+impl Features {
+    /// `exnref` belongs to exception handling, every other value type is
+    /// always accepted.
+    pub(crate) fn accepts_value_type(&self, value_type: ValueType) -> bool {
+        value_type != ValueType::ExnRef || self.exceptions
+    }
+}
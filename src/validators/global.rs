@@ -1,6 +1,9 @@
-use crate::types::{GlobalSegment, GlobalType};
+use crate::types::{GlobalSegment, FuncIndex};
 use crate::InstructionReaderError;
 use crate::validators::code::{is_expr_const_and_of_right_type, CodeValidationError};
+use crate::validators::index_space::IndexSpace;
+use crate::validators::features::Features;
+use std::{error, fmt};
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum GlobalValidationError {
@@ -8,6 +11,24 @@ pub enum GlobalValidationError {
     InstructionReaderError(InstructionReaderError),
 }
 
+impl fmt::Display for GlobalValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GlobalValidationError::CodeValidation(_) => write!(f, "invalid global initializer"),
+            GlobalValidationError::InstructionReaderError(_) => write!(f, "malformed global initializer"),
+        }
+    }
+}
+
+impl error::Error for GlobalValidationError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            GlobalValidationError::CodeValidation(e) => Some(e),
+            GlobalValidationError::InstructionReaderError(e) => Some(e),
+        }
+    }
+}
+
 impl From<InstructionReaderError> for GlobalValidationError {
     fn from(e: InstructionReaderError) -> Self {
         GlobalValidationError::InstructionReaderError(e)
@@ -20,11 +41,18 @@ impl From<CodeValidationError> for GlobalValidationError {
     }
 }
 
-pub fn validate_global_type(global: &mut GlobalSegment, globals: &[GlobalType]) -> Result<(), GlobalValidationError>{
-    is_expr_const_and_of_right_type(
+/// Validates the initializer of `global`, returning the function it refers
+/// to if it is a `ref.func`.
+pub fn validate_global_type(
+    global: &mut GlobalSegment,
+    index_space: &IndexSpace,
+    features: &Features,
+) -> Result<Option<FuncIndex>, GlobalValidationError> {
+    let referenced_function = is_expr_const_and_of_right_type(
         &mut global.instruction_reader,
         global.global_type.var_type,
-        globals
+        index_space,
+        features
     )?;
-    Ok(())
+    Ok(referenced_function)
 }
\ No newline at end of file
@@ -1,34 +1,87 @@
-use crate::types::{ImportDescriptor, TypeIndex};
-use crate::types::ImportDescriptor::{Func, Memory};
-use crate::validators::import::ImportValidationError::{InvalidFuncTypeIndex, InvalidMemoryTypeMemoryLimits};
+use crate::types::ImportDescriptor;
+use crate::types::ImportDescriptor::{Func, Table, Memory, Tag};
+use crate::validators::import::ImportValidationError::{InvalidFuncTypeIndex, InvalidTableTypeTableLimits, InvalidMemoryTypeMemoryLimits};
+use crate::validators::import::ImportValidationError::{InvalidTagType, DisabledTags, DisabledMemory64};
 use crate::validators::type_index::{TypeIndexValidationError, validate_type_index};
+use crate::validators::table::{TableLimitsValidationError, validate_table_type};
 use crate::validators::memory::{MemoryLimitsValidationError, validate_memory_type};
+use crate::validators::tag::{TagValidationError, validate_tag_type};
+use crate::validators::index_space::IndexSpace;
+use crate::validators::features::Features;
+use std::{error, fmt};
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum ImportValidationError {
     InvalidFuncTypeIndex,
+    InvalidTableTypeTableLimits,
     InvalidMemoryTypeMemoryLimits,
+    InvalidTagType,
+    DisabledTags,
+    DisabledMemory64,
 }
 
+impl fmt::Display for ImportValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidFuncTypeIndex => write!(f, "imported function refers to an unknown type"),
+            InvalidTableTypeTableLimits => write!(f, "imported table has invalid limits"),
+            InvalidMemoryTypeMemoryLimits => write!(f, "imported memory has invalid limits"),
+            InvalidTagType => write!(f, "imported tag has an invalid type"),
+            DisabledTags => write!(f, "imported tags need exception handling support"),
+            DisabledMemory64 => write!(f, "imported 64-bit memories need memory64 support"),
+        }
+    }
+}
+
+impl error::Error for ImportValidationError {}
+
 impl From<TypeIndexValidationError> for ImportValidationError {
     fn from(_: TypeIndexValidationError) -> Self {
         InvalidFuncTypeIndex
     }
 }
 
+impl From<TableLimitsValidationError> for ImportValidationError {
+    fn from(_: TableLimitsValidationError) -> Self {
+        InvalidTableTypeTableLimits
+    }
+}
+
 impl From<MemoryLimitsValidationError> for ImportValidationError {
-    fn from(_: MemoryLimitsValidationError) -> Self {
-        InvalidMemoryTypeMemoryLimits
+    fn from(e: MemoryLimitsValidationError) -> Self {
+        match e {
+            MemoryLimitsValidationError::DisabledMemory64 => DisabledMemory64,
+            _ => InvalidMemoryTypeMemoryLimits,
+        }
+    }
+}
+
+impl From<TagValidationError> for ImportValidationError {
+    fn from(_: TagValidationError) -> Self {
+        InvalidTagType
     }
 }
 
-pub(crate) fn validate_import_desc(import_desc: &ImportDescriptor, max_type_index: Option<TypeIndex>) -> Result<(), ImportValidationError> {
+pub(crate) fn validate_import_desc(
+    import_desc: &ImportDescriptor,
+    index_space: &IndexSpace,
+    features: &Features,
+) -> Result<(), ImportValidationError> {
     match import_desc {
         Func { type_index } => {
-            validate_type_index(&type_index, max_type_index)?
+            validate_type_index(type_index, index_space.get_max_type_index())?
+        },
+        Table(table) => {
+            validate_table_type(table)?
         },
         Memory(memory) => {
-            validate_memory_type(&memory)?
+            validate_memory_type(memory, features)?
+        },
+        Tag(tag_type) => {
+            if !features.exceptions {
+                return Err(DisabledTags);
+            }
+            validate_tag_type(tag_type, &index_space.function_types)?
         },
         _ => {},
     }
@@ -0,0 +1,276 @@
+use crate::types::{TypeIndex, FuncIndex, TableIndex, MemoryIndex, GlobalIndex, ElementIndex, FunctionType, TableType, MemoryType, GlobalType, ImportDescriptor, ValueType};
+use crate::types::{TagIndex, TagType};
+use crate::validators::module::Result;
+use crate::validators::features::Features;
+use crate::ValidationError::{MultipleTables, MultipleMemories};
+use std::collections::HashSet;
+
+/// The index spaces of a module as built by the `Validator`.
+///
+/// Imports come first in each index space, followed by the items defined in
+/// the module, so an index below the import count of its space refers to an
+/// imported item. The index spaces are only complete once the whole module
+/// has been validated.
+#[derive(Debug, Default)]
+pub struct IndexSpace {
+    pub(crate) function_types: Vec<FunctionType>,
+    pub(crate) function_type_indices: Vec<TypeIndex>,
+    pub(crate) tables: Vec<TableType>,
+    pub(crate) memories: Vec<MemoryType>,
+    pub(crate) globals: Vec<GlobalType>,
+    pub(crate) tags: Vec<TagType>,
+    pub(crate) element_types: Vec<ValueType>,
+    pub(crate) function_references: HashSet<FuncIndex>,
+    pub(crate) data_count: Option<u32>,
+    pub(crate) num_func_imports: u32,
+    pub(crate) num_table_imports: u32,
+    pub(crate) num_memory_imports: u32,
+    pub(crate) num_global_imports: u32,
+    pub(crate) num_tag_imports: u32,
+}
+
+impl IndexSpace {
+    pub fn new() -> IndexSpace {
+        Self::default()
+    }
+
+    /// Returns the function type declared at `type_index` in the type section.
+    pub fn get_type(&self, type_index: TypeIndex) -> Option<&FunctionType> {
+        self.function_types.get(type_index.0 as usize)
+    }
+
+    /// Returns the index of the type of the function at `func_index`.
+    pub fn get_function_type_index(&self, func_index: FuncIndex) -> Option<TypeIndex> {
+        self.function_type_indices.get(func_index.0 as usize).copied()
+    }
+
+    /// Returns the type of the function at `func_index`.
+    pub fn get_function_type(&self, func_index: FuncIndex) -> Option<&FunctionType> {
+        self.get_function_type_index(func_index).and_then(|type_index| self.get_type(type_index))
+    }
+
+    pub fn get_table_type(&self, table_index: TableIndex) -> Option<&TableType> {
+        self.tables.get(table_index.0 as usize)
+    }
+
+    pub fn get_memory_type(&self, memory_index: MemoryIndex) -> Option<&MemoryType> {
+        self.memories.get(memory_index.0 as usize)
+    }
+
+    pub fn get_global_type(&self, global_index: GlobalIndex) -> Option<&GlobalType> {
+        self.globals.get(global_index.0 as usize)
+    }
+
+    pub fn get_tag_type(&self, tag_index: TagIndex) -> Option<&TagType> {
+        self.tags.get(tag_index.0 as usize)
+    }
+
+    /// Returns the type of the references held by the element segment at `element_index`.
+    pub fn get_element_type(&self, element_index: ElementIndex) -> Option<ValueType> {
+        self.element_types.get(element_index.0 as usize).copied()
+    }
+
+    pub fn get_type_count(&self) -> u32 {
+        self.function_types.len() as u32
+    }
+
+    pub fn get_function_count(&self) -> u32 {
+        self.function_type_indices.len() as u32
+    }
+
+    pub fn get_table_count(&self) -> u32 {
+        self.tables.len() as u32
+    }
+
+    pub fn get_memory_count(&self) -> u32 {
+        self.memories.len() as u32
+    }
+
+    pub fn get_global_count(&self) -> u32 {
+        self.globals.len() as u32
+    }
+
+    pub fn get_tag_count(&self) -> u32 {
+        self.tags.len() as u32
+    }
+
+    pub fn get_element_count(&self) -> u32 {
+        self.element_types.len() as u32
+    }
+
+    /// Returns the number of data segments announced by the data count
+    /// section, if the module has one.
+    pub fn get_data_count(&self) -> Option<u32> {
+        self.data_count
+    }
+
+    pub fn get_imported_function_count(&self) -> u32 {
+        self.num_func_imports
+    }
+
+    pub fn get_imported_table_count(&self) -> u32 {
+        self.num_table_imports
+    }
+
+    pub fn get_imported_memory_count(&self) -> u32 {
+        self.num_memory_imports
+    }
+
+    pub fn get_imported_global_count(&self) -> u32 {
+        self.num_global_imports
+    }
+
+    pub fn get_imported_tag_count(&self) -> u32 {
+        self.num_tag_imports
+    }
+
+    pub fn is_imported_function(&self, func_index: FuncIndex) -> bool {
+        func_index.0 < self.num_func_imports
+    }
+
+    pub fn is_imported_table(&self, table_index: TableIndex) -> bool {
+        table_index.0 < self.num_table_imports
+    }
+
+    pub fn is_imported_memory(&self, memory_index: MemoryIndex) -> bool {
+        memory_index.0 < self.num_memory_imports
+    }
+
+    pub fn is_imported_global(&self, global_index: GlobalIndex) -> bool {
+        global_index.0 < self.num_global_imports
+    }
+
+    pub fn is_imported_tag(&self, tag_index: TagIndex) -> bool {
+        tag_index.0 < self.num_tag_imports
+    }
+
+    /// Whether the function at `func_index` is declared as referenced outside
+    /// of function bodies, by an element segment, an export or a global
+    /// initializer, which `ref.func` requires.
+    pub fn is_function_referenced(&self, func_index: FuncIndex) -> bool {
+        self.function_references.contains(&func_index)
+    }
+
+    pub(crate) fn get_max_type_index(&self) -> Option<TypeIndex> {
+        if self.function_types.is_empty() {
+            None
+        } else {
+            Some(TypeIndex(self.function_types.len() as u32 - 1))
+        }
+    }
+
+    pub(crate) fn get_max_global_index(&self) -> Option<GlobalIndex> {
+        if self.globals.is_empty() {
+            None
+        } else {
+            Some(GlobalIndex(self.globals.len() as u32 - 1))
+        }
+    }
+
+    pub(crate) fn get_max_function_index(&self) -> Option<FuncIndex> {
+        if self.function_type_indices.is_empty() {
+            None
+        } else {
+            Some(FuncIndex(self.function_type_indices.len() as u32 - 1))
+        }
+    }
+
+    pub(crate) fn get_defined_function_count(&self) -> u32 {
+        self.function_type_indices.len() as u32 - self.num_func_imports
+    }
+
+    pub(crate) fn get_max_table_index(&self) -> Option<TableIndex> {
+        if self.tables.is_empty() {
+            None
+        } else {
+            Some(TableIndex(self.tables.len() as u32 - 1))
+        }
+    }
+
+    pub(crate) fn get_max_memory_index(&self) -> Option<MemoryIndex> {
+        if self.memories.is_empty() {
+            None
+        } else {
+            Some(MemoryIndex(self.memories.len() as u32 - 1))
+        }
+    }
+
+    pub(crate) fn get_max_tag_index(&self) -> Option<TagIndex> {
+        if self.tags.is_empty() {
+            None
+        } else {
+            Some(TagIndex(self.tags.len() as u32 - 1))
+        }
+    }
+
+    /// Records an imported item, `offset` being where the import was read.
+    pub(crate) fn add_import_desc(&mut self, import_desc: &ImportDescriptor, features: &Features, offset: usize) -> Result<()> {
+        match import_desc {
+            ImportDescriptor::Func { type_index } => {
+                self.num_func_imports += 1;
+                self.add_type_index(*type_index);
+            }
+            ImportDescriptor::Table(table_type) => {
+                self.num_table_imports += 1;
+                self.add_table_type(table_type, features, offset)?;
+            }
+            ImportDescriptor::Memory(memory_type) => {
+                self.num_memory_imports += 1;
+                self.add_memory_type(memory_type, features, offset)?;
+            }
+            ImportDescriptor::Global(global_type) => {
+                self.num_global_imports += 1;
+                self.add_global_type(global_type);
+            }
+            ImportDescriptor::Tag(tag_type) => {
+                self.num_tag_imports += 1;
+                self.add_tag_type(tag_type);
+            }
+        }
+        Ok(())
+    }
+
+    /// Imported and defined tables share one index space, of which the MVP
+    /// allows a single entry unless reference types are enabled.
+    pub(crate) fn add_table_type(&mut self, table_type: &TableType, features: &Features, offset: usize) -> Result<()> {
+        if !features.reference_types && !self.tables.is_empty() {
+            return Err(MultipleTables { offset });
+        }
+        self.tables.push(*table_type);
+        Ok(())
+    }
+
+    /// Imported and defined memories share one index space, of which the MVP
+    /// allows a single entry unless multi-memory is enabled.
+    pub(crate) fn add_memory_type(&mut self, memory_type: &MemoryType, features: &Features, offset: usize) -> Result<()> {
+        if !features.multi_memory && !self.memories.is_empty() {
+            return Err(MultipleMemories { offset });
+        }
+        self.memories.push(*memory_type);
+        Ok(())
+    }
+
+    pub(crate) fn add_function_type(&mut self, function_type: FunctionType) {
+        self.function_types.push(function_type);
+    }
+
+    pub(crate) fn add_type_index(&mut self, type_index: TypeIndex) {
+        self.function_type_indices.push(type_index);
+    }
+
+    pub(crate) fn add_global_type(&mut self, global_type: &GlobalType) {
+        self.globals.push(*global_type)
+    }
+
+    pub(crate) fn add_tag_type(&mut self, tag_type: &TagType) {
+        self.tags.push(*tag_type);
+    }
+
+    pub(crate) fn add_element_type(&mut self, element_type: ValueType) {
+        self.element_types.push(element_type);
+    }
+
+    pub(crate) fn add_function_reference(&mut self, func_index: FuncIndex) {
+        self.function_references.insert(func_index);
+    }
+}
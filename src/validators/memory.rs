@@ -1,7 +1,9 @@
 use crate::types::{Limits, MemoryType};
-use crate::validators::memory::MemoryLimitsValidationError::InvalidMemoryLimits;
+use crate::validators::memory::MemoryLimitsValidationError::{InvalidMemoryLimits, InvalidMemory64Limits, SharedMemoryWithoutMaximum, DisabledMemory64};
+use crate::validators::features::Features;
+use std::{error, fmt};
 
-fn limits_in_range(limits: &Limits, range: u32) -> bool {
+pub(crate) fn limits_in_range(limits: &Limits, range: u64) -> bool {
     let min = limits.min;
     min <= range && if let Some(max) = limits.max {
         max <= range && min <= max
@@ -13,12 +15,40 @@ fn limits_in_range(limits: &Limits, range: u32) -> bool {
 #[derive(PartialEq, Eq, Debug)]
 pub enum MemoryLimitsValidationError {
     InvalidMemoryLimits,
+    InvalidMemory64Limits,
+    SharedMemoryWithoutMaximum,
+    DisabledMemory64,
 }
 
-pub fn validate_memory_type(memory: &MemoryType) -> Result<(), MemoryLimitsValidationError> {
-    let MemoryType { limits } = memory;
-    if !limits_in_range(limits, 65536) {
+impl fmt::Display for MemoryLimitsValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidMemoryLimits => write!(f, "memory limits must be at most 65536 pages and min must not exceed max"),
+            InvalidMemory64Limits => {
+                write!(f, "64-bit memory limits must be at most 2^48 pages and min must not exceed max")
+            },
+            SharedMemoryWithoutMaximum => write!(f, "shared memory must have a maximum"),
+            DisabledMemory64 => write!(f, "64-bit memories need memory64 support"),
+        }
+    }
+}
+
+impl error::Error for MemoryLimitsValidationError {}
+
+pub fn validate_memory_type(memory: &MemoryType, features: &Features) -> Result<(), MemoryLimitsValidationError> {
+    let MemoryType { limits, shared, memory64 } = memory;
+    if *memory64 {
+        if !features.memory64 {
+            return Err(DisabledMemory64);
+        }
+        if !limits_in_range(limits, 1 << 48) {
+            return Err(InvalidMemory64Limits);
+        }
+    } else if !limits_in_range(limits, 65536) {
         return Err(InvalidMemoryLimits);
     }
+    if *shared && limits.max.is_none() {
+        return Err(SharedMemoryWithoutMaximum);
+    }
     Ok(())
 }
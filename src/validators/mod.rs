@@ -1,11 +1,30 @@
+pub use crate::validators::preamble::PreambleValidationError;
+pub use crate::validators::import::ImportValidationError;
+pub use crate::validators::type_index::TypeIndexValidationError;
+pub use crate::validators::memory::MemoryLimitsValidationError;
+pub use crate::validators::table::TableLimitsValidationError;
+pub use crate::validators::global::GlobalValidationError;
+pub use crate::validators::export::ExportValidationError;
+pub use crate::validators::start::StartValidationError;
+pub use crate::validators::element::ElementValidationError;
+pub use crate::validators::data::DataValidationError;
+pub use crate::validators::tag::TagValidationError;
+pub use crate::validators::code::{CodeValidationError, Operand};
+pub use crate::validators::index_space::IndexSpace;
+pub use crate::validators::features::Features;
+
 pub mod module;
 pub mod preamble;
 mod import;
 mod type_index;
 mod memory;
+mod table;
 mod global;
 mod export;
 mod start;
 mod element;
 pub(crate) mod code;
-mod data;
\ No newline at end of file
+mod index_space;
+mod features;
+mod data;
+mod tag;
\ No newline at end of file
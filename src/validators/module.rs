@@ -1,69 +1,200 @@
-use crate::{Chunk, SectionReader, ImportReaderError, FunctionReaderError, TableReaderError, MemoryReaderError, GlobalReaderError, ExportReaderError, TypeReaderError, ElementReaderError, DataReaderError, CodeReaderError, InstructionReaderError};
-use std::result;
+use crate::{Chunk, SectionReader, ImportReaderError, FunctionReaderError, TableReaderError, MemoryReaderError, TagReaderError, GlobalReaderError, ExportReaderError, TypeReaderError, ElementReaderError, DataReaderError, CodeReaderError, InstructionReaderError};
+use std::{error, fmt, result};
 use crate::validators::preamble::{validate_preamble, PreambleValidationError};
 use crate::validators::import::{validate_import_desc, ImportValidationError};
 use crate::validators::type_index::{validate_type_index, TypeIndexValidationError};
-use crate::types::{TypeIndex, GlobalType, ImportDescriptor, FuncIndex, TableIndex, MemoryIndex, GlobalIndex, FunctionType, TableType, MemoryType};
+use crate::types::{FuncIndex, ExportDescriptor, ImportDescriptor, ValueType};
+use crate::validators::index_space::IndexSpace;
+use crate::validators::table::{validate_table_type, TableLimitsValidationError};
 use crate::validators::memory::{validate_memory_type, MemoryLimitsValidationError};
+use crate::validators::tag::{validate_tag_type, TagValidationError};
 use crate::validators::global::{validate_global_type, GlobalValidationError};
 use crate::validators::export::{ExportValidator, ExportValidationError};
 use crate::validators::start::{validate_start, StartValidationError};
 use crate::validators::element::{validate_element, ElementValidationError};
 use crate::validators::data::{validate_data, DataValidationError};
-use crate::ValidationError::UnknownSection;
+use crate::ValidationError::{UnknownSection, SectionOutOfOrder, FunctionCountMismatch, DataCountMismatch, ImportValidation, FunctionValidation, TableValidation, MemoryValidation, MultipleTables, MultipleMemories, DisabledValueType, TagValidation, GlobalValidation, ExportValidation, StartValidation, ElementValidation, DataValidation};
 use crate::validators::code::{CodeValidator, CodeValidationError};
+use crate::validators::features::Features;
 
 pub struct Validator {
-    context: ValidationContext,
+    features: Features,
+    index_space: IndexSpace,
+    previous_section_id: Option<u8>,
+    function_section_offset: usize,
+    code_section_seen: bool,
+    data_count_section_offset: usize,
+    data_section_seen: bool,
 }
 
+/// Errors raised while validating a module. Reader errors carry their own
+/// offsets, validation errors carry the absolute offset of the offending item
+/// and, for function bodies, the index of the function being validated.
 #[derive(PartialEq, Eq, Debug)]
 pub enum ValidationError {
     PreambleValidation(PreambleValidationError),
     TypeReader(TypeReaderError),
-    ImportValidation(ImportValidationError),
+    ImportValidation { error: ImportValidationError, offset: usize },
     ImportReader(ImportReaderError),
-    FunctionValidation(TypeIndexValidationError),
+    FunctionValidation { error: TypeIndexValidationError, offset: usize },
     FunctionReader(FunctionReaderError),
     TableReader(TableReaderError),
+    TableValidation { error: TableLimitsValidationError, offset: usize },
     MemoryReader(MemoryReaderError),
-    MemoryValidation(MemoryLimitsValidationError),
+    MemoryValidation { error: MemoryLimitsValidationError, offset: usize },
+    TagReader(TagReaderError),
+    TagValidation { error: TagValidationError, offset: usize },
     GlobalReader(GlobalReaderError),
-    GlobalValidation(GlobalValidationError),
+    GlobalValidation { error: GlobalValidationError, offset: usize },
     ExportReader(ExportReaderError),
-    ExportValidation(ExportValidationError),
-    StartValidation(StartValidationError),
+    ExportValidation { error: ExportValidationError, offset: usize },
+    StartValidation { error: StartValidationError, offset: usize },
     ElementReader(ElementReaderError),
-    ElementValidation(ElementValidationError),
+    ElementValidation { error: ElementValidationError, offset: usize },
     DataReader(DataReaderError),
-    DataValidation(DataValidationError),
+    DataValidation { error: DataValidationError, offset: usize },
     CodeReader(CodeReaderError),
     InstructionReader(InstructionReaderError),
-    CodeValidation(CodeValidationError),
-    UnknownSection(u8),
+    CodeValidation { error: CodeValidationError, func_index: FuncIndex, offset: usize },
+    UnknownSection { id: u8, offset: usize },
+    SectionOutOfOrder { id: u8, previous_id: u8, offset: usize },
+    FunctionCountMismatch { function_count: u32, code_count: u32, offset: usize },
+    DataCountMismatch { data_count: u32, segment_count: u32, offset: usize },
+    MultipleTables { offset: usize },
+    MultipleMemories { offset: usize },
+    DisabledValueType { value_type: ValueType, offset: usize },
 }
 
-impl From<PreambleValidationError> for ValidationError {
-    fn from(e: PreambleValidationError) -> Self {
-        ValidationError::PreambleValidation(e)
+impl ValidationError {
+    /// Returns the absolute offset in the module at which the error was detected.
+    pub fn offset(&self) -> usize {
+        match self {
+            ValidationError::PreambleValidation(_) => 0,
+            ValidationError::TypeReader(e) => e.offset(),
+            ValidationError::ImportReader(e) => e.offset(),
+            ValidationError::FunctionReader(e) => e.offset(),
+            ValidationError::TableReader(e) => e.offset(),
+            ValidationError::MemoryReader(e) => e.offset(),
+            ValidationError::TagReader(e) => e.offset(),
+            ValidationError::GlobalReader(e) => e.offset(),
+            ValidationError::ExportReader(e) => e.offset(),
+            ValidationError::ElementReader(e) => e.offset(),
+            ValidationError::DataReader(e) => e.offset(),
+            ValidationError::CodeReader(e) => e.offset(),
+            ValidationError::InstructionReader(e) => e.offset(),
+            ValidationError::ImportValidation { offset, .. } |
+            ValidationError::FunctionValidation { offset, .. } |
+            ValidationError::TableValidation { offset, .. } |
+            ValidationError::MemoryValidation { offset, .. } |
+            ValidationError::TagValidation { offset, .. } |
+            ValidationError::GlobalValidation { offset, .. } |
+            ValidationError::ExportValidation { offset, .. } |
+            ValidationError::StartValidation { offset, .. } |
+            ValidationError::ElementValidation { offset, .. } |
+            ValidationError::DataValidation { offset, .. } |
+            ValidationError::CodeValidation { offset, .. } |
+            ValidationError::UnknownSection { offset, .. } |
+            ValidationError::SectionOutOfOrder { offset, .. } |
+            ValidationError::FunctionCountMismatch { offset, .. } |
+            ValidationError::DataCountMismatch { offset, .. } |
+            ValidationError::MultipleTables { offset } |
+            ValidationError::MultipleMemories { offset } |
+            ValidationError::DisabledValueType { offset, .. } => *offset,
+        }
     }
 }
 
-impl From<TypeReaderError> for ValidationError {
-    fn from(e: TypeReaderError) -> Self {
-        ValidationError::TypeReader(e)
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::PreambleValidation(_) => write!(f, "invalid module preamble"),
+            ValidationError::TypeReader(e) => e.fmt(f),
+            ValidationError::ImportReader(e) => e.fmt(f),
+            ValidationError::FunctionReader(e) => e.fmt(f),
+            ValidationError::TableReader(e) => e.fmt(f),
+            ValidationError::MemoryReader(e) => e.fmt(f),
+            ValidationError::TagReader(e) => e.fmt(f),
+            ValidationError::GlobalReader(e) => e.fmt(f),
+            ValidationError::ExportReader(e) => e.fmt(f),
+            ValidationError::ElementReader(e) => e.fmt(f),
+            ValidationError::DataReader(e) => e.fmt(f),
+            ValidationError::CodeReader(e) => e.fmt(f),
+            ValidationError::InstructionReader(e) => e.fmt(f),
+            ImportValidation { offset, .. } => write!(f, "invalid import at offset {}", offset),
+            FunctionValidation { offset, .. } => write!(f, "invalid function declaration at offset {}", offset),
+            TableValidation { offset, .. } => write!(f, "invalid table at offset {}", offset),
+            MemoryValidation { offset, .. } => write!(f, "invalid memory at offset {}", offset),
+            TagValidation { offset, .. } => write!(f, "invalid tag at offset {}", offset),
+            GlobalValidation { offset, .. } => write!(f, "invalid global at offset {}", offset),
+            ExportValidation { offset, .. } => write!(f, "invalid export at offset {}", offset),
+            StartValidation { offset, .. } => write!(f, "invalid start function at offset {}", offset),
+            ElementValidation { offset, .. } => write!(f, "invalid element segment at offset {}", offset),
+            DataValidation { offset, .. } => write!(f, "invalid data segment at offset {}", offset),
+            ValidationError::CodeValidation { func_index, offset, .. } => {
+                write!(f, "invalid body of function {} at offset {}", func_index.0, offset)
+            }
+            UnknownSection { id, offset } => write!(f, "unknown section with id {} at offset {}", id, offset),
+            SectionOutOfOrder { id, previous_id, offset } if id == previous_id => {
+                write!(f, "duplicate section with id {} at offset {}", id, offset)
+            }
+            SectionOutOfOrder { id, previous_id, offset } => {
+                write!(f, "section with id {} at offset {} can't follow section with id {}", id, offset, previous_id)
+            }
+            FunctionCountMismatch { function_count, code_count, offset } => {
+                write!(f, "{} functions declared but {} function bodies defined, at offset {}", function_count, code_count, offset)
+            }
+            DataCountMismatch { data_count, segment_count, offset } => {
+                write!(f, "data count of {} doesn't match the {} data segments, at offset {}", data_count, segment_count, offset)
+            }
+            MultipleTables { offset } => write!(f, "multiple tables are not allowed, found another one at offset {}", offset),
+            MultipleMemories { offset } => write!(f, "multiple memories are not allowed, found another one at offset {}", offset),
+            DisabledValueType { value_type, offset } => write!(f, "{} support is not enabled, found at offset {}", value_type, offset),
+        }
     }
 }
 
-impl From<ImportValidationError> for ValidationError {
-    fn from(e: ImportValidationError) -> Self {
-        ValidationError::ImportValidation(e)
+impl error::Error for ValidationError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ValidationError::PreambleValidation(e) => Some(e),
+            ValidationError::TypeReader(e) => e.source(),
+            ValidationError::ImportReader(e) => e.source(),
+            ValidationError::FunctionReader(e) => e.source(),
+            ValidationError::TableReader(e) => e.source(),
+            ValidationError::MemoryReader(e) => e.source(),
+            ValidationError::TagReader(e) => e.source(),
+            ValidationError::GlobalReader(e) => e.source(),
+            ValidationError::ExportReader(e) => e.source(),
+            ValidationError::ElementReader(e) => e.source(),
+            ValidationError::DataReader(e) => e.source(),
+            ValidationError::CodeReader(e) => e.source(),
+            ValidationError::InstructionReader(e) => e.source(),
+            ImportValidation { error, .. } => Some(error),
+            FunctionValidation { error, .. } => Some(error),
+            TableValidation { error, .. } => Some(error),
+            MemoryValidation { error, .. } => Some(error),
+            TagValidation { error, .. } => Some(error),
+            GlobalValidation { error, .. } => Some(error),
+            ExportValidation { error, .. } => Some(error),
+            StartValidation { error, .. } => Some(error),
+            ElementValidation { error, .. } => Some(error),
+            DataValidation { error, .. } => Some(error),
+            ValidationError::CodeValidation { error, .. } => Some(error),
+            UnknownSection { .. } | SectionOutOfOrder { .. } | FunctionCountMismatch { .. } | DataCountMismatch { .. } => None,
+            MultipleTables { .. } | MultipleMemories { .. } | DisabledValueType { .. } => None,
+        }
     }
 }
 
-impl From<TypeIndexValidationError> for ValidationError {
-    fn from(e: TypeIndexValidationError) -> Self {
-        ValidationError::FunctionValidation(e)
+impl From<PreambleValidationError> for ValidationError {
+    fn from(e: PreambleValidationError) -> Self {
+        ValidationError::PreambleValidation(e)
+    }
+}
+
+impl From<TypeReaderError> for ValidationError {
+    fn from(e: TypeReaderError) -> Self {
+        ValidationError::TypeReader(e)
     }
 }
 
@@ -91,9 +222,9 @@ impl From<MemoryReaderError> for ValidationError {
     }
 }
 
-impl From<MemoryLimitsValidationError> for ValidationError {
-    fn from(e: MemoryLimitsValidationError) -> Self {
-        ValidationError::MemoryValidation(e)
+impl From<TagReaderError> for ValidationError {
+    fn from(e: TagReaderError) -> Self {
+        ValidationError::TagReader(e)
     }
 }
 
@@ -103,54 +234,24 @@ impl From<GlobalReaderError> for ValidationError {
     }
 }
 
-impl From<GlobalValidationError> for ValidationError {
-    fn from(e: GlobalValidationError) -> Self {
-        ValidationError::GlobalValidation(e)
-    }
-}
-
 impl From<ExportReaderError> for ValidationError {
     fn from(e: ExportReaderError) -> Self {
         ValidationError::ExportReader(e)
     }
 }
 
-impl From<ExportValidationError> for ValidationError {
-    fn from(e: ExportValidationError) -> Self {
-        ValidationError::ExportValidation(e)
-    }
-}
-
-impl From<StartValidationError> for ValidationError {
-    fn from(e: StartValidationError) -> Self {
-        ValidationError::StartValidation(e)
-    }
-}
-
 impl From<ElementReaderError> for ValidationError {
     fn from(e: ElementReaderError) -> Self {
         ValidationError::ElementReader(e)
     }
 }
 
-impl From<ElementValidationError> for ValidationError {
-    fn from(e: ElementValidationError) -> Self {
-        ValidationError::ElementValidation(e)
-    }
-}
-
 impl From<DataReaderError> for ValidationError {
     fn from(e: DataReaderError) -> Self {
         ValidationError::DataReader(e)
     }
 }
 
-impl From<DataValidationError> for ValidationError {
-    fn from(e: DataValidationError) -> Self {
-        ValidationError::DataValidation(e)
-    }
-}
-
 impl From<CodeReaderError> for ValidationError {
     fn from(e: CodeReaderError) -> Self {
         ValidationError::CodeReader(e)
@@ -163,117 +264,80 @@ impl From<InstructionReaderError> for ValidationError {
     }
 }
 
-impl From<CodeValidationError> for ValidationError {
-    fn from(e: CodeValidationError) -> Self {
-        ValidationError::CodeValidation(e)
-    }
-}
-
 pub type Result<T, E = ValidationError> = result::Result<T, E>;
 
-struct ValidationContext {
-    function_types: Vec<FunctionType>,
-    globals: Vec<GlobalType>,
-    function_type_indices: Vec<TypeIndex>,
-    num_func_imports: u32,
-    max_table_index: Option<TableIndex>,
-    max_memory_index: Option<MemoryIndex>,
+/// Returns the rank of a known non-custom section in the module order.
+fn section_order(id: u8) -> u8 {
+    match id {
+        1..=5 => id,
+        // The tag section goes between the memory and global sections.
+        13 => 6,
+        6..=9 => id + 1,
+        // The data count section goes between the element and code sections.
+        12 => 11,
+        10 | 11 => id + 2,
+        _ => unreachable!("section {} has no place in the module order", id),
+    }
 }
 
-impl ValidationContext {
-    fn new() -> ValidationContext {
-        ValidationContext {
-            function_types: Vec::new(),
-            globals: Vec::new(),
-            function_type_indices: Vec::new(),
-            num_func_imports: 0,
-            max_table_index: None,
-            max_memory_index: None,
-        }
+impl Validator {
+    pub fn new() -> Validator {
+        Self::with_features(Features::default())
     }
 
-    fn get_max_type_index(&self) -> Option<TypeIndex> {
-        if self.function_types.is_empty() {
-            None
-        } else {
-            Some(TypeIndex(self.function_types.len() as u32 - 1))
+    /// Creates a validator that also accepts the proposals enabled in `features`.
+    pub fn with_features(features: Features) -> Validator {
+        Validator {
+            features,
+            index_space: IndexSpace::new(),
+            previous_section_id: None,
+            function_section_offset: 0,
+            code_section_seen: false,
+            data_count_section_offset: 0,
+            data_section_seen: false,
         }
     }
 
-    fn get_max_global_index(&self) -> Option<GlobalIndex> {
-        if self.globals.is_empty() {
-            None
-        } else {
-            Some(GlobalIndex(self.globals.len() as u32 - 1))
-        }
+    /// Returns the index spaces built from the sections validated so far,
+    /// which describe the whole module once `Chunk::Done` has been validated.
+    pub fn get_index_space(&self) -> &IndexSpace {
+        &self.index_space
     }
 
-    fn get_max_function_index(&self) -> Option<FuncIndex> {
-        if self.function_type_indices.is_empty() {
-            None
-        } else {
-            Some(FuncIndex(self.function_type_indices.len() as u32 - 1))
+    /// Checks what can only be checked once every section has been seen.
+    fn validate_module_end(&self) -> Result<()> {
+        let function_count = self.index_space.get_defined_function_count();
+        if function_count > 0 && !self.code_section_seen {
+            return Err(FunctionCountMismatch { function_count, code_count: 0, offset: self.function_section_offset });
         }
-    }
-
-    fn get_max_table_index(&self) -> Option<TableIndex> {
-        self.max_table_index
-    }
-
-    fn get_max_memory_index(&self) -> Option<MemoryIndex> {
-        self.max_memory_index
-    }
-
-    fn add_import_desc(&mut self, import_desc: &ImportDescriptor) {
-        match import_desc {
-            ImportDescriptor::Func { type_index } => {
-                self.num_func_imports += 1;
-                self.add_type_index(*type_index);
-            }
-            ImportDescriptor::Table(table_type) => {
-                self.add_table_type(table_type);
-            }
-            ImportDescriptor::Memory(memory_type) => {
-                self.add_memory_type(memory_type);
-            }
-            ImportDescriptor::Global(global_type) => {
-                self.add_global_type(global_type);
+        if let Some(data_count) = self.index_space.data_count {
+            if data_count > 0 && !self.data_section_seen {
+                return Err(DataCountMismatch { data_count, segment_count: 0, offset: self.data_count_section_offset });
             }
         }
+        Ok(())
     }
 
-    fn add_table_type(&mut self, _table_type: &TableType) {
-        self.max_table_index = Some(match self.max_table_index {
-            None => { TableIndex(0) }
-            Some(current) => { TableIndex(current.0 + 1) }
-        });
-    }
-
-    fn add_memory_type(&mut self, _memory_type: &MemoryType) {
-        self.max_memory_index = Some(match self.max_memory_index {
-            None => { MemoryIndex(0) }
-            Some(current) => { MemoryIndex(current.0 + 1) }
-        });
-    }
-
-    fn add_function_type(&mut self, function_type: FunctionType) {
-        self.function_types.push(function_type);
-    }
-
-    fn add_type_index(&mut self, type_index: TypeIndex) {
-        self.function_type_indices.push(type_index);
-    }
-
-    fn add_global_type(&mut self, global_type: &GlobalType) {
-        self.globals.push(*global_type)
+    /// Value types of proposals that aren't enabled are rejected wherever
+    /// a module declares them.
+    fn validate_value_type(&self, value_type: ValueType, offset: usize) -> Result<()> {
+        if !self.features.accepts_value_type(value_type) {
+            return Err(DisabledValueType { value_type, offset });
+        }
+        Ok(())
     }
-}
 
-impl Validator {
-    pub fn new() -> Validator {
-        Validator {
-            context: ValidationContext::new()
+    /// Checks that a non-custom section comes after the previous one in the
+    /// order required by the binary format, which also rules out duplicates.
+    fn validate_section_order(&mut self, section_reader: &SectionReader) -> Result<()> {
+        let id = section_reader.get_id();
+        if let Some(previous_id) = self.previous_section_id {
+            if section_order(id) <= section_order(previous_id) {
+                return Err(SectionOutOfOrder { id, previous_id, offset: section_reader.get_section_offset() });
+            }
         }
+        self.previous_section_id = Some(id);
+        Ok(())
     }
 
     pub fn validate(&mut self, chunk: &Chunk) -> Result<()> {
@@ -282,111 +346,169 @@ impl Validator {
                 validate_preamble(magic_number, version)?;
             }
             Chunk::Section(ref section_reader) => {
+                match section_reader {
+                    SectionReader::Custom(_) | SectionReader::Unknown { .. } => {}
+                    _ => self.validate_section_order(section_reader)?,
+                }
                 match section_reader {
                     SectionReader::Custom(_) => {}
                     SectionReader::Type(reader) => {
-                        for function_type in reader.clone() {
-                            let function_type = function_type?;
-                            self.context.add_function_type(function_type);
+                        for function_type in reader.clone().into_iter_with_offsets() {
+                            let (offset, function_type) = function_type?;
+                            for value_type in function_type.params().chain(function_type.results()) {
+                                self.validate_value_type(value_type, offset)?;
+                            }
+                            self.index_space.add_function_type(function_type);
                         }
                     },
                     SectionReader::Import(reader) => {
-                        for import in reader.clone() {
-                            let import = import?;
+                        for import in reader.clone().into_iter_with_offsets() {
+                            let (offset, import) = import?;
                             let import_desc = import.import_descriptor;
-                            validate_import_desc(&import_desc, self.context.get_max_type_index())?;
-                            self.context.add_import_desc(&import_desc);
+                            match import_desc {
+                                ImportDescriptor::Table(table_type) => self.validate_value_type(table_type.element_type, offset)?,
+                                ImportDescriptor::Global(global_type) => self.validate_value_type(global_type.var_type, offset)?,
+                                _ => {},
+                            }
+                            validate_import_desc(&import_desc, &self.index_space, &self.features)
+                                .map_err(|error| ImportValidation { error, offset })?;
+                            self.index_space.add_import_desc(&import_desc, &self.features, offset)?;
                         }
                     },
                     SectionReader::Function(reader) => {
-                        for type_index in reader.clone() {
-                            let type_index = type_index?;
-                            validate_type_index(&type_index, self.context.get_max_type_index())?;
-                            self.context.add_type_index(type_index);
+                        self.function_section_offset = reader.get_section_offset();
+                        for type_index in reader.clone().into_iter_with_offsets() {
+                            let (offset, type_index) = type_index?;
+                            validate_type_index(&type_index, self.index_space.get_max_type_index())
+                                .map_err(|error| FunctionValidation { error, offset })?;
+                            self.index_space.add_type_index(type_index);
                         }
                     },
                     SectionReader::Table(reader) => {
-                        for table in reader.clone() {
-                            let table = table?;
-                            self.context.add_table_type(&table);
+                        for table_type in reader.clone().into_iter_with_offsets() {
+                            let (offset, table_type) = table_type?;
+                            self.validate_value_type(table_type.element_type, offset)?;
+                            validate_table_type(&table_type)
+                                .map_err(|error| TableValidation { error, offset })?;
+                            self.index_space.add_table_type(&table_type, &self.features, offset)?;
                         }
                     },
                     SectionReader::Memory(reader) => {
-                        for memory_type in reader.clone() {
-                            let memory_type = memory_type?;
-                            validate_memory_type(&memory_type)?;
-                            self.context.add_memory_type(&memory_type);
+                        for memory_type in reader.clone().into_iter_with_offsets() {
+                            let (offset, memory_type) = memory_type?;
+                            validate_memory_type(&memory_type, &self.features)
+                                .map_err(|error| MemoryValidation { error, offset })?;
+                            self.index_space.add_memory_type(&memory_type, &self.features, offset)?;
+                        }
+                    },
+                    SectionReader::Tag(reader) => {
+                        // Without exception handling, tag sections are as
+                        // unknown as they were before it.
+                        if !self.features.exceptions {
+                            return Err(UnknownSection { id: 13, offset: reader.get_section_offset() });
+                        }
+                        for tag_type in reader.clone().into_iter_with_offsets() {
+                            let (offset, tag_type) = tag_type?;
+                            validate_tag_type(&tag_type, &self.index_space.function_types)
+                                .map_err(|error| TagValidation { error, offset })?;
+                            self.index_space.add_tag_type(&tag_type);
                         }
                     },
                     SectionReader::Global(reader) => {
-                        for global in reader.clone() {
-                            let mut global = global?;
-                            validate_global_type(&mut global, &self.context.globals)?;
-                            self.context.add_global_type(&global.global_type);
+                        for global in reader.clone().into_iter_with_offsets() {
+                            let (offset, mut global) = global?;
+                            self.validate_value_type(global.global_type.var_type, offset)?;
+                            let referenced_function = validate_global_type(&mut global, &self.index_space, &self.features)
+                                .map_err(|error| GlobalValidation { error, offset })?;
+                            self.index_space.add_global_type(&global.global_type);
+                            if let Some(func_index) = referenced_function {
+                                self.index_space.add_function_reference(func_index);
+                            }
                         }
                     },
                     SectionReader::Export(reader) => {
                         let mut export_validator = ExportValidator::new();
-                        for export in reader.clone() {
-                            let export = export?;
+                        for export in reader.clone().into_iter_with_offsets() {
+                            let (offset, export) = export?;
                             export_validator.validate(
                                 &export,
-                                self.context.get_max_function_index(),
-                                self.context.get_max_table_index(),
-                                self.context.get_max_memory_index(),
-                                self.context.get_max_global_index(),
-                            )?;
+                                self.index_space.get_max_function_index(),
+                                self.index_space.get_max_table_index(),
+                                self.index_space.get_max_memory_index(),
+                                self.index_space.get_max_global_index(),
+                                self.index_space.get_max_tag_index(),
+                            ).map_err(|error| ExportValidation { error, offset })?;
+                            if let ExportDescriptor::Func { func_index } = export.export_descriptor {
+                                self.index_space.add_function_reference(func_index);
+                            }
                         }
                     },
                     SectionReader::Start(reader) => {
                         let func_index = reader.get_func_index();
-                        validate_start(func_index, &self.context.function_type_indices, &self.context.function_types)?;
+                        validate_start(func_index, &self.index_space.function_type_indices, &self.index_space.function_types)
+                            .map_err(|error| StartValidation { error, offset: reader.get_original_position() })?;
                     },
                     SectionReader::Element(reader) => {
-                        for element_segment in reader.clone() {
-                            let mut element_segment = element_segment?;
-                            validate_element(
-                                &mut element_segment,
-                                self.context.max_table_index,
-                                self.context.get_max_function_index(),
-                                &self.context.globals
-                            )?;
+                        for element_segment in reader.clone().into_iter_with_offsets() {
+                            let (offset, mut element_segment) = element_segment?;
+                            self.validate_value_type(element_segment.element_type, offset)?;
+                            let referenced_functions = validate_element(&mut element_segment, &self.index_space, &self.features)
+                                .map_err(|error| ElementValidation { error, offset })?;
+                            self.index_space.add_element_type(element_segment.element_type);
+                            for func_index in referenced_functions {
+                                self.index_space.add_function_reference(func_index);
+                            }
                         }
                     },
                     SectionReader::Code(reader) => {
-                        let mut function_index = 0u32;
-                        for code in reader.clone() {
+                        self.code_section_seen = true;
+                        let function_count = self.index_space.get_defined_function_count();
+                        if reader.get_count() != function_count {
+                            return Err(FunctionCountMismatch {
+                                function_count,
+                                code_count: reader.get_count(),
+                                offset: reader.get_section_offset(),
+                            });
+                        }
+                        for (function_index, code) in reader.clone().into_iter().enumerate() {
                             let code = code?;
 
                             let mut code_validator = CodeValidator::new(code);
                             code_validator.validate(
-                                &self.context.globals,
-                                &self.context.function_types,
-                                &self.context.function_type_indices,
-                                FuncIndex(function_index + self.context.num_func_imports),
-                                self.context.get_max_table_index(),
-                                self.context.get_max_memory_index(),
+                                &self.index_space,
+                                &self.features,
+                                FuncIndex(function_index as u32 + self.index_space.num_func_imports),
                             )?;
-                            function_index += 1;
                         }
                     }
                     SectionReader::Data(reader) => {
-                        for data_segment in reader.clone() {
-                            let mut data_segment = data_segment?;
-                            validate_data(
-                                &mut data_segment,
-                                self.context.get_max_memory_index(),
-                                &self.context.globals
-                            )?;
+                        self.data_section_seen = true;
+                        if let Some(data_count) = self.index_space.data_count {
+                            if reader.get_count() != data_count {
+                                return Err(DataCountMismatch {
+                                    data_count,
+                                    segment_count: reader.get_count(),
+                                    offset: reader.get_section_offset(),
+                                });
+                            }
                         }
+                        for data_segment in reader.clone().into_iter_with_offsets() {
+                            let (offset, mut data_segment) = data_segment?;
+                            validate_data(&mut data_segment, &self.index_space, &self.features)
+                                .map_err(|error| DataValidation { error, offset })?;
+                        }
+                    }
+                    SectionReader::DataCount(reader) => {
+                        self.data_count_section_offset = reader.get_section_offset();
+                        self.index_space.data_count = Some(reader.get_count());
                     }
-                    SectionReader::Unknown(id) => {
-                        return Err(UnknownSection(*id));
+                    SectionReader::Unknown { id, offset, .. } => {
+                        return Err(UnknownSection { id: *id, offset: *offset });
                     }
                 }
             }
-            Chunk::Done => {
-            }
+            Chunk::NeedMoreData { .. } => {}
+            Chunk::Done => self.validate_module_end()?,
         }
         Ok(())
     }
@@ -396,4 +518,1128 @@ impl Default for Validator {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::encoder::*;
+    use crate::{Parser, Validator, Chunk, SectionReader, ValidationError, Limits, TableType, MemoryType, ImportDescriptor};
+    use crate::TableLimitsValidationError::InvalidTableLimits;
+    use crate::Instruction::{End, Nop, Block, Branch};
+    use crate::{Instruction, BlockType};
+    use crate::CodeValidationError::{MissingDataCount, UndeclaredFunctionReference, TypeMismatch, InvalidLaneIndex, InvalidAtomicAlignment};
+    use crate::CodeValidationError::{UndefinedTable, InvalidTypeIndex, MissingEnd, InstructionAfterEnd, InvalidUntypedSelect};
+    use crate::CodeValidationError::{OperandStackEmpty, TargetLabelsArityMismatch, DisabledFeature, TailCallResultsMismatch};
+    use crate::CodeValidationError::{CatchClauseMismatch, InvalidRethrowLabel, CatchAfterCatchAll, UndefinedMemory, InvalidMemoryOffset};
+    use crate::CodeValidationError::{NonConstantInstruction, InvalidInitExpr, ConstExprDefinedGlobal, ConstExprMutableGlobal};
+    use crate::{GlobalValidationError, ElementValidationError, DataValidationError, ElementReaderError};
+    use crate::readers::binary::BinaryReaderError;
+    use crate::TagValidationError::NonEmptyResults;
+    use crate::Features;
+    use crate::CodeValidationError;
+    use crate::text::parse;
+    use crate::MemoryLimitsValidationError::{SharedMemoryWithoutMaximum, DisabledMemory64};
+    use crate::ElementValidationError::ElementTypeMismatch;
+    use crate::Operand;
+    use crate::ValueType;
+
+    fn validate_module(module: &[u8]) -> Result<(), ValidationError> {
+        validate_module_with_features(module, Features::default())
+    }
+
+    fn validate_module_with_features(module: &[u8], features: Features) -> Result<(), ValidationError> {
+        let mut parser = Parser::new();
+        let mut validator = Validator::with_features(features);
+        let mut position = 0;
+        loop {
+            let (consumed, chunk) = parser.parse(&module[position..], true).unwrap();
+            validator.validate(&chunk)?;
+            if let Chunk::Done = chunk {
+                return Ok(());
+            }
+            position += consumed;
+        }
+    }
+
+    /// The position of the first occurrence of `bytes` in `module`.
+    fn position_of(module: &[u8], bytes: &[u8]) -> usize {
+        module.windows(bytes.len()).position(|window| window == bytes).unwrap()
+    }
+
+    fn sections() -> (TypeSectionEncoder, FunctionSectionEncoder, CodeSectionEncoder) {
+        let mut types = TypeSectionEncoder::new();
+        types.function_type(&[], &[]);
+        let mut functions = FunctionSectionEncoder::new();
+        functions.type_index(0.into());
+        let mut body = FunctionBodyEncoder::new(&[]);
+        body.instruction(&End);
+        let mut code = CodeSectionEncoder::new();
+        code.function(&body);
+        (types, functions, code)
+    }
+
+    #[test]
+    fn custom_sections_go_anywhere() {
+        let (types, functions, code) = sections();
+        let custom = CustomSectionEncoder::new("anywhere", &[1, 2, 3]);
+        let mut module = ModuleEncoder::new();
+        module.section(&custom)
+            .section(&types)
+            .section(&custom)
+            .section(&functions)
+            .section(&custom)
+            .section(&code)
+            .section(&custom);
+        assert_eq!(validate_module(&module.finish()), Ok(()));
+    }
+
+    #[test]
+    fn out_of_order_sections() {
+        let mut tables = TableSectionEncoder::new();
+        tables.table(&TableType::new(ValueType::FuncRef, Limits::new(1, None)));
+        let mut memories = MemorySectionEncoder::new();
+        memories.memory(&MemoryType::new(Limits::new(1, None)));
+        let mut prefix = ModuleEncoder::new();
+        prefix.section(&memories);
+        let offset = prefix.finish().len();
+        let mut module = ModuleEncoder::new();
+        module.section(&memories).section(&tables);
+        let error = validate_module(&module.finish()).unwrap_err();
+        assert_eq!(error, ValidationError::SectionOutOfOrder { id: 4, previous_id: 5, offset });
+        assert_eq!(error.to_string(), format!("section with id 4 at offset {} can't follow section with id 5", offset));
+    }
+
+    #[test]
+    fn duplicate_sections() {
+        let (types, functions, _) = sections();
+        let mut prefix = ModuleEncoder::new();
+        prefix.section(&types);
+        let offset = prefix.finish().len();
+        let mut module = ModuleEncoder::new();
+        module.section(&types).section(&types).section(&functions);
+        let error = validate_module(&module.finish()).unwrap_err();
+        assert_eq!(error, ValidationError::SectionOutOfOrder { id: 1, previous_id: 1, offset });
+        assert_eq!(error.to_string(), format!("duplicate section with id 1 at offset {}", offset));
+    }
+
+    #[test]
+    fn function_and_code_counts_must_match() {
+        let (types, mut functions, code) = sections();
+        functions.type_index(0.into());
+        let mut prefix = ModuleEncoder::new();
+        prefix.section(&types).section(&functions);
+        let offset = prefix.finish().len();
+        let mut module = ModuleEncoder::new();
+        module.section(&types).section(&functions).section(&code);
+        let error = validate_module(&module.finish()).unwrap_err();
+        assert_eq!(error, ValidationError::FunctionCountMismatch { function_count: 2, code_count: 1, offset });
+
+        let mut module = ModuleEncoder::new();
+        module.section(&types).section(&code);
+        assert_eq!(
+            validate_module(&module.finish()),
+            Err(ValidationError::FunctionCountMismatch { function_count: 0, code_count: 1, offset: 14 })
+        );
+    }
+
+    #[test]
+    fn functions_without_code_are_reported_at_the_end() {
+        let (types, functions, _) = sections();
+        let mut module = ModuleEncoder::new();
+        module.section(&types).section(&functions);
+        let error = validate_module(&module.finish()).unwrap_err();
+        assert_eq!(error, ValidationError::FunctionCountMismatch { function_count: 1, code_count: 0, offset: 14 });
+        assert_eq!(error.to_string(), "1 functions declared but 0 function bodies defined, at offset 14");
+    }
+
+    #[test]
+    fn table_limits_are_validated() {
+        let mut tables = TableSectionEncoder::new();
+        tables.table(&TableType::new(ValueType::FuncRef, Limits::new(2, Some(1))));
+        let mut module = ModuleEncoder::new();
+        module.section(&tables);
+        let error = validate_module(&module.finish()).unwrap_err();
+        assert_eq!(error, ValidationError::TableValidation { error: InvalidTableLimits, offset: 11 });
+        assert_eq!(error.to_string(), "invalid table at offset 11");
+    }
+
+    const REFERENCE_TYPES: Features = Features {
+        tail_call: false, exceptions: false, multi_memory: false, memory64: false, extended_const: false, reference_types: true
+    };
+
+    /// A funcref table 0 and an externref table 1.
+    const REFERENCE_TABLES: &str = "(type (func)) (table 1 funcref) (table 1 externref)";
+
+    fn reference_types_error(text: &str) -> ValidationError {
+        let module = parse(&format!("{} {}", REFERENCE_TABLES, text)).unwrap();
+        validate_module_with_features(&module, REFERENCE_TYPES).unwrap_err()
+    }
+
+    #[test]
+    fn tables_of_references_are_used_by_index() {
+        let body = r#"
+            (func
+              (table.set 1 (i32.const 0) (ref.null extern))
+              (call_indirect 0 (type 0)
+                (select (result i32)
+                  (ref.is_null (table.get 1 (i32.const 0)))
+                  (table.grow 0 (ref.func 0) (i32.const 1))
+                  (i32.const 1))))
+        "#;
+        let elements = "(elem declare func 0) (elem externref (ref.null extern))";
+        let module = parse(&format!("{} {} {}", REFERENCE_TABLES, body, elements)).unwrap();
+        assert_eq!(validate_module_with_features(&module, REFERENCE_TYPES), Ok(()));
+
+        match reference_types_error(body) {
+            ValidationError::CodeValidation { error: UndeclaredFunctionReference(func_index), .. } => {
+                assert_eq!(func_index, 0.into());
+            },
+            error => panic!("expected an undeclared function reference, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn element_segments_must_match_their_table() {
+        match reference_types_error("(func) (elem (table 1) (i32.const 0) func 0)") {
+            ValidationError::ElementValidation { error, .. } => {
+                assert_eq!(error, ElementTypeMismatch { table_type: ValueType::ExternRef, element_type: ValueType::FuncRef });
+                assert_eq!(error.to_string(), "element segment of funcref can't initialize a table of externref");
+            },
+            error => panic!("expected an element type mismatch, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn huge_element_counts_run_out_of_bytes() {
+        let mut module = ModuleEncoder::new().finish();
+        // An active segment for table 0 at offset 0 declaring u32::MAX functions.
+        module.extend_from_slice(&[9, 10, 1, 0, 0x41, 0, 0x0B, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F]);
+        assert_eq!(
+            validate_module(&module),
+            Err(ValidationError::ElementReader(ElementReaderError::BinaryReaderError(BinaryReaderError::UnexpectedEof(20))))
+        );
+    }
+
+    #[test]
+    fn call_indirect_requires_a_funcref_table() {
+        match reference_types_error("(func (call_indirect 1 (type 0) (i32.const 0)))") {
+            ValidationError::CodeValidation { error: TypeMismatch { expected, actual }, .. } => {
+                assert_eq!(expected, Operand::Known(ValueType::FuncRef));
+                assert_eq!(actual, Operand::Known(ValueType::ExternRef));
+            },
+            error => panic!("expected a type mismatch, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn call_indirect_checks_its_table_type_and_signature() {
+        let signature = "(type (func (param i32 i64) (result f32))) (table 1 funcref)";
+        let module = parse(&format!(
+            "{} (func (result f32) (call_indirect (type 0) (i32.const 1) (i64.const 2) (i32.const 0)))",
+            signature
+        )).unwrap();
+        assert_eq!(validate_module(&module), Ok(()));
+
+        let wrong_param = format!(
+            "{} (func (result f32) (call_indirect (type 0) (i64.const 1) (i64.const 2) (i32.const 0)))",
+            signature
+        );
+        assert_eq!(
+            code_validation_error(&wrong_param),
+            TypeMismatch { expected: Operand::Known(ValueType::I32), actual: Operand::Known(ValueType::I64) }
+        );
+        let wrong_result = format!(
+            "{} (func (result i64) (call_indirect (type 0) (i32.const 1) (i64.const 2) (i32.const 0)))",
+            signature
+        );
+        assert_eq!(
+            code_validation_error(&wrong_result),
+            TypeMismatch { expected: Operand::Known(ValueType::I64), actual: Operand::Known(ValueType::F32) }
+        );
+        let no_table = "(type (func)) (func (call_indirect (type 0) (i32.const 0)))";
+        assert_eq!(code_validation_error(no_table), UndefinedTable);
+
+        // The text format doesn't refer to undefined types, so patch the immediate in.
+        let mut module = parse("(type (func)) (table 1 funcref) (func (call_indirect (type 0) (i32.const 0)))").unwrap();
+        let call = position_of(&module, &[0x11, 0, 0]);
+        module[call + 1] = 3;
+        match validate_module(&module) {
+            Err(ValidationError::CodeValidation { error: InvalidTypeIndex(type_index), .. }) => {
+                assert_eq!(type_index, 3.into());
+            },
+            result => panic!("expected an unknown type, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn reserved_indices_must_be_zero_without_their_proposal() {
+        let bodies = [
+            "(func (drop (memory.size 1)))",
+            "(func (drop (memory.grow 1 (i32.const 1))))",
+            "(func (memory.fill 1 (i32.const 0) (i32.const 0) (i32.const 0)))",
+            "(func (memory.copy 0 1 (i32.const 0) (i32.const 0) (i32.const 0)))",
+        ];
+        let multi_memory = Features {
+            tail_call: false, exceptions: false, multi_memory: true, memory64: false, extended_const: false, reference_types: false
+        };
+        for body in bodies {
+            assert_eq!(code_validation_error(&format!("(memory 1) {}", body)), DisabledFeature("multi-memory"));
+            let module = parse(&format!("(memory 1) (memory 1) {}", body)).unwrap();
+            match validate_module(&module) {
+                Err(ValidationError::MultipleMemories { .. }) => {},
+                result => panic!("expected the second memory to be rejected, got {:?}", result),
+            }
+            assert_eq!(validate_module_with_features(&module, multi_memory), Ok(()));
+        }
+
+        let call = "(func (call_indirect 1 (type 0) (i32.const 0)))";
+        let error = code_validation_error(&format!("(type (func)) (table 1 funcref) {}", call));
+        assert_eq!(error, DisabledFeature("reference types"));
+        assert_eq!(error.to_string(), "reference types support is not enabled");
+        let module = parse(&format!("(type (func)) (table 1 funcref) (table 1 funcref) {}", call)).unwrap();
+        match validate_module(&module) {
+            Err(ValidationError::MultipleTables { .. }) => {},
+            result => panic!("expected the second table to be rejected, got {:?}", result),
+        }
+        assert_eq!(validate_module_with_features(&module, REFERENCE_TYPES), Ok(()));
+    }
+
+    #[test]
+    fn only_one_table_is_allowed() {
+        let mut tables = TableSectionEncoder::new();
+        tables.table(&TableType::new(ValueType::FuncRef, Limits::new(1, None)))
+            .table(&TableType::new(ValueType::FuncRef, Limits::new(1, None)));
+        let mut module = ModuleEncoder::new();
+        module.section(&tables);
+        let module = module.finish();
+        let error = validate_module(&module).unwrap_err();
+        assert_eq!(error, ValidationError::MultipleTables { offset: 14 });
+        assert_eq!(error.to_string(), "multiple tables are not allowed, found another one at offset 14");
+        assert_eq!(validate_module_with_features(&module, REFERENCE_TYPES), Ok(()));
+
+        let mut imports = ImportSectionEncoder::new();
+        imports.import("env", "table", &ImportDescriptor::Table(TableType::new(ValueType::FuncRef, Limits::new(1, None))));
+        let mut tables = TableSectionEncoder::new();
+        tables.table(&TableType::new(ValueType::FuncRef, Limits::new(1, None)));
+        let mut module = ModuleEncoder::new();
+        module.section(&imports).section(&tables);
+        match validate_module(&module.finish()) {
+            Err(ValidationError::MultipleTables { .. }) => {},
+            result => panic!("expected the imported table to count towards the limit, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn imported_memories_count_towards_the_limit() {
+        let mut imports = ImportSectionEncoder::new();
+        imports.import("env", "memory", &ImportDescriptor::Memory(MemoryType::new(Limits::new(1, None))));
+        let mut memories = MemorySectionEncoder::new();
+        memories.memory(&MemoryType::new(Limits::new(1, None)));
+        let mut prefix = ModuleEncoder::new();
+        prefix.section(&imports);
+        let offset = prefix.finish().len() + 3;
+        let mut module = ModuleEncoder::new();
+        module.section(&imports).section(&memories);
+        assert_eq!(validate_module(&module.finish()), Err(ValidationError::MultipleMemories { offset }));
+    }
+
+    #[test]
+    fn index_spaces_put_imports_first() {
+        let module = parse(r#"
+            (type (func))
+            (type (func (param i32)))
+            (import "env" "f" (func (type 1)))
+            (import "env" "g" (global i32))
+            (import "env" "m" (memory 1 4 shared))
+            (table 1 2 funcref)
+            (global (mut i64) (i64.const 0))
+            (func (type 0))
+        "#).unwrap();
+
+        let mut parser = Parser::new();
+        let mut validator = Validator::new();
+        let mut position = 0;
+        loop {
+            let (consumed, chunk) = parser.parse(&module[position..], true).unwrap();
+            validator.validate(&chunk).unwrap();
+            if let Chunk::Done = chunk {
+                break;
+            }
+            position += consumed;
+        }
+
+        let index_space = validator.get_index_space();
+        assert_eq!(index_space.get_function_count(), 2);
+        assert_eq!(index_space.get_imported_function_count(), 1);
+        assert!(index_space.is_imported_function(0.into()));
+        assert!(!index_space.is_imported_function(1.into()));
+        assert_eq!(index_space.get_function_type_index(0.into()), Some(1.into()));
+        let function_type = index_space.get_function_type(0.into()).unwrap();
+        assert_eq!(function_type.params().collect::<Vec<_>>(), vec![ValueType::I32]);
+        assert_eq!(function_type.results().count(), 0);
+        assert_eq!(index_space.get_function_type(1.into()).unwrap().params().count(), 0);
+        assert!(index_space.get_function_type(2.into()).is_none());
+        let table_type = index_space.get_table_type(0.into()).unwrap();
+        assert_eq!(table_type.element_type(), ValueType::FuncRef);
+        assert_eq!((table_type.limits().min(), table_type.limits().max()), (1, Some(2)));
+        assert!(!index_space.is_imported_table(0.into()));
+        assert_eq!(index_space.get_memory_count(), 1);
+        let memory_type = index_space.get_memory_type(0.into()).unwrap();
+        assert!(memory_type.is_shared());
+        assert_eq!((memory_type.limits().min(), memory_type.limits().max()), (1, Some(4)));
+        assert!(index_space.is_imported_global(0.into()));
+        let global_type = index_space.get_global_type(0.into()).unwrap();
+        assert_eq!((global_type.value_type(), global_type.is_mutable()), (ValueType::I32, false));
+        let global_type = index_space.get_global_type(1.into()).unwrap();
+        assert_eq!((global_type.value_type(), global_type.is_mutable()), (ValueType::I64, true));
+    }
+
+    /// A module using passive data segment 0, along with its data count
+    /// section and its data section.
+    fn bulk_memory_module() -> (Vec<u8>, [u8; 3], [u8; 7]) {
+        let module = parse(r#"
+            (memory 1)
+            (data $d "hi")
+            (func (memory.init $d (i32.const 0) (i32.const 0) (i32.const 2)) (data.drop $d))
+        "#).unwrap();
+        (module, [12, 1, 1], [11, 5, 1, 1, 2, b'h', b'i'])
+    }
+
+    #[test]
+    fn passive_data_is_used_through_the_data_count() {
+        let (mut module, data_count, _) = bulk_memory_module();
+        assert_eq!(validate_module(&module), Ok(()));
+
+        let start = position_of(&module, &data_count);
+        module.drain(start..start + data_count.len());
+        match validate_module(&module) {
+            Err(ValidationError::CodeValidation { error: MissingDataCount, .. }) => {},
+            result => panic!("expected a missing data count, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn data_count_must_match_the_data_section() {
+        let (mut module, data_count, data) = bulk_memory_module();
+        let start = position_of(&module, &data_count);
+        module[start + 2] = 2;
+        let offset = position_of(&module, &data);
+        let error = validate_module(&module).unwrap_err();
+        assert_eq!(error, ValidationError::DataCountMismatch { data_count: 2, segment_count: 1, offset });
+        assert_eq!(error.to_string(), format!("data count of 2 doesn't match the 1 data segments, at offset {}", offset));
+    }
+
+    #[test]
+    fn data_count_goes_before_code() {
+        let (_, data_count, data) = bulk_memory_module();
+        let mut module = parse(r#"(memory 1) (data "hi") (func)"#).unwrap();
+        let offset = position_of(&module, &data);
+        module.splice(offset..offset, data_count);
+        assert_eq!(
+            validate_module(&module),
+            Err(ValidationError::SectionOutOfOrder { id: 12, previous_id: 10, offset })
+        );
+    }
+
+    /// An instruction in the text format, with the types of the operands it
+    /// pops and of the result it pushes.
+    type Signature<'a> = (&'a str, &'a [ValueType], Option<ValueType>);
+
+    /// A function passing its params to `instruction` and returning its result.
+    fn function_text(instruction: &str, params: &[ValueType], result: Option<ValueType>) -> String {
+        let mut text = String::from("(func");
+        if !params.is_empty() {
+            let params = params.iter().map(ValueType::to_string).collect::<Vec<_>>();
+            text.push_str(&format!(" (param {})", params.join(" ")));
+        }
+        if let Some(result) = result {
+            text.push_str(&format!(" (result {})", result));
+        }
+        for local_index in 0..params.len() {
+            text.push_str(&format!(" local.get {}", local_index));
+        }
+        format!("{} {})", text, instruction)
+    }
+
+    /// Reads the function bodies of `module` and encodes their instructions
+    /// again, which must give back the same code section. Returns the names
+    /// of the instructions read.
+    fn round_trip_bodies(module: &[u8]) -> Vec<&'static str> {
+        let mut parser = Parser::new();
+        let mut position = 0;
+        let reader = loop {
+            let (consumed, chunk) = parser.parse(&module[position..], true).unwrap();
+            match chunk {
+                Chunk::Section(SectionReader::Code(reader)) => break reader,
+                Chunk::Done => panic!("expected a code section"),
+                _ => position += consumed,
+            }
+        };
+        let raw_bytes = reader.get_raw_bytes();
+        let mut names = Vec::new();
+        let mut code = CodeSectionEncoder::new();
+        for body in reader {
+            let body = body.unwrap();
+            let mut locals_reader = body.get_locals_reader().unwrap();
+            let locals = (0..locals_reader.get_count()).map(|_| locals_reader.read().unwrap()).collect::<Vec<_>>();
+            let proof = locals_reader.get_iteration_proof().unwrap();
+            let mut encoder = FunctionBodyEncoder::new(&locals);
+            for instruction in body.get_instruction_reader(proof).unwrap() {
+                let instruction = instruction.unwrap();
+                names.push(instruction.name());
+                encoder.instruction(&instruction);
+            }
+            code.function(&encoder);
+        }
+        let mut module = ModuleEncoder::new();
+        module.section(&code);
+        assert_eq!(raw_bytes, &module.finish()[8..]);
+        names
+    }
+
+    /// A value type other than `value_type`, to feed instructions a wrong operand.
+    fn other_type(value_type: ValueType) -> ValueType {
+        if value_type == ValueType::V128 { ValueType::F32 } else { ValueType::V128 }
+    }
+
+    /// Checks that each instruction of `family` round trips through the binary
+    /// format, and that after `prelude` it validates with exactly its types.
+    fn check_instructions(prelude: &str, family: &[Signature]) {
+        for (instruction, params, result) in family {
+            let text = format!("{} {}", prelude, function_text(instruction, params, *result));
+            let module = parse(&text).unwrap();
+            assert_eq!(validate_module(&module), Ok(()), "{}", text);
+            let names = round_trip_bodies(&module);
+            assert_eq!(names[params.len()], instruction.split(' ').next().unwrap(), "{}", text);
+
+            if let Some((last, rest)) = params.split_last() {
+                let mut wrong_params = rest.to_vec();
+                wrong_params.push(other_type(*last));
+                let text = format!("{} {}", prelude, function_text(instruction, &wrong_params, *result));
+                assert_eq!(
+                    code_validation_error(&text),
+                    TypeMismatch { expected: Operand::Known(*last), actual: Operand::Known(other_type(*last)) },
+                    "{}", text
+                );
+            }
+            if let Some(result) = result {
+                let text = format!("{} {}", prelude, function_text(instruction, params, Some(other_type(*result))));
+                assert_eq!(
+                    code_validation_error(&text),
+                    TypeMismatch { expected: Operand::Known(other_type(*result)), actual: Operand::Known(*result) },
+                    "{}", text
+                );
+            }
+        }
+    }
+
+    const V128: ValueType = ValueType::V128;
+    const I32: ValueType = ValueType::I32;
+    const I64: ValueType = ValueType::I64;
+    const F32: ValueType = ValueType::F32;
+    const F64: ValueType = ValueType::F64;
+
+    #[test]
+    fn simd_loads_and_stores_round_trip() {
+        check_instructions("(memory 1)", &[
+            ("v128.load", &[I32], Some(V128)),
+            ("v128.load offset=16 align=8", &[I32], Some(V128)),
+            ("v128.load8x8_s", &[I32], Some(V128)),
+            ("v128.load32x2_u align=4", &[I32], Some(V128)),
+            ("v128.load16_splat", &[I32], Some(V128)),
+            ("v128.load64_zero", &[I32], Some(V128)),
+            ("v128.store offset=4", &[I32, V128], None),
+            ("v128.load8_lane 15", &[I32, V128], Some(V128)),
+            ("v128.load16_lane offset=2 7", &[I32, V128], Some(V128)),
+            ("v128.load32_lane align=1 3", &[I32, V128], Some(V128)),
+            ("v128.load64_lane 1", &[I32, V128], Some(V128)),
+            ("v128.store8_lane 15", &[I32, V128], None),
+            ("v128.store16_lane 7", &[I32, V128], None),
+            ("v128.store32_lane offset=4 align=4 3", &[I32, V128], None),
+            ("v128.store64_lane 1", &[I32, V128], None),
+        ]);
+    }
+
+    #[test]
+    fn simd_shuffles_and_swizzles_round_trip() {
+        check_instructions("", &[
+            ("i8x16.shuffle 0 17 2 19 4 21 6 23 8 25 10 27 12 29 14 31", &[V128, V128], Some(V128)),
+            ("i8x16.shuffle 31 31 31 31 31 31 31 31 31 31 31 31 31 31 31 31", &[V128, V128], Some(V128)),
+            ("i8x16.swizzle", &[V128, V128], Some(V128)),
+        ]);
+        let text = function_text("i8x16.shuffle 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 32", &[V128, V128], Some(V128));
+        assert_eq!(code_validation_error(&text), InvalidLaneIndex(32));
+    }
+
+    #[test]
+    fn simd_lane_indices_are_bounded_by_the_shape() {
+        let lanes: &[(&str, u8, &[ValueType], Option<ValueType>)] = &[
+            ("i8x16.extract_lane_s", 16, &[V128], Some(I32)),
+            ("i8x16.extract_lane_u", 16, &[V128], Some(I32)),
+            ("i8x16.replace_lane", 16, &[V128, I32], Some(V128)),
+            ("i16x8.extract_lane_s", 8, &[V128], Some(I32)),
+            ("i16x8.extract_lane_u", 8, &[V128], Some(I32)),
+            ("i16x8.replace_lane", 8, &[V128, I32], Some(V128)),
+            ("i32x4.extract_lane", 4, &[V128], Some(I32)),
+            ("i32x4.replace_lane", 4, &[V128, I32], Some(V128)),
+            ("i64x2.extract_lane", 2, &[V128], Some(I64)),
+            ("i64x2.replace_lane", 2, &[V128, I64], Some(V128)),
+            ("f32x4.extract_lane", 4, &[V128], Some(F32)),
+            ("f32x4.replace_lane", 4, &[V128, F32], Some(V128)),
+            ("f64x2.extract_lane", 2, &[V128], Some(F64)),
+            ("f64x2.replace_lane", 2, &[V128, F64], Some(V128)),
+            ("v128.load8_lane", 16, &[I32, V128], Some(V128)),
+            ("v128.load16_lane", 8, &[I32, V128], Some(V128)),
+            ("v128.load32_lane", 4, &[I32, V128], Some(V128)),
+            ("v128.load64_lane", 2, &[I32, V128], Some(V128)),
+            ("v128.store8_lane", 16, &[I32, V128], None),
+            ("v128.store16_lane", 8, &[I32, V128], None),
+            ("v128.store32_lane", 4, &[I32, V128], None),
+            ("v128.store64_lane", 2, &[I32, V128], None),
+        ];
+        for (name, lane_count, params, result) in lanes {
+            let first = format!("{} 0", name);
+            let last = format!("{} {}", name, lane_count - 1);
+            check_instructions("(memory 1)", &[(&first, params, *result), (&last, params, *result)]);
+            let text = format!("(memory 1) {}", function_text(&format!("{} {}", name, lane_count), params, *result));
+            assert_eq!(code_validation_error(&text), InvalidLaneIndex(*lane_count), "{}", text);
+        }
+        assert_eq!(InvalidLaneIndex(4).to_string(), "invalid lane index 4");
+    }
+
+    #[test]
+    fn simd_splats_round_trip() {
+        check_instructions("", &[
+            ("i8x16.splat", &[I32], Some(V128)),
+            ("i16x8.splat", &[I32], Some(V128)),
+            ("i32x4.splat", &[I32], Some(V128)),
+            ("i64x2.splat", &[I64], Some(V128)),
+            ("f32x4.splat", &[F32], Some(V128)),
+            ("f64x2.splat", &[F64], Some(V128)),
+        ]);
+    }
+
+    #[test]
+    fn simd_narrows_and_extends_round_trip() {
+        check_instructions("", &[
+            ("i8x16.narrow_i16x8_s", &[V128, V128], Some(V128)),
+            ("i8x16.narrow_i16x8_u", &[V128, V128], Some(V128)),
+            ("i16x8.narrow_i32x4_s", &[V128, V128], Some(V128)),
+            ("i16x8.narrow_i32x4_u", &[V128, V128], Some(V128)),
+            ("i16x8.extend_low_i8x16_s", &[V128], Some(V128)),
+            ("i16x8.extend_high_i8x16_u", &[V128], Some(V128)),
+            ("i32x4.extend_low_i16x8_u", &[V128], Some(V128)),
+            ("i32x4.extend_high_i16x8_s", &[V128], Some(V128)),
+            ("i64x2.extend_low_i32x4_s", &[V128], Some(V128)),
+            ("i64x2.extend_high_i32x4_u", &[V128], Some(V128)),
+            ("i16x8.extadd_pairwise_i8x16_s", &[V128], Some(V128)),
+            ("i32x4.extadd_pairwise_i16x8_u", &[V128], Some(V128)),
+            ("i16x8.extmul_low_i8x16_s", &[V128, V128], Some(V128)),
+            ("i32x4.extmul_high_i16x8_u", &[V128, V128], Some(V128)),
+            ("i64x2.extmul_low_i32x4_u", &[V128, V128], Some(V128)),
+        ]);
+    }
+
+    #[test]
+    fn simd_dot_products_round_trip() {
+        check_instructions("", &[
+            ("i32x4.dot_i16x8_s", &[V128, V128], Some(V128)),
+            ("i16x8.q15mulr_sat_s", &[V128, V128], Some(V128)),
+        ]);
+    }
+
+    #[test]
+    fn simd_conversions_round_trip() {
+        check_instructions("", &[
+            ("i32x4.trunc_sat_f32x4_s", &[V128], Some(V128)),
+            ("i32x4.trunc_sat_f32x4_u", &[V128], Some(V128)),
+            ("i32x4.trunc_sat_f64x2_s_zero", &[V128], Some(V128)),
+            ("i32x4.trunc_sat_f64x2_u_zero", &[V128], Some(V128)),
+            ("f32x4.convert_i32x4_s", &[V128], Some(V128)),
+            ("f32x4.convert_i32x4_u", &[V128], Some(V128)),
+            ("f64x2.convert_low_i32x4_s", &[V128], Some(V128)),
+            ("f64x2.convert_low_i32x4_u", &[V128], Some(V128)),
+            ("f32x4.demote_f64x2_zero", &[V128], Some(V128)),
+            ("f64x2.promote_low_f32x4", &[V128], Some(V128)),
+        ]);
+    }
+
+    /// Atomic memory instructions with their signatures and the log2 of
+    /// their natural alignment, which is the only one they may use.
+    fn atomic_instructions() -> Vec<(String, Vec<ValueType>, Option<ValueType>, u32)> {
+        let mut instructions = vec![
+            (String::from("memory.atomic.notify"), vec![I32, I32], Some(I32), 2),
+            (String::from("memory.atomic.wait32"), vec![I32, I32, I64], Some(I32), 2),
+            (String::from("memory.atomic.wait64"), vec![I32, I64, I64], Some(I32), 3),
+        ];
+        // Full width accesses, then the narrower ones zero-extending into an i32 or i64.
+        let accesses = [
+            ("i32", "", "", I32, 2),
+            ("i64", "", "", I64, 3),
+            ("i32", "8", "_u", I32, 0),
+            ("i32", "16", "_u", I32, 1),
+            ("i64", "8", "_u", I64, 0),
+            ("i64", "16", "_u", I64, 1),
+            ("i64", "32", "_u", I64, 2),
+        ];
+        for (value_type, width, extend, operand, alignment) in accesses {
+            instructions.push((format!("{}.atomic.load{}{}", value_type, width, extend), vec![I32], Some(operand), alignment));
+            instructions.push((format!("{}.atomic.store{}", value_type, width), vec![I32, operand], None, alignment));
+            for operator in ["add", "sub", "and", "or", "xor", "xchg"] {
+                let name = format!("{}.atomic.rmw{}.{}{}", value_type, width, operator, extend);
+                instructions.push((name, vec![I32, operand], Some(operand), alignment));
+            }
+            let name = format!("{}.atomic.rmw{}.cmpxchg{}", value_type, width, extend);
+            instructions.push((name, vec![I32, operand, operand], Some(operand), alignment));
+        }
+        instructions
+    }
+
+    const SHARED_MEMORY: &str = "(memory 1 1 shared)";
+
+    #[test]
+    fn atomic_accesses_round_trip() {
+        let instructions = atomic_instructions();
+        assert_eq!(instructions.len(), 3 + 7 * 9);
+        for (name, params, result, alignment) in instructions.iter() {
+            let natural = format!("{} offset=8 align={}", name, 1 << alignment);
+            check_instructions(SHARED_MEMORY, &[(name, params, *result), (&natural, params, *result)]);
+        }
+        check_instructions("", &[("atomic.fence", &[], None)]);
+    }
+
+    #[test]
+    fn atomics_use_natural_alignment() {
+        for (name, params, result, alignment) in atomic_instructions() {
+            let mut alignments = vec![alignment + 1];
+            if alignment > 0 {
+                alignments.push(alignment - 1);
+            }
+            for wrong in alignments {
+                let instruction = format!("{} align={}", name, 1 << wrong);
+                let text = format!("{} {}", SHARED_MEMORY, function_text(&instruction, &params, result));
+                assert_eq!(code_validation_error(&text), InvalidAtomicAlignment, "{}", text);
+            }
+        }
+        assert_eq!(InvalidAtomicAlignment.to_string(), "atomic accesses must use their natural alignment");
+    }
+
+    #[test]
+    fn shared_memories_need_a_maximum() {
+        match validate_module(&parse("(memory 1 shared)").unwrap()) {
+            Err(ValidationError::MemoryValidation { error, .. }) => {
+                assert_eq!(error, SharedMemoryWithoutMaximum);
+                assert_eq!(error.to_string(), "shared memory must have a maximum");
+            },
+            result => panic!("expected a shared memory without maximum, got {:?}", result),
+        }
+    }
+
+    fn code_validation_error(text: &str) -> CodeValidationError {
+        match validate_module(&parse(text).unwrap()) {
+            Err(ValidationError::CodeValidation { error, .. }) => error,
+            result => panic!("expected a code validation error for {}, got {:?}", text, result),
+        }
+    }
+
+    #[test]
+    fn untyped_select_takes_the_type_of_its_known_operand() {
+        let modules = [
+            "(func unreachable select drop)",
+            "(func (result i32) unreachable i32.const 1 select)",
+            "(func (result i64) unreachable i64.const 1 i32.const 0 select)",
+        ];
+        for module in modules {
+            assert_eq!(validate_module(&parse(module).unwrap()), Ok(()), "{}", module);
+        }
+        assert_eq!(
+            code_validation_error("(func (result i32) unreachable i64.const 1 select)"),
+            TypeMismatch { expected: Operand::Known(ValueType::I32), actual: Operand::Known(ValueType::I64) }
+        );
+        assert_eq!(
+            code_validation_error("(func (result i32) i32.const 1 i64.const 2 i32.const 0 select)"),
+            TypeMismatch { expected: Operand::Known(ValueType::I64), actual: Operand::Known(ValueType::I32) }
+        );
+        assert_eq!(
+            code_validation_error("(func unreachable ref.null extern i32.const 0 select drop)"),
+            InvalidUntypedSelect(ValueType::ExternRef)
+        );
+    }
+
+    #[test]
+    fn multi_value_bodies_are_valid() {
+        let bodies = [
+            // Block params are taken from the enclosing stack.
+            "(func (result i32) (i32.const 1) (i32.const 2) (block (param i32 i32) (result i32) (i32.add)))",
+            // Branching to a loop carries its params.
+            "(func (param i32) (result i32)
+               (local.get 0)
+               (loop $l (param i32) (result i32)
+                 (i32.const 1) (i32.sub) (local.tee 0) (local.get 0) (br_if $l)))",
+            // Both branches of an if start from its params.
+            "(func (param i32) (result i32 i32)
+               (i32.const 1) (i32.const 2)
+               (if (param i32 i32) (result i32 i32) (local.get 0)
+                 (then)
+                 (else (drop) (drop) (i32.const 3) (i32.const 4))))",
+            // An if without else passes its params through.
+            "(func (param i32) (result i32)
+               (i32.const 1) (local.get 0)
+               (if (param i32) (result i32) (then (i32.const 2) (i32.add))))",
+            // Functions return several values, and calls push all of them.
+            "(func $pair (result i32 i64) (i32.const 1) (i64.const 2) (return))
+             (func (result i32) (call $pair) (drop))",
+            // br_table labels of the same arity.
+            "(func (param i32) (result i32 i32)
+               (block $a (result i32 i32)
+                 (block $b (result i32 i32)
+                   (i32.const 1) (i32.const 2) (local.get 0) (br_table $a $b))))",
+            // Unreachable code only needs the arities of br_table labels to agree.
+            "(func (result i32)
+               (block $a (result i32)
+                 (block $b (result f32) (unreachable) (br_table $a $b))
+                 (drop) (i32.const 0)))",
+            // br_if leaves the values for its label on the stack.
+            "(func (param i32) (result i32)
+               (block $b (result i32) (i32.const 1) (local.get 0) (br_if $b) (i32.const 1) (i32.add)))",
+        ];
+        for body in bodies.iter() {
+            assert_eq!(validate_module(&parse(body).unwrap()), Ok(()), "{}", body);
+        }
+    }
+
+    #[test]
+    fn invalid_multi_value_bodies_are_rejected() {
+        assert_eq!(code_validation_error("(func (block (param i32) (drop)))"), OperandStackEmpty);
+        assert_eq!(
+            code_validation_error("(func (f32.const 0) (block (param i32) (drop)))"),
+            TypeMismatch { expected: Operand::Known(ValueType::I32), actual: Operand::Known(ValueType::F32) }
+        );
+        assert_eq!(
+            code_validation_error("(func (result i64) (i32.const 1) (i32.const 1) (if (param i32) (result i64) (then (drop) (i64.const 0))))"),
+            TypeMismatch { expected: Operand::Known(ValueType::I64), actual: Operand::Known(ValueType::I32) }
+        );
+        assert_eq!(
+            code_validation_error("(func (param i32)
+               (block $a (result i32) (block $b (i32.const 0) (local.get 0) (br_table $a $b)) (i32.const 0))
+               (drop))"),
+            TargetLabelsArityMismatch { expected: 0, actual: 1 }
+        );
+        assert_eq!(code_validation_error("(func (result i32 i32) (i32.const 1))"), OperandStackEmpty);
+        assert_eq!(code_validation_error("(func (i32.const 0) (loop $l (param i32) (drop) (br $l)))"), OperandStackEmpty);
+        assert_eq!(code_validation_error("(func (param i32) (block (local.get 0) (br_if 0) (i32.add)))"), OperandStackEmpty);
+
+        let body_error = |instructions: &[Instruction]| {
+            let (types, functions, _) = sections();
+            let mut body = FunctionBodyEncoder::new(&[]);
+            for instruction in instructions {
+                body.instruction(instruction);
+            }
+            let mut code = CodeSectionEncoder::new();
+            code.function(&body);
+            let mut module = ModuleEncoder::new();
+            module.section(&types).section(&functions).section(&code);
+            match validate_module(&module.finish()) {
+                Err(ValidationError::CodeValidation { error, .. }) => error,
+                result => panic!("expected {:?} to be rejected, got {:?}", instructions, result),
+            }
+        };
+        assert_eq!(body_error(&[Nop]), MissingEnd);
+        assert_eq!(body_error(&[Block { block_type: BlockType::Empty }, End]), MissingEnd);
+        assert_eq!(body_error(&[End, Nop]), InstructionAfterEnd);
+        assert_eq!(body_error(&[End, Branch { label_index: 0.into() }]), InstructionAfterEnd);
+        assert_eq!(body_error(&[End, End]), InstructionAfterEnd);
+        assert_eq!(InstructionAfterEnd.to_string(), "instructions found after the end of the function");
+    }
+
+    #[test]
+    fn tail_calls_are_opted_into() {
+        let module = parse(r#"
+            (type $t (func (param i32) (result i64)))
+            (table 1 funcref)
+            (func $f (param i32) (result i64)
+              (return_call $f (local.get 0)))
+            (func (param i32) (result i64)
+              (return_call_indirect (type $t) (local.get 0) (i32.const 0)))
+        "#).unwrap();
+        match validate_module(&module) {
+            Err(ValidationError::CodeValidation { error, .. }) => {
+                assert_eq!(error, DisabledFeature("tail call"));
+                assert_eq!(error.to_string(), "tail call support is not enabled");
+            },
+            result => panic!("expected tail calls to be rejected, got {:?}", result),
+        }
+        let features = Features { tail_call: true, exceptions: false, multi_memory: false, memory64: false, extended_const: false, reference_types: false };
+        assert_eq!(validate_module_with_features(&module, features), Ok(()));
+
+        let module = parse(r#"
+            (func $f (param i32) (result i64) (i64.const 0))
+            (func (result i32) (return_call $f (i32.const 0)))
+        "#).unwrap();
+        match validate_module_with_features(&module, features) {
+            Err(ValidationError::CodeValidation { error, func_index, .. }) => {
+                assert_eq!(error, TailCallResultsMismatch);
+                assert_eq!(func_index, 1.into());
+            },
+            result => panic!("expected a tail call results mismatch, got {:?}", result),
+        }
+    }
+
+    fn exceptions() -> Features {
+        Features { tail_call: false, exceptions: true, multi_memory: false, memory64: false, extended_const: false, reference_types: false }
+    }
+
+    #[test]
+    fn exceptions_are_opted_into() {
+        let module = parse(r#"
+            (tag $e (param i32))
+            (func (param i32) (result i32)
+              (try (result i32)
+                (do (local.get 0) (throw $e))
+                (catch $e)
+                (catch_all (rethrow 0))))
+            (func (result i32)
+              (try (result i32)
+                (do (try (do (i32.const 1) (throw $e)) (delegate 0)) (i32.const 0))
+                (catch $e)))
+            (func (result i32)
+              (block $b (result i32)
+                (try_table (catch $e $b) (i32.const 2) (throw $e))
+                (i32.const 0)))
+            (func (param exnref)
+              (block $b (result exnref)
+                (try_table (catch_all_ref $b) (local.get 0) (throw_ref))
+                (local.get 0))
+              (throw_ref))
+        "#).unwrap();
+        match validate_module(&module) {
+            Err(ValidationError::DisabledValueType { value_type, .. }) => assert_eq!(value_type, ValueType::ExnRef),
+            result => panic!("expected the exnref param to be rejected, got {:?}", result),
+        }
+        assert_eq!(validate_module_with_features(&module, exceptions()), Ok(()));
+        match validate_module(&parse("(tag (param i32))").unwrap()) {
+            Err(ValidationError::UnknownSection { id, .. }) => assert_eq!(id, 13),
+            result => panic!("expected the tag section to be rejected, got {:?}", result),
+        }
+
+        let declarations = [
+            "(table 1 exnref) (func (param exnref) (result exnref) (ref.null exn))",
+            "(table 1 exnref)",
+            r#"(import "env" "g" (global exnref))"#,
+            "(global exnref (ref.null exn))",
+        ];
+        for text in declarations {
+            let module = parse(text).unwrap();
+            match validate_module(&module) {
+                Err(error @ ValidationError::DisabledValueType { value_type: ValueType::ExnRef, offset }) => {
+                    assert_eq!(error.to_string(), format!("exnref support is not enabled, found at offset {}", offset));
+                },
+                result => panic!("expected exnref to be rejected in {}, got {:?}", text, result),
+            }
+            assert_eq!(validate_module_with_features(&module, exceptions()), Ok(()));
+        }
+        let bodies = [
+            "(func (local exnref))",
+            "(func (drop (ref.null exn)))",
+            "(func (drop (block (result exnref) (unreachable))))",
+        ];
+        for text in bodies {
+            assert_eq!(code_validation_error(text), DisabledFeature("exception handling"));
+            assert_eq!(validate_module_with_features(&parse(text).unwrap(), exceptions()), Ok(()));
+        }
+    }
+
+    #[test]
+    fn exception_handling_errors_are_reported() {
+        let cases = [
+            ("(tag $e (param i32))
+              (func (block $b (try_table (catch $e $b) (nop))))", CatchClauseMismatch(0.into())),
+            ("(func (block (rethrow 0)))", InvalidRethrowLabel(0.into())),
+            ("(func try catch_all catch_all end)", CatchAfterCatchAll),
+        ];
+        for (text, expected) in cases.iter() {
+            let module = parse(text).unwrap();
+            match validate_module_with_features(&module, exceptions()) {
+                Err(ValidationError::CodeValidation { error, .. }) => assert_eq!(&error, expected),
+                result => panic!("expected {:?} for {}, got {:?}", expected, text, result),
+            }
+        }
+        let module = parse("(tag (result i32))").unwrap();
+        match validate_module_with_features(&module, exceptions()) {
+            Err(ValidationError::TagValidation { error, .. }) => assert_eq!(error, NonEmptyResults(0.into())),
+            result => panic!("expected a tag with results to be rejected, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn tag_section_comes_before_globals() {
+        let mut module = parse("(tag) (global i32 (i32.const 0))").unwrap();
+        assert_eq!(validate_module_with_features(&module, exceptions()), Ok(()));
+        // Swap the tag section (id 13) behind the global section (id 6).
+        let tag_section = [13, 3, 1, 0, 0];
+        let start = module.windows(5).position(|w| w == tag_section).unwrap();
+        module.drain(start..start + 5);
+        module.extend_from_slice(&tag_section);
+        match validate_module_with_features(&module, exceptions()) {
+            Err(ValidationError::SectionOutOfOrder { .. }) => {},
+            result => panic!("expected the tag section to be out of order, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn multi_memory_is_opted_into() {
+        let module = parse(r#"
+            (memory 1)
+            (memory $m 1)
+            (data $d (memory $m) (i32.const 0) "hi")
+            (func (result i32)
+              (i32.store $m offset=4 (i32.const 0) (i32.load $m (i32.const 8)))
+              (memory.copy 0 $m (i32.const 0) (i32.const 0) (i32.const 2))
+              (memory.init $m $d (i32.const 0) (i32.const 0) (i32.const 2))
+              (memory.fill $m (i32.const 0) (i32.const 0) (i32.const 2))
+              (drop (memory.grow $m (i32.const 1)))
+              (memory.size $m))
+        "#).unwrap();
+        match validate_module(&module) {
+            Err(ValidationError::MultipleMemories { .. }) => {},
+            result => panic!("expected the second memory to be rejected, got {:?}", result),
+        }
+        let features = Features { tail_call: false, exceptions: false, multi_memory: true, memory64: false, extended_const: false, reference_types: false };
+        assert_eq!(validate_module_with_features(&module, features), Ok(()));
+
+        let module = parse("(memory 1) (memory 1) (func (drop (i32.load 2 (i32.const 0))))").unwrap();
+        match validate_module_with_features(&module, features) {
+            Err(ValidationError::CodeValidation { error, .. }) => assert_eq!(error, UndefinedMemory),
+            result => panic!("expected memory 2 to be unknown, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn memory64_is_opted_into() {
+        let module = parse(r#"
+            (memory i64 1 70000)
+            (memory 1)
+            (data (memory 0) (i64.const 0x1_0000_0000) "hi")
+            (func (param i64) (result i64)
+              (i64.store offset=0x1_0000_0000 (local.get 0) (i64.load (local.get 0)))
+              (memory.fill (local.get 0) (i32.const 0) (i64.const 2))
+              (memory.copy 1 0 (i32.const 0) (local.get 0) (i32.const 2))
+              (drop (memory.grow (i64.const 1)))
+              (memory.size))
+        "#).unwrap();
+        match validate_module(&module) {
+            Err(ValidationError::MemoryValidation { error, .. }) => assert_eq!(error, DisabledMemory64),
+            result => panic!("expected the 64-bit memory to be rejected, got {:?}", result),
+        }
+        let features = Features { tail_call: false, exceptions: false, multi_memory: true, memory64: true, extended_const: false, reference_types: false };
+        assert_eq!(validate_module_with_features(&module, features), Ok(()));
+
+        let bodies = [
+            ("(memory i64 1) (func (drop (i32.load (i32.const 0))))", TypeMismatch { expected: Operand::Known(ValueType::I64), actual: Operand::Known(ValueType::I32) }),
+            ("(memory 1) (func (drop (i32.load offset=0x1_0000_0000 (i32.const 0))))", InvalidMemoryOffset),
+        ];
+        for (text, expected) in bodies.iter() {
+            match validate_module_with_features(&parse(text).unwrap(), features) {
+                Err(ValidationError::CodeValidation { error, .. }) => assert_eq!(&error, expected),
+                result => panic!("expected {:?} for {}, got {:?}", expected, text, result),
+            }
+        }
+        let module = parse(r#"(memory i64 1) (data (i32.const 0) "hi")"#).unwrap();
+        match validate_module_with_features(&module, features) {
+            Err(ValidationError::DataValidation { .. }) => {},
+            result => panic!("expected an i32 offset into a 64-bit memory to be rejected, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn init_exprs_end_at_their_end_instruction() {
+        // The immediate of `i32.const 11` is a 0x0B byte, like `end`.
+        let module = parse("(global i32 (i32.const 11)) (global i64 (i64.const 0x0B0B))").unwrap();
+        let mut parser = Parser::new();
+        let mut validator = Validator::new();
+        let mut position = 0;
+        loop {
+            let (consumed, chunk) = parser.parse(&module[position..], true).unwrap();
+            validator.validate(&chunk).unwrap();
+            if let Chunk::Done = chunk {
+                break;
+            }
+            position += consumed;
+        }
+        assert_eq!(validator.get_index_space().get_global_count(), 2);
+    }
+
+    fn global_validation_error(text: &str, features: Features) -> CodeValidationError {
+        match validate_module_with_features(&parse(text).unwrap(), features) {
+            Err(ValidationError::GlobalValidation { error: GlobalValidationError::CodeValidation(error), .. }) => error,
+            result => panic!("expected a global validation error for {}, got {:?}", text, result),
+        }
+    }
+
+    #[test]
+    fn extended_const_is_opted_into() {
+        let module = parse(r#"
+            (import "env" "base" (global i32))
+            (memory 1)
+            (global i32 (i32.add (i32.const 1) (i32.mul (i32.const 2) (i32.const 3))))
+            (global i64 (i64.sub (i64.const 0) (i64.const 1)))
+            (data (i32.add (global.get 0) (i32.const 16)) "hi")
+        "#).unwrap();
+        let disabled = Features::default();
+        let text = r#"(global i32 (i32.add (i32.const 1) (i32.mul (i32.const 2) (i32.const 3))))"#;
+        match global_validation_error(text, disabled) {
+            NonConstantInstruction { instruction, .. } => assert_eq!(instruction, "i32.mul"),
+            error => panic!("expected i32.mul to be rejected, got {:?}", error),
+        }
+        let features = Features { tail_call: false, exceptions: false, multi_memory: false, memory64: false, extended_const: true, reference_types: false };
+        assert_eq!(validate_module_with_features(&module, features), Ok(()));
+
+        let cases = [
+            ("(global i32 (i32.div_s (i32.const 1) (i32.const 1)))", NonConstantInstruction { instruction: "i32.div_s", offset: 17 }),
+            ("(global i32 (i32.add (i64.const 1) (i32.const 1)))", TypeMismatch { expected: Operand::Known(ValueType::I32), actual: Operand::Known(ValueType::I64) }),
+            ("(global i32 (i32.add (i32.const 1)))", OperandStackEmpty),
+            ("(global i32 (i32.const 1) (i32.const 2))", InvalidInitExpr),
+        ];
+        for (text, expected) in cases.iter() {
+            assert_eq!(&global_validation_error(text, features), expected, "{}", text);
+        }
+        let error = NonConstantInstruction { instruction: "i32.div_s", offset: 17 };
+        assert_eq!(error.to_string(), "i32.div_s at offset 17 is not allowed in a constant expression");
+    }
+
+    #[test]
+    fn init_exprs_only_read_imported_immutable_globals() {
+        let module = parse(r#"
+            (import "env" "base" (global i32))
+            (table 1 funcref)
+            (memory 1)
+            (global i32 (global.get 0))
+            (elem (global.get 0) func)
+            (data (global.get 0) "hi")
+        "#).unwrap();
+        assert_eq!(validate_module(&module), Ok(()));
+
+        let defined = "(global i32 (i32.const 0)) (global i32 (global.get 0))";
+        assert_eq!(global_validation_error(defined, Features::default()), ConstExprDefinedGlobal(0.into()));
+        let mutable = r#"(import "env" "g" (global (mut i32))) (global i32 (global.get 0))"#;
+        assert_eq!(global_validation_error(mutable, Features::default()), ConstExprMutableGlobal(0.into()));
+        assert_eq!(
+            ConstExprMutableGlobal(0.into()).to_string(),
+            "constant expressions can't read mutable global 0"
+        );
+
+        let module = parse("(global i32 (i32.const 0)) (table 1 funcref) (elem (global.get 0) func)").unwrap();
+        match validate_module(&module) {
+            Err(ValidationError::ElementValidation { error, .. }) => {
+                assert_eq!(error, ElementValidationError::CodeValidation(ConstExprDefinedGlobal(0.into())));
+            },
+            result => panic!("expected the element offset to be rejected, got {:?}", result),
+        }
+        let module = parse(r#"(import "env" "g" (global (mut i32))) (memory 1) (data (global.get 0) "hi")"#).unwrap();
+        match validate_module(&module) {
+            Err(ValidationError::DataValidation { error, .. }) => {
+                assert_eq!(error, DataValidationError::CodeValidation(ConstExprMutableGlobal(0.into())));
+            },
+            result => panic!("expected the data offset to be rejected, got {:?}", result),
+        }
+    }
+}
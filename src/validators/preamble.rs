@@ -1,3 +1,5 @@
+use std::{error, fmt};
+
 const WASM_MAGIC_NUMBER: &[u8; 4] = b"\0asm";
 const WASM_SUPPORTED_VERSION: u32 = 0x1;
 
@@ -7,6 +9,17 @@ pub enum PreambleValidationError {
     BadMagicNumber,
 }
 
+impl fmt::Display for PreambleValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreambleValidationError::BadVersion => write!(f, "unsupported module version"),
+            PreambleValidationError::BadMagicNumber => write!(f, "bad magic number"),
+        }
+    }
+}
+
+impl error::Error for PreambleValidationError {}
+
 pub(crate) fn validate_preamble(magic_number: &[u8; 4], version: u32) -> Result<(), PreambleValidationError> {
     if magic_number == WASM_MAGIC_NUMBER {
         if version == WASM_SUPPORTED_VERSION {
@@ -1,6 +1,8 @@
 use crate::types::{FuncIndex, TypeIndex, FunctionType};
 use crate::validators::start::StartValidationError::{InvalidFuncIndex, InvalidTypeIndex, InvalidType};
+use std::{error, fmt};
 
+#[allow(clippy::enum_variant_names)]
 #[derive(PartialEq, Eq, Debug)]
 pub enum StartValidationError {
     InvalidFuncIndex(FuncIndex),
@@ -8,6 +10,18 @@ pub enum StartValidationError {
     InvalidType,
 }
 
+impl fmt::Display for StartValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidFuncIndex(index) => write!(f, "start function {} is unknown", index.0),
+            InvalidTypeIndex(index) => write!(f, "start function refers to unknown type {}", index.0),
+            InvalidType => write!(f, "start function must take no parameters and return no results"),
+        }
+    }
+}
+
+impl error::Error for StartValidationError {}
+
 pub fn validate_start(
     start_func_index: FuncIndex,
     func_type_indices: &[TypeIndex],
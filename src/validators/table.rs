@@ -0,0 +1,27 @@
+use crate::types::TableType;
+use crate::validators::memory::limits_in_range;
+use crate::validators::table::TableLimitsValidationError::InvalidTableLimits;
+use std::{error, fmt};
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum TableLimitsValidationError {
+    InvalidTableLimits,
+}
+
+impl fmt::Display for TableLimitsValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidTableLimits => write!(f, "table limits min must not exceed max"),
+        }
+    }
+}
+
+impl error::Error for TableLimitsValidationError {}
+
+pub fn validate_table_type(table: &TableType) -> Result<(), TableLimitsValidationError> {
+    let TableType { limits, .. } = table;
+    if !limits_in_range(limits, u32::MAX as u64) {
+        return Err(InvalidTableLimits);
+    }
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use crate::types::{TagType, TypeIndex, FunctionType};
+use crate::validators::tag::TagValidationError::{InvalidTypeIndex, NonEmptyResults};
+use std::{error, fmt};
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum TagValidationError {
+    InvalidTypeIndex(TypeIndex),
+    NonEmptyResults(TypeIndex),
+}
+
+impl fmt::Display for TagValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidTypeIndex(index) => write!(f, "tag refers to unknown type {}", index.0),
+            NonEmptyResults(index) => write!(f, "tag type {} must not have results", index.0),
+        }
+    }
+}
+
+impl error::Error for TagValidationError {}
+
+/// Exceptions only carry values, so the type of a tag can't have results.
+pub fn validate_tag_type(tag_type: &TagType, function_types: &[FunctionType]) -> Result<(), TagValidationError> {
+    match function_types.get(tag_type.type_index.0 as usize) {
+        None => Err(InvalidTypeIndex(tag_type.type_index)),
+        Some(function_type) if !function_type.results.is_empty() => Err(NonEmptyResults(tag_type.type_index)),
+        Some(_) => Ok(()),
+    }
+}
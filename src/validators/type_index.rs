@@ -1,11 +1,22 @@
 use crate::validators::type_index::TypeIndexValidationError::InvalidTypeIndex;
 use crate::types::TypeIndex;
+use std::{error, fmt};
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum TypeIndexValidationError {
     InvalidTypeIndex,
 }
 
+impl fmt::Display for TypeIndexValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidTypeIndex => write!(f, "unknown type"),
+        }
+    }
+}
+
+impl error::Error for TypeIndexValidationError {}
+
 pub fn validate_type_index(type_index: &TypeIndex, max_type_index: Option<TypeIndex>) -> Result<(), TypeIndexValidationError> {
     if let Some(index) = max_type_index {
         if *type_index > index {